@@ -0,0 +1,159 @@
+//! Compiles data/stations.csv into the typed station tables `constants.rs`
+//! includes, so the station data is reviewed as plain rows rather than Rust.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const SOURCE: &str = "data/stations.csv";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SOURCE);
+    let csv = fs::read_to_string(SOURCE).unwrap_or_else(|e| panic!("{}: {}", SOURCE, e));
+
+    let mut info = String::new();
+    let mut stations = String::new();
+    let mut codes = String::new();
+    for (row, fields) in records(&csv).into_iter().skip(1) {
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        // A bad row is left out with a warning; the round-trip test of
+        // `STATION_INFO` against the file then fails on it
+        match station(&fields) {
+            Ok(station) => station.write(&mut info, &mut stations, &mut codes),
+            Err(e) => warn(&format!("{}:{}: {}", SOURCE, row, e)),
+        }
+    }
+
+    let generated = format!(
+        "/// Every station of {source}\n\
+         pub const STATION_INFO: &[StationInfo] = &[\n{info}];\n\n\
+         /// Station names mapped to UIC codes, the table the local lookup searches\n\
+         pub const STATIONS: &[(&str, i32)] = &[\n{stations}];\n\n\
+         /// Official NS abbreviations (the Stations API `code` field), accepted\n\
+         /// wherever a station is queried: `trip asd ut`\n\
+         pub const STATION_CODES: &[(&str, &str)] = &[\n{codes}];\n",
+        source = SOURCE,
+    );
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("stations.rs");
+    fs::write(out, generated).unwrap();
+}
+
+fn warn(message: &str) {
+    println!("cargo:warning={}", message);
+}
+
+/// One row of the file, checked
+struct Station<'a> {
+    name: &'a str,
+    uic_code: i32,
+    code: &'a str,
+    country: &'a str,
+    coordinates: Option<(f64, f64)>,
+    station_type: &'a str,
+}
+
+fn station(fields: &[String]) -> Result<Station<'_>, String> {
+    let [name, uic_code, code, country, lat, lng, station_type] = fields else {
+        return Err("expected name,uic_code,code,country,lat,lng,type".to_string());
+    };
+    let uic_code = uic_code
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid UIC code {:?}", uic_code))?;
+    let coordinates = match (lat.trim(), lng.trim()) {
+        ("", "") => None,
+        (lat, lng) => match (lat.parse::<f64>(), lng.parse::<f64>()) {
+            (Ok(lat), Ok(lng)) => Some((lat, lng)),
+            _ => return Err(format!("invalid coordinates {:?}, {:?}", lat, lng)),
+        },
+    };
+    Ok(Station {
+        name: name.trim(),
+        uic_code,
+        code: code.trim(),
+        country: country.trim(),
+        coordinates,
+        station_type: station_type.trim(),
+    })
+}
+
+impl Station<'_> {
+    fn write(&self, info: &mut String, stations: &mut String, codes: &mut String) {
+        let optional = |value: &str| match value {
+            "" => "None".to_string(),
+            value => format!("Some({:?})", value),
+        };
+        let coordinates = match self.coordinates {
+            Some((lat, lng)) => format!("Some(({:?}, {:?}))", lat, lng),
+            None => "None".to_string(),
+        };
+        writeln!(
+            info,
+            "    StationInfo {{ name: {:?}, uic_code: {}, code: {}, country: {:?}, coordinates: {}, \
+             station_type: {} }},",
+            self.name,
+            self.uic_code,
+            optional(self.code),
+            self.country,
+            coordinates,
+            optional(self.station_type)
+        )
+        .unwrap();
+        writeln!(stations, "    ({:?}, {}),", self.name, self.uic_code).unwrap();
+        if !self.code.is_empty() {
+            writeln!(codes, "    ({:?}, {:?}),", self.code, self.name).unwrap();
+        }
+    }
+}
+
+/// The records of `csv` with the line each starts on. Fields are split on
+/// commas outside double quotes, `""` is a quote inside a quoted field and
+/// quoted fields may span lines (RFC 4180, as `output::to_csv()` writes it).
+/// A last record with an unterminated quote is left out with a warning.
+fn records(csv: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        warn(&format!("{}:{}: unterminated quoted field", SOURCE, start));
+    } else if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    records
+}
@@ -0,0 +1,48 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+/// One row of the travel log `advisor` reads
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedJourney {
+    pub at: NaiveDateTime,
+    pub from: String,
+    pub to: String,
+}
+
+/// What the logged journeys would have cost with one subscription
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SubscriptionCost {
+    pub subscription: String,
+    /// Monthly fees over the logged months
+    pub fees_cents: i64,
+    /// The discounted fares
+    pub fares_cents: i64,
+    pub total_cents: i64,
+    /// Full fare minus `total_cents`, negative when the subscription costs more
+    pub saving_cents: i64,
+}
+
+/// The route travelled most, the one a Traject Vrij would cover
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RouteShare {
+    pub from: String,
+    pub to: String,
+    pub journeys: usize,
+    pub full_fare_cents: i64,
+}
+
+/// Full fare against every subscription for a travel log
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Advice {
+    /// Journeys priced, those without a known fare are left out
+    pub journeys: usize,
+    pub unpriced: usize,
+    /// Calendar months the log spans, each paying one monthly fee
+    pub months: usize,
+    pub full_fare_cents: i64,
+    /// Cheapest first
+    pub subscriptions: Vec<SubscriptionCost>,
+    /// The cheapest subscription if any beats full fare
+    pub best: Option<String>,
+    pub busiest_route: Option<RouteShare>,
+}
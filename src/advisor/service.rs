@@ -0,0 +1,213 @@
+use crate::advisor::models::{Advice, LoggedJourney, RouteShare, SubscriptionCost};
+use crate::output::TableRow;
+use crate::prices::Discount;
+use chrono::{Datelike, NaiveDateTime};
+use std::collections::{HashMap, HashSet};
+
+/// Read a travel log of `date,time,from,to` rows such as
+/// `2024-06-03,08:12,Den Haag C,Utrecht Centraal`. A `date,...` header line,
+/// blank lines and `#` comments are skipped
+pub fn parse_travel_log(csv: &str) -> Result<Vec<LoggedJourney>, Box<dyn std::error::Error>> {
+    let mut journeys = Vec::new();
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("date,") {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [date, time, from, to] = fields[..] else {
+            return Err(format!(
+                "❌ Line {} of the travel log needs date,time,from,to: {}",
+                number + 1,
+                line
+            )
+            .into());
+        };
+        let at = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M")
+            .map_err(|_| {
+                format!(
+                    "❌ Line {} of the travel log has no valid YYYY-MM-DD,HH:MM: {}",
+                    number + 1,
+                    line
+                )
+            })?;
+        journeys.push(LoggedJourney {
+            at,
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+    Ok(journeys)
+}
+
+/// The same key for both directions of a route
+pub fn route_key(from: &str, to: &str) -> (String, String) {
+    if from < to {
+        (from.to_string(), to.to_string())
+    } else {
+        (to.to_string(), from.to_string())
+    }
+}
+
+/// Weigh full fare against every subscription for the logged journeys, with
+/// `fare_of` giving a journey's full fare in cents (None when unknown). With
+/// the monthly fee of a Traject Vrij for the busiest route (NS only lists it
+/// per route on ns.nl) that is weighed too: the fee, plus full fare for the
+/// journeys off the route
+pub fn advise(
+    journeys: &[LoggedJourney],
+    traject_vrij_fee_cents: Option<i64>,
+    fare_of: impl Fn(&LoggedJourney) -> Option<i32>,
+) -> Advice {
+    let priced: Vec<(&LoggedJourney, i32)> = journeys
+        .iter()
+        .filter_map(|journey| fare_of(journey).map(|fare| (journey, fare)))
+        .collect();
+    let months = priced
+        .iter()
+        .map(|(journey, _)| (journey.at.year(), journey.at.month()))
+        .collect::<HashSet<_>>()
+        .len();
+    let full_fare: i64 = priced.iter().map(|(_, fare)| i64::from(*fare)).sum();
+
+    let mut subscriptions: Vec<SubscriptionCost> = Discount::SUBSCRIPTIONS
+        .iter()
+        .map(|subscription| {
+            let fees = i64::from(subscription.monthly_fee_cents()) * months as i64;
+            let fares: i64 = priced
+                .iter()
+                .map(|(journey, fare)| i64::from(subscription.apply(*fare, journey.at)))
+                .sum();
+            SubscriptionCost {
+                subscription: subscription.label().to_string(),
+                fees_cents: fees,
+                fares_cents: fares,
+                total_cents: fees + fares,
+                saving_cents: full_fare - fees - fares,
+            }
+        })
+        .collect();
+    let busiest_route = busiest_route(&priced);
+    if let (Some(fee), Some(route)) = (traject_vrij_fee_cents, &busiest_route) {
+        let fees = fee * months as i64;
+        let fares = full_fare - route.full_fare_cents;
+        subscriptions.push(SubscriptionCost {
+            subscription: format!("Traject Vrij {} - {}", route.from, route.to),
+            fees_cents: fees,
+            fares_cents: fares,
+            total_cents: fees + fares,
+            saving_cents: full_fare - fees - fares,
+        });
+    }
+    subscriptions.sort_by_key(|cost| cost.total_cents);
+    let best = subscriptions
+        .first()
+        .filter(|cost| cost.saving_cents > 0)
+        .map(|cost| cost.subscription.clone());
+
+    Advice {
+        journeys: priced.len(),
+        unpriced: journeys.len() - priced.len(),
+        months,
+        full_fare_cents: full_fare,
+        subscriptions,
+        best,
+        busiest_route,
+    }
+}
+
+impl TableRow for SubscriptionCost {
+    fn headers() -> &'static [&'static str] {
+        &["Subscription", "Fees", "Fares", "Total", "Saving"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let euros = |cents: i64| format!("€{:.2}", cents as f64 / 100.0);
+        vec![
+            self.subscription.clone(),
+            euros(self.fees_cents),
+            euros(self.fares_cents),
+            euros(self.total_cents),
+            if self.saving_cents < 0 {
+                format!("-{}", euros(-self.saving_cents))
+            } else {
+                euros(self.saving_cents)
+            },
+        ]
+    }
+}
+
+/// The route with the most journeys, counting both directions together
+fn busiest_route(priced: &[(&LoggedJourney, i32)]) -> Option<RouteShare> {
+    let mut routes: HashMap<(String, String), (usize, i64)> = HashMap::new();
+    for (journey, fare) in priced {
+        let route = routes.entry(route_key(&journey.from, &journey.to)).or_default();
+        route.0 += 1;
+        route.1 += i64::from(*fare);
+    }
+    routes
+        .into_iter()
+        .max_by(|(a, (a_count, _)), (b, (b_count, _))| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|((from, to), (journeys, full_fare_cents))| RouteShare {
+            from,
+            to,
+            journeys,
+            full_fare_cents,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_travel_log() {
+        let log = "date,time,from,to\n# commute\n2024-06-03,08:12,Den Haag C,Utrecht Centraal\n\n";
+        let journeys = parse_travel_log(log).unwrap();
+        assert_eq!(journeys.len(), 1);
+        assert_eq!(journeys[0].to, "Utrecht Centraal");
+
+        assert!(parse_travel_log("2024-06-03,08:12,Den Haag C").is_err());
+        assert!(parse_travel_log("2024-06-03,8h,Den Haag C,Gouda").is_err());
+    }
+
+    #[test]
+    fn test_advise() {
+        // Monday June 3rd 2024: a peak and an off-peak journey, and one unknown route
+        let log = "2024-06-03,08:00,Den Haag C,Utrecht Centraal\n\
+                   2024-06-03,12:00,Utrecht Centraal,Den Haag C\n\
+                   2024-06-03,12:00,Gouda,Nowhere";
+        let journeys = parse_travel_log(log).unwrap();
+        let advice = advise(&journeys, None, |journey| (journey.to != "Nowhere").then_some(1000));
+
+        assert_eq!((advice.journeys, advice.unpriced, advice.months), (2, 1, 1));
+        assert_eq!(advice.full_fare_cents, 2000);
+        // Dal Voordeel: full fare at peak, 40% off after, plus one monthly fee
+        let dal = advice
+            .subscriptions
+            .iter()
+            .find(|cost| cost.subscription == "Dal Voordeel")
+            .unwrap();
+        assert_eq!((dal.fares_cents, dal.fees_cents), (1600, 560));
+        assert_eq!(advice.best, None);
+        assert_eq!(advice.busiest_route.unwrap().journeys, 2);
+        assert!(!advice.subscriptions.iter().any(|cost| cost.subscription.starts_with("Traject")));
+    }
+
+    #[test]
+    fn test_advise_traject_vrij() {
+        // Two months of commuting plus one trip off the route
+        let log = "2024-06-03,08:00,Den Haag C,Utrecht Centraal
+                   2024-06-03,17:00,Utrecht Centraal,Den Haag C
+                   2024-07-01,08:00,Den Haag C,Utrecht Centraal
+                   2024-07-06,10:00,Den Haag C,Gouda";
+        let journeys = parse_travel_log(log).unwrap();
+        let advice = advise(&journeys, Some(1000), |_| Some(1500));
+
+        let traject_vrij = &advice.subscriptions[0];
+        assert_eq!(traject_vrij.subscription, "Traject Vrij Den Haag C - Utrecht Centraal");
+        assert_eq!((traject_vrij.fees_cents, traject_vrij.fares_cents), (2000, 1500));
+        assert_eq!(traject_vrij.saving_cents, 6000 - 3500);
+        assert_eq!(advice.best.as_deref(), Some(traject_vrij.subscription.as_str()));
+    }
+}
@@ -1,14 +1,82 @@
-use clap::{Parser, Subcommand};
+use chrono::{Local, NaiveDate};
+use clap::{CommandFactory, Parser, Subcommand};
 use dotenv::dotenv;
-use nstimes::cache::PriceCache;
+use nstimes::cache::CacheArgs;
+use nstimes::client::{configure_proxy, record_to, replay_from};
 use nstimes::commands;
+use nstimes::commands::departures::DepartureOptions;
+use nstimes::commands::price::PriceOptions;
+use nstimes::commands::trip::TripOptions;
+use nstimes::deprecations::{rewrite_args, RENAMES};
+use nstimes::monitor::{parse_sink, EventLog};
+use nstimes::i18n::{set_lang, tr, trf, Lang, Text};
+use nstimes::output::{
+    apply_color_choice, ColorChoice, OutputFormat, OutputOptions, Template, TimeDisplay,
+};
+use nstimes::prices::{CityPick, Discount, FareQuery, Passengers};
+#[cfg(feature = "server")]
+use nstimes::server::{self, ServeArgs};
+use nstimes::stations::models::StationCandidate;
+use nstimes::stations::{set_include_foreign, set_uic_only, StationLookupError};
+use nstimes::trips::{
+    planned_time, CancelledMode, DayBoundary, TripQuery, TripSort, MIN_TRANSFER_MINUTES,
+    SERVICE_DAY_START_HOUR,
+};
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(author, version, about)]
 struct Args {
-    /// Enable price caching with specified file path
+    #[command(flatten)]
+    cache_args: CacheArgs,
+
+    /// Record every NS API request and response (token redacted) to this file
+    #[arg(long, global = true, value_name = "FILE")]
+    record: Option<String>,
+
+    /// Answer NS API calls from a file made with --record instead of the network
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "record")]
+    replay: Option<String>,
+
+    /// Custom output template, e.g. "{departure:%H:%M} tr.{track} {type}"
+    #[arg(long, global = true, conflicts_with = "output")]
+    format: Option<String>,
+
+    /// Output format for trip and price results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Draw --output table with unicode box borders
+    #[arg(long = "box", global = true)]
+    boxed: bool,
+
+    /// Show departures as clock times, countdowns ("in 7 min") or both
+    #[arg(long, global = true, value_enum, default_value_t = TimeDisplay::Absolute)]
+    times: TimeDisplay,
+
+    /// When to use colors (auto honors NO_COLOR and non-terminal output)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Disable colors, shorthand for --color never
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Replace symbols and colors with explicit words for screen readers
+    #[arg(long, global = true)]
+    accessible_output: bool,
+
+    /// Take every station argument as a UIC code such as 8400621, skipping name lookup
+    #[arg(long, global = true)]
+    uic: bool,
+
+    /// Also match partial and misspelled station names abroad (full foreign names and codes always work)
     #[arg(long, global = true)]
-    cache: Option<String>,
+    include_foreign: bool,
+
+    /// Language for labels and messages (default: NSTIMES_LANG, then English)
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
 
     #[command(subcommand)]
     command: Commands,
@@ -22,6 +90,85 @@ enum Commands {
         from: String,
         /// Destination station name to search for
         to: String,
+        /// Maximum number of trips to show
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+        limit: Option<u16>,
+        /// Print the journeys as a GeoJSON FeatureCollection
+        #[arg(long)]
+        geojson: bool,
+        /// Also write the journeys as calendar events to an .ics file
+        #[arg(long, value_name = "FILE")]
+        ical: Option<String>,
+        /// Travel date (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        date: Option<String>,
+        /// Departure time (HH:MM), defaults to now
+        #[arg(long)]
+        time: Option<String>,
+        /// Date and departure time in one go, e.g. "2024-06-01 08:30"
+        #[arg(long, conflicts_with_all = ["date", "time"])]
+        at: Option<String>,
+        /// Only show journeys with at most this many transfers
+        #[arg(long, value_name = "N")]
+        max_transfers: Option<u32>,
+        /// Only show direct trains, shorthand for --max-transfers 0
+        #[arg(long, conflicts_with = "max_transfers")]
+        direct: bool,
+        /// Skip these train types, e.g. --exclude-type SPR or --exclude-type IC,SPR
+        #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+        exclude_type: Vec<String>,
+        /// Only show these train types, e.g. --only-type IC
+        #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+        only_type: Vec<String>,
+        /// Skip journeys with a train that needs a supplement (Intercity direct, ICE)
+        #[arg(long)]
+        no_supplement: bool,
+        /// Only plan wheelchair-accessible journeys and show the accessibility notes
+        #[arg(long)]
+        accessible: bool,
+        /// Plan the journeys through this station (sent to NS as `viaUicCode`, so the
+        /// route is forced through it rather than filtered afterwards)
+        #[arg(long)]
+        via: Option<String>,
+        /// Show the typical delay of on-time journeys, learned from the `monitor` change log
+        #[arg(long)]
+        predict: bool,
+        /// Also open this search in the ns.nl journey planner in the default browser
+        #[arg(long)]
+        open: bool,
+        /// Show the estimated CO₂ saved per journey compared to driving the same distance
+        #[arg(long)]
+        co2: bool,
+        /// Warn about journeys that run into the rush-hour ban on bikes
+        #[arg(long)]
+        bike: bool,
+        /// Warn about transfers with less than this many minutes to change trains
+        #[arg(long, value_name = "MINUTES", default_value_t = MIN_TRANSFER_MINUTES)]
+        min_transfer: i64,
+        /// List the intermediate stations with their times under every leg
+        #[arg(long)]
+        stops: bool,
+        /// Order of the listed trips, e.g. --sort duration for the fastest first
+        #[arg(long, value_enum, default_value_t = TripSort::Departure)]
+        sort: TripSort,
+        /// Show cancelled trips, hide them, or list each with the next running alternative
+        #[arg(long, value_enum, default_value_t = CancelledMode::Show)]
+        cancelled: CancelledMode,
+        /// Show the next page(s) of journeys after the first result, e.g. --later 2
+        #[arg(long, value_name = "PAGES", num_args = 0..=1, default_missing_value = "1")]
+        later: Option<u16>,
+        /// Show the page(s) of journeys before the first result
+        #[arg(
+            long,
+            value_name = "PAGES",
+            num_args = 0..=1,
+            default_missing_value = "1",
+            conflicts_with = "later"
+        )]
+        earlier: Option<u16>,
+        /// Latest arrival time (HH:MM) instead of a departure time, combines with --date
+        #[arg(long, value_name = "TIME", conflicts_with_all = ["time", "at"])]
+        arrive_by: Option<String>,
     },
     /// Get price information for a trip
     Price {
@@ -32,37 +179,421 @@ enum Commands {
         /// Travel class: 1 for first class, 2 for second class (default: 2)
         #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
         class: Option<u8>,
-        /// Get price for return trip instead of single trip
+        /// Get price for return trip instead of single trip (formerly --return)
+        #[arg(long)]
+        round_trip: bool,
+        /// Also show prices converted to this currency (ECB daily rates), e.g. GBP or USD
+        #[arg(long)]
+        currency: Option<String>,
+        /// For city names such as "amsterdam": pick the cheapest or fastest member station pair
+        #[arg(long, value_enum, default_value_t = CityPick::Cheapest)]
+        city_pick: CityPick,
+        /// Also show the supplement the next journeys need (Intercity direct, ICE) and the total with it
+        #[arg(long)]
+        supplement: bool,
+        /// Number of adults travelling
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=99))]
+        adults: u32,
+        /// Number of children aged 4 to 11 travelling (younger ones travel free)
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=99))]
+        children: u32,
+        /// Include the discount of this subscription, e.g. --discount dal-voordeel
+        /// (peak or off-peak by the current time)
+        #[arg(long, value_enum, default_value_t = Discount::None)]
+        discount: Discount,
+        /// Price as one joint journey when part of it is run by other carriers (Arriva, Keolis, ...)
+        #[arg(long)]
+        joint: bool,
+        /// List every single and return product of both classes, grouped and cheapest first
+        #[arg(long, conflicts_with_all = ["class", "round_trip"])]
+        all_products: bool,
+        /// Price for a group of this many adults, with the group product that applies
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(2..=99),
+            conflicts_with_all = ["adults", "children"]
+        )]
+        group: Option<u32>,
+        /// Add the day bike ticket (Dagkaart Fiets) to the total and warn about the rush-hour bike ban
+        #[arg(long)]
+        bike: bool,
+        /// Also show how the fare changed across tariff years, from the price cache (needs --cache)
+        #[arg(long)]
+        history: bool,
+        /// Price both classes side by side with what 1st class costs extra
+        #[arg(long, conflicts_with_all = ["class", "all_products"])]
+        compare_class: bool,
+        /// Travel date (YYYY-MM-DD) for date-dependent products and promotions; dated prices are not cached
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Find trips between two stations with the ticket price attached
+    Plan {
+        /// Start station name to search for
+        from: String,
+        /// Destination station name to search for
+        to: String,
+        /// Maximum number of trips to show
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+        limit: Option<u16>,
+        /// Travel class: 1 for first class, 2 for second class (default: 2)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        class: Option<u8>,
+        /// Travel date (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        date: Option<String>,
+        /// Departure time (HH:MM), defaults to now
+        #[arg(long)]
+        time: Option<String>,
+    },
+    /// Work out whether a subscription would have paid off for a travel log
+    Advisor {
+        /// CSV travel log with date,time,from,to rows, e.g. 2024-06-03,08:12,Den Haag C,Utrecht Centraal
+        log: String,
+        /// Travel class: 1 for first class, 2 for second class (default: 2)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        class: Option<u8>,
+        /// Monthly fee in euros of a Traject Vrij for the busiest route (listed per route on ns.nl), to weigh it too
+        #[arg(long, value_name = "EUROS", value_parser = euro_cents)]
+        traject_vrij: Option<i64>,
+    },
+    /// Show the first running trip of the day between two stations
+    First {
+        /// Start station name to search for
+        from: String,
+        /// Destination station name to search for
+        to: String,
+        /// Travel date (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Show the last running trip of the day between two stations
+    Last {
+        /// Start station name to search for
+        from: String,
+        /// Destination station name to search for
+        to: String,
+        /// Travel date (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Show the next departures from a station
+    Departures {
+        /// Station name to search for
+        station: String,
+        /// Number of departures to show
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..))]
+        limit: u16,
+        /// Write a standalone HTML departure board to this file instead
+        #[arg(long, value_name = "FILE")]
+        html: Option<String>,
+        /// Let the HTML board reload itself every SECS seconds
+        #[arg(long, value_name = "SECS", requires = "html")]
+        refresh: Option<u64>,
+        /// Skip these train types, e.g. --exclude-type SPR or --exclude-type IC,SPR
+        #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+        exclude_type: Vec<String>,
+        /// Only show these train types, e.g. --only-type IC
+        #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+        only_type: Vec<String>,
+    },
+    /// Show the OV-fiets rental bikes available at a station now
+    Ovfiets {
+        /// Station name to search for
+        station: String,
+    },
+    /// Fastest travel times between every pair of a set of stations, and the
+    /// station that is quickest to reach for all of them
+    Matrix {
+        /// Station names to search for, at least two
+        #[arg(required = true, num_args = 2..)]
+        stations: Vec<String>,
+        /// Journey searches to run at the same time (1-8)
+        #[arg(long, default_value_t = 4)]
+        jobs: usize,
+        /// Search every pair again instead of using travel times cached in the last day
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// Plan two journeys to a common station with aligned arrival times
+    Meet {
+        /// Station the other person departs from
+        their_from: String,
+        /// Station you depart from
+        my_from: String,
+        /// Station where you meet
+        station: String,
+    },
+    /// Follow one trip until it departs and report track, delay and status changes
+    Follow {
+        /// Start station name to search for
+        from: String,
+        /// Destination station name to search for
+        to: String,
+        /// Planned departure time (HH:MM) of the trip to follow
+        #[arg(long)]
+        departure: String,
+        /// Travel date (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        date: Option<String>,
+        /// Seconds between refreshes
+        #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(10..))]
+        interval: u64,
+        /// Change log file, read to escalate and appended to (default: monitor.jsonl in the cache directory)
         #[arg(long)]
-        r#return: bool,
+        log: Option<String>,
+        /// Also raise a desktop notification for every change
+        #[cfg(feature = "notify")]
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Inspect the price cache given with --cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Audit or sync the local station table
+    Stations {
+        #[command(subcommand)]
+        action: StationsAction,
+    },
+    /// Watch journeys for delay, platform and status changes
+    Monitor {
+        #[command(subcommand)]
+        action: MonitorAction,
+    },
+    /// Run the HTTP API server
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+}
+
+#[derive(Subcommand)]
+enum MonitorAction {
+    /// Poll the trips between two stations and log every change
+    Watch {
+        /// Start station name to search for
+        from: String,
+        /// Destination station name to search for
+        to: String,
+        /// Seconds between refreshes
+        #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(10..))]
+        interval: u64,
+        /// Change log file (default: monitor.jsonl in the cache directory)
+        #[arg(long, value_name = "FILE")]
+        log: Option<String>,
+        /// Where to send each refresh: stdout, json:<path>, an http(s) URL (signed
+        /// with `URL#secret=<secret>`) or, with the mqtt feature,
+        /// mqtt://[user[:password]@]host[:port]/topic (repeatable)
+        #[arg(long = "sink", value_name = "SINK", default_value = "stdout")]
+        sinks: Vec<String>,
+    },
+    /// Show the recorded changes per journey
+    History {
+        /// Change log file (default: monitor.jsonl in the cache directory)
+        #[arg(long, value_name = "FILE")]
+        log: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Entry counts and hits, misses, stale hits and API fetches over all runs
+    Stats,
+    /// Fetch and store the prices of a list of routes in both classes
+    Warm {
+        /// File with one `from,to` route per line (default: the busiest intercity routes)
+        routes: Option<String>,
+        /// Routes priced at the same time (1-8)
+        #[arg(long, default_value_t = 4)]
+        jobs: usize,
+    },
+    /// Write every cached price to a file (gzip for .gz) or stdout
+    Export {
+        /// File to write (default: stdout)
+        file: Option<String>,
     },
+    /// Merge an exported cache into this one; the price expiring last wins
+    Import {
+        /// File written by `cache export`, or another cache file
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StationsAction {
+    /// Compare the local station table against the live NS stations API
+    Validate {
+        /// Also write the live station list as a corrected data/stations.csv to this file
+        #[arg(long, value_name = "FILE")]
+        write: Option<String>,
+    },
+    /// Print the compiled-in station table with all fields (JSON, or --output csv|table)
+    Export,
+    /// Fetch the full station list from the NS API into the local data file
+    /// that station lookups prefer over the compiled-in table
+    Sync {
+        /// Data file to write (default: NS_STATIONS_FILE or nstimes-stations.json in the temp directory)
+        #[arg(long, value_name = "FILE")]
+        file: Option<String>,
+    },
+}
+
+/// `--long` names of every option that consumes a value, across all subcommands
+fn value_options(command: &clap::Command) -> Vec<String> {
+    let mut names: Vec<String> = command
+        .get_arguments()
+        .filter(|arg| arg.get_action().takes_values())
+        .filter_map(|arg| arg.get_long())
+        .map(|long| format!("--{}", long))
+        .collect();
+    for sub in command.get_subcommands() {
+        names.extend(value_options(sub));
+    }
+    names
 }
 
 fn main() {
     if let Err(e) = run() {
-        eprintln!("Error: {}", e);
+        if let Some(StationLookupError::Ambiguous { query, matches }) = e.downcast_ref() {
+            print_candidates(query, matches);
+        }
+        eprintln!("{}: {}", tr(Text::Error), e);
         std::process::exit(1);
     }
 }
 
+/// The stations an ambiguous query matched, for the user to refine it with
+fn print_candidates(query: &str, matches: &[StationCandidate]) {
+    println!("{}", trf(Text::AmbiguousQuery, &[query]));
+    for candidate in matches {
+        println!("{} - {}", candidate.uic_code, candidate.name);
+    }
+}
+
 fn run() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
-    let args = Args::parse();
+    let value_options = value_options(&Args::command());
+    let rewritten = rewrite_args(std::env::args(), RENAMES, |name| {
+        value_options.iter().any(|long| long == name)
+    });
+    rewritten.warn();
+    let args = Args::parse_from(rewritten.args);
+    set_lang(args.lang);
+    set_uic_only(args.uic);
+    set_include_foreign(args.include_foreign);
+    configure_proxy(args.cache_args.proxy_url.as_deref())?;
 
-    // Initialize cache if --cache flag is provided
-    let cache = if let Some(cache_path) = &args.cache {
-        Some(PriceCache::new(cache_path)?)
-    } else {
-        None
+    if let Some(path) = &args.record {
+        record_to(path)?;
+    }
+    if let Some(path) = &args.replay {
+        replay_from(path)?;
+    }
+
+    let cache = args.cache_args.open()?;
+    let output = OutputOptions {
+        format: args.output,
+        template: args.format.as_deref().map(Template::parse).transpose()?,
+        accessible: args.accessible_output,
+        times: args.times,
+        boxed: args.boxed,
     };
 
+    if output.accessible || args.no_color {
+        apply_color_choice(ColorChoice::Never);
+    } else {
+        apply_color_choice(args.color);
+    }
+
     match args.command {
-        Commands::Trip { from, to } => commands::trip::execute(&from, &to)?,
+        Commands::Trip {
+            from,
+            to,
+            limit,
+            geojson,
+            ical,
+            date,
+            time,
+            at,
+            arrive_by,
+            predict,
+            open,
+            co2,
+            bike,
+            min_transfer,
+            stops,
+            sort,
+            cancelled,
+            later,
+            earlier,
+            via,
+            max_transfers,
+            direct,
+            exclude_type,
+            only_type,
+            no_supplement,
+            accessible,
+        } => {
+            let (date, time) = match &at {
+                Some(at) => {
+                    let (date, time) = at
+                        .split_once(' ')
+                        .ok_or("❌ --at expects \"YYYY-MM-DD HH:MM\"")?;
+                    (Some(date), Some(time.trim()))
+                }
+                None => (date.as_deref(), arrive_by.as_deref().or(time.as_deref())),
+            };
+            let date_time = if date.is_some() || time.is_some() {
+                Some(planned_time(date, time, Local::now())?)
+            } else {
+                None
+            };
+            let query = TripQuery {
+                limit: limit.map(usize::from),
+                date_time,
+                arrive_by: arrive_by.is_some(),
+                max_transfers: if direct { Some(0) } else { max_transfers },
+                exclude_types: exclude_type,
+                only_types: only_type,
+                no_supplement,
+                accessible,
+                pages: i32::from(later.unwrap_or(0)) - i32::from(earlier.unwrap_or(0)),
+                ..Default::default()
+            };
+            let options = TripOptions {
+                geojson,
+                ical,
+                history: predict.then(EventLog::open_default).transpose()?,
+                via,
+                open,
+                stops,
+                sort,
+                cancelled,
+                co2,
+                min_transfer,
+                bike,
+            };
+            commands::trip::execute(&from, &to, &query, &options, &output)?
+        }
         Commands::Price {
             from,
             to,
             class,
-            r#return,
+            round_trip,
+            currency,
+            city_pick,
+            supplement,
+            adults,
+            children,
+            discount,
+            joint,
+            all_products,
+            compare_class,
+            group,
+            bike,
+            history,
+            date,
         } => {
             let travel_class = class.map(|c| {
                 if c == 1 {
@@ -71,9 +602,219 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
                     "SECOND_CLASS".to_string()
                 }
             });
-            commands::price::execute(&from, &to, travel_class, r#return, cache.as_ref())?
+            let options = PriceOptions {
+                travel_class,
+                is_return: round_trip,
+                currency,
+                city_pick,
+                supplement,
+                fare: FareQuery {
+                    passengers: match group {
+                        Some(size) => Passengers {
+                            adults: size,
+                            children: 0,
+                        },
+                        None => Passengers { adults, children },
+                    },
+                    discount,
+                    joint,
+                    planned_departure_time: date
+                        .as_deref()
+                        .map(|date| planned_time(Some(date), None, Local::now()))
+                        .transpose()?,
+                },
+                all_products,
+                compare_class,
+                group,
+                bike,
+                history,
+            };
+            commands::price::execute(&from, &to, &options, cache.as_ref(), &output)?
+        }
+        Commands::Plan {
+            from,
+            to,
+            limit,
+            class,
+            date,
+            time,
+        } => {
+            let date_time = if date.is_some() || time.is_some() {
+                Some(planned_time(date.as_deref(), time.as_deref(), Local::now())?)
+            } else {
+                None
+            };
+            let query = TripQuery {
+                limit: limit.map(usize::from),
+                date_time,
+                ..Default::default()
+            };
+            let travel_class = match class {
+                Some(1) => Some("FIRST_CLASS"),
+                Some(_) => Some("SECOND_CLASS"),
+                None => None,
+            };
+            commands::plan::execute(&from, &to, &query, travel_class, cache.as_ref(), &output)?
+        }
+        Commands::Advisor {
+            log,
+            class,
+            traject_vrij,
+        } => {
+            let travel_class = match class {
+                Some(1) => Some("FIRST_CLASS"),
+                Some(_) => Some("SECOND_CLASS"),
+                None => None,
+            };
+            commands::advisor::execute(
+                &log,
+                travel_class,
+                traject_vrij,
+                cache.as_ref(),
+                &output,
+            )?
+        }
+        Commands::First { from, to, date } => {
+            let date = service_date(date.as_deref())?;
+            commands::boundary::execute(&from, &to, date, DayBoundary::First, &output)?
+        }
+        Commands::Last { from, to, date } => {
+            let date = service_date(date.as_deref())?;
+            commands::boundary::execute(&from, &to, date, DayBoundary::Last, &output)?
+        }
+        Commands::Departures {
+            station,
+            limit,
+            html,
+            refresh,
+            exclude_type,
+            only_type,
+        } => {
+            let options = DepartureOptions {
+                limit: usize::from(limit),
+                html,
+                refresh,
+                exclude_types: exclude_type,
+                only_types: only_type,
+            };
+            commands::departures::execute(&station, &options, &output)?
+        }
+        Commands::Ovfiets { station } => commands::ovfiets::execute(&station, &output)?,
+        Commands::Matrix {
+            stations,
+            jobs,
+            refresh,
+        } => commands::matrix::execute(&stations, jobs, refresh, &output)?,
+        Commands::Meet {
+            their_from,
+            my_from,
+            station,
+        } => commands::meet::execute(&their_from, &my_from, &station)?,
+        Commands::Follow {
+            from,
+            to,
+            departure,
+            date,
+            interval,
+            log,
+            #[cfg(feature = "notify")]
+            notify,
+        } => {
+            #[cfg(not(feature = "notify"))]
+            let notify = false;
+            let departure = planned_time(date.as_deref(), Some(&departure), Local::now())?;
+            commands::follow::execute(
+                &from,
+                &to,
+                departure,
+                Duration::from_secs(interval),
+                &event_log(log.as_deref())?,
+                notify,
+            )?
+        }
+        Commands::Cache { action } => match action {
+            CacheAction::Stats => commands::cache::stats(cache.as_ref(), &output)?,
+            CacheAction::Warm { routes, jobs } => {
+                commands::cache::warm(routes.as_deref(), jobs, cache.as_ref(), &output)?
+            }
+            CacheAction::Export { file } => {
+                commands::cache::export(file.as_deref(), cache.as_ref())?
+            }
+            CacheAction::Import { file } => {
+                commands::cache::import(&file, cache.as_ref(), &output)?
+            }
+        },
+        Commands::Stations { action } => match action {
+            StationsAction::Validate { write } => {
+                commands::stations::validate(write.as_deref(), &output)?
+            }
+            StationsAction::Export => commands::stations::export(&output)?,
+            StationsAction::Sync { file } => commands::stations::sync(file.as_deref(), &output)?,
+        },
+        Commands::Monitor { action } => match action {
+            MonitorAction::Watch {
+                from,
+                to,
+                interval,
+                log,
+                sinks,
+            } => {
+                let sinks = sinks
+                    .iter()
+                    .map(|spec| parse_sink(spec))
+                    .collect::<Result<Vec<_>, _>>()?;
+                commands::monitor::watch(
+                    &from,
+                    &to,
+                    Duration::from_secs(interval),
+                    &event_log(log.as_deref())?,
+                    &sinks,
+                )?
+            }
+            MonitorAction::History { log } => {
+                commands::monitor::history(&event_log(log.as_deref())?)?
+            }
+        },
+        // The server revalidates on its own interval, nothing is left to do after it stops
+        #[cfg(feature = "server")]
+        Commands::Serve(serve_args) => {
+            let runtime = tokio::runtime::Runtime::new()?;
+            return runtime.block_on(server::serve(&serve_args, cache));
         }
     }
 
+    // Stale prices were answered, refresh them now the output is out
+    if let Some(cache) = &cache {
+        cache.run_revalidations();
+    }
     Ok(())
 }
+
+/// `--date` for `first`/`last`; without it the current service day, so just
+/// after midnight it still means yesterday's last train
+fn service_date(date: Option<&str>) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    match date {
+        Some(d) => Ok(NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| format!("❌ Invalid date '{}', expected YYYY-MM-DD", d))?),
+        None => {
+            let now = Local::now() - chrono::Duration::hours(i64::from(SERVICE_DAY_START_HOUR));
+            Ok(now.date_naive())
+        }
+    }
+}
+
+/// An amount in euros such as 289.10, as cents
+fn euro_cents(value: &str) -> Result<i64, String> {
+    match value.parse::<f64>() {
+        Ok(euros) if euros.is_finite() && euros >= 0.0 => Ok((euros * 100.0).round() as i64),
+        _ => Err(format!("expected an amount in euros such as 289.10, got '{}'", value)),
+    }
+}
+
+/// `--log`, or the log in the cache directory
+fn event_log(path: Option<&str>) -> Result<EventLog, Box<dyn std::error::Error>> {
+    match path {
+        Some(path) => Ok(EventLog::new(path)),
+        None => EventLog::open_default(),
+    }
+}
@@ -0,0 +1,50 @@
+use super::models::CacheTtl;
+use super::service::{cache_spec, PriceCache};
+use crate::migrations;
+
+/// Cache and proxy flags shared by the `cli` and `server` binaries
+#[derive(clap::Args, Debug, Clone)]
+pub struct CacheArgs {
+    /// Enable price caching with specified file path; without a path the
+    /// cache lives in $XDG_CACHE_HOME/nstimes/prices.json (or the platform's
+    /// cache directory). A bare --cache goes after any subcommand
+    #[arg(long, global = true, value_name = "PATH")]
+    pub cache: Option<Option<String>>,
+
+    /// Expiry of newly cached prices: tariff-year (next January 1st), a
+    /// duration like 30d or 2w, or a fixed date like 2026-06-01
+    #[arg(long, global = true, value_name = "TTL", default_value_t = CacheTtl::TariffYear)]
+    pub cache_ttl: CacheTtl,
+
+    /// Most routes kept in the price cache, unbounded by default; the least
+    /// recently used are evicted (a shared Redis cache only from memory)
+    #[arg(long, global = true, value_name = "N")]
+    pub cache_max_entries: Option<usize>,
+
+    /// Answer with expired cached prices right away and refresh them
+    /// afterwards: in the background for the server, once the output is
+    /// printed for other commands
+    #[arg(long, global = true)]
+    pub cache_stale_while_revalidate: bool,
+
+    /// Send NS API calls through this proxy, e.g. http://proxy.local:3128
+    /// (default: NSTIMES_PROXY_URL, then HTTPS_PROXY/HTTP_PROXY/ALL_PROXY; NO_PROXY applies)
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy_url: Option<String>,
+}
+
+impl CacheArgs {
+    /// Resolve `--cache`, migrate what is stored there and open the cache
+    /// with the other flags applied; None without `--cache`
+    pub fn open(&self) -> Result<Option<PriceCache>, Box<dyn std::error::Error>> {
+        let cache_path = cache_spec(self.cache.clone())?;
+        migrations::run(cache_path.as_deref()).log();
+
+        Ok(PriceCache::open(cache_path.as_deref())?.map(|cache| {
+            cache
+                .with_ttl(self.cache_ttl)
+                .with_max_entries(self.cache_max_entries.unwrap_or(usize::MAX))
+                .with_stale_while_revalidate(self.cache_stale_while_revalidate)
+        }))
+    }
+}
@@ -0,0 +1,469 @@
+use super::models::{CacheCounters, CacheEntry};
+use crate::migrations::{upgrade_cache, NewerCacheVersion};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long changes to the JSON cache may wait before they are written out;
+/// writes within this window are coalesced into one save
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Version of the cache file format this build writes. Bump it and add the
+/// upgrade from the previous version to `migrations::CACHE_UPGRADES`
+/// whenever `CacheEntry` or the keys change shape.
+pub const CACHE_VERSION: u32 = 2;
+
+/// Extension that makes the JSON cache gzip-compressed:
+/// `--cache prices.cache.gz`
+const GZIP_EXTENSION: &str = ".gz";
+
+/// The first bytes of every gzip stream
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// What a cache file holds: `{"version": 1, "entries": {key: entry}}`.
+/// Files from before the version was stored are a bare map of entries,
+/// version 0.
+#[derive(Serialize)]
+pub struct CacheFile<'a, T> {
+    pub version: u32,
+    pub entries: &'a T,
+}
+
+impl<'a, T: Serialize> CacheFile<'a, T> {
+    /// `entries` in the current format version
+    pub fn new(entries: &'a T) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            entries,
+        }
+    }
+}
+
+/// Where `PriceCache` keeps its entries. Keys are `PriceKey`s in their
+/// normalized `Display` form; expiry is checked by the caller, so a
+/// backend hands out expired entries too (they serve as stale fallbacks and
+/// carry the price history).
+///
+/// Implement it to store prices elsewhere (SQLite, a KV store) and hand it
+/// to `PriceCache::from_backend()`; `MemoryBackend` is the smallest example.
+pub trait CacheBackend: Send + Sync {
+    /// The entry stored under `key`
+    fn load(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Replace the entry under `key` with what `update` makes of the current one
+    fn update(
+        &self,
+        key: &str,
+        update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Every stored entry, for statistics
+    fn entries(&self) -> Vec<CacheEntry>;
+
+    /// The keys of every stored entry
+    fn keys(&self) -> Vec<String>;
+
+    /// Whether other processes use the same entries (Redis), so this one
+    /// must not evict them or list every key on its own account
+    fn is_shared(&self) -> bool {
+        false
+    }
+
+    /// Drop the entries under `keys`, returning how many were removed
+    fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>>;
+
+    /// Drop the expired entries, returning how many were removed
+    fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>>;
+
+    /// Write out the changes held back since the last save
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    /// The usage counters stored with the cache, summed over every process
+    /// that used it
+    fn counters(&self) -> CacheCounters;
+
+    /// Add `delta` to the stored usage counters
+    fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Entries in one JSON file, read at startup and rewritten after changes.
+/// Changes are coalesced: the file is saved by the first change
+/// `SAVE_INTERVAL` after an unsaved one, by `flush()` and on drop.
+/// Writes go to `<path>.tmp` first and replace the file with a rename, so a
+/// crash mid-write leaves the previous version intact. Usage counters live
+/// next to it in `<path>.stats`.
+///
+/// A cache path ending in `.gz` is written gzip-compressed (compact JSON,
+/// a fraction of the size for caches of every station pair); otherwise the
+/// file is pretty-printed JSON. Loading detects gzip by its magic bytes, so
+/// a file compressed by hand keeps working, and stays compressed.
+///
+/// Files of an older `CACHE_VERSION` are upgraded on load; a file of a
+/// newer version is refused instead of being overwritten.
+pub struct JsonFileBackend {
+    path: String,
+    /// Whether saves gzip the file
+    compressed: bool,
+    /// In-memory cache entries (uses interior mutability for thread-safe updates)
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    /// When the oldest change not yet in the file was made
+    dirty_since: Mutex<Option<Instant>>,
+}
+
+impl JsonFileBackend {
+    /// Load or create the cache file at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path_str = path.as_ref().to_string_lossy().to_string();
+        let mut compressed = path_str.ends_with(GZIP_EXTENSION);
+
+        let entries = if path.as_ref().exists() {
+            // Load existing cache
+            let content = fs::read(&path)?;
+            compressed |= content.starts_with(&GZIP_MAGIC);
+            match parse_entries(&content) {
+                Ok(entries) => entries,
+                Err(e) if e.is::<NewerCacheVersion>() => {
+                    return Err(format!("{}: {}", path_str, e).into());
+                }
+                Err(e) => {
+                    // Keep the unreadable file for inspection instead of overwriting it
+                    let backup = with_suffix(path.as_ref(), "corrupt");
+                    fs::rename(&path, &backup)?;
+                    eprintln!(
+                        "⚠️  Failed to parse cache file, moved it to {} and starting fresh: {}",
+                        backup.display(),
+                        e
+                    );
+                    HashMap::new()
+                }
+            }
+        } else {
+            // Create parent directory if it doesn't exist
+            if let Some(parent) = path.as_ref().parent()
+                && !parent.exists()
+            {
+                fs::create_dir_all(parent)?;
+            }
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path: path_str,
+            compressed,
+            entries: Mutex::new(entries),
+            dirty_since: Mutex::new(None),
+        })
+    }
+
+    /// Note a change, saving when the oldest unsaved one is `SAVE_INTERVAL` old
+    fn changed(
+        &self,
+        entries: &HashMap<String, CacheEntry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut dirty_since = self.dirty_since.lock().map_err(|_| "Failed to lock cache")?;
+        if dirty_since.get_or_insert_with(Instant::now).elapsed() >= SAVE_INTERVAL {
+            self.save(entries)?;
+            *dirty_since = None;
+        }
+        Ok(())
+    }
+
+    /// Save the cache to disk: write and fsync a temporary file next to the
+    /// cache, then rename it over the cache file
+    fn save(
+        &self,
+        entries: &HashMap<String, CacheEntry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        write_file(Path::new(&self.path), &CacheFile::new(entries), self.compressed)
+    }
+
+    fn counters_path(&self) -> PathBuf {
+        with_suffix(Path::new(&self.path), "stats")
+    }
+}
+
+/// The entries of the cache file at `path`, as exported by `cache export`
+pub fn read_entries(
+    path: impl AsRef<Path>,
+) -> Result<HashMap<String, CacheEntry>, Box<dyn std::error::Error>> {
+    parse_entries(&fs::read(path)?)
+}
+
+/// Write entries in the cache file format, gzip-compressed when `path` ends in `.gz`
+pub fn write_entries(
+    path: impl AsRef<Path>,
+    entries: &impl Serialize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let compressed = path.to_string_lossy().ends_with(GZIP_EXTENSION);
+    write_file(path, &CacheFile::new(entries), compressed)
+}
+
+/// The entries of a cache file, upgraded to the current format version
+fn parse_entries(
+    content: &[u8],
+) -> Result<HashMap<String, CacheEntry>, Box<dyn std::error::Error>> {
+    let mut file = upgrade_cache(read_json(content)?, &mut Vec::new())?;
+    Ok(serde_json::from_value(file["entries"].take())?)
+}
+
+/// The JSON of a cache file, plain or gzip-compressed
+pub(crate) fn read_json(content: &[u8]) -> Result<Value, Box<dyn std::error::Error>> {
+    if content.starts_with(&GZIP_MAGIC) {
+        let mut json = String::new();
+        GzDecoder::new(content).read_to_string(&mut json)?;
+        Ok(serde_json::from_str(&json)?)
+    } else {
+        Ok(serde_json::from_slice(content)?)
+    }
+}
+
+/// Save `value` to `path` as gzip-compressed compact JSON or as pretty JSON
+pub(crate) fn write_file(
+    path: &Path,
+    value: &impl Serialize,
+    compressed: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if compressed {
+        write_gzip(path, value)
+    } else {
+        write_json(path, value)
+    }
+}
+
+/// Write `value` as JSON to `path` through a fsynced temporary file and a rename
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = with_suffix(path, "tmp");
+    let mut writer = BufWriter::new(fs::File::create(&temp)?);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// `write_json()`, gzip-compressed
+fn write_gzip(path: &Path, value: &impl Serialize) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = with_suffix(path, "tmp");
+    let file = BufWriter::new(fs::File::create(&temp)?);
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    serde_json::to_writer(&mut encoder, value)?;
+    let mut writer = encoder.finish()?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// `path` with `.suffix` appended to the file name
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+impl CacheBackend for JsonFileBackend {
+    fn load(&self, key: &str) -> Option<CacheEntry> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let entry = update(entries.remove(key));
+        entries.insert(key.to_string(), entry);
+        self.changed(&entries)
+    }
+
+    fn entries(&self) -> Vec<CacheEntry> {
+        match self.entries.lock() {
+            Ok(entries) => entries.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        match self.entries.lock() {
+            Ok(entries) => entries.keys().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let removed = keys.iter().filter(|key| entries.remove(*key).is_some()).count();
+        if removed > 0 {
+            self.changed(&entries)?;
+        }
+        Ok(removed)
+    }
+
+    fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.is_expired());
+        let removed = before - entries.len();
+        if removed > 0 {
+            self.save(&entries)?;
+            *self.dirty_since.lock().map_err(|_| "Failed to lock cache")? = None;
+        }
+        Ok(removed)
+    }
+
+    fn counters(&self) -> CacheCounters {
+        fs::read_to_string(self.counters_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>> {
+        // Under the entries lock, so two caches of one process never interleave
+        let _entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        write_json(&self.counters_path(), &self.counters().plus(delta))
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let mut dirty_since = self.dirty_since.lock().map_err(|_| "Failed to lock cache")?;
+        if dirty_since.is_some() {
+            self.save(&entries)?;
+            *dirty_since = None;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for JsonFileBackend {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("⚠️  Failed to save the price cache: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_corrupt_cache_file_is_recovered() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_corrupt.json");
+        let backup = with_suffix(&path, "corrupt");
+        let _ = fs::remove_file(&backup);
+
+        // A write cut off halfway through
+        fs::write(&path, r#"{"Amsterdam-Utrecht-2": {"price_cents": 9"#)?;
+        let backend = JsonFileBackend::open(&path)?;
+        assert!(backend.entries().is_empty());
+        assert!(fs::read_to_string(&backup)?.starts_with(r#"{"Amsterdam"#));
+
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        backend.flush()?;
+        assert!(!with_suffix(&path, "tmp").exists());
+        let reopened = JsonFileBackend::open(&path)?;
+        assert_eq!(reopened.load("Amsterdam-Utrecht-2").map(|e| e.price_cents), Some(940));
+
+        fs::remove_file(&path)?;
+        fs::remove_file(&backup)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_cache_version_is_refused() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_newer.json");
+        let newer = format!(r#"{{"version": {}, "entries": {{}}}}"#, CACHE_VERSION + 1);
+        fs::write(&path, &newer)?;
+
+        assert!(JsonFileBackend::open(&path).is_err());
+        assert_eq!(fs::read_to_string(&path)?, newer);
+        assert!(!with_suffix(&path, "corrupt").exists());
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_leftover_temp_file_is_ignored() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_leftover.json");
+        let temp = with_suffix(&path, "tmp");
+        let _ = fs::remove_file(&path);
+
+        let backend = JsonFileBackend::open(&path)?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        drop(backend);
+        // A crash before the rename leaves only the temporary file behind
+        fs::write(&temp, "{")?;
+
+        let reopened = JsonFileBackend::open(&path)?;
+        assert_eq!(reopened.load("Amsterdam-Utrecht-2").map(|e| e.price_cents), Some(940));
+        reopened.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(960, 2))?;
+        reopened.flush()?;
+        assert!(!temp.exists());
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_cache_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_compressed.json.gz");
+        let _ = fs::remove_file(&path);
+
+        let backend = JsonFileBackend::open(&path)?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        backend.flush()?;
+        assert!(fs::read(&path)?.starts_with(&GZIP_MAGIC));
+        let reopened = JsonFileBackend::open(&path)?;
+        assert_eq!(reopened.load("Amsterdam-Utrecht-2").map(|e| e.price_cents), Some(940));
+
+        // Detected by content, not name: a gzipped file under a plain name
+        let renamed = env::temp_dir().join("test_cache_compressed_renamed.json");
+        fs::rename(&path, &renamed)?;
+        let detected = JsonFileBackend::open(&renamed)?;
+        assert_eq!(detected.load("Amsterdam-Utrecht-2").map(|e| e.price_cents), Some(940));
+        detected.update("Amsterdam-Gouda-2", &mut |_| CacheEntry::new(1210, 2))?;
+        detected.flush()?;
+        assert!(fs::read(&renamed)?.starts_with(&GZIP_MAGIC));
+
+        fs::remove_file(&renamed)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_writes_are_coalesced() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_coalesced.json");
+        let _ = fs::remove_file(&path);
+
+        let backend = JsonFileBackend::open(&path)?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        backend.update("Amsterdam-Gouda-2", &mut |_| CacheEntry::new(1210, 2))?;
+        assert!(!path.exists());
+
+        backend.flush()?;
+        assert_eq!(JsonFileBackend::open(&path)?.keys().len(), 2);
+
+        // Once the interval passed, the next change saves right away
+        *backend.dirty_since.lock().unwrap() = Instant::now().checked_sub(SAVE_INTERVAL);
+        backend.remove(&["Amsterdam-Gouda-2".to_string()])?;
+        assert_eq!(JsonFileBackend::open(&path)?.keys().len(), 1);
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
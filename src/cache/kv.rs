@@ -0,0 +1,423 @@
+use super::backend::{with_suffix, CacheBackend, CACHE_VERSION};
+use super::models::{CacheCounters, CacheEntry};
+use crate::migrations::{upgrade_cache, NewerCacheVersion};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Superseded records the log may hold before it is compacted, on top of
+/// the header, one record per live entry and the counters
+const COMPACT_SLACK: usize = 1000;
+
+/// First line of the log, the format version of the entries that follow
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// One line of the log after the header; the last record of a key wins
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Record<E> {
+    Set { key: String, entry: E },
+    Remove { key: String },
+    /// The usage counters summed over every process so far
+    Counters(CacheCounters),
+}
+
+/// Entries in an append-only log, one JSON record per line, replayed into
+/// memory on open. Every change appends one record and syncs it, so a
+/// write costs the size of one entry instead of the whole cache and is on
+/// disk before `update()` returns; a crash can only cut off the record
+/// being written, which is skipped on the next open.
+///
+/// The log is compacted (rewritten through `<path>.tmp` and a rename, like
+/// the JSON file) once superseded records outnumber the live entries by
+/// `COMPACT_SLACK`, and by `remove_expired()`. Usage counters are records
+/// of the log too.
+///
+/// Logs of an older `CACHE_VERSION` are upgraded on open, newer ones are
+/// refused. One process uses a log at a time: opening takes an exclusive
+/// `flock` on `<path>.lock` (not the log itself, whose inode compaction
+/// replaces) and fails while another process holds it, instead of losing
+/// its writes or appending to an unlinked log after a compaction.
+pub struct KvBackend {
+    path: PathBuf,
+    state: Mutex<KvState>,
+    /// Held for the lifetime of the backend, the lock is released on drop
+    _lock: File,
+}
+
+struct KvState {
+    entries: HashMap<String, CacheEntry>,
+    counters: CacheCounters,
+    log: File,
+    /// Records in the log, superseded ones included
+    records: usize,
+}
+
+impl KvBackend {
+    /// Open or create the log at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let lock = lock(&path)?;
+
+        let replayed = match File::open(&path) {
+            Ok(file) => match replay(file) {
+                Ok(replayed) => Some(replayed),
+                Err(e) if e.is::<NewerCacheVersion>() => {
+                    return Err(format!("{}: {}", path.display(), e).into());
+                }
+                Err(e) => {
+                    // Keep the unreadable log for inspection instead of overwriting it
+                    let backup = with_suffix(&path, "corrupt");
+                    fs::rename(&path, &backup)?;
+                    eprintln!(
+                        "⚠️  Failed to read cache log, moved it to {} and starting fresh: {}",
+                        backup.display(),
+                        e
+                    );
+                    None
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let (entries, counters, records, rewrite) = match replayed {
+            Some(replayed) => replayed,
+            None => (HashMap::new(), CacheCounters::default(), 0, true),
+        };
+        let mut state = KvState {
+            entries,
+            counters,
+            log: OpenOptions::new().create(true).append(true).open(&path)?,
+            records,
+        };
+        if rewrite {
+            state.compact(&path)?;
+        }
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+            _lock: lock,
+        })
+    }
+}
+
+/// Take the exclusive lock of the log at `path`, failing at once when
+/// another process has it
+#[cfg(unix)]
+fn lock(path: &Path) -> Result<File, Box<dyn std::error::Error>> {
+    use std::os::unix::io::AsRawFd;
+
+    let lock_path = with_suffix(path, "lock");
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+    // SAFETY: the descriptor is open for as long as `file` lives
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let e = std::io::Error::last_os_error();
+        if e.kind() == std::io::ErrorKind::WouldBlock {
+            return Err(format!(
+                "{} is in use by another nstimes process (locked through {})",
+                path.display(),
+                lock_path.display()
+            )
+            .into());
+        }
+        return Err(format!("{}: {}", lock_path.display(), e).into());
+    }
+    Ok(file)
+}
+
+/// Other platforms have no `flock`; the log is opened unlocked
+#[cfg(not(unix))]
+fn lock(path: &Path) -> Result<File, Box<dyn std::error::Error>> {
+    let lock_path = with_suffix(path, "lock");
+    Ok(OpenOptions::new().create(true).truncate(false).write(true).open(lock_path)?)
+}
+
+/// The entries, counters and record count of a log, and whether it needs
+/// rewriting (skipped records, an older version or no header yet)
+type Replayed = (HashMap<String, CacheEntry>, CacheCounters, usize, bool);
+
+fn replay(file: File) -> Result<Replayed, Box<dyn std::error::Error>> {
+    let mut lines = BufReader::new(file).lines();
+    let header = match lines.next() {
+        Some(line) => serde_json::from_str::<Header>(&line?)?,
+        None => return Ok((HashMap::new(), CacheCounters::default(), 0, true)),
+    };
+    if header.version > CACHE_VERSION {
+        return Err(NewerCacheVersion(header.version).into());
+    }
+
+    let mut entries = serde_json::Map::new();
+    let mut counters = CacheCounters::default();
+    let mut records = 1;
+    let mut skipped = 0;
+    for line in lines {
+        match serde_json::from_str::<Record<Value>>(&line?) {
+            Ok(Record::Set { key, entry }) => {
+                entries.insert(key, entry);
+            }
+            Ok(Record::Remove { key }) => {
+                entries.remove(&key);
+            }
+            Ok(Record::Counters(total)) => counters = total,
+            // A record cut off by a crash
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        }
+        records += 1;
+    }
+    if skipped > 0 {
+        eprintln!("⚠️  Skipped {} unreadable record(s) of the cache log", skipped);
+    }
+
+    let upgrade = header.version < CACHE_VERSION;
+    let mut file = serde_json::json!({"version": header.version, "entries": entries});
+    if upgrade {
+        file = upgrade_cache(file, &mut Vec::new())?;
+    }
+    let entries = serde_json::from_value(file["entries"].take())?;
+    Ok((entries, counters, records, upgrade || skipped > 0))
+}
+
+impl KvState {
+    /// Append `record` to the log and sync it. A failed write is cut off
+    /// again, so a partial record can't run into the next one
+    fn append(
+        &mut self,
+        record: &Record<&CacheEntry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let length = self.log.metadata()?.len();
+        if let Err(e) = self.log.write_all(&line).and_then(|()| self.log.sync_data()) {
+            let _ = self.log.set_len(length);
+            return Err(e.into());
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Compact the log once it holds `COMPACT_SLACK` superseded records
+    fn compact_if_due(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if self.records > self.entries.len() + 2 + COMPACT_SLACK {
+            self.compact(path)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the log with one record per live entry: write and fsync
+    /// `<path>.tmp`, rename it over the log and append to the new file
+    fn compact(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let temp = with_suffix(path, "tmp");
+        let mut writer = BufWriter::new(File::create(&temp)?);
+        serde_json::to_writer(&mut writer, &Header { version: CACHE_VERSION })?;
+        writer.write_all(b"\n")?;
+        for (key, entry) in &self.entries {
+            let record = Record::Set { key: key.clone(), entry };
+            serde_json::to_writer(&mut writer, &record)?;
+            writer.write_all(b"\n")?;
+        }
+        serde_json::to_writer(&mut writer, &Record::<&CacheEntry>::Counters(self.counters))?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&temp, path)?;
+
+        self.log = OpenOptions::new().append(true).open(path)?;
+        self.records = self.entries.len() + 2;
+        Ok(())
+    }
+}
+
+impl CacheBackend for KvBackend {
+    fn load(&self, key: &str) -> Option<CacheEntry> {
+        self.state.lock().ok()?.entries.get(key).cloned()
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+        let entry = update(state.entries.get(key).cloned());
+        state.append(&Record::Set { key: key.to_string(), entry: &entry })?;
+        state.entries.insert(key.to_string(), entry);
+        state.compact_if_due(&self.path)
+    }
+
+    fn entries(&self) -> Vec<CacheEntry> {
+        match self.state.lock() {
+            Ok(state) => state.entries.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        match self.state.lock() {
+            Ok(state) => state.entries.keys().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+        let mut removed = 0;
+        for key in keys {
+            if state.entries.remove(key).is_some() {
+                state.append(&Record::Remove { key: key.clone() })?;
+                removed += 1;
+            }
+        }
+        state.compact_if_due(&self.path)?;
+        Ok(removed)
+    }
+
+    fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+        let before = state.entries.len();
+        state.entries.retain(|_, entry| !entry.is_expired());
+        let removed = before - state.entries.len();
+        if removed > 0 {
+            state.compact(&self.path)?;
+        }
+        Ok(removed)
+    }
+
+    fn counters(&self) -> CacheCounters {
+        self.state.lock().map(|state| state.counters).unwrap_or_default()
+    }
+
+    fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>> {
+        let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+        state.counters = state.counters.plus(delta);
+        let record = Record::Counters(state.counters);
+        state.append(&record)?;
+        state.compact_if_due(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_log_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_round_trip.kv");
+        let _ = fs::remove_file(&path);
+
+        let backend = KvBackend::open(&path)?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        backend.update("Amsterdam-Gouda-2", &mut |_| CacheEntry::new(1210, 2))?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(960, 2))?;
+        backend.remove(&["Amsterdam-Gouda-2".to_string()])?;
+        backend.add_counters(&CacheCounters { hits: 3, ..Default::default() })?;
+        // Every change is in the log before the backend is dropped
+        let synced = fs::read_to_string(&path)?;
+        drop(backend);
+        assert_eq!(fs::read_to_string(&path)?, synced);
+        let reopened = KvBackend::open(&path)?;
+
+        assert_eq!(reopened.keys(), ["Amsterdam-Utrecht-2"]);
+        assert_eq!(reopened.load("Amsterdam-Utrecht-2").map(|e| e.price_cents), Some(960));
+        assert_eq!(reopened.counters().hits, 3);
+
+        fs::remove_file(&path)?;
+        fs::remove_file(with_suffix(&path, "lock"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_torn_record_is_skipped() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_torn.kv");
+        let _ = fs::remove_file(&path);
+
+        let backend = KvBackend::open(&path)?;
+        backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(940, 2))?;
+        drop(backend);
+        // A crash halfway through appending the next record
+        let mut log = OpenOptions::new().append(true).open(&path)?;
+        log.write_all(br#"{"set":{"key":"Amsterdam-Gouda-2","entry":{"price_c"#)?;
+
+        let reopened = KvBackend::open(&path)?;
+        assert_eq!(reopened.keys(), ["Amsterdam-Utrecht-2"]);
+        reopened.update("Amsterdam-Gouda-2", &mut |_| CacheEntry::new(1210, 2))?;
+        drop(reopened);
+        assert_eq!(KvBackend::open(&path)?.keys().len(), 2);
+
+        fs::remove_file(&path)?;
+        fs::remove_file(with_suffix(&path, "lock"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_is_compacted() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_compacted.kv");
+        let _ = fs::remove_file(&path);
+
+        let backend = KvBackend::open(&path)?;
+        // One more superseded record than the slack allows
+        let last = COMPACT_SLACK as u32 + 1;
+        for price in 0..=last {
+            backend.update("Amsterdam-Utrecht-2", &mut |_| CacheEntry::new(price, 2))?;
+        }
+        // Header, the one entry and the counters
+        assert_eq!(fs::read_to_string(&path)?.lines().count(), 3);
+        assert!(!with_suffix(&path, "tmp").exists());
+        drop(backend);
+        let reopened = KvBackend::open(&path)?;
+        let price = reopened.load("Amsterdam-Utrecht-2").map(|e| e.price_cents);
+        assert_eq!(price, Some(last));
+
+        fs::remove_file(&path)?;
+        fs::remove_file(with_suffix(&path, "lock"))?;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_log_is_locked() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_locked.kv");
+        let _ = fs::remove_file(&path);
+
+        let backend = KvBackend::open(&path)?;
+        let e = KvBackend::open(&path).err().ok_or("opened a locked log")?;
+        assert!(e.to_string().contains("in use by another nstimes process"));
+        drop(backend);
+        assert!(KvBackend::open(&path).is_ok());
+
+        fs::remove_file(&path)?;
+        fs::remove_file(with_suffix(&path, "lock"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_log_version_is_refused() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_newer.kv");
+        let newer = format!("{{\"version\":{}}}\n", CACHE_VERSION + 1);
+        fs::write(&path, &newer)?;
+
+        assert!(KvBackend::open(&path).is_err());
+        assert_eq!(fs::read_to_string(&path)?, newer);
+
+        fs::remove_file(&path)?;
+        fs::remove_file(with_suffix(&path, "lock"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use super::backend::CacheBackend;
+use super::models::{CacheCounters, CacheEntry};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+/// A least-recently-used layer in front of another backend, unbounded
+/// unless `--cache-max-entries` sets a bound.
+///
+/// Hot routes are answered from memory instead of the backend (no file lock,
+/// no Redis round trip). With a bound, the least recently used route is
+/// evicted from memory right away, and a backend this process owns (a cache
+/// file) also has it removed with the next write, flush or when the cache is
+/// dropped. A shared backend (Redis) keeps it: other servers may still be
+/// using the route, so there the bound only limits this process's memory.
+/// The in-memory state is never locked across a backend call.
+pub struct LruBackend {
+    inner: Box<dyn CacheBackend>,
+    max_entries: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    /// Ever-increasing use counter, the recency of a slot
+    tick: u64,
+    /// The keys this process used with their entry once loaded, plus every
+    /// key of an owned backend once bounded
+    slots: HashMap<String, Slot>,
+    /// `(last use, key)` of every slot, least recently used first; keys only
+    /// known from the backend count as used at tick 0
+    by_use: BTreeSet<(u64, String)>,
+    /// Evicted keys the backend still holds
+    evicted: Vec<String>,
+    /// Whether the keys already in the backend are tracked
+    has_backend_keys: bool,
+}
+
+struct Slot {
+    used: u64,
+    entry: Option<CacheEntry>,
+}
+
+impl LruState {
+    /// Mark `key` as just used, holding `entry` when given
+    fn touch(&mut self, key: &str, entry: Option<CacheEntry>) {
+        self.tick += 1;
+        let used = self.tick;
+        match self.slots.get_mut(key) {
+            Some(slot) => {
+                self.by_use.remove(&(slot.used, key.to_string()));
+                slot.used = used;
+                if entry.is_some() {
+                    slot.entry = entry;
+                }
+            }
+            None => {
+                self.slots.insert(key.to_string(), Slot { used, entry });
+            }
+        }
+        self.by_use.insert((used, key.to_string()));
+        self.evicted.retain(|evicted| evicted != key);
+    }
+
+    /// Stop tracking `key`
+    fn forget(&mut self, key: &str) {
+        if let Some(slot) = self.slots.remove(key) {
+            self.by_use.remove(&(slot.used, key.to_string()));
+        }
+    }
+
+    /// Evict the least recently used keys until at most `max_entries` remain,
+    /// queueing them for removal from the backend when it is `owned`
+    fn evict(&mut self, max_entries: usize, owned: bool) {
+        while self.slots.len() > max_entries {
+            let Some((_, oldest)) = self.by_use.pop_first() else {
+                return;
+            };
+            self.slots.remove(&oldest);
+            if owned {
+                self.evicted.push(oldest);
+            }
+        }
+    }
+}
+
+impl LruBackend {
+    /// Put an LRU of `max_entries` routes (`usize::MAX` for no bound) in
+    /// front of `inner`. An owned backend already holding more routes is
+    /// trimmed with the first write or flush.
+    pub fn new(inner: Box<dyn CacheBackend>, max_entries: usize) -> Self {
+        let mut lru = Self {
+            inner,
+            max_entries: usize::MAX,
+            state: Mutex::new(LruState::default()),
+        };
+        lru.set_max_entries(max_entries);
+        lru
+    }
+
+    /// Change the bound, evicting what no longer fits
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        let owned = !self.inner.is_shared();
+        let Ok(state) = self.state.get_mut() else {
+            return;
+        };
+        // Trimming an owned backend needs every key it holds; a shared one
+        // is never listed, that would be a full scan of the Redis database
+        if owned && max_entries < usize::MAX && !state.has_backend_keys {
+            for key in self.inner.keys() {
+                if !state.slots.contains_key(&key) {
+                    state.by_use.insert((0, key.clone()));
+                    state.slots.insert(key, Slot { used: 0, entry: None });
+                }
+            }
+            state.has_backend_keys = true;
+        }
+        state.evict(max_entries, owned);
+    }
+
+    /// Remove the evicted keys from the backend, outside the state lock
+    fn remove_evicted(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let evicted = match self.state.lock() {
+            Ok(mut state) => std::mem::take(&mut state.evicted),
+            Err(_) => return Err("Failed to lock cache".into()),
+        };
+        if evicted.is_empty() {
+            return Ok(());
+        }
+        if let Err(e) = self.inner.remove(&evicted) {
+            // Try again with the next write or flush
+            if let Ok(mut state) = self.state.lock() {
+                state.evicted.extend(evicted);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+impl CacheBackend for LruBackend {
+    fn load(&self, key: &str) -> Option<CacheEntry> {
+        {
+            let mut state = self.state.lock().ok()?;
+            if let Some(entry) = state.slots.get(key).and_then(|slot| slot.entry.clone()) {
+                state.touch(key, None);
+                return Some(entry);
+            }
+        }
+
+        // Not in memory yet, or stored by another process sharing the backend
+        let entry = self.inner.load(key)?;
+        let mut state = self.state.lock().ok()?;
+        state.touch(key, Some(entry.clone()));
+        state.evict(self.max_entries, !self.inner.is_shared());
+        Some(entry)
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut stored = None;
+        self.inner.update(key, &mut |previous| {
+            let entry = update(previous);
+            stored = Some(entry.clone());
+            entry
+        })?;
+        {
+            let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+            state.touch(key, stored);
+            state.evict(self.max_entries, !self.inner.is_shared());
+        }
+        self.remove_evicted()
+    }
+
+    fn entries(&self) -> Vec<CacheEntry> {
+        if let Err(e) = self.remove_evicted() {
+            eprintln!("⚠️  Failed to remove evicted cache entries: {}", e);
+        }
+        self.inner.entries()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let keys = self.inner.keys();
+        match self.state.lock() {
+            Ok(state) if !state.evicted.is_empty() => {
+                keys.into_iter().filter(|key| !state.evicted.contains(key)).collect()
+            }
+            _ => keys,
+        }
+    }
+
+    fn is_shared(&self) -> bool {
+        self.inner.is_shared()
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>> {
+        {
+            let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+            for key in keys {
+                state.forget(key);
+            }
+        }
+        self.remove_evicted()?;
+        self.inner.remove(keys)
+    }
+
+    fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        self.remove_evicted()?;
+        let removed = self.inner.remove_expired()?;
+        if removed == 0 {
+            return Ok(0);
+        }
+        // Listing an owned backend is cheap, a shared one is never listed
+        let remaining: Option<HashSet<String>> =
+            (!self.inner.is_shared()).then(|| self.inner.keys().into_iter().collect());
+        let mut state = self.state.lock().map_err(|_| "Failed to lock cache")?;
+        let gone: Vec<String> = state
+            .slots
+            .iter()
+            .filter(|(key, slot)| match &remaining {
+                Some(remaining) => !remaining.contains(*key),
+                None => slot.entry.as_ref().is_some_and(CacheEntry::is_expired),
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in gone {
+            state.forget(&key);
+        }
+        Ok(removed)
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.remove_evicted()?;
+        self.inner.flush()
+    }
+
+    fn counters(&self) -> CacheCounters {
+        self.inner.counters()
+    }
+
+    fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.add_counters(delta)
+    }
+}
+
+impl Drop for LruBackend {
+    fn drop(&mut self) {
+        if let Err(e) = CacheBackend::flush(self) {
+            eprintln!("⚠️  Failed to save the price cache: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{JsonFileBackend, MemoryBackend};
+    use std::env;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A memory backend posing as one shared with other servers, counting
+    /// the calls an LRU must not make on it
+    struct Shared {
+        inner: MemoryBackend,
+        listed: Arc<AtomicUsize>,
+        removed: Arc<AtomicUsize>,
+    }
+
+    impl CacheBackend for Shared {
+        fn load(&self, key: &str) -> Option<CacheEntry> {
+            self.inner.load(key)
+        }
+
+        fn update(
+            &self,
+            key: &str,
+            update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            self.inner.update(key, update)
+        }
+
+        fn entries(&self) -> Vec<CacheEntry> {
+            self.inner.entries()
+        }
+
+        fn keys(&self) -> Vec<String> {
+            self.listed.fetch_add(1, Ordering::Relaxed);
+            self.inner.keys()
+        }
+
+        fn is_shared(&self) -> bool {
+            true
+        }
+
+        fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>> {
+            self.removed.fetch_add(keys.len(), Ordering::Relaxed);
+            self.inner.remove(keys)
+        }
+
+        fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>> {
+            self.inner.remove_expired()
+        }
+
+        fn counters(&self) -> CacheCounters {
+            self.inner.counters()
+        }
+
+        fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>> {
+            self.inner.add_counters(delta)
+        }
+    }
+
+    fn entry(price_cents: u32) -> CacheEntry {
+        CacheEntry::new(price_cents, 2)
+    }
+
+    #[test]
+    fn test_least_recently_used_route_is_evicted() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_lru.json");
+        let _ = fs::remove_file(&path);
+
+        let lru = LruBackend::new(Box::new(JsonFileBackend::open(&path)?), 2);
+        lru.update("a", &mut |_| entry(100))?;
+        lru.update("b", &mut |_| entry(200))?;
+        assert_eq!(lru.load("a").map(|e| e.price_cents), Some(100));
+        lru.update("c", &mut |_| entry(300))?;
+
+        assert!(lru.load("b").is_none());
+        assert_eq!(lru.load("a").map(|e| e.price_cents), Some(100));
+        drop(lru);
+
+        // The eviction reached the file
+        let mut keys = JsonFileBackend::open(&path)?.keys();
+        keys.sort();
+        assert_eq!(keys, ["a", "c"]);
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_cache_is_trimmed_on_flush() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_cache_lru_trim.json");
+        let _ = fs::remove_file(&path);
+
+        let file = JsonFileBackend::open(&path)?;
+        for key in ["a", "b", "c", "d"] {
+            file.update(key, &mut |_| entry(100))?;
+        }
+        drop(file);
+
+        let lru = LruBackend::new(Box::new(JsonFileBackend::open(&path)?), 3);
+        assert_eq!(lru.keys().len(), 3);
+        assert_eq!(JsonFileBackend::open(&path)?.keys().len(), 4);
+        drop(lru);
+        assert_eq!(JsonFileBackend::open(&path)?.keys().len(), 3);
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_backend_is_never_listed_or_trimmed() -> Result<(), Box<dyn std::error::Error>> {
+        let (listed, removed) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let shared = Shared {
+            inner: MemoryBackend::new(),
+            listed: Arc::clone(&listed),
+            removed: Arc::clone(&removed),
+        };
+        shared.update("a", &mut |_| entry(100))?;
+
+        let lru = LruBackend::new(Box::new(shared), 1);
+        lru.update("b", &mut |_| entry(200))?;
+        lru.update("c", &mut |_| entry(300))?;
+        lru.flush()?;
+
+        // "b" left this process's memory but is still there for other servers
+        assert_eq!(lru.load("b").map(|e| e.price_cents), Some(200));
+        assert_eq!(lru.load("a").map(|e| e.price_cents), Some(100));
+        assert_eq!((listed.load(Ordering::Relaxed), removed.load(Ordering::Relaxed)), (0, 0));
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use super::backend::CacheBackend;
+use super::models::{CacheCounters, CacheEntry};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Entries in memory only, gone with the process. For tests and library
+/// users who want price caching without a file or server; also the
+/// smallest example of a `CacheBackend` to start a new backend from.
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    counters: Mutex<CacheCounters>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheBackend for MemoryBackend {
+    fn load(&self, key: &str) -> Option<CacheEntry> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    fn update(
+        &self,
+        key: &str,
+        update: &mut dyn FnMut(Option<CacheEntry>) -> CacheEntry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let entry = update(entries.remove(key));
+        entries.insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    fn entries(&self) -> Vec<CacheEntry> {
+        match self.entries.lock() {
+            Ok(entries) => entries.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        match self.entries.lock() {
+            Ok(entries) => entries.keys().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        Ok(keys.iter().filter(|key| entries.remove(*key).is_some()).count())
+    }
+
+    fn remove_expired(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.is_expired());
+        Ok(before - entries.len())
+    }
+
+    fn counters(&self) -> CacheCounters {
+        self.counters.lock().map(|counters| *counters).unwrap_or_default()
+    }
+
+    fn add_counters(&self, delta: &CacheCounters) -> Result<(), Box<dyn std::error::Error>> {
+        let mut counters = self.counters.lock().map_err(|_| "Failed to lock cache")?;
+        *counters = counters.plus(delta);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{PriceCache, PriceKey};
+
+    #[test]
+    fn test_price_cache_over_memory_backend() -> Result<(), Box<dyn std::error::Error>> {
+        let cache = PriceCache::from_backend("memory", Box::new(MemoryBackend::new()));
+        let key = PriceKey::new("Amsterdam", "Utrecht", 2);
+        assert_eq!(cache.get(&key), None);
+        cache.set(&key, 940)?;
+        assert_eq!(cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+
+        cache.flush()?;
+        let stats = cache.stats();
+        assert_eq!((stats.total_entries, stats.all_time.hits, stats.all_time.misses), (1, 1, 1));
+        assert_eq!(cache.path(), "memory");
+        Ok(())
+    }
+}
@@ -1,5 +1,29 @@
+#[cfg(any(feature = "cli", feature = "server"))]
+pub mod args;
+pub mod backend;
+#[cfg(feature = "cache-kv")]
+pub mod kv;
+pub mod lru;
+pub mod memory;
 pub mod models;
+pub mod redis;
+pub mod responses;
 pub mod service;
 
-pub use models::CacheEntry;
-pub use service::{CacheStats, PriceCache};
+#[cfg(any(feature = "cli", feature = "server"))]
+pub use args::CacheArgs;
+pub use backend::{
+    read_entries, write_entries, CacheBackend, CacheFile, JsonFileBackend, CACHE_VERSION,
+    SAVE_INTERVAL,
+};
+#[cfg(feature = "cache-kv")]
+pub use kv::KvBackend;
+pub use lru::LruBackend;
+pub use memory::MemoryBackend;
+pub use models::{CacheCounters, CacheEntry, CacheTtl, PriceKey};
+pub use redis::{RedisBackend, REDIS_SCHEME};
+pub use responses::ResponseCache;
+pub use service::{
+    cache_spec, read_owned_file, user_cache_dir, CacheStats, CachedPrice, ImportReport, PriceCache,
+    Refresh, KV_PREFIX, NO_PRICES_SECS, REVALIDATE_INTERVAL,
+};
@@ -1,4 +1,103 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How long a newly cached price stays valid (`--cache-ttl`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum CacheTtl {
+    /// Until the next January 1st, when NS changes its tariffs ("tariff-year")
+    #[default]
+    TariffYear,
+    /// A number of days after the price was fetched ("30d", "2w")
+    Days(u32),
+    /// Until a fixed date ("2026-06-01")
+    Until(NaiveDate),
+    /// Seconds a route without prices is remembered for, the policy of
+    /// `CacheEntry::no_prices()` markers ("no-prices:3600s"). Not a valid
+    /// `--cache-ttl`, so only a stored entry can carry it
+    NoPrices(u64),
+}
+
+impl CacheTtl {
+    /// Expiry date of a price fetched on `today`
+    pub fn expires_at(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            CacheTtl::TariffYear => {
+                NaiveDate::from_ymd_opt(today.year() + 1, 1, 1).unwrap_or(NaiveDate::MAX)
+            }
+            CacheTtl::Days(days) => today
+                .checked_add_days(chrono::Days::new(u64::from(*days)))
+                .unwrap_or(NaiveDate::MAX),
+            CacheTtl::Until(date) => *date,
+            CacheTtl::NoPrices(secs) => today
+                .checked_add_days(chrono::Days::new(secs.div_ceil(86_400)))
+                .unwrap_or(NaiveDate::MAX),
+        }
+    }
+}
+
+impl FromStr for CacheTtl {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let invalid = || {
+            format!(
+                "invalid cache TTL '{}', expected tariff-year, a duration like 30d or 2w, or a date like 2026-06-01",
+                spec
+            )
+        };
+        if spec.eq_ignore_ascii_case("tariff-year") {
+            return Ok(CacheTtl::TariffYear);
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+            return Ok(CacheTtl::Until(date));
+        }
+
+        let (count, days_per_unit) = match spec.char_indices().last() {
+            Some((at, 'd')) => (&spec[..at], 1),
+            Some((at, 'w')) => (&spec[..at], 7),
+            _ => return Err(invalid()),
+        };
+        match count.parse::<u32>().ok().and_then(|n| n.checked_mul(days_per_unit)) {
+            Some(days) if days > 0 => Ok(CacheTtl::Days(days)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for CacheTtl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheTtl::TariffYear => write!(f, "tariff-year"),
+            CacheTtl::Days(days) => write!(f, "{}d", days),
+            CacheTtl::Until(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            CacheTtl::NoPrices(secs) => write!(f, "no-prices:{}s", secs),
+        }
+    }
+}
+
+impl TryFrom<String> for CacheTtl {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, String> {
+        match spec.strip_prefix("no-prices:").and_then(|secs| secs.strip_suffix('s')) {
+            Some(secs) => secs
+                .parse()
+                .map(CacheTtl::NoPrices)
+                .map_err(|_| format!("invalid no-prices TTL '{}'", spec)),
+            None => spec.parse(),
+        }
+    }
+}
+
+impl From<CacheTtl> for String {
+    fn from(ttl: CacheTtl) -> Self {
+        ttl.to_string()
+    }
+}
 
 /// A cached price entry with expiration date
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,25 +106,121 @@ pub struct CacheEntry {
     pub price_cents: u32,
     /// Travel class (1 or 2)
     pub travel_class: u8,
-    /// Expiration date in ISO format (YYYY-MM-DD)
-    /// Prices expire on January 1st each year
+    /// Expiration date in ISO format (YYYY-MM-DD), set from `ttl`
     pub expires_at: String,
+    /// The policy `expires_at` was set by. Entries written before the policy
+    /// was stored all expired on January 1st
+    #[serde(default)]
+    pub ttl: CacheTtl,
+    /// The price in every tariff year it was fetched in, oldest first.
+    /// Empty in cache files written before the history was kept
+    #[serde(default)]
+    pub history: Vec<PriceRecord>,
+    /// Set when the price API had no prices for the route: the entry is a
+    /// marker valid until then instead of a price (`price_cents` is 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_prices_until: Option<DateTime<Utc>>,
+    /// Price per adult in cents for group fares, where `price_cents` is the
+    /// total; omitted when the two are the same
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_per_adult_cents: Option<u32>,
+}
+
+/// The price of a route in one tariff year (NS tariffs change on January 1st)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceRecord {
+    pub tariff_year: i32,
+    pub price_cents: u32,
 }
 
 impl CacheEntry {
     /// Create a new cache entry with expiration set to next January 1st
     pub fn new(price_cents: u32, travel_class: u8) -> Self {
-        let expires_at = Self::next_january_first();
-        Self {
+        Self::with_ttl(price_cents, travel_class, CacheTtl::TariffYear)
+    }
+
+    /// Create a new cache entry expiring as `ttl` says
+    pub fn with_ttl(price_cents: u32, travel_class: u8, ttl: CacheTtl) -> Self {
+        use chrono::Local;
+
+        let today = Local::now().date_naive();
+        let mut entry = Self {
             price_cents,
             travel_class,
-            expires_at,
+            expires_at: ttl.expires_at(today).format("%Y-%m-%d").to_string(),
+            ttl,
+            history: Vec::new(),
+            no_prices_until: None,
+            price_per_adult_cents: None,
+        };
+        entry.record(price_cents, today.year());
+        entry
+    }
+
+    /// A marker for a route without prices, valid for `ttl` from now
+    pub fn no_prices(travel_class: u8, ttl: std::time::Duration) -> Self {
+        let until = Utc::now() + ttl;
+        Self {
+            price_cents: 0,
+            travel_class,
+            expires_at: until.date_naive().format("%Y-%m-%d").to_string(),
+            ttl: CacheTtl::NoPrices(ttl.as_secs()),
+            history: Vec::new(),
+            no_prices_until: Some(until),
+            price_per_adult_cents: None,
         }
     }
 
+    /// Whether this entry records that the route has no prices
+    pub fn is_no_prices(&self) -> bool {
+        self.no_prices_until.is_some()
+    }
+
+    /// Store a newly fetched price, keeping the prices of earlier tariff years
+    pub fn record(&mut self, price_cents: u32, tariff_year: i32) {
+        self.price_cents = price_cents;
+        match self.history.last_mut() {
+            Some(last) if last.tariff_year == tariff_year => last.price_cents = price_cents,
+            _ => self.history.push(PriceRecord {
+                tariff_year,
+                price_cents,
+            }),
+        }
+    }
+
+    /// Tariff year the price was fetched in: the year before `expires_at`
+    /// under the tariff-year policy, else the latest year of the history
+    pub fn tariff_year(&self) -> Option<i32> {
+        match self.ttl {
+            CacheTtl::TariffYear => NaiveDate::parse_from_str(&self.expires_at, "%Y-%m-%d")
+                .ok()
+                .map(|expiry| expiry.year() - 1),
+            _ => self.history.last().map(|record| record.tariff_year),
+        }
+    }
+
+    /// `expires_at` as a date, None when it can't be read
+    pub fn expiry_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.expires_at, "%Y-%m-%d").ok()
+    }
+
+    /// Add the tariff years of `other` this entry has no price for
+    pub fn merge_history(&mut self, other: &[PriceRecord]) {
+        for record in other {
+            if !self.history.iter().any(|own| own.tariff_year == record.tariff_year) {
+                self.history.push(record.clone());
+            }
+        }
+        self.history.sort_by_key(|record| record.tariff_year);
+    }
+
     /// Check if this cache entry has expired
     pub fn is_expired(&self) -> bool {
-        use chrono::{Local, NaiveDate};
+        use chrono::Local;
+
+        if let Some(until) = self.no_prices_until {
+            return Utc::now() >= until;
+        }
 
         let now = Local::now().date_naive();
 
@@ -37,17 +232,142 @@ impl CacheEntry {
             true
         }
     }
+}
+
+/// Every parameter that changes the price of a route, the identity of a
+/// cached price. Stored as
+/// `from|to|class=2|type=single|adults=1|children=0|joint=false|discount=NO_DISCOUNT`
+/// with the stations in alphabetical order, as a fare is the same both
+/// ways; `|` never occurs in station names, unlike the `-` of legacy keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceKey {
+    pub from: String,
+    pub to: String,
+    pub travel_class: u8,
+    /// Price API travel type: "single" or "return"
+    pub travel_type: String,
+    pub adults: u32,
+    pub children: u32,
+    pub joint: bool,
+    /// Price API discount, None for the full fare
+    pub discount: Option<String>,
+}
+
+/// `discount` of full fares in a stored key
+const FULL_FARE: &str = "NO_DISCOUNT";
+
+impl PriceKey {
+    /// The full fare of a single ticket for one adult
+    pub fn new(from: &str, to: &str, travel_class: u8) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            travel_class,
+            travel_type: "single".to_string(),
+            adults: 1,
+            children: 0,
+            joint: false,
+            discount: None,
+        }
+    }
 
-    /// Calculate the next January 1st from today
-    fn next_january_first() -> String {
-        use chrono::{Datelike, Local};
+    /// The same fare with a price API discount (None for the full fare)
+    pub fn with_discount(mut self, discount: Option<&str>) -> Self {
+        self.discount = discount.map(str::to_string);
+        self
+    }
 
-        let now = Local::now();
-        let current_year = now.year();
+    /// Read a stored key; None for keys in another (legacy) form
+    pub fn parse(key: &str) -> Option<Self> {
+        let mut parts = key.split('|');
+        let (from, to) = (parts.next()?, parts.next()?);
+        let mut field = |name: &str| parts.next()?.strip_prefix(name)?.strip_prefix('=');
+        let travel_class = field("class")?.parse().ok()?;
+        let travel_type = field("type")?.to_string();
+        let adults = field("adults")?.parse().ok()?;
+        let children = field("children")?.parse().ok()?;
+        let joint = field("joint")?.parse().ok()?;
+        let discount = field("discount")?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            travel_class,
+            travel_type,
+            adults,
+            children,
+            joint,
+            discount: (discount != FULL_FARE).then(|| discount.to_string()),
+        })
+    }
+}
 
-        // Next January 1st is always in the next year
-        let next_year = current_year + 1;
-        format!("{}-01-01", next_year)
+impl fmt::Display for PriceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, second) = if self.from < self.to {
+            (&self.from, &self.to)
+        } else {
+            (&self.to, &self.from)
+        };
+        write!(
+            f,
+            "{}|{}|class={}|type={}|adults={}|children={}|joint={}|discount={}",
+            first,
+            second,
+            self.travel_class,
+            self.travel_type,
+            self.adults,
+            self.children,
+            self.joint,
+            self.discount.as_deref().unwrap_or(FULL_FARE)
+        )
+    }
+}
+
+/// How well the price cache is doing: `hits` and `misses` count the
+/// lookups, `stale_hits` the misses answered with an expired price while the
+/// price API was degraded, `fetches` the prices asked from the price API
+/// while the cache was enabled (uncacheable queries included)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct CacheCounters {
+    pub hits: u64,
+    pub misses: u64,
+    pub stale_hits: u64,
+    pub fetches: u64,
+}
+
+impl CacheCounters {
+    /// Sum of two sets of counters
+    pub fn plus(&self, other: &CacheCounters) -> CacheCounters {
+        CacheCounters {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            stale_hits: self.stale_hits + other.stale_hits,
+            fetches: self.fetches + other.fetches,
+        }
+    }
+
+    /// What was counted since `earlier`
+    pub fn since(&self, earlier: &CacheCounters) -> CacheCounters {
+        CacheCounters {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+            stale_hits: self.stale_hits.saturating_sub(earlier.stale_hits),
+            fetches: self.fetches.saturating_sub(earlier.fetches),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == CacheCounters::default()
+    }
+
+    /// Share of lookups answered from the cache, stale answers included
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| (self.hits + self.stale_hits) as f64 / lookups as f64)
     }
 }
 
@@ -55,6 +375,18 @@ impl CacheEntry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_counter_arithmetic() {
+        let earlier = CacheCounters { hits: 2, misses: 1, stale_hits: 0, fetches: 1 };
+        let now = CacheCounters { hits: 5, misses: 3, stale_hits: 1, fetches: 2 };
+        let delta = now.since(&earlier);
+        assert_eq!(delta, CacheCounters { hits: 3, misses: 2, stale_hits: 1, fetches: 1 });
+        assert_eq!(earlier.plus(&delta), now);
+        assert_eq!(now.hit_rate(), Some(0.75));
+        assert_eq!(CacheCounters::default().hit_rate(), None);
+        assert!(now.since(&now).is_empty());
+    }
+
     #[test]
     fn test_expiration_format() {
         let entry = CacheEntry::new(1000, 2);
@@ -69,6 +401,10 @@ mod tests {
             price_cents: 1000,
             travel_class: 2,
             expires_at: "2020-01-01".to_string(),
+            ttl: CacheTtl::TariffYear,
+            history: Vec::new(),
+            no_prices_until: None,
+            price_per_adult_cents: None,
         };
         assert!(expired.is_expired());
 
@@ -77,7 +413,83 @@ mod tests {
             price_cents: 1000,
             travel_class: 2,
             expires_at: "2099-01-01".to_string(),
+            ttl: CacheTtl::TariffYear,
+            history: Vec::new(),
+            no_prices_until: None,
+            price_per_adult_cents: None,
         };
         assert!(!valid.is_expired());
+
+        // Markers for routes without prices expire by the clock
+        let mut marker = CacheEntry::no_prices(2, std::time::Duration::from_secs(3600));
+        assert_eq!(marker.ttl, CacheTtl::NoPrices(3600));
+        assert!(!marker.is_expired());
+        marker.no_prices_until = Some(Utc::now() - chrono::Duration::hours(1));
+        assert!(marker.is_expired());
+    }
+
+    #[test]
+    fn test_history_per_tariff_year() {
+        let mut entry = CacheEntry {
+            price_cents: 900,
+            travel_class: 2,
+            expires_at: "2024-01-01".to_string(),
+            ttl: CacheTtl::TariffYear,
+            history: Vec::new(),
+            no_prices_until: None,
+            price_per_adult_cents: None,
+        };
+        assert_eq!(entry.tariff_year(), Some(2023));
+
+        entry.record(900, 2023);
+        entry.record(940, 2024);
+        entry.record(960, 2024);
+        assert_eq!(entry.price_cents, 960);
+        assert_eq!(
+            entry.history,
+            vec![
+                PriceRecord { tariff_year: 2023, price_cents: 900 },
+                PriceRecord { tariff_year: 2024, price_cents: 960 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ttl_policies() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        for (spec, ttl, expiry) in [
+            ("tariff-year", CacheTtl::TariffYear, date(2027, 1, 1)),
+            ("30d", CacheTtl::Days(30), date(2026, 11, 13)),
+            ("2w", CacheTtl::Days(14), date(2026, 10, 28)),
+            ("2026-12-01", CacheTtl::Until(date(2026, 12, 1)), date(2026, 12, 1)),
+        ] {
+            let parsed: CacheTtl = spec.parse().unwrap();
+            assert_eq!(parsed, ttl, "{}", spec);
+            assert_eq!(parsed.expires_at(today), expiry, "{}", spec);
+            assert_eq!(parsed.to_string().parse::<CacheTtl>(), Ok(ttl));
+        }
+        for invalid in ["", "0d", "30", "d", "1y", "2026-13-01", "no-prices:60s"] {
+            assert!(invalid.parse::<CacheTtl>().is_err(), "{}", invalid);
+        }
+
+        let entry = CacheEntry::with_ttl(940, 2, CacheTtl::Days(30));
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""ttl":"30d""#));
+        let stored: CacheEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(stored.ttl, CacheTtl::Days(30));
+        assert_eq!(stored.tariff_year(), entry.history.last().map(|r| r.tariff_year));
+
+        let marker = CacheEntry::no_prices(2, std::time::Duration::from_secs(60));
+        let json = serde_json::to_string(&marker).unwrap();
+        assert!(json.contains(r#""ttl":"no-prices:60s""#));
+        let stored: CacheEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(stored.ttl, CacheTtl::NoPrices(60));
+
+        let legacy: CacheEntry = serde_json::from_str(
+            r#"{"price_cents": 940, "travel_class": 2, "expires_at": "2025-01-01"}"#,
+        )
+        .unwrap();
+        assert_eq!((legacy.ttl, legacy.tariff_year()), (CacheTtl::TariffYear, Some(2024)));
     }
 }
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Most responses kept at once; the oldest goes first when the cache is full
+const MAX_ENTRIES: usize = 1024;
+
+/// Stored responses by namespace and request parameters, with when they were stored
+type Responses = HashMap<(&'static str, String), (Instant, String)>;
+
+/// Short-lived answers of API calls, the second cache tier next to the
+/// price cache. Entries are keyed by a namespace (an endpoint such as
+/// "trips") and the request parameters, and live for the namespace's TTL,
+/// so polling loops and busy server endpoints reuse a response for a few
+/// seconds instead of asking NS again. Namespaces without a TTL are not
+/// cached. Everything lives in memory, for the life of the process.
+#[derive(Default)]
+pub struct ResponseCache {
+    ttls: HashMap<&'static str, Duration>,
+    entries: Mutex<Responses>,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep responses of `namespace` for `ttl`; a zero TTL disables caching it
+    pub fn with_ttl(mut self, namespace: &'static str, ttl: Duration) -> Self {
+        if ttl.is_zero() {
+            self.ttls.remove(namespace);
+        } else {
+            self.ttls.insert(namespace, ttl);
+        }
+        self
+    }
+
+    /// How long responses of `namespace` are kept, `None` when not cached
+    pub fn ttl(&self, namespace: &str) -> Option<Duration> {
+        self.ttls.get(namespace).copied()
+    }
+
+    /// The response stored for these request parameters, unless it expired
+    pub fn get(&self, namespace: &'static str, key: &str) -> Option<String> {
+        self.get_at(namespace, key, Instant::now())
+    }
+
+    fn get_at(&self, namespace: &'static str, key: &str, now: Instant) -> Option<String> {
+        let ttl = self.ttl(namespace)?;
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&(namespace, key.to_string()))
+            .filter(|(stored, _)| now.duration_since(*stored) < ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// The response stored for these request parameters however old, with its
+    /// age: expired entries stay until the cache fills up, so a degraded
+    /// endpoint can still be answered from them
+    pub fn get_stale(&self, namespace: &'static str, key: &str) -> Option<(Duration, String)> {
+        self.get_stale_at(namespace, key, Instant::now())
+    }
+
+    fn get_stale_at(
+        &self,
+        namespace: &'static str,
+        key: &str,
+        now: Instant,
+    ) -> Option<(Duration, String)> {
+        self.ttl(namespace)?;
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&(namespace, key.to_string()))
+            .map(|(stored, response)| (now.duration_since(*stored), response.clone()))
+    }
+
+    /// Store a response, dropping expired ones and, when still full, the oldest
+    pub fn insert(&self, namespace: &'static str, key: &str, response: &str) {
+        self.insert_at(namespace, key, response, Instant::now());
+    }
+
+    fn insert_at(&self, namespace: &'static str, key: &str, response: &str, now: Instant) {
+        if self.ttl(namespace).is_none() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|(namespace, _), (stored, _)| {
+                self.ttls
+                    .get(namespace)
+                    .is_some_and(|ttl| now.duration_since(*stored) < *ttl)
+            });
+        }
+        if entries.len() >= MAX_ENTRIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert((namespace, key.to_string()), (now, response.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaces_expire_separately() {
+        let cache = ResponseCache::new()
+            .with_ttl("trips", Duration::from_secs(30))
+            .with_ttl("departures", Duration::from_secs(60))
+            .with_ttl("price", Duration::ZERO);
+        let start = Instant::now();
+
+        cache.insert_at("trips", "8400282-8400621", "[trips]", start);
+        cache.insert_at("departures", "8400621", "[departures]", start);
+        cache.insert_at("price", "8400282-8400621", "[price]", start);
+        assert_eq!(cache.get_at("trips", "8400282-8400621", start).as_deref(), Some("[trips]"));
+        assert_eq!(cache.get_at("trips", "8400621-8400282", start), None);
+        assert_eq!(cache.get_at("price", "8400282-8400621", start), None);
+
+        let later = start + Duration::from_secs(45);
+        assert_eq!(cache.get_at("trips", "8400282-8400621", later), None);
+        assert_eq!(
+            cache.get_stale_at("trips", "8400282-8400621", later),
+            Some((Duration::from_secs(45), "[trips]".to_string()))
+        );
+        assert_eq!(cache.get_stale_at("price", "8400282-8400621", later), None);
+        assert_eq!(cache.get_at("departures", "8400621", later).as_deref(), Some("[departures]"));
+    }
+
+    #[test]
+    fn test_full_cache_drops_oldest() {
+        let cache = ResponseCache::new().with_ttl("trips", Duration::from_secs(30));
+        let start = Instant::now();
+        for n in 0..MAX_ENTRIES {
+            cache.insert_at("trips", &n.to_string(), "", start + Duration::from_millis(n as u64));
+        }
+        let now = start + Duration::from_secs(1);
+        cache.insert_at("trips", "new", "", now);
+
+        assert_eq!(cache.entries.lock().unwrap().len(), MAX_ENTRIES);
+        assert_eq!(cache.get_at("trips", "0", now), None);
+        assert!(cache.get_at("trips", "1", now).is_some());
+        assert!(cache.get_at("trips", "new", now).is_some());
+    }
+}
@@ -1,176 +1,749 @@
-use super::models::CacheEntry;
-use std::collections::HashMap;
+use super::backend::{CacheBackend, JsonFileBackend};
+#[cfg(feature = "cache-kv")]
+use super::kv::KvBackend;
+use super::lru::LruBackend;
+use super::models::{CacheCounters, CacheEntry, CacheTtl, PriceKey, PriceRecord};
+use super::redis::{RedisBackend, REDIS_SCHEME};
+use chrono::{Datelike, Local};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::BufWriter;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often the server runs the refreshes queued by stale-while-revalidate
+pub const REVALIDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default of `NS_CACHE_NO_PRICES_SECS`, how long a route the price API had
+/// no prices for is answered from the cache
+pub const NO_PRICES_SECS: u64 = 3600;
+
+/// Prefix of the cache spec selecting the key-value log backend (`cache-kv`
+/// feature): `--cache kv:prices.kv`
+pub const KV_PREFIX: &str = "kv:";
+
+/// The cache spec of a `--cache [PATH]` flag: the given path, the default
+/// location for a bare `--cache`, None without the flag
+pub fn cache_spec(
+    flag: Option<Option<String>>,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match flag {
+        Some(Some(spec)) => Ok(Some(spec)),
+        Some(None) => match default_cache_path(|var| std::env::var(var).ok()) {
+            Some(path) => Ok(Some(path.to_string_lossy().to_string())),
+            None => {
+                Err("No cache directory found, set XDG_CACHE_HOME or pass --cache <path>".into())
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+/// `prices.json` in the user's cache directory, see `cache_dir()`
+fn default_cache_path(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    Some(cache_dir(var)?.join("prices.json"))
+}
+
+/// The nstimes directory in the user's cache directory, where the price
+/// cache, the synced station data and the exchange rates live by default
+pub fn user_cache_dir() -> Option<PathBuf> {
+    cache_dir(|var| std::env::var(var).ok())
+}
+
+/// `$XDG_CACHE_HOME/nstimes` when set, else `~/Library/Caches/nstimes` on
+/// macOS, `%LOCALAPPDATA%\nstimes\cache` on Windows and `~/.cache/nstimes`
+/// elsewhere (as the `directories` crate resolves them)
+fn cache_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let set = |name: &str| var(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    match set("XDG_CACHE_HOME").filter(|dir| dir.is_absolute()) {
+        Some(xdg) => Some(xdg.join("nstimes")),
+        None if cfg!(target_os = "macos") => Some(set("HOME")?.join("Library/Caches/nstimes")),
+        None if cfg!(windows) => Some(set("LOCALAPPDATA")?.join("nstimes").join("cache")),
+        None => Some(set("HOME")?.join(".cache/nstimes")),
+    }
+}
+
+/// Contents of `path`, or None when it is missing or owned by someone other
+/// than this user (or root): a file another user planted is never read
+pub fn read_owned_file(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    if !is_owned(&file.metadata().ok()?) {
+        eprintln!("⚠️  Ignoring {}: it belongs to another user", path.display());
+        return None;
+    }
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+#[cfg(unix)]
+fn is_owned(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: geteuid() has no preconditions and always succeeds
+    let uid = unsafe { libc::geteuid() };
+    metadata.uid() == uid || metadata.uid() == 0
+}
+
+/// Other platforms keep per-user directories private to their user
+#[cfg(not(unix))]
+fn is_owned(_: &fs::Metadata) -> bool {
+    true
+}
+
+/// What the cache holds for a fare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedPrice {
+    Price(u32),
+    /// The price API had no prices for the route
+    NoPrices,
+}
+
+/// Fetch a new price for a stale entry and store it in the given cache
+pub type Refresh = Box<dyn FnOnce(&PriceCache) -> Result<(), Box<dyn std::error::Error>> + Send>;
 
 /// Price cache that stores prices with expiration dates
 pub struct PriceCache {
-    /// Path to the cache file
-    path: String,
-    /// In-memory cache entries (uses interior mutability for thread-safe updates)
-    /// Key format: "station1-station2-class" where stations are alphabetically sorted
-    entries: Mutex<HashMap<String, CacheEntry>>,
+    /// Where the entries live: the cache file path or the Redis server
+    location: String,
+    /// Entries by `PriceKey`, behind the LRU layer (unbounded until
+    /// `with_max_entries()`)
+    backend: LruBackend,
+    /// Expiry policy of newly stored prices
+    ttl: CacheTtl,
+    /// Lookups and fetches of this process
+    counters: Mutex<CacheCounters>,
+    /// The part of `counters` already added to the backend's stored counters
+    persisted: Mutex<CacheCounters>,
+    /// How long routes without prices are remembered, zero to not remember them
+    no_prices_ttl: Duration,
+    /// Serve expired entries and refresh them in the background
+    stale_while_revalidate: bool,
+    /// Refreshes waiting for `run_revalidations()`
+    queued: Mutex<Vec<(String, Refresh)>>,
+    /// Keys with a refresh queued or running, so each is fetched only once
+    revalidating: Mutex<HashSet<String>>,
 }
 
 impl PriceCache {
+    /// A price cache storing its entries in `backend`, shown as `location`
+    /// (by `path()` and in logs). The way to plug in a backend of your own:
+    /// everything above the `CacheBackend` trait, `get_prices()` included,
+    /// works the same for every backend
+    pub fn from_backend(location: impl Into<String>, backend: Box<dyn CacheBackend>) -> Self {
+        Self {
+            location: location.into(),
+            backend: LruBackend::new(backend, usize::MAX),
+            ttl: CacheTtl::default(),
+            counters: Mutex::default(),
+            persisted: Mutex::default(),
+            no_prices_ttl: Duration::from_secs(
+                std::env::var("NS_CACHE_NO_PRICES_SECS")
+                    .ok()
+                    .and_then(|secs| secs.parse().ok())
+                    .unwrap_or(NO_PRICES_SECS),
+            ),
+            stale_while_revalidate: false,
+            queued: Mutex::default(),
+            revalidating: Mutex::default(),
+        }
+    }
+
     /// Load or create a new price cache from the given file path
     pub fn new(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
-        let path_str = path.as_ref().to_string_lossy().to_string();
-
-        let entries = if path.as_ref().exists() {
-            // Load existing cache
-            let content = fs::read_to_string(&path)?;
-            match serde_json::from_str(&content) {
-                Ok(entries) => entries,
-                Err(e) => {
-                    eprintln!("⚠️  Failed to parse cache file, starting fresh: {}", e);
-                    HashMap::new()
-                }
-            }
-        } else {
-            // Create parent directory if it doesn't exist
-            if let Some(parent) = path.as_ref().parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent)?;
-                }
-            }
-            HashMap::new()
+        let location = path.as_ref().to_string_lossy().to_string();
+        Ok(Self::from_backend(location, Box::new(JsonFileBackend::open(path)?)))
+    }
+
+    /// A price cache in the Redis database at `url`, shared by every server
+    /// pointing at it
+    pub fn redis(url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let backend = RedisBackend::connect(url)?;
+        Ok(Self::from_backend(backend.location(), Box::new(backend)))
+    }
+
+    /// A price cache in the key-value log at `path`, see `KvBackend`
+    #[cfg(feature = "cache-kv")]
+    pub fn kv(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let location = format!("{}{}", KV_PREFIX, path.as_ref().display());
+        Ok(Self::from_backend(location, Box::new(KvBackend::open(path)?)))
+    }
+
+    /// Store new prices with this expiry policy instead of the tariff year.
+    /// Entries already in the cache keep the expiry they were stored with.
+    pub fn with_ttl(mut self, ttl: CacheTtl) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Keep at most `max_entries` routes, evicting the least recently used
+    /// ones, and answer recently used routes from memory
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.backend.set_max_entries(max_entries);
+        self
+    }
+
+    /// Remember routes without prices for `ttl` instead of `NS_CACHE_NO_PRICES_SECS`
+    pub fn with_no_prices_ttl(mut self, ttl: Duration) -> Self {
+        self.no_prices_ttl = ttl;
+        self
+    }
+
+    /// Answer with expired entries right away and refresh them in the
+    /// background instead of waiting for the price API
+    pub fn with_stale_while_revalidate(mut self, enabled: bool) -> Self {
+        self.stale_while_revalidate = enabled;
+        self
+    }
+
+    /// Whether expired entries are served while they are refreshed
+    pub fn stale_while_revalidate(&self) -> bool {
+        self.stale_while_revalidate
+    }
+
+    /// Queue `refresh` for the stale entry under `key`, unless one is already
+    /// queued or running. Returns whether it was queued
+    pub fn revalidate(&self, key: &PriceKey, refresh: Refresh) -> bool {
+        let key = key.to_string();
+        let (Ok(mut revalidating), Ok(mut queued)) = (self.revalidating.lock(), self.queued.lock())
+        else {
+            return false;
         };
+        if !revalidating.insert(key.clone()) {
+            return false;
+        }
+        queued.push((key, refresh));
+        true
+    }
 
-        Ok(Self {
-            path: path_str,
-            entries: Mutex::new(entries),
-        })
+    /// Run the queued refreshes, returning how many ran. A failed refresh
+    /// is logged and the stale entry stays, to be refreshed by a later lookup
+    pub fn run_revalidations(&self) -> usize {
+        let queued = match self.queued.lock() {
+            Ok(mut queued) => std::mem::take(&mut *queued),
+            Err(_) => return 0,
+        };
+        let count = queued.len();
+        for (key, refresh) in queued {
+            if let Err(e) = refresh(self) {
+                eprintln!("⚠️  Failed to refresh the cached price of {}: {}", key, e);
+            }
+            if let Ok(mut revalidating) = self.revalidating.lock() {
+                revalidating.remove(&key);
+            }
+        }
+        count
     }
 
-    /// Get a cached price for a station pair and travel class
-    /// Returns None if not found or expired
-    pub fn get(
-        &self,
-        from: &str,
-        to: &str,
-        travel_class: u8,
-    ) -> Option<u32> {
-        let key = Self::normalize_key(from, to, travel_class);
-
-        let entries = self.entries.lock().ok()?;
-        if let Some(entry) = entries.get(&key) {
-            if !entry.is_expired() {
-                return Some(entry.price_cents);
+    /// Open the cache at `path`, or return None when caching is disabled
+    ///
+    /// A `redis://` URL selects the Redis backend and a `kv:<path>` spec the
+    /// key-value log; builds without the `cache-kv` feature reject `kv:`
+    /// instead of silently using JSON.
+    pub fn open(path: Option<&str>) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        match path {
+            #[cfg(feature = "cache-kv")]
+            Some(spec) if spec.starts_with(KV_PREFIX) => {
+                Self::kv(&spec[KV_PREFIX.len()..]).map(Some)
             }
+            #[cfg(not(feature = "cache-kv"))]
+            Some(spec) if spec.starts_with(KV_PREFIX) => {
+                Err(format!("Cache backend '{}' needs the cache-kv feature", spec).into())
+            }
+            Some(url) if url.starts_with(REDIS_SCHEME) => Self::redis(url).map(Some),
+            _ => path.map(Self::new).transpose(),
         }
+    }
 
-        None
+    /// Path of the backing cache file, or the Redis server without credentials
+    pub fn path(&self) -> &str {
+        &self.location
     }
 
-    /// Set a cached price for a station pair and travel class
-    /// Automatically calculates expiration date (next January 1st)
-    pub fn set(
-        &self,
-        from: &str,
-        to: &str,
-        travel_class: u8,
-        price_cents: u32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let key = Self::normalize_key(from, to, travel_class);
-        let entry = CacheEntry::new(price_cents, travel_class);
+    /// Get a cached price for every price-affecting parameter in `key`
+    /// Returns None if not found, expired or the route has no prices
+    pub fn get(&self, key: &PriceKey) -> Option<u32> {
+        match self.lookup(key)? {
+            CachedPrice::Price(price) => Some(price),
+            CachedPrice::NoPrices => None,
+        }
+    }
+
+    /// What the cache knows about the fare of `key`: its price or that the
+    /// route has no prices. Returns None if not found or expired
+    pub fn lookup(&self, key: &PriceKey) -> Option<CachedPrice> {
+        let cached = self
+            .backend
+            .load(&key.to_string())
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| {
+                if entry.is_no_prices() {
+                    CachedPrice::NoPrices
+                } else {
+                    CachedPrice::Price(entry.price_cents)
+                }
+            });
+        self.count(|counters| match cached {
+            Some(_) => counters.hits += 1,
+            None => counters.misses += 1,
+        });
+        cached
+    }
 
-        if let Ok(mut entries) = self.entries.lock() {
-            entries.insert(key, entry);
-            drop(entries); // Release lock before saving
-            self.save()?;
+    /// Get a cached price even if it has expired
+    /// Used as a fallback when the price API is too slow to wait for
+    pub fn get_stale(&self, key: &PriceKey) -> Option<u32> {
+        let price = self
+            .backend
+            .load(&key.to_string())
+            .filter(|entry| !entry.is_no_prices())
+            .map(|entry| entry.price_cents);
+        if price.is_some() {
+            self.count(|counters| counters.stale_hits += 1);
         }
+        price
+    }
 
-        Ok(())
+    /// Cached price per adult of `key`, which differs from the total for
+    /// groups; also served when expired, like `get_stale()`
+    pub fn per_adult(&self, key: &PriceKey) -> Option<u32> {
+        self.backend
+            .load(&key.to_string())
+            .filter(|entry| !entry.is_no_prices())
+            .map(|entry| entry.price_per_adult_cents.unwrap_or(entry.price_cents))
     }
 
-    /// Normalize station pair into a consistent cache key
-    /// A->B is the same as B->A, so we sort alphabetically
-    /// Format: "station1-station2-class"
-    fn normalize_key(from: &str, to: &str, travel_class: u8) -> String {
-        let (first, second) = if from < to {
-            (from, to)
-        } else {
-            (to, from)
-        };
+    /// Count a price fetched from the price API while this cache was enabled
+    pub fn record_fetch(&self) {
+        self.count(|counters| counters.fetches += 1);
+    }
+
+    fn count(&self, update: impl FnOnce(&mut CacheCounters)) {
+        if let Ok(mut counters) = self.counters.lock() {
+            update(&mut counters);
+        }
+    }
 
-        format!("{}-{}-{}", first, second, travel_class)
+    /// The recorded price per tariff year, oldest first
+    pub fn history(&self, key: &PriceKey) -> Vec<PriceRecord> {
+        self.backend
+            .load(&key.to_string())
+            .map(|entry| entry.history)
+            .unwrap_or_default()
     }
 
-    /// Save the cache to disk
-    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let entries = self.entries.lock().map_err(|_| "Failed to lock cache")?;
-        let file = fs::File::create(&self.path)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &*entries)?;
-        Ok(())
+    /// Set a cached price for every price-affecting parameter in `key`
+    /// The expiration date follows the cache's TTL policy (next January 1st
+    /// by default); the price of earlier tariff years stays in the entry's history
+    pub fn set(&self, key: &PriceKey, price_cents: u32) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_fare(key, price_cents, price_cents)
     }
 
-    /// Get cache statistics
-    pub fn stats(&self) -> CacheStats {
-        if let Ok(entries) = self.entries.lock() {
-            let total = entries.len();
-            let expired = entries.values().filter(|e| e.is_expired()).count();
-            let valid = total - expired;
-
-            CacheStats {
-                total_entries: total,
-                valid_entries: valid,
-                expired_entries: expired,
+    /// Like `set()`, for a fare whose total differs from the price per adult
+    /// (several passengers)
+    pub fn set_fare(
+        &self,
+        key: &PriceKey,
+        price_cents: u32,
+        per_adult_cents: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.backend.update(&key.to_string(), &mut |previous| {
+            let mut entry = CacheEntry::with_ttl(price_cents, key.travel_class, self.ttl);
+            entry.price_per_adult_cents = (per_adult_cents != price_cents).then_some(per_adult_cents);
+            if let Some(previous) = previous {
+                entry.history = previous.history;
+                entry.record(price_cents, Local::now().year());
             }
-        } else {
-            CacheStats {
-                total_entries: 0,
-                valid_entries: 0,
-                expired_entries: 0,
+            entry
+        })
+    }
+
+    /// Remember that the price API has no prices for `key`, for
+    /// `NS_CACHE_NO_PRICES_SECS` (nothing is stored when that is 0)
+    pub fn set_no_prices(&self, key: &PriceKey) -> Result<(), Box<dyn std::error::Error>> {
+        if self.no_prices_ttl.is_zero() {
+            return Ok(());
+        }
+        self.backend.update(&key.to_string(), &mut |previous| {
+            let mut entry = CacheEntry::no_prices(key.travel_class, self.no_prices_ttl);
+            if let Some(previous) = previous {
+                entry.history = previous.history;
             }
+            entry
+        })
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.backend.entries();
+        let total = entries.len();
+        let expired = entries.iter().filter(|e| e.is_expired()).count();
+        let valid = total - expired;
+
+        let session = self.counters.lock().map(|c| *c).unwrap_or_default();
+        let persisted = self.persisted.lock().map(|c| *c).unwrap_or_default();
+        CacheStats {
+            total_entries: total,
+            valid_entries: valid,
+            expired_entries: expired,
+            session,
+            all_time: self.backend.counters().plus(&session.since(&persisted)),
+        }
+    }
+
+    /// Write out cached prices still held back by write coalescing, and add
+    /// the usage counted since the last flush to the stored counters
+    pub fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let session = *self.counters.lock().map_err(|_| "Failed to lock cache")?;
+        let mut persisted = self.persisted.lock().map_err(|_| "Failed to lock cache")?;
+        let delta = session.since(&persisted);
+        if !delta.is_empty() {
+            self.backend.add_counters(&delta)?;
+            *persisted = session;
         }
+        self.backend.flush()
+    }
+
+    /// Every stored entry by key, in key order, for `cache export`
+    pub fn export(&self) -> BTreeMap<String, CacheEntry> {
+        self.backend
+            .keys()
+            .into_iter()
+            .filter_map(|key| {
+                let entry = self.backend.load(&key)?;
+                Some((key, entry))
+            })
+            .collect()
+    }
+
+    /// Merge entries exported from another cache. Of two entries for the
+    /// same key the one expiring last wins, keeping the price history of
+    /// both; keys not in the `PriceKey` form are skipped.
+    pub fn import(
+        &self,
+        entries: HashMap<String, CacheEntry>,
+    ) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        let mut report = ImportReport::default();
+        for (key, imported) in entries {
+            let Some(key) = PriceKey::parse(&key) else {
+                report.skipped += 1;
+                continue;
+            };
+            self.backend.update(&key.to_string(), &mut |current| match current {
+                None => {
+                    report.added += 1;
+                    imported.clone()
+                }
+                Some(current) if imported.expiry_date() > current.expiry_date() => {
+                    report.updated += 1;
+                    let mut entry = imported.clone();
+                    entry.merge_history(&current.history);
+                    entry
+                }
+                Some(mut current) => {
+                    report.kept += 1;
+                    current.merge_history(&imported.history);
+                    current
+                }
+            })?;
+        }
+        Ok(report)
     }
 
     /// Clean up expired entries from the cache
     pub fn cleanup(&self) -> Result<usize, Box<dyn std::error::Error>> {
-        let removed = if let Ok(mut entries) = self.entries.lock() {
-            let before = entries.len();
-            entries.retain(|_, entry| !entry.is_expired());
-            before - entries.len()
-        } else {
-            0
-        };
+        self.backend.remove_expired()
+    }
+}
 
-        if removed > 0 {
-            self.save()?;
+impl Drop for PriceCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("⚠️  Failed to save the price cache: {}", e);
         }
-
-        Ok(removed)
     }
 }
 
 /// Cache statistics
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct CacheStats {
     pub total_entries: usize,
     pub valid_entries: usize,
     pub expired_entries: usize,
+    /// Usage since this process opened the cache
+    pub session: CacheCounters,
+    /// Usage summed over every process that used the cache
+    pub all_time: CacheCounters,
+}
+
+/// What `PriceCache::import()` did with the imported entries
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    /// Keys the cache did not have
+    pub added: usize,
+    /// Entries replaced by an imported one expiring later
+    pub updated: usize,
+    /// Entries expiring as late or later than the imported one
+    pub kept: usize,
+    /// Imported keys in a legacy form
+    pub skipped: usize,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
     use std::env;
+    use std::fs;
 
     #[test]
     fn test_normalize_key() {
         // A->B should equal B->A
-        let key1 = PriceCache::normalize_key("Amsterdam", "Utrecht", 2);
-        let key2 = PriceCache::normalize_key("Utrecht", "Amsterdam", 2);
+        let key1 = PriceKey::new("Amsterdam", "Utrecht", 2).to_string();
+        let key2 = PriceKey::new("Utrecht", "Amsterdam", 2).to_string();
         assert_eq!(key1, key2);
-        assert_eq!(key1, "Amsterdam-Utrecht-2");
+        assert_eq!(
+            key1,
+            concat!(
+                "Amsterdam|Utrecht|class=2|type=single|",
+                "adults=1|children=0|joint=false|discount=NO_DISCOUNT"
+            )
+        );
 
         // Different class should produce different key
-        let key3 = PriceCache::normalize_key("Amsterdam", "Utrecht", 1);
+        let key3 = PriceKey::new("Amsterdam", "Utrecht", 1).to_string();
         assert_ne!(key1, key3);
+
+        // Discounted fares, return tickets and other passengers are cached apart
+        let discounted =
+            PriceKey::new("Amsterdam", "Utrecht", 2).with_discount(Some("DISCOUNT_40_PERCENT"));
+        assert!(discounted.to_string().ends_with("|discount=DISCOUNT_40_PERCENT"));
+        let family = PriceKey {
+            travel_type: "return".to_string(),
+            adults: 2,
+            children: 1,
+            ..PriceKey::new("Utrecht", "Amsterdam", 2)
+        };
+        assert_ne!(family.to_string(), key1);
+
+        // Stored keys read back (with the stations in key order)
+        assert_eq!(PriceKey::parse(&discounted.to_string()), Some(discounted));
+        let parsed = PriceKey::parse(&family.to_string()).unwrap();
+        assert_eq!((parsed.from.as_str(), parsed.adults, parsed.children), ("Amsterdam", 2, 1));
+        assert_eq!(PriceKey::parse("Amsterdam-Utrecht-2"), None);
+    }
+
+    #[test]
+    fn test_ttl_sets_expiry_of_new_prices() -> Result<(), Box<dyn std::error::Error>> {
+        let cache_path = env::temp_dir().join("test_cache_ttl.json");
+        let _ = fs::remove_file(&cache_path);
+
+        let cache = PriceCache::new(&cache_path)?.with_ttl(CacheTtl::Days(30));
+        cache.set(&PriceKey::new("Amsterdam", "Utrecht", 2), 940)?;
+        let today = Local::now().date_naive();
+        let key = PriceKey::new("Amsterdam", "Utrecht", 2);
+        let entry = cache.backend.load(&key.to_string()).unwrap();
+        assert_eq!(entry.ttl, CacheTtl::Days(30));
+        assert_eq!(entry.expires_at, CacheTtl::Days(30).expires_at(today).to_string());
+        assert_eq!(cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+
+        cache.flush()?;
+        fs::remove_file(&cache_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_usage_counters_add_up_across_runs() -> Result<(), Box<dyn std::error::Error>> {
+        let cache_path = env::temp_dir().join("test_cache_counters.json");
+        let _ = fs::remove_file(&cache_path);
+        let _ = fs::remove_file(env::temp_dir().join("test_cache_counters.json.stats"));
+
+        let cache = PriceCache::new(&cache_path)?;
+        assert_eq!(cache.get(&PriceKey::new("Amsterdam", "Utrecht", 2)), None);
+        cache.record_fetch();
+        cache.set(&PriceKey::new("Amsterdam", "Utrecht", 2), 940)?;
+        assert_eq!(cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+        assert_eq!(cache.get_stale(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+        let run = CacheCounters { hits: 1, misses: 1, stale_hits: 1, fetches: 1 };
+        assert_eq!(cache.stats().session, run);
+        drop(cache);
+
+        let cache = PriceCache::new(&cache_path)?;
+        assert_eq!(cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+        let stats = cache.stats();
+        assert_eq!(stats.session.hits, 1);
+        assert_eq!(stats.all_time, run.plus(&stats.session));
+        drop(cache);
+
+        fs::remove_file(&cache_path)?;
+        fs::remove_file(env::temp_dir().join("test_cache_counters.json.stats"))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_keeps_entry_expiring_last() -> Result<(), Box<dyn std::error::Error>> {
+        let cache_path = env::temp_dir().join("test_cache_import.json");
+        let _ = fs::remove_file(&cache_path);
+        let dated = |price_cents, expires_at: &str, tariff_year| CacheEntry {
+            price_cents,
+            travel_class: 2,
+            expires_at: expires_at.to_string(),
+            ttl: CacheTtl::TariffYear,
+            history: vec![PriceRecord { tariff_year, price_cents }],
+            no_prices_until: None,
+            price_per_adult_cents: None,
+        };
+        let amsterdam = PriceKey::new("Amsterdam", "Utrecht", 2).to_string();
+        let gouda = PriceKey::new("Gouda", "Utrecht", 2).to_string();
+        let zwolle = PriceKey::new("Utrecht", "Zwolle", 2).to_string();
+
+        let cache = PriceCache::new(&cache_path)?;
+        cache.backend.update(&amsterdam, &mut |_| dated(900, "2098-01-01", 2097))?;
+        cache.backend.update(&gouda, &mut |_| dated(700, "2099-01-01", 2098))?;
+
+        let report = cache.import(HashMap::from([
+            (amsterdam.clone(), dated(940, "2099-01-01", 2098)),
+            (gouda.clone(), dated(650, "2098-01-01", 2097)),
+            (zwolle.clone(), dated(1900, "2099-01-01", 2098)),
+            ("Amsterdam-Utrecht-2".to_string(), dated(940, "2099-01-01", 2098)),
+        ]))?;
+        assert_eq!(report, ImportReport { added: 1, updated: 1, kept: 1, skipped: 1 });
+
+        assert_eq!(cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2)), Some(940));
+        assert_eq!(cache.get(&PriceKey::new("Gouda", "Utrecht", 2)), Some(700));
+        assert_eq!(cache.get(&PriceKey::new("Zwolle", "Utrecht", 2)), Some(1900));
+        let years: Vec<i32> = cache
+            .history(&PriceKey::new("Gouda", "Utrecht", 2))
+            .iter()
+            .map(|record| record.tariff_year)
+            .collect();
+        assert_eq!(years, [2097, 2098]);
+
+        let exported = cache.export();
+        assert_eq!(exported.keys().collect::<Vec<_>>(), [&amsterdam, &gouda, &zwolle]);
+
+        cache.flush()?;
+        fs::remove_file(&cache_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_routes_without_prices_are_remembered() -> Result<(), Box<dyn std::error::Error>> {
+        let cache_path = env::temp_dir().join("test_cache_no_prices.json");
+        let _ = fs::remove_file(&cache_path);
+        let key = PriceKey::new("Maastricht", "Vlissingen", 2);
+
+        let cache = PriceCache::new(&cache_path)?.with_no_prices_ttl(Duration::from_secs(60));
+        cache.set_no_prices(&key)?;
+        assert_eq!(cache.lookup(&key), Some(CachedPrice::NoPrices));
+        assert_eq!(cache.get(&key), None);
+        assert_eq!(cache.get_stale(&key), None);
+
+        // The marker expires by the clock, and a price replaces it
+        cache.backend.update(&key.to_string(), &mut |_| {
+            let mut marker = CacheEntry::no_prices(2, Duration::from_secs(60));
+            marker.no_prices_until = Some(Utc::now() - chrono::Duration::seconds(1));
+            marker
+        })?;
+        assert_eq!(cache.lookup(&key), None);
+        cache.set(&key, 2790)?;
+        assert_eq!(cache.lookup(&key), Some(CachedPrice::Price(2790)));
+        drop(cache);
+
+        // With a zero TTL nothing is remembered
+        let cache = PriceCache::new(&cache_path)?.with_no_prices_ttl(Duration::ZERO);
+        let other = PriceKey::new("Maastricht", "Den Helder", 2);
+        cache.set_no_prices(&other)?;
+        assert_eq!(cache.lookup(&other), None);
+
+        cache.flush()?;
+        fs::remove_file(&cache_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_revalidation_runs_once_per_key() -> Result<(), Box<dyn std::error::Error>> {
+        let cache_path = env::temp_dir().join("test_cache_revalidate.json");
+        let _ = fs::remove_file(&cache_path);
+        let key = PriceKey::new("Amsterdam", "Utrecht", 2);
+
+        let cache = PriceCache::new(&cache_path)?.with_stale_while_revalidate(true);
+        let refresh = |price_cents| -> Refresh {
+            let key = key.clone();
+            Box::new(move |cache: &PriceCache| cache.set(&key, price_cents))
+        };
+        assert!(cache.revalidate(&key, refresh(940)));
+        assert!(!cache.revalidate(&PriceKey::new("Utrecht", "Amsterdam", 2), refresh(960)));
+        assert_eq!(cache.get(&key), None);
+
+        assert_eq!(cache.run_revalidations(), 1);
+        assert_eq!(cache.get(&key), Some(940));
+        assert_eq!(cache.run_revalidations(), 0);
+
+        // Once done, the key can be refreshed again
+        assert!(cache.revalidate(&key, refresh(960)));
+        assert_eq!(cache.run_revalidations(), 1);
+        assert_eq!(cache.get(&key), Some(960));
+
+        cache.flush()?;
+        fs::remove_file(&cache_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_cache_path() {
+        let vars = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            default_cache_path(vars(&[("XDG_CACHE_HOME", "/var/cache"), ("HOME", "/home/ns")])),
+            Some(PathBuf::from("/var/cache/nstimes/prices.json"))
+        );
+        if cfg!(all(unix, not(target_os = "macos"))) {
+            // A relative XDG_CACHE_HOME is invalid and ignored
+            let home = vars(&[("XDG_CACHE_HOME", "cache"), ("HOME", "/home/ns")]);
+            assert_eq!(
+                default_cache_path(home),
+                Some(PathBuf::from("/home/ns/.cache/nstimes/prices.json"))
+            );
+            assert_eq!(default_cache_path(vars(&[])), None);
+        }
+
+        let stations = env::temp_dir().join("test_read_owned_file.json");
+        fs::write(&stations, "{}").unwrap();
+        assert_eq!(read_owned_file(&stations).as_deref(), Some("{}"));
+        fs::remove_file(&stations).unwrap();
+        assert_eq!(read_owned_file(&stations), None);
+
+        assert_eq!(cache_spec(None).unwrap(), None);
+        let given = cache_spec(Some(Some("prices.cache".to_string()))).unwrap();
+        assert_eq!(given.as_deref(), Some("prices.cache"));
+    }
+
+    #[test]
+    #[cfg(feature = "cache-kv")]
+    fn test_open_kv_backend() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(PriceCache::open(None)?.is_none());
+
+        let path = env::temp_dir().join("test_open_kv_backend.kv");
+        let _ = fs::remove_file(&path);
+        let spec = format!("kv:{}", path.display());
+        let key = PriceKey::new("Amsterdam", "Utrecht", 2);
+        let cache = PriceCache::open(Some(&spec))?.ok_or("no cache")?;
+        assert_eq!(cache.path(), spec);
+        cache.set(&key, 940)?;
+        drop(cache);
+
+        let reopened = PriceCache::open(Some(&spec))?.ok_or("no cache")?;
+        assert_eq!(reopened.get(&key), Some(940));
+        fs::remove_file(&path)?;
+        fs::remove_file(path.with_extension("kv.lock"))?;
+        Ok(())
     }
 
     #[test]
@@ -185,23 +758,30 @@ mod tests {
         let cache = PriceCache::new(&cache_path)?;
 
         // Set a price
-        cache.set("Amsterdam", "Utrecht", 2, 940)?;
+        cache.set(&PriceKey::new("Amsterdam", "Utrecht", 2), 940)?;
 
         // Get it back
-        let price = cache.get("Amsterdam", "Utrecht", 2);
+        let price = cache.get(&PriceKey::new("Amsterdam", "Utrecht", 2));
         assert_eq!(price, Some(940));
 
         // Check reverse direction works
-        let price_reverse = cache.get("Utrecht", "Amsterdam", 2);
+        let price_reverse = cache.get(&PriceKey::new("Utrecht", "Amsterdam", 2));
         assert_eq!(price_reverse, Some(940));
 
         // Different class should not match
-        let price_different_class = cache.get("Amsterdam", "Utrecht", 1);
+        let price_different_class = cache.get(&PriceKey::new("Amsterdam", "Utrecht", 1));
         assert_eq!(price_different_class, None);
 
-        // Load cache from disk
+        // Nor should a discounted fare
+        let discounted =
+            PriceKey::new("Amsterdam", "Utrecht", 2).with_discount(Some("DISCOUNT_40_PERCENT"));
+        let price_discounted = cache.get(&discounted);
+        assert_eq!(price_discounted, None);
+
+        // Load cache from disk once the coalesced write is out
+        cache.flush()?;
         let cache2 = PriceCache::new(&cache_path)?;
-        let price_reloaded = cache2.get("Amsterdam", "Utrecht", 2);
+        let price_reloaded = cache2.get(&PriceKey::new("Amsterdam", "Utrecht", 2));
         assert_eq!(price_reloaded, Some(940));
 
         // Clean up
@@ -0,0 +1,7 @@
+pub mod models;
+pub mod service;
+pub mod session;
+
+pub use models::{Endpoint, EndpointStats, PoolStats};
+pub use service::*;
+pub use session::{record_to, replay_from};
@@ -0,0 +1,302 @@
+use super::models::{
+    budget_cooldown, budget_strikes, build_proxy, extra_headers, no_proxy, percentile,
+    pool_idle_per_host, pool_idle_timeout, proxy_url, user_agent, Endpoint, EndpointStats,
+    PoolStats,
+};
+use super::session::{in_session, is_replaying, SessionMiddleware};
+use crate::cache::ResponseCache;
+use std::collections::VecDeque;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use ureq::typestate::{WithBody, WithoutBody};
+use ureq::{Agent, Proxy, RequestBuilder};
+
+/// Number of recent calls per endpoint kept for the p50/p99 latencies
+const LATENCY_WINDOW: usize = 1000;
+
+/// Shared HTTP client for all NS API calls
+///
+/// Applies the subscription key header, times every call against the
+/// endpoint's latency budget, and marks endpoints that repeatedly blow their
+/// budget as degraded so callers can fall back to cached data. The agent keeps
+/// idle connections to the NS gateway alive so later calls skip the TCP and
+/// TLS handshakes.
+pub struct ApiClient {
+    agent: Agent,
+    pool: PoolStats,
+    counters: [Counters; Endpoint::ALL.len()],
+    /// Recent responses by endpoint and request parameters, see `send_cached()`
+    responses: ResponseCache,
+}
+
+#[derive(Default)]
+struct Counters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    over_budget: AtomicU64,
+    consecutive_over: AtomicU64,
+    total_ms: AtomicU64,
+    max_ms: AtomicU64,
+    /// Durations of the last `LATENCY_WINDOW` calls in milliseconds
+    recent_ms: Mutex<VecDeque<u64>>,
+    degraded_until: Mutex<Option<Instant>>,
+}
+
+static PROXY: OnceLock<Option<Proxy>> = OnceLock::new();
+
+/// Route NS API calls through `explicit` (`--proxy-url`) or the proxy from the
+/// environment, honoring `NO_PROXY`
+///
+/// Must run before the first call. A malformed proxy URL is an error here,
+/// while a client created without this call silently ignores it.
+pub fn configure_proxy(explicit: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let proxy = match proxy_url(explicit) {
+        Some(url) => Some(build_proxy(&url, &no_proxy())?),
+        None => None,
+    };
+    PROXY
+        .set(proxy)
+        .map_err(|_| "the proxy is already configured".into())
+}
+
+/// The process-wide client instance
+pub fn client() -> &'static ApiClient {
+    static CLIENT: OnceLock<ApiClient> = OnceLock::new();
+    CLIENT.get_or_init(ApiClient::new)
+}
+
+impl ApiClient {
+    fn new() -> Self {
+        let pool = PoolStats {
+            max_idle_per_host: pool_idle_per_host(),
+            idle_timeout_secs: pool_idle_timeout().as_secs(),
+            latency_window: LATENCY_WINDOW,
+        };
+
+        Self {
+            agent: Agent::config_builder()
+                .proxy(PROXY.get().cloned().unwrap_or_else(Proxy::try_from_env))
+                // Every call goes to the NS gateway, so the per-host limit is the pool size
+                .max_idle_connections(pool.max_idle_per_host)
+                .max_idle_connections_per_host(pool.max_idle_per_host)
+                .max_idle_age(pool_idle_timeout())
+                .middleware(SessionMiddleware)
+                .build()
+                .into(),
+            pool,
+            counters: Default::default(),
+            responses: Endpoint::ALL.iter().fold(ResponseCache::new(), |cache, endpoint| {
+                cache.with_ttl(endpoint.name(), endpoint.response_ttl())
+            }),
+        }
+    }
+
+    /// Keep-alive pool settings, reported next to the latency metrics
+    pub fn pool(&self) -> &PoolStats {
+        &self.pool
+    }
+
+    /// Start a GET request with the NS authentication headers, the configured
+    /// User-Agent and any `NSTIMES_HEADERS` applied
+    pub fn get(&self, url: &str) -> Result<RequestBuilder<WithoutBody>, Box<dyn std::error::Error>> {
+        // Replayed sessions never reach NS, so they work without a token
+        let ns_api_token = match env::var("NS_API_TOKEN") {
+            Ok(token) => token,
+            Err(_) if is_replaying() => "replay".to_string(),
+            Err(_) => return Err("NS_API_TOKEN not found".into()),
+        };
+
+        let mut request = self
+            .agent
+            .get(url)
+            .header("User-Agent", user_agent())
+            .header("Cache-Control", "no-cache")
+            .header("Ocp-Apim-Subscription-Key", &ns_api_token);
+        for (name, value) in extra_headers()? {
+            request = request.header(name, value);
+        }
+        Ok(request)
+    }
+
+    /// Start a POST to somewhere other than NS (webhook sinks); it goes through
+    /// the same agent, so the proxy and keep-alive pool apply, but carries only
+    /// the User-Agent and none of the NS authentication headers
+    pub fn post(&self, url: &str) -> RequestBuilder<WithBody> {
+        self.agent.post(url).header("User-Agent", user_agent())
+    }
+
+    /// Send a request and return the response body, recording latency metrics
+    pub fn send(
+        &self,
+        endpoint: Endpoint,
+        request: RequestBuilder<WithoutBody>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let start = Instant::now();
+        let result = request
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string());
+        self.record(endpoint, start.elapsed(), result.is_ok());
+
+        Ok(result?)
+    }
+
+    /// `send()`, answered from the responses of the last
+    /// `Endpoint::response_ttl()` when one was sent with the same `params`
+    /// (every parameter of the request, the key of the short-lived cache).
+    /// While the endpoint `is_degraded()` an expired response is served too,
+    /// instead of waiting on NS again. Recorded and replayed sessions always send.
+    pub fn send_cached(
+        &self,
+        endpoint: Endpoint,
+        params: &str,
+        request: RequestBuilder<WithoutBody>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if in_session() {
+            return self.send(endpoint, request);
+        }
+        if let Some(body) = self.responses.get(endpoint.name(), params) {
+            return Ok(body);
+        }
+        if self.is_degraded(endpoint)
+            && let Some((age, body)) = self.responses.get_stale(endpoint.name(), params)
+        {
+            eprintln!(
+                "⚠️  NS {} API is degraded, using its answer from {}s ago",
+                endpoint,
+                age.as_secs()
+            );
+            return Ok(body);
+        }
+        let body = self.send(endpoint, request)?;
+        self.responses.insert(endpoint.name(), params, &body);
+        Ok(body)
+    }
+
+    /// Whether the endpoint repeatedly exceeded its budget and callers should
+    /// prefer cached data until the cooldown expires
+    pub fn is_degraded(&self, endpoint: Endpoint) -> bool {
+        let counters = &self.counters[endpoint.index()];
+        let Ok(degraded_until) = counters.degraded_until.lock() else {
+            return false;
+        };
+        degraded_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Latency metrics for every endpoint
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        Endpoint::ALL
+            .iter()
+            .map(|endpoint| {
+                let counters = &self.counters[endpoint.index()];
+                let calls = counters.calls.load(Ordering::Relaxed);
+                let total_ms = counters.total_ms.load(Ordering::Relaxed);
+                let recent: Vec<u64> = counters
+                    .recent_ms
+                    .lock()
+                    .map(|recent| recent.iter().copied().collect())
+                    .unwrap_or_default();
+
+                EndpointStats {
+                    endpoint: endpoint.name().to_string(),
+                    budget_ms: endpoint.budget().as_millis() as u64,
+                    calls,
+                    errors: counters.errors.load(Ordering::Relaxed),
+                    over_budget: counters.over_budget.load(Ordering::Relaxed),
+                    avg_ms: total_ms.checked_div(calls).unwrap_or(0),
+                    p50_ms: percentile(&recent, 50),
+                    p99_ms: percentile(&recent, 99),
+                    max_ms: counters.max_ms.load(Ordering::Relaxed),
+                    degraded: self.is_degraded(*endpoint),
+                }
+            })
+            .collect()
+    }
+
+    fn record(&self, endpoint: Endpoint, elapsed: Duration, ok: bool) {
+        let counters = &self.counters[endpoint.index()];
+        let elapsed_ms = elapsed.as_millis() as u64;
+
+        counters.calls.fetch_add(1, Ordering::Relaxed);
+        counters.total_ms.fetch_add(elapsed_ms, Ordering::Relaxed);
+        counters.max_ms.fetch_max(elapsed_ms, Ordering::Relaxed);
+        if let Ok(mut recent) = counters.recent_ms.lock() {
+            if recent.len() == LATENCY_WINDOW {
+                recent.pop_front();
+            }
+            recent.push_back(elapsed_ms);
+        }
+        if !ok {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let budget = endpoint.budget();
+        if elapsed <= budget {
+            counters.consecutive_over.store(0, Ordering::Relaxed);
+            return;
+        }
+
+        counters.over_budget.fetch_add(1, Ordering::Relaxed);
+        let strikes = counters.consecutive_over.fetch_add(1, Ordering::Relaxed) + 1;
+        eprintln!(
+            "⚠️  NS {} API call took {}ms (budget {}ms)",
+            endpoint,
+            elapsed_ms,
+            budget.as_millis()
+        );
+
+        if strikes >= budget_strikes()
+            && let Ok(mut degraded_until) = counters.degraded_until.lock()
+        {
+            let cooldown = budget_cooldown();
+            *degraded_until = Some(Instant::now() + cooldown);
+            counters.consecutive_over.store(0, Ordering::Relaxed);
+            eprintln!(
+                "⚠️  NS {} API exceeded its budget {} times in a row, preferring cached data for {}s",
+                endpoint,
+                strikes,
+                cooldown.as_secs()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrades_after_repeated_overruns() {
+        let client = ApiClient::new();
+        let slow = Endpoint::Trips.budget() + Duration::from_millis(1);
+
+        for _ in 0..budget_strikes() - 1 {
+            client.record(Endpoint::Trips, slow, true);
+        }
+        assert!(!client.is_degraded(Endpoint::Trips));
+
+        client.record(Endpoint::Trips, slow, true);
+        assert!(client.is_degraded(Endpoint::Trips));
+        assert!(!client.is_degraded(Endpoint::Price));
+
+        let stats = &client.stats()[Endpoint::Trips.index()];
+        assert_eq!(stats.calls, budget_strikes());
+        assert_eq!(stats.p50_ms, slow.as_millis() as u64);
+        assert_eq!(stats.over_budget, budget_strikes());
+        assert!(stats.degraded);
+    }
+
+    #[test]
+    fn test_in_budget_call_resets_strikes() {
+        let client = ApiClient::new();
+        let slow = Endpoint::Price.budget() + Duration::from_millis(1);
+
+        for _ in 0..budget_strikes() - 1 {
+            client.record(Endpoint::Price, slow, true);
+        }
+        client.record(Endpoint::Price, Duration::from_millis(1), true);
+        client.record(Endpoint::Price, slow, true);
+        assert!(!client.is_degraded(Endpoint::Price));
+    }
+}
@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use ureq::http::{Request, Response};
+use ureq::middleware::{Middleware, MiddlewareNext};
+use ureq::{Body, SendBody};
+
+/// Written in place of the NS API token in session files
+const REDACTED: &str = "<redacted>";
+
+/// Request headers whose values never end up in a session file
+const SECRET_HEADERS: [&str; 3] = [
+    "ocp-apim-subscription-key",
+    "authorization",
+    "proxy-authorization",
+];
+
+/// One upstream call as stored in a session file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    /// HTTP status, absent when the call failed before a response arrived
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Network or protocol error instead of a response
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Contents of a `--record` / `--replay` file
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionFile {
+    /// nstimes version that made the recording
+    pub version: String,
+    pub exchanges: Vec<Exchange>,
+}
+
+/// Whether upstream calls are recorded to or answered from a session file
+enum Session {
+    Record {
+        path: PathBuf,
+        exchanges: Mutex<Vec<Exchange>>,
+    },
+    Replay {
+        exchanges: Vec<Exchange>,
+        used: Mutex<Vec<bool>>,
+    },
+}
+
+static SESSION: OnceLock<Session> = OnceLock::new();
+
+/// Capture every NS API call of this run in `path`, with the API token redacted
+pub fn record_to(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = PathBuf::from(path);
+    write_session(&path, &[])?;
+    install(Session::Record {
+        path,
+        exchanges: Mutex::new(Vec::new()),
+    })
+}
+
+/// Answer NS API calls from a recording instead of the network
+pub fn replay_from(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("❌ Could not read session file {}: {}", path, e))?;
+    let file: SessionFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("❌ {} is not a session file: {}", path, e))?;
+
+    install(Session::Replay {
+        used: Mutex::new(vec![false; file.exchanges.len()]),
+        exchanges: file.exchanges,
+    })
+}
+
+fn install(session: Session) -> Result<(), Box<dyn std::error::Error>> {
+    SESSION
+        .set(session)
+        .map_err(|_| "A record/replay session is already active".into())
+}
+
+/// Whether calls are recorded or replayed, so every one must reach the session
+pub fn in_session() -> bool {
+    SESSION.get().is_some()
+}
+
+/// Whether calls are served from a recording, so no NS API token is needed
+pub fn is_replaying() -> bool {
+    matches!(SESSION.get(), Some(Session::Replay { .. }))
+}
+
+fn write_session(path: &Path, exchanges: &[Exchange]) -> io::Result<()> {
+    let file = SessionFile {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        exchanges: exchanges.to_vec(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&file)?)
+}
+
+/// Replace the API token wherever it could leak into a recording
+fn redact(text: &str) -> String {
+    match env::var("NS_API_TOKEN") {
+        Ok(token) if !token.is_empty() => text.replace(&token, REDACTED),
+        _ => text.to_string(),
+    }
+}
+
+/// Index of the recorded answer for a call: the first unused match, or the
+/// last match again once all have been used (e.g. a polling loop)
+fn pick(exchanges: &[Exchange], used: &mut [bool], method: &str, url: &str) -> Option<usize> {
+    let matching = |(_, e): &(usize, &Exchange)| e.method == method && e.url == url;
+
+    let index = exchanges
+        .iter()
+        .enumerate()
+        .filter(matching)
+        .find(|(i, _)| !used[*i])
+        .or_else(|| exchanges.iter().enumerate().rfind(matching))
+        .map(|(i, _)| i)?;
+    used[index] = true;
+    Some(index)
+}
+
+/// ureq middleware installed on the shared client
+pub(crate) struct SessionMiddleware;
+
+impl Middleware for SessionMiddleware {
+    fn handle(
+        &self,
+        request: Request<SendBody>,
+        next: MiddlewareNext,
+    ) -> Result<Response<Body>, ureq::Error> {
+        // Only NS API calls belong in a session, not webhook deliveries
+        let ns_call = request.headers().contains_key("ocp-apim-subscription-key");
+        match SESSION.get() {
+            None => next.handle(request),
+            Some(_) if !ns_call => next.handle(request),
+            Some(Session::Record { path, exchanges }) => record(path, exchanges, request, next),
+            Some(Session::Replay { exchanges, used }) => replay(exchanges, used, &request),
+        }
+    }
+}
+
+fn record(
+    path: &Path,
+    exchanges: &Mutex<Vec<Exchange>>,
+    request: Request<SendBody>,
+    next: MiddlewareNext,
+) -> Result<Response<Body>, ureq::Error> {
+    let method = request.method().to_string();
+    let url = redact(&request.uri().to_string());
+    let request_headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let secret = SECRET_HEADERS
+                .iter()
+                .any(|secret| name.as_str().eq_ignore_ascii_case(secret));
+            let value = if secret {
+                REDACTED.to_string()
+            } else {
+                redact(value.to_str().unwrap_or_default())
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+
+    let mut exchange = Exchange {
+        method,
+        url,
+        request_headers,
+        status: None,
+        body: None,
+        error: None,
+    };
+
+    let result = next.handle(request).and_then(|response| {
+        let (parts, mut body) = response.into_parts();
+        let mime_type = body.mime_type().map(str::to_string);
+        let bytes = body.read_to_vec()?;
+
+        exchange.status = Some(parts.status.as_u16());
+        exchange.body = Some(redact(&String::from_utf8_lossy(&bytes)));
+
+        let mut builder = Body::builder();
+        if let Some(mime_type) = mime_type {
+            builder = builder.mime_type(mime_type);
+        }
+        Ok(Response::from_parts(parts, builder.data(bytes)))
+    });
+    match &result {
+        Err(ureq::Error::StatusCode(code)) => exchange.status = Some(*code),
+        Err(e) => exchange.error = Some(redact(&e.to_string())),
+        Ok(_) => {}
+    }
+
+    if let Ok(mut exchanges) = exchanges.lock() {
+        exchanges.push(exchange);
+        if let Err(e) = write_session(path, &exchanges) {
+            eprintln!("⚠️  Could not write session file {}: {}", path.display(), e);
+        }
+    }
+    result
+}
+
+fn replay(
+    exchanges: &[Exchange],
+    used: &Mutex<Vec<bool>>,
+    request: &Request<SendBody>,
+) -> Result<Response<Body>, ureq::Error> {
+    let method = request.method().to_string();
+    let url = redact(&request.uri().to_string());
+
+    let index = used
+        .lock()
+        .ok()
+        .and_then(|mut used| pick(exchanges, &mut used, &method, &url))
+        .ok_or_else(|| {
+            ureq::Error::Io(io::Error::other(format!(
+                "no recorded response for {} {}",
+                method, url
+            )))
+        })?;
+    let exchange = &exchanges[index];
+
+    if let Some(error) = &exchange.error {
+        return Err(ureq::Error::Io(io::Error::other(error.clone())));
+    }
+    let status = exchange.status.unwrap_or(200);
+    if status >= 400 {
+        return Err(ureq::Error::StatusCode(status));
+    }
+
+    let body = Body::builder()
+        .mime_type("application/json")
+        .data(exchange.body.clone().unwrap_or_default());
+    Response::builder()
+        .status(status)
+        .body(body)
+        .map_err(ureq::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(url: &str, body: &str) -> Exchange {
+        Exchange {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            request_headers: BTreeMap::new(),
+            status: Some(200),
+            body: Some(body.to_string()),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_picks_in_order_then_repeats_last() {
+        let exchanges = vec![
+            exchange("https://ns/trips", "first"),
+            exchange("https://ns/price", "price"),
+            exchange("https://ns/trips", "second"),
+        ];
+        let mut used = vec![false; exchanges.len()];
+
+        assert_eq!(
+            pick(&exchanges, &mut used, "GET", "https://ns/trips"),
+            Some(0)
+        );
+        assert_eq!(
+            pick(&exchanges, &mut used, "GET", "https://ns/trips"),
+            Some(2)
+        );
+        assert_eq!(
+            pick(&exchanges, &mut used, "GET", "https://ns/trips"),
+            Some(2)
+        );
+        assert_eq!(
+            pick(&exchanges, &mut used, "POST", "https://ns/trips"),
+            None
+        );
+    }
+}
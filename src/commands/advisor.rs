@@ -0,0 +1,122 @@
+use crate::advisor::{advise, parse_travel_log, route_key, LoggedJourney};
+use crate::cache::PriceCache;
+use crate::i18n::{tr, trf, Text};
+use crate::output::{terminal_width, to_table, OutputFormat, OutputOptions};
+use crate::prices::{cheapest, get_prices, FareQuery};
+use crate::stations::models::Station;
+use crate::stations::{lookup_station_local, StationLookupResult};
+use std::collections::HashMap;
+
+/// Implements `advisor <log.csv>`: whether a subscription would have paid off
+/// for the logged journeys. Every route is priced once, both directions
+/// together; `traject_vrij_fee_cents` is the monthly Traject Vrij fee of the
+/// busiest route
+pub fn execute(
+    path: &str,
+    travel_class: Option<&str>,
+    traject_vrij_fee_cents: Option<i64>,
+    cache: Option<&PriceCache>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let log = std::fs::read_to_string(path)
+        .map_err(|e| format!("❌ Cannot read travel log {}: {}", path, e))?;
+    let journeys = parse_travel_log(&log)?;
+
+    let mut stations: HashMap<&str, Option<Station>> = HashMap::new();
+    for name in journeys.iter().flat_map(|journey| [&journey.from, &journey.to]) {
+        stations.entry(name).or_insert_with(|| match lookup_station_local(name) {
+            StationLookupResult::Single(station) => Some(station),
+            _ => {
+                eprintln!("⚠️  {}", trf(Text::UnknownLogStation, &[name]));
+                None
+            }
+        });
+    }
+
+    // Journeys under their full station names, the fare of every route by those names
+    let mut resolved: Vec<LoggedJourney> = Vec::new();
+    let mut fares: HashMap<(String, String), Option<i32>> = HashMap::new();
+    for journey in &journeys {
+        let (Some(Some(from)), Some(Some(to))) = (
+            stations.get(journey.from.as_str()),
+            stations.get(journey.to.as_str()),
+        ) else {
+            continue;
+        };
+        resolved.push(LoggedJourney {
+            at: journey.at,
+            from: from.names.long.clone(),
+            to: to.names.long.clone(),
+        });
+        let key = route_key(&from.names.long, &to.names.long);
+        if fares.contains_key(&key) {
+            continue;
+        }
+        let fare = match get_prices(from, to, travel_class, Some("single"), &FareQuery::default(), cache) {
+            Ok(response) => cheapest(&response.payload.prices).map(|price| price.total_price_in_cents),
+            Err(e) => {
+                eprintln!(
+                    "⚠️  {}",
+                    trf(Text::NoFareFor, &[&from.names.long, &to.names.long, &e.to_string()])
+                );
+                None
+            }
+        };
+        fares.insert(key, fare);
+    }
+
+    let mut advice = advise(&resolved, traject_vrij_fee_cents, |journey| {
+        fares.get(&route_key(&journey.from, &journey.to)).copied().flatten()
+    });
+    advice.unpriced += journeys.len() - resolved.len();
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&advice)?);
+        return Ok(());
+    }
+
+    let euros = |cents: i64| format!("{:.2}", cents as f64 / 100.0);
+    println!(
+        "📊 {}",
+        trf(
+            Text::AdvisorSummary,
+            &[&advice.journeys.to_string(), &advice.months.to_string()]
+        )
+    );
+    if advice.unpriced > 0 {
+        println!("⚠️  {}", trf(Text::UnpricedJourneys, &[&advice.unpriced.to_string()]));
+    }
+    println!("{}", trf(Text::FullFareTotal, &[&euros(advice.full_fare_cents)]));
+    println!();
+    print!(
+        "{}",
+        to_table(&advice.subscriptions, output.table_style(), terminal_width())
+    );
+    println!();
+
+    match (&advice.best, advice.subscriptions.first()) {
+        (Some(best), Some(cost)) => println!(
+            "✅ {}",
+            trf(Text::BestSubscription, &[best, &euros(cost.saving_cents)])
+        ),
+        _ => println!("✅ {}", tr(Text::FullFareCheapest)),
+    }
+    // Without its fee the busiest route only gets a hint
+    if traject_vrij_fee_cents.is_none()
+        && let Some(route) = &advice.busiest_route
+    {
+        println!(
+            "💡 {}",
+            trf(
+                Text::TrajectVrijHint,
+                &[
+                    &route.from,
+                    &route.to,
+                    &route.journeys.to_string(),
+                    &euros(route.full_fare_cents)
+                ]
+            )
+        );
+    }
+    Ok(())
+}
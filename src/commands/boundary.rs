@@ -0,0 +1,49 @@
+use crate::i18n::{tr, trf, Text};
+use crate::output::{OutputFormat, OutputOptions};
+use crate::stations::pick_station_local;
+use crate::trips::{day_boundary_trip, DayBoundary, TripQuery};
+use chrono::{Local, NaiveDate};
+
+/// Implements `first` and `last`: the first or last running trip of the day
+pub fn execute(
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+    boundary: DayBoundary,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station_from = pick_station_local(from)?;
+    let station_to = pick_station_local(to)?;
+
+    let trip = day_boundary_trip(&station_from, &station_to, date, boundary, &TripQuery::default())?;
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&trip)?);
+        return Ok(());
+    }
+
+    let date_label = date.format("%Y-%m-%d").to_string();
+    let heading = match boundary {
+        DayBoundary::First => Text::FirstJourney,
+        DayBoundary::Last => Text::LastJourney,
+    };
+    println!(
+        "{}",
+        trf(heading, &[&station_from.names.long, &station_to.names.long, &date_label])
+    );
+
+    let Some(trip) = trip else {
+        println!("{}", tr(Text::NoJourneysThatDay));
+        return Ok(());
+    };
+
+    if output.accessible {
+        println!("{}", trip.accessible_description());
+    } else {
+        println!("{}", trip.display_line(output.times, Local::now().fixed_offset()));
+        for line in trip.leg_lines(false) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
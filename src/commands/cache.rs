@@ -0,0 +1,139 @@
+use crate::cache::{read_entries, write_entries, CacheCounters, CacheFile, PriceCache};
+use crate::constants::BUSIEST_ROUTES;
+use crate::i18n::{trf, Text};
+use crate::output::{OutputFormat, OutputOptions};
+use crate::prices::{parse_routes, warm_cache};
+
+/// Show the entries of the price cache and how often it answered lookups,
+/// summed over every run that used it
+pub fn stats(
+    cache: Option<&PriceCache>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = cache.ok_or("❌ cache stats needs --cache <path>")?;
+    let stats = cache.stats();
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("💾 {}", trf(Text::PriceCacheAt, &[cache.path()]));
+    println!(
+        "  {}",
+        trf(
+            Text::CacheEntries,
+            &[
+                &stats.total_entries.to_string(),
+                &stats.valid_entries.to_string(),
+                &stats.expired_entries.to_string()
+            ]
+        )
+    );
+    println!("  {}", usage(&stats.all_time));
+    Ok(())
+}
+
+fn usage(counters: &CacheCounters) -> String {
+    let rate = counters
+        .hit_rate()
+        .map(|rate| format!(", hit rate {:.0}%", rate * 100.0))
+        .unwrap_or_default();
+    format!(
+        "Hits: {}, misses: {}, stale hits: {}, API fetches: {}{}",
+        counters.hits, counters.misses, counters.stale_hits, counters.fetches, rate
+    )
+}
+
+/// Price every route of `file` (or the built-in busiest routes) in both
+/// classes into the cache, so a fresh deployment starts warm
+pub fn warm(
+    file: Option<&str>,
+    jobs: usize,
+    cache: Option<&PriceCache>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = cache.ok_or("❌ cache warm needs --cache <path>")?;
+    let routes = match file {
+        Some(path) => parse_routes(&std::fs::read_to_string(path)?)?,
+        None => BUSIEST_ROUTES
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect(),
+    };
+
+    let report = warm_cache(&routes, jobs, cache);
+    cache.flush()?;
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for failure in &report.failures {
+        eprintln!(
+            "⚠️  {} → {} ({}): {}",
+            failure.from, failure.to, failure.class, failure.error
+        );
+    }
+    println!(
+        "🔥 {}",
+        trf(
+            Text::CacheWarmed,
+            &[&report.cached.to_string(), &report.routes.to_string(), cache.path()]
+        )
+    );
+    Ok(())
+}
+
+/// Write every entry of the cache to `file` (gzip-compressed for `.gz`) or
+/// stdout, in the cache file format another cache can import or use directly
+pub fn export(
+    file: Option<&str>,
+    cache: Option<&PriceCache>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = cache.ok_or("❌ cache export needs --cache <path>")?;
+    let entries = cache.export();
+    match file {
+        Some(path) => {
+            write_entries(path, &entries)?;
+            let count = entries.len().to_string();
+            eprintln!("📤 {}", trf(Text::CacheExported, &[&count, cache.path(), path]));
+        }
+        None => println!("{}", serde_json::to_string_pretty(&CacheFile::new(&entries))?),
+    }
+    Ok(())
+}
+
+/// Merge an exported cache (plain or gzip-compressed) into the cache; the
+/// entry expiring last wins
+pub fn import(
+    file: &str,
+    cache: Option<&PriceCache>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = cache.ok_or("❌ cache import needs --cache <path>")?;
+    let report = cache.import(read_entries(file)?)?;
+    cache.flush()?;
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!(
+        "📥 {}",
+        trf(
+            Text::CacheImported,
+            &[
+                file,
+                cache.path(),
+                &report.added.to_string(),
+                &report.updated.to_string(),
+                &report.kept.to_string()
+            ]
+        )
+    );
+    if report.skipped > 0 {
+        eprintln!("⚠️  Skipped {} entries with legacy keys", report.skipped);
+    }
+    Ok(())
+}
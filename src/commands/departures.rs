@@ -0,0 +1,85 @@
+use crate::departures::departures;
+use crate::i18n::{tr, trf, Text};
+use crate::output::{
+    departures_to_html, departures_to_waybar, terminal_width, to_csv, to_table, OutputFormat,
+    OutputOptions,
+};
+use crate::stations::pick_station_local;
+use crate::trips::train_type_allowed;
+use chrono::Local;
+
+/// Options for the `departures` command
+#[derive(Default)]
+pub struct DepartureOptions {
+    /// Number of departures to list
+    pub limit: usize,
+    /// Write a standalone HTML departure board to this file
+    pub html: Option<String>,
+    /// Make the HTML board reload itself every this many seconds
+    pub refresh: Option<u64>,
+    /// Train categories to skip, e.g. ["SPR"]
+    pub exclude_types: Vec<String>,
+    /// When not empty, only list these train categories, e.g. ["IC"]
+    pub only_types: Vec<String>,
+}
+
+pub fn execute(
+    station: &str,
+    options: &DepartureOptions,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station = pick_station_local(station)?;
+    let mut departures = departures(&station.id.uic_code, options.limit)?;
+    departures.retain(|departure| {
+        train_type_allowed(&departure.train_type, &options.only_types, &options.exclude_types)
+    });
+
+    if let Some(path) = &options.html {
+        let html = departures_to_html(
+            &station.names.long,
+            &departures,
+            options.refresh,
+            Local::now().fixed_offset(),
+        );
+        std::fs::write(path, html)?;
+        println!("📄 {}", trf(Text::WroteDepartureBoard, &[&station.names.long, path]));
+        return Ok(());
+    }
+
+    if let Some(template) = &output.template {
+        for departure in &departures {
+            println!("{}", template.render(departure)?);
+        }
+        return Ok(());
+    }
+
+    match output.format {
+        OutputFormat::Csv => print!("{}", to_csv(&departures)),
+        OutputFormat::Waybar => println!("{}", departures_to_waybar(&departures)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&departures)?),
+        OutputFormat::Table => print!(
+            "{}",
+            to_table(&departures, output.table_style(), terminal_width())
+        ),
+        OutputFormat::Text => {
+            let now = Local::now().fixed_offset();
+            println!("{}", trf(Text::DeparturesFrom, &[&station.names.long]));
+            for departure in &departures {
+                if output.accessible {
+                    println!("{}", departure.accessible_description());
+                } else {
+                    println!("{}", departure.display_line(output.times, now));
+                }
+                for message in &departure.messages {
+                    if output.accessible {
+                        println!("{}: {}", tr(Text::Notice), message);
+                    } else {
+                        println!("  💬 {}", message);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
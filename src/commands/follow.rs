@@ -0,0 +1,175 @@
+use crate::i18n::{tr, trf, Text};
+use crate::monitor::{escalation, journey_key, ChangeEvent, EventLog, Follower, Urgency};
+use crate::stations::pick_station_local;
+use crate::trips::{trips, Trip, TripQuery};
+use chrono::{DateTime, FixedOffset, Local};
+use std::time::Duration;
+
+/// Follow one trip until it departs, printing every track, delay and status
+/// change and optionally raising a desktop notification for it. Changes are
+/// appended to `log`; a cancellation, a long delay or a journey that keeps
+/// changing (counted over the whole log) is escalated with 🚨 and a critical
+/// notification
+pub fn execute(
+    from: &str,
+    to: &str,
+    departure: DateTime<FixedOffset>,
+    interval: Duration,
+    log: &EventLog,
+    notify: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station_from = pick_station_local(from)?;
+    let station_to = pick_station_local(to)?;
+    let route = format!("{} → {}", station_from.names.long, station_to.names.long);
+    let query = TripQuery {
+        date_time: Some(departure),
+        ..Default::default()
+    };
+
+    let mut history = log.load()?;
+    let mut follower = Follower::new(departure);
+    let mut missing = false;
+    let mut notify_failed = false;
+    let mut log_failed = false;
+
+    loop {
+        let changes = match trips(&station_from, &station_to, &query) {
+            Ok(trips) => {
+                let first_poll = follower.current().is_none();
+                let changes = follower.observe(&trips);
+                match (&changes, follower.current()) {
+                    (Some(_), Some(trip)) if first_poll => {
+                        let every = interval.as_secs().to_string();
+                        println!("👀 {}", trf(Text::FollowingTrip, &[&describe(trip), &every]));
+                    }
+                    (None, None) => {
+                        return Err(format!(
+                            "❌ No trip {} departing at {}",
+                            route,
+                            departure.format("%H:%M")
+                        )
+                        .into());
+                    }
+                    _ => {}
+                }
+                changes
+            }
+            Err(e) if follower.current().is_some() => {
+                eprintln!("⚠️  Failed to refresh {}: {}", route, e);
+                Some(Vec::new())
+            }
+            Err(e) => return Err(e),
+        };
+
+        let Some(trip) = follower.current() else {
+            unreachable!("the first poll either finds the trip or returns");
+        };
+
+        match changes {
+            None if !missing => {
+                eprintln!("⚠️  {}", tr(Text::TripNoLongerListed));
+                missing = true;
+            }
+            None => {}
+            Some(changes) => {
+                missing = false;
+                let journey = journey_key(trip);
+                for change in changes {
+                    let urgency = escalation(&history, &journey, &change);
+                    let message = format!("{}: {}", describe(trip), change);
+                    let event = ChangeEvent {
+                        observed_at: Local::now().fixed_offset(),
+                        journey: journey.clone(),
+                        change,
+                    };
+                    if !log_failed && let Err(e) = log.append(std::slice::from_ref(&event)) {
+                        eprintln!("⚠️  Failed to write the change log: {}", e);
+                        log_failed = true;
+                    }
+
+                    let icon = match urgency {
+                        Urgency::Critical => "🚨",
+                        Urgency::Normal => "🔔",
+                    };
+                    println!("{} {} {}", icon, event.observed_at.format("%H:%M"), message);
+                    history.push(event);
+                    if notify
+                        && !notify_failed
+                        && let Err(e) = desktop_notification(&route, &message, urgency)
+                    {
+                        eprintln!("⚠️  Desktop notifications unavailable: {}", e);
+                        notify_failed = true;
+                    }
+                }
+            }
+        }
+
+        if trip.is_cancelled() {
+            println!("❌ {}", trf(Text::TripIsCancelled, &[&describe(trip)]));
+            return Ok(());
+        }
+        if has_departed(trip, Local::now().fixed_offset()) {
+            println!("🚆 {}", trf(Text::TripDeparted, &[&describe(trip)]));
+            return Ok(());
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// e.g. "08:12+3 IC tr.5b"
+fn describe(trip: &Trip) -> String {
+    let delay = match trip.departure_delay() {
+        Some(delay) if delay > 0 => format!("+{}", delay),
+        _ => String::new(),
+    };
+    format!(
+        "{}{} {} tr.{}",
+        trip.departure_time.format("%H:%M"),
+        delay,
+        trip.train_type,
+        trip.track
+    )
+}
+
+fn has_departed(trip: &Trip, now: DateTime<FixedOffset>) -> bool {
+    trip.actual_departure_time.unwrap_or(trip.departure_time) < now
+}
+
+/// Show a desktop notification via `notify-send` or, on macOS, `osascript`
+/// (which has no urgency levels)
+#[cfg(feature = "notify")]
+fn desktop_notification(title: &str, body: &str, urgency: Urgency) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", body, title);
+        let mut command = std::process::Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(target_os = "windows") {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "not supported on Windows",
+        ));
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        if urgency == Urgency::Critical {
+            command.args(["--urgency", "critical"]);
+        }
+        command.args([title, body]);
+        command
+    };
+
+    command
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(not(feature = "notify"))]
+fn desktop_notification(_title: &str, _body: &str, _urgency: Urgency) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "built without the notify feature",
+    ))
+}
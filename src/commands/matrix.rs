@@ -0,0 +1,77 @@
+use crate::i18n::{trf, Text};
+use crate::matrix::{travel_matrix, travel_time_cache_path, TravelTimeCache};
+use crate::output::{OutputFormat, OutputOptions};
+use crate::stations::pick_station_local;
+use crate::trips::format_duration;
+use chrono::Duration;
+
+/// Implements `matrix`: the fastest travel time between every pair of the
+/// given stations, with the best place to meet
+pub fn execute(
+    queries: &[String],
+    jobs: usize,
+    refresh: bool,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stations = queries
+        .iter()
+        .map(|query| pick_station_local(query))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Without a cache directory every pair is searched
+    let cache = travel_time_cache_path().map(TravelTimeCache::open);
+    let matrix = travel_matrix(&stations, jobs, cache.as_ref(), refresh);
+    if let Some(cache) = &cache
+        && let Err(e) = cache.save()
+    {
+        eprintln!("⚠️  Could not save the travel-time cache: {}", e);
+    }
+    for failure in &matrix.failures {
+        eprintln!("⚠️  {} → {}: {}", failure.from, failure.to, failure.error);
+    }
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&matrix)?);
+        return Ok(());
+    }
+
+    // Columns are headed by the NS abbreviation, or the start of the name
+    let headers: Vec<String> = stations
+        .iter()
+        .map(|station| {
+            station
+                .id
+                .code
+                .clone()
+                .unwrap_or_else(|| station.names.long.chars().take(6).collect())
+        })
+        .collect();
+    let name_width = matrix.stations.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+
+    println!("{}", trf(Text::TravelTimesBetween, &[&stations.len().to_string()]));
+    print!("{:<width$}", "", width = name_width);
+    for header in &headers {
+        print!("  {:>6}", header);
+    }
+    println!();
+    for (name, row) in matrix.stations.iter().zip(&matrix.minutes) {
+        print!("{:<width$}", name, width = name_width);
+        for minutes in row {
+            let cell = minutes.map_or("—".to_string(), |m| format_duration(Duration::minutes(m)));
+            print!("  {:>6}", cell);
+        }
+        println!();
+    }
+
+    if let Some((best, worst)) = matrix.best_meeting_point() {
+        println!();
+        println!(
+            "{}",
+            trf(
+                Text::BestMeetingPoint,
+                &[&matrix.stations[best], &format_duration(Duration::minutes(worst))],
+            )
+        );
+    }
+    Ok(())
+}
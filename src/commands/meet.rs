@@ -0,0 +1,57 @@
+use crate::i18n::{tr, trf, Text};
+use crate::stations::pick_station_local;
+use crate::trips::{align_arrivals, trips, Trip, TripQuery};
+
+pub fn execute(
+    their_from: &str,
+    my_from: &str,
+    meeting_point: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station_theirs = pick_station_local(their_from)?;
+    let station_mine = pick_station_local(my_from)?;
+    let station_meet = pick_station_local(meeting_point)?;
+
+    println!(
+        "{}",
+        trf(
+            Text::MeetingAt,
+            &[
+                &station_meet.names.long,
+                &station_theirs.names.long,
+                &station_mine.names.long,
+            ],
+        )
+    );
+
+    let theirs = trips(&station_theirs, &station_meet, &TripQuery::default())?;
+    let mine = trips(&station_mine, &station_meet, &TripQuery::default())?;
+
+    let pairs = align_arrivals(&theirs, &mine);
+    if pairs.is_empty() {
+        println!("{}", tr(Text::NoJourneysForBoth));
+        return Ok(());
+    }
+
+    println!();
+    println!("{:<28} | {:<28} | {}", tr(Text::Them), tr(Text::You), tr(Text::Wait));
+    for (i, j, gap) in pairs {
+        let wait = match gap {
+            0 => tr(Text::ArriveTogether).to_string(),
+            g if g > 0 => trf(Text::TheyWait, &[&g.to_string()]),
+            g => trf(Text::YouWait, &[&(-g).to_string()]),
+        };
+        println!("{:<28} | {:<28} | {}", summary(&theirs[i]), summary(&mine[j]), wait);
+    }
+
+    Ok(())
+}
+
+fn summary(trip: &Trip) -> String {
+    format!(
+        "{} -> {} {} tr.{}",
+        trip.departure_time.format("%H:%M"),
+        trip.effective_arrival().format("%H:%M"),
+        trip.train_type,
+        trip.track
+    )
+}
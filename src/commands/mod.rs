@@ -1,2 +1,13 @@
+pub mod advisor;
+pub mod boundary;
+pub mod cache;
+pub mod departures;
+pub mod follow;
+pub mod matrix;
+pub mod meet;
+pub mod monitor;
+pub mod ovfiets;
+pub mod plan;
 pub mod price;
+pub mod stations;
 pub mod trip;
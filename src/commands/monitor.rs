@@ -0,0 +1,53 @@
+use crate::i18n::{trf, Text};
+use crate::monitor::{history_by_journey, run_watch, EventLog, Sink};
+use crate::stations::pick_station_local;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// Poll the trips between two stations, log every delay/platform/status change
+/// and hand each refresh to all sinks
+pub fn watch(
+    from: &str,
+    to: &str,
+    interval: Duration,
+    log: &EventLog,
+    sinks: &[Box<dyn Sink>],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station_from = pick_station_local(from)?;
+    let station_to = pick_station_local(to)?;
+    let route = format!("{} → {}", station_from.names.long, station_to.names.long);
+    let targets: Vec<String> = sinks.iter().map(|sink| sink.describe()).collect();
+    println!(
+        "👀 {}",
+        trf(
+            Text::WatchingRoute,
+            &[
+                &route,
+                &interval.as_secs().to_string(),
+                &log.path().display().to_string(),
+                &targets.join(", ")
+            ]
+        )
+    );
+
+    // Runs until the process is interrupted
+    run_watch(&station_from, &station_to, interval, log, sinks, &AtomicBool::new(false));
+    Ok(())
+}
+
+/// Print the logged changes grouped per journey
+pub fn history(log: &EventLog) -> Result<(), Box<dyn std::error::Error>> {
+    let events = log.load()?;
+    if events.is_empty() {
+        println!("{}", trf(Text::NoChangesRecorded, &[&log.path().display().to_string()]));
+        return Ok(());
+    }
+
+    for (journey, events) in history_by_journey(&events) {
+        println!("{}", journey);
+        for event in events {
+            println!("  {} {}", event.observed_at.format("%H:%M"), event.change);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::i18n::{tr, trf, Text};
+use crate::output::{OutputFormat, OutputOptions};
+use crate::places::bike_rentals;
+use crate::stations::{pick_station_local, station_code};
+use colored::*;
+
+/// Implements `ovfiets`: the OV-fiets rental locations of a station and the
+/// bikes available at each right now
+pub fn execute(station: &str, output: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let station = pick_station_local(station)?;
+    let rentals = bike_rentals(&station_code(&station)?)?;
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&rentals)?);
+        return Ok(());
+    }
+
+    if rentals.is_empty() {
+        println!("{}", trf(Text::NoOvFiets, &[&station.names.long]));
+        return Ok(());
+    }
+
+    println!("{}", trf(Text::OvFietsAt, &[&station.names.long]));
+    for rental in &rentals {
+        let stock = match rental.available {
+            Some(0) => trf(Text::BikesAvailable, &["0"]).red().to_string(),
+            Some(count) => trf(Text::BikesAvailable, &[&count.to_string()]),
+            None => tr(Text::BikesUnknown).to_string(),
+        };
+        println!("  {}: {}", rental.name, stock);
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::cache::PriceCache;
+use crate::i18n::{tr, trf, Text};
+use crate::output::{OutputFormat, OutputOptions};
+use crate::prices::models::Price;
+use crate::prices::{cheapest, get_prices, FareQuery};
+use crate::stations::pick_station_local;
+use crate::trips::{trips, Trip, TripQuery};
+use chrono::Local;
+
+/// Implements `plan`: the journeys and the ticket price for them, looked up
+/// once for the same pair of stations
+pub fn execute(
+    from: &str,
+    to: &str,
+    query: &TripQuery,
+    travel_class: Option<&str>,
+    cache: Option<&PriceCache>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let station_from = pick_station_local(from)?;
+    let station_to = pick_station_local(to)?;
+    if output.is_human() {
+        println!(
+            "{}",
+            trf(Text::FindingJourney, &[&station_from.names.long, &station_to.names.long])
+        );
+    }
+
+    let trips = trips(&station_from, &station_to, query)?;
+    let fare = FareQuery {
+        planned_departure_time: query.date_time,
+        ..Default::default()
+    };
+    let prices = get_prices(&station_from, &station_to, travel_class, Some("single"), &fare, cache)?;
+    let price = cheapest(&prices.payload.prices);
+
+    if output.format == OutputFormat::Json {
+        let plan = serde_json::json!({
+            "from": station_from.names.long,
+            "to": station_to.names.long,
+            "price": price,
+            "trips": trips,
+        });
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    if price.is_none() {
+        println!("{}", tr(Text::NoPrices));
+    }
+    let now = Local::now().fixed_offset();
+    for trip in &trips {
+        if output.accessible {
+            println!("{}", trip.accessible_description());
+        } else {
+            println!("{}", trip.display_line(output.times, now));
+            for line in trip.leg_lines(false) {
+                println!("{}", line);
+            }
+        }
+        if let Some(price) = price {
+            let line = ticket_line(price, trip);
+            if output.accessible {
+                println!("{}", line);
+            } else {
+                println!("  🎫 {}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The fare for one journey, plus its supplement when it runs an Intercity direct or ICE
+fn ticket_line(price: &Price, trip: &Trip) -> String {
+    let euros = |cents: i32| format!("{:.2}", cents as f64 / 100.0);
+    let fare = trf(
+        Text::TicketPrice,
+        &[&euros(price.total_price_in_cents), price.class_label()],
+    );
+    match trip.supplement_cents() {
+        Some(supplement) => format!(
+            "{}, {}",
+            fare,
+            trf(
+                Text::WithSupplement,
+                &[
+                    &euros(supplement),
+                    &trip.supplement_types().join(", "),
+                    &euros(price.total_price_in_cents + supplement)
+                ]
+            )
+        ),
+        None => fare,
+    }
+}
@@ -1,75 +1,487 @@
-use crate::cache::PriceCache;
-use crate::prices::get_prices;
-use crate::stations::pick_station_local;
+use crate::cache::{PriceCache, PriceKey};
+use crate::commands::trip::supplement_line;
+use crate::currency::{get_rates, models::currency_symbol};
+use crate::i18n::{tr, trf, Text};
+use crate::international::abroad;
+use crate::output::{
+    prices_to_waybar, terminal_width, to_csv, to_table, OutputFormat, OutputOptions,
+};
+use crate::prices::models::{PriceApiResponse, PricesResponse};
+use crate::prices::{
+    bikes_restricted, cheapest, compare_classes, get_prices, group_fare, sort_products, CityPick,
+    FareQuery, Passengers, BIKE_DAY_TICKET_CENTS,
+};
+use crate::stations::models::Station;
+use crate::stations::pick_stations;
+use crate::trips::{next_supplement_trip, trips, TripQuery};
+use chrono::Local;
 use colored::*;
 
+/// Options for the `price` command
+#[derive(Default)]
+pub struct PriceOptions {
+    /// "FIRST_CLASS" or "SECOND_CLASS" (API default when None)
+    pub travel_class: Option<String>,
+    pub is_return: bool,
+    /// ISO currency code to show converted amounts in, e.g. "GBP"
+    pub currency: Option<String>,
+    /// Which member station pair to show when a city such as "amsterdam" is given
+    pub city_pick: CityPick,
+    /// Also show the supplement the next journeys need, if any
+    pub supplement: bool,
+    /// Passengers and subscription discount to price for
+    pub fare: FareQuery,
+    /// List the single and return products of both classes instead of one
+    pub all_products: bool,
+    /// Show both classes side by side instead of one
+    pub compare_class: bool,
+    /// Price for a group of this many adults (also set in `fare.passengers`)
+    pub group: Option<u32>,
+    /// Add the day bicycle ticket and warn about the rush-hour bike ban
+    pub bike: bool,
+    /// Also show the fare per tariff year from the price cache
+    pub history: bool,
+}
+
 pub fn execute(
     from: &str,
     to: &str,
-    travel_class: Option<String>,
-    is_return: bool,
+    options: &PriceOptions,
     cache: Option<&PriceCache>,
+    output: &OutputOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let station_from = pick_station_local(from)?;
-    let station_to = pick_station_local(to)?;
+    if options.history && cache.is_none() {
+        return Err("❌ --history reads the price cache, pass --cache FILE".into());
+    }
+    let froms = pick_stations(from)?;
+    let tos = pick_stations(to)?;
+    // City groups are domestic, so only a single pair can cross the border.
+    // International fares are not cached
+    let foreign = froms.first().zip(tos.first()).and_then(|(from, to)| abroad(from, to));
+    let cache = if foreign.is_some() { None } else { cache };
+
+    let class_param = options.travel_class.as_deref();
+    let travel_type = if options.is_return { Some("return") } else { Some("single") };
+
+    // Fetch exchange rates up front so an unknown currency fails before the price lookup
+    let conversion = match &options.currency {
+        Some(currency) => {
+            let rates = get_rates()?;
+            if !rates.rates.contains_key(&currency.to_uppercase()) {
+                let mut known: Vec<&String> = rates.rates.keys().collect();
+                known.sort();
+                let known: Vec<&str> = known.iter().map(|c| c.as_str()).collect();
+                return Err(format!(
+                    "❌ Unknown currency '{}'. ECB rates are available for: {}",
+                    currency,
+                    known.join(", ")
+                )
+                .into());
+            }
+            Some((currency.to_uppercase(), rates))
+        }
+        // Also show cross-border fares in the local currency there, when the rates are at hand
+        None => foreign
+            .map(|country| country.currency())
+            .filter(|currency| *currency != "EUR")
+            .and_then(|currency| Some((currency.to_string(), get_rates().ok()?))),
+    };
 
-    let class_param = travel_class.as_deref();
-    let travel_type = if is_return { Some("return") } else { Some("single") };
+    let fetch = |from: &Station, to: &Station| {
+        if options.all_products {
+            all_products(from, to, &options.fare, cache)
+        } else {
+            get_prices(from, to, class_param, travel_type, &options.fare, cache)
+        }
+    };
+
+    let (station_from, station_to, mut response) = if froms.len() == 1 && tos.len() == 1 {
+        if output.is_human() {
+            println!(
+                "{}",
+                trf(Text::GettingPrices, &[&froms[0].names.long, &tos[0].names.long])
+            );
+            if let Some(country) = foreign {
+                println!("🌍 {}", trf(Text::InternationalJourney, &[country.name()]));
+            }
+        }
+        let response = fetch(&froms[0], &tos[0])?;
+        (&froms[0], &tos[0], response)
+    } else {
+        let pairs = froms.len() * tos.len();
+        if output.is_human() {
+            println!(
+                "{}",
+                trf(Text::ComparingStations, &[&pairs.to_string(), from, to])
+            );
+        }
+        let (station_from, station_to, response) =
+            best_pair(&froms, &tos, options.city_pick, fetch)?;
+        if output.is_human() {
+            let pick = match options.city_pick {
+                CityPick::Cheapest => tr(Text::Cheapest),
+                CityPick::Fastest => tr(Text::Fastest),
+            };
+            println!(
+                "📍 {}",
+                trf(
+                    Text::ChosenStations,
+                    &[&station_from.names.long, &station_to.names.long, pick]
+                )
+            );
+        }
+        (station_from, station_to, response)
+    };
+    if options.all_products {
+        sort_products(&mut response.payload.prices);
+    }
 
-    println!(
-        "Getting prices from {} to {}",
-        station_from.names.long, station_to.names.long,
-    );
+    // Without --class the prices above are 2nd class
+    if options.compare_class {
+        let first_class = get_prices(
+            station_from,
+            station_to,
+            Some("FIRST_CLASS"),
+            travel_type,
+            &options.fare,
+            cache,
+        )?;
+        let rows = compare_classes(&response.payload.prices, &first_class.payload.prices);
+        match output.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+            OutputFormat::Csv => print!("{}", to_csv(&rows)),
+            _ if rows.is_empty() => println!("{}", tr(Text::NoPrices)),
+            _ => {
+                println!();
+                print!("{}", to_table(&rows, output.table_style(), terminal_width()));
+            }
+        }
+        return Ok(());
+    }
 
-    let response = get_prices(&station_from, &station_to, class_param, travel_type, cache)?;
+    let mut supplement_types = String::new();
+    let supplement = if options.supplement {
+        let trip = next_supplement_trip(station_from, station_to)?;
+        if let Some(trip) = &trip
+            && let Some(cents) = trip.supplement_cents()
+        {
+            supplement_types = trip.supplement_types().join(", ");
+            for price in &mut response.payload.prices {
+                price.supplement_in_cents = Some(cents);
+            }
+        }
+        Some(match &trip {
+            Some(trip) => supplement_line(trip),
+            None => tr(Text::NoSupplement).to_string(),
+        })
+    } else {
+        None
+    };
+
+    if let Some(template) = &output.template {
+        for price in &response.payload.prices {
+            println!("{}", template.render(price)?);
+        }
+        return Ok(());
+    }
+
+    match output.format {
+        OutputFormat::Csv => {
+            print!("{}", to_csv(&response.payload.prices));
+            return Ok(());
+        }
+        OutputFormat::Waybar => {
+            println!("{}", prices_to_waybar(&response.payload.prices));
+            return Ok(());
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&response.payload.prices)?);
+            return Ok(());
+        }
+        OutputFormat::Text | OutputFormat::Table => {}
+    }
 
     if response.payload.prices.is_empty() {
-        println!("No prices found for this route.");
+        let text = if foreign.is_some() { Text::NoInternationalFare } else { Text::NoPrices };
+        println!("{}", tr(text));
         return Ok(());
     }
 
-    println!();
-    for price in &response.payload.prices {
-        let total_euros = price.total_price_in_cents as f64 / 100.0;
-        let per_adult_euros = price.price_per_adult_in_cents as f64 / 100.0;
+    let fare_units = response
+        .payload
+        .fare_units
+        .map(|units| trf(Text::FareUnits, &[&units.to_string()]));
 
-        let class_str = match price.travel_class.as_str() {
-            "FIRST_CLASS" => "1st class",
-            "SECOND_CLASS" => "2nd class",
-            _ => &price.travel_class,
+    let travel_time = options.fare.planned_departure_time.map(|time| time.naive_local());
+    let bike_warning = options.bike
+        && bikes_restricted(travel_time.unwrap_or_else(|| Local::now().naive_local()));
+    let group_line = options.group.and_then(|size| {
+        let price = group_fare(&response.payload.prices)?;
+        let text = if price.is_group_product() {
+            Text::GroupProduct
+        } else {
+            Text::GroupNoProduct
         };
+        Some(trf(
+            text,
+            &[
+                &size.to_string(),
+                &price.display_name,
+                &euros(price.total_price_in_cents / size as i32),
+                &euros(price.total_price_in_cents),
+            ],
+        ))
+    });
+
+    if output.accessible {
+        for price in &response.payload.prices {
+            println!("{}", price.accessible_description());
+        }
+        if let Some(fare_units) = &fare_units {
+            println!("{}", fare_units);
+        }
+        if let Some(group_line) = &group_line {
+            println!("{}", group_line);
+        }
+        if bike_warning {
+            println!("{}", tr(Text::BikesRestricted));
+        }
+        if let Some(supplement) = supplement {
+            println!("{}", supplement);
+        }
+        return Ok(());
+    }
 
+    println!();
+    if let Some(fare_units) = &fare_units {
+        println!("{}", fare_units);
+        println!();
+    }
+    let mut group = None;
+    for price in &response.payload.prices {
+        if options.all_products && group.as_ref() != Some(&price.product_group()) {
+            let travel_type = match price.travel_type.as_deref() {
+                Some("return") => tr(Text::ReturnJourney),
+                _ => tr(Text::SingleJourney),
+            };
+            println!("{}", format!("{} · {}", travel_type, price.class_label()).bold());
+            group = Some(price.product_group());
+        }
         let mut line = format!(
-            "€{:.2} - {} ({})",
-            total_euros,
+            "€{} - {} ({})",
+            euros(price.total_price_in_cents),
             price.display_name.bold(),
-            class_str
+            price.class_label()
         );
 
         if price.is_best_option {
-            line = format!("{} {}", line, "⭐ Best option".green());
+            line = format!("{} {}", line, format!("⭐ {}", tr(Text::BestOption)).green());
         }
 
         println!("{}", line);
-        println!("  Per adult: €{:.2}", per_adult_euros);
-
-        if let Some(discount) = price.discount_in_cents {
-            if discount > 0 {
-                let discount_euros = discount as f64 / 100.0;
-                println!("  Discount: €{:.2}", discount_euros);
+        println!("  {}: €{}", tr(Text::PerAdult), euros(price.price_per_adult_in_cents));
+        if let (Some(supplement), Some(total)) =
+            (price.supplement_in_cents, price.total_with_supplement_cents())
+        {
+                println!(
+                "  {}",
+                trf(Text::WithSupplement, &[&euros(supplement), &supplement_types, &euros(total)])
+            );
+        }
+        if options.bike {
+                println!(
+                "  {}",
+                trf(
+                    Text::BikeTicket,
+                    &[
+                        &euros(BIKE_DAY_TICKET_CENTS),
+                        &euros(price.total_price_in_cents + BIKE_DAY_TICKET_CENTS)
+                    ]
+                )
+            );
+        }
+        if options.group.is_none() && options.fare.passengers != Passengers::default() {
+                let adults = options.fare.passengers.adults;
+            println!(
+                "  {}",
+                trf(
+                    Text::AdultsTotal,
+                    &[&adults.to_string(), &euros(price.price_per_adult_in_cents * adults as i32)]
+                )
+            );
+            if options.fare.passengers.children > 0 {
+                println!(
+                    "  {}",
+                    trf(
+                        Text::ChildrenTotal,
+                        &[
+                            &options.fare.passengers.children.to_string(),
+                            &euros(price.children_total_cents(options.fare.passengers))
+                        ]
+                    )
+                );
             }
         }
 
+        if let Some((currency, rates)) = &conversion
+            && let Some(converted) = rates.convert(price.total_price_in_cents, currency)
+        {
+            println!(
+                "  {}",
+                trf(
+                    Text::ConvertedPrice,
+                    &[
+                        &currency_symbol(currency),
+                        &format!("{:.2}", converted),
+                        &euros(price.total_price_in_cents),
+                        &rates.rates[currency].to_string(),
+                        &rates.date
+                    ]
+                )
+            );
+        }
+
+        if let Some(discount) = price.discount_in_cents
+            && discount > 0
+        {
+            println!("  {}: €{}", tr(Text::Discount), euros(discount));
+        }
+
         if price.discount_type != "NONE" {
-            println!("  Discount type: {}", price.discount_type);
+            println!("  {}: {}", tr(Text::DiscountType), price.discount_type);
         }
 
         if let Some(operator) = &price.operator_name {
-            println!("  Operator: {}", operator);
+            println!("  {}: {}", tr(Text::Operator), operator);
         }
 
         println!();
     }
 
+    if let Some(group_line) = group_line {
+        println!("👥 {}", group_line);
+    }
+    if options.history
+        && let Some(cache) = cache
+    {
+        print_history(cache, station_from, station_to, options.travel_class.as_deref());
+    }
+    if bike_warning {
+        println!("🚲 {}", tr(Text::BikesRestricted));
+    }
+    if let Some(supplement) = supplement {
+        println!("💶 {}", supplement);
+    }
+
     Ok(())
 }
+
+/// The cached full fare of the route per tariff year, with the change each year
+fn print_history(cache: &PriceCache, from: &Station, to: &Station, travel_class: Option<&str>) {
+    let (class, label) = match travel_class {
+        Some("FIRST_CLASS") => (1, "1st class"),
+        _ => (2, "2nd class"),
+    };
+    let history = cache.history(&PriceKey::new(&from.names.long, &to.names.long, class));
+    if history.is_empty() {
+        println!("📈 {}", tr(Text::NoFareHistory));
+        return;
+    }
+
+    println!("📈 {}", trf(Text::FareHistory, &[label]));
+    let euros = |cents: i64| format!("€{:.2}", cents as f64 / 100.0);
+    let mut previous: Option<u32> = None;
+    for record in &history {
+        let mut line = format!("  {}: {}", record.tariff_year, euros(record.price_cents.into()));
+        if let Some(previous) = previous.filter(|previous| *previous > 0) {
+            let change = i64::from(record.price_cents) - i64::from(previous);
+            line.push_str(&format!(
+                " ({}{}, {:+.1}%)",
+                if change < 0 { "-" } else { "+" },
+                euros(change.abs()),
+                change as f64 / previous as f64 * 100.0
+            ));
+        }
+        println!("{}", line);
+        previous = Some(record.price_cents);
+    }
+}
+
+/// Every single and return product in both classes, tagged with its travel type
+fn all_products(
+    from: &Station,
+    to: &Station,
+    fare: &FareQuery,
+    cache: Option<&PriceCache>,
+) -> Result<PriceApiResponse, Box<dyn std::error::Error>> {
+    let mut response = PriceApiResponse {
+        payload: PricesResponse {
+            prices: Vec::new(),
+            fare_units: None,
+        },
+    };
+    for travel_type in ["single", "return"] {
+        for class in ["SECOND_CLASS", "FIRST_CLASS"] {
+            let prices = get_prices(from, to, Some(class), Some(travel_type), fare, cache)?;
+            response.payload.fare_units = response.payload.fare_units.or(prices.payload.fare_units);
+            response.payload.prices.extend(prices.payload.prices.into_iter().map(|mut price| {
+                price.travel_type.get_or_insert_with(|| travel_type.to_string());
+                price
+            }));
+        }
+    }
+    Ok(response)
+}
+
+/// Price every station pair and keep the cheapest, or the one with the fastest
+/// next journey. Pairs that fail are skipped with a warning.
+fn best_pair<'a>(
+    froms: &'a [Station],
+    tos: &'a [Station],
+    pick: CityPick,
+    fetch: impl Fn(&Station, &Station) -> Result<PriceApiResponse, Box<dyn std::error::Error>>,
+) -> Result<(&'a Station, &'a Station, PriceApiResponse), Box<dyn std::error::Error>> {
+    let mut best: Option<(i64, &Station, &Station, PriceApiResponse)> = None;
+
+    for from in froms {
+        for to in tos.iter().filter(|to| to.id.uic_code != from.id.uic_code) {
+            let score = fetch(from, to).and_then(|response| {
+                let score = match pick {
+                    CityPick::Cheapest => cheapest(&response.payload.prices)
+                        .map(|price| i64::from(price.total_price_in_cents)),
+                    CityPick::Fastest => {
+                        let query = TripQuery {
+                            limit: Some(1),
+                            ..Default::default()
+                        };
+                        trips(from, to, &query)?
+                            .first()
+                            .map(|trip| trip.duration_minutes)
+                    }
+                };
+                Ok((score, response))
+            });
+
+            match score {
+                Ok((Some(score), response)) => {
+                    if best.as_ref().is_none_or(|(best_score, ..)| score < *best_score) {
+                        best = Some((score, from, to, response));
+                    }
+                }
+                Ok((None, _)) => {}
+                Err(e) => eprintln!(
+                    "⚠️  Skipping {} → {}: {}",
+                    from.names.long, to.names.long, e
+                ),
+            }
+        }
+    }
+
+    best.map(|(_, from, to, response)| (from, to, response))
+        .ok_or_else(|| format!("❌ {}", tr(Text::NoPrices)).into())
+}
+
+/// Cents as euros with two decimals, e.g. "12.30"
+fn euros(cents: i32) -> String {
+    format!("{:.2}", f64::from(cents) / 100.0)
+}
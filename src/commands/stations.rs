@@ -0,0 +1,111 @@
+use crate::constants::{STATION_INFO, STATIONS};
+use crate::i18n::{trf, Text};
+use crate::output::{terminal_width, to_csv, to_table, OutputFormat, OutputOptions};
+use crate::stations::{
+    fetch_all_stations, station_data, station_data_path, stations_to_csv, validate_stations,
+};
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Dump the compiled-in station table with every field: JSON by default,
+/// `--output csv` in the data/stations.csv layout or `--output table`
+pub fn export(output: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    match output.format {
+        OutputFormat::Csv => print!("{}", to_csv(STATION_INFO)),
+        OutputFormat::Table => print!(
+            "{}",
+            to_table(STATION_INFO, output.table_style(), terminal_width())
+        ),
+        _ => println!("{}", serde_json::to_string_pretty(STATION_INFO)?),
+    }
+    Ok(())
+}
+
+/// Fetch the full station list into the data file that local lookups
+/// prefer over the compiled-in table
+pub fn sync(file: Option<&str>, output: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let live = fetch_all_stations()?;
+    let data = station_data(&live, Utc::now());
+    if data.stations.is_empty() {
+        return Err("❌ The NS stations API returned no stations, keeping the current data file".into());
+    }
+
+    let default = station_data_path();
+    let path = match file {
+        Some(file) => PathBuf::from(file),
+        None => default
+            .clone()
+            .ok_or("❌ No cache directory found, set NS_STATIONS_FILE or pass a file")?,
+    };
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&data)?)?;
+
+    if output.format == OutputFormat::Json {
+        let summary = serde_json::json!({
+            "path": path,
+            "stations": data.stations.len(),
+            "synced_at": data.synced_at,
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        let count = data.stations.len().to_string();
+        println!("✅ {}", trf(Text::StationsSynced, &[&count, &path.display().to_string()]));
+    }
+    if Some(&path) != default.as_ref() {
+        eprintln!(
+            "💡 Set NS_STATIONS_FILE={} so station lookups read this file",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Cross-check the local station table against the live stations API and,
+/// with `write`, save the live list as a corrected data/stations.csv
+pub fn validate(
+    write: Option<&str>,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let live = fetch_all_stations()?;
+    let report = validate_stations(STATIONS, &live);
+
+    if output.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.is_clean() {
+        let counts = [STATIONS.len().to_string(), live.len().to_string()];
+        println!("✅ {}", trf(Text::StationsMatch, &[&counts[0], &counts[1]]));
+    } else {
+        let counts = [STATIONS.len().to_string(), live.len().to_string()];
+        println!("🔧 {}", trf(Text::StationsChecked, &[&counts[0], &counts[1]]));
+        for entry in &report.removed {
+            println!("  - removed: {} ({})", entry.name, entry.uic_code);
+        }
+        for change in &report.changed_uic {
+            println!(
+                "  ~ UIC changed: {} {} → {}",
+                change.local.name, change.local.uic_code, change.live.uic_code
+            );
+        }
+        for change in &report.renamed {
+            println!(
+                "  ~ renamed: {} → {} ({})",
+                change.local.name, change.live.name, change.live.uic_code
+            );
+        }
+        for entry in &report.added {
+            println!("  + new: {} ({})", entry.name, entry.uic_code);
+        }
+    }
+
+    if let Some(path) = write {
+        std::fs::write(path, stations_to_csv(&live))?;
+        eprintln!(
+            "📄 Wrote the corrected station table to {}, replace data/stations.csv with it",
+            path
+        );
+    }
+
+    Ok(())
+}
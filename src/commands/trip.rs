@@ -1,13 +1,347 @@
-use crate::stations::pick_station_local;
-use crate::trips::trips;
-
-pub fn execute(from: &str, to: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let station_from = pick_station_local(from)?;
-    let station_to = pick_station_local(to)?;
-    println!(
-        "Finding journey from {} to {}",
-        station_from.names.long, station_to.names.long,
-    );
-    trips(station_from, station_to)?;
+use crate::disruptions::{active_disruptions, likely_cause, Disruption};
+use crate::i18n::{tr, trf, Text};
+use crate::output::{
+    terminal_width, to_csv, to_table, trips_to_geojson, trips_to_ical, trips_to_waybar,
+    OutputFormat, OutputOptions,
+};
+use crate::monitor::{typical_delay, EventLog};
+use crate::prices::bikes_restricted_during;
+use crate::stations::models::Station;
+use crate::stations::{pick_station_local, pick_stations};
+use crate::trips::{
+    next_alternative, planner_url, sort_trips, trips, CancelledMode, Transfer, TransferRisk, Trip,
+    TripQuery, TripSort,
+};
+use chrono::Local;
+
+/// Options for the `trip` command beyond the search itself
+#[derive(Default)]
+pub struct TripOptions {
+    /// Print the journeys as GeoJSON instead of text
+    pub geojson: bool,
+    /// Also write the journeys to this .ics file
+    pub ical: Option<String>,
+    /// Annotate on-time journeys with their typical delay from this monitor log
+    pub history: Option<EventLog>,
+    /// Station name the journey must pass through
+    pub via: Option<String>,
+    /// Open the same search in the ns.nl journey planner
+    pub open: bool,
+    /// List the intermediate stations of every leg
+    pub stops: bool,
+    /// Order of the listed trips
+    pub sort: TripSort,
+    /// Show, hide or annotate cancelled trips
+    pub cancelled: CancelledMode,
+    /// Show the estimated CO₂ saved compared to driving
+    pub co2: bool,
+    /// Minutes needed to change trains before a transfer is flagged
+    pub min_transfer: i64,
+    /// Warn about journeys that run into the rush-hour bike ban
+    pub bike: bool,
+}
+
+pub fn execute(
+    from: &str,
+    to: &str,
+    query: &TripQuery,
+    options: &TripOptions,
+    output: &OutputOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let geojson = options.geojson;
+    let froms = pick_stations(from)?;
+    let tos = pick_stations(to)?;
+    let grouped = froms.len() > 1 || tos.len() > 1;
+    let station_via = options.via.as_deref().map(pick_station_local).transpose()?;
+    if output.is_human() && !geojson {
+        let from_name = &group_label(from, &froms);
+        let to_name = &group_label(to, &tos);
+        let header = match &station_via {
+            Some(via) => trf(Text::FindingJourneyVia, &[from_name, to_name, &via.names.long]),
+            None => trf(Text::FindingJourney, &[from_name, to_name]),
+        };
+        println!("{}", header);
+    }
+
+    let query = TripQuery {
+        via_uic: station_via.as_ref().map(|via| via.id.uic_code.clone()),
+        ..query.clone()
+    };
+    let mut trips = if grouped {
+        group_trips(&froms, &tos, &query)?
+    } else {
+        trips(&froms[0], &tos[0], &query)?
+    };
+    // Arrive-by results are already ordered by arrival
+    if options.sort != TripSort::Departure || !query.arrive_by {
+        sort_trips(&mut trips, options.sort);
+    }
+    if options.cancelled == CancelledMode::Hide {
+        trips.retain(|trip| !trip.is_cancelled());
+    }
+
+    if let Some(path) = &options.ical {
+        std::fs::write(path, trips_to_ical(&trips))?;
+        if output.is_human() {
+            println!("📅 {}", trf(Text::WroteTrips, &[&trips.len().to_string(), path]));
+        }
+    }
+
+    if options.open {
+        // A group opens the stations of its best journey
+        let best = trips.first();
+        let station_from = best
+            .and_then(|trip| member(&froms, trip.origin_uic.as_deref()))
+            .unwrap_or(&froms[0]);
+        let station_to = best
+            .and_then(|trip| member(&tos, trip.destination_uic.as_deref()))
+            .unwrap_or(&tos[0]);
+        let url = planner_url(station_from, station_to, station_via.as_ref(), &query);
+        if output.is_human() {
+            println!("🌐 {}", url);
+        }
+        if let Err(e) = open_in_browser(&url) {
+            eprintln!("⚠️  {}", trf(Text::BrowserFailed, &[&e.to_string()]));
+        }
+    }
+
+    if geojson {
+        println!("{}", serde_json::to_string_pretty(&trips_to_geojson(&trips))?);
+        return Ok(());
+    }
+
+    if let Some(template) = &output.template {
+        for trip in &trips {
+            println!("{}", template.render(trip)?);
+        }
+        return Ok(());
+    }
+
+    match output.format {
+        OutputFormat::Csv => print!("{}", to_csv(&trips)),
+        OutputFormat::Waybar => println!("{}", trips_to_waybar(&trips)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&trips)?),
+        OutputFormat::Table => print!(
+            "{}",
+            to_table(&trips, output.table_style(), terminal_width())
+        ),
+        OutputFormat::Text => {
+            let now = Local::now().fixed_offset();
+            let disruptions = disruptions_for(&trips);
+            let history = match &options.history {
+                Some(log) => log.load()?,
+                None => Vec::new(),
+            };
+            for (index, trip) in trips.iter().enumerate() {
+                if output.accessible {
+                    println!("{}", trip.accessible_description());
+                } else {
+                    println!("{}", trip.display_line(output.times, now));
+                }
+                if !output.accessible {
+                    if grouped {
+                        println!("  📍 {} → {}", trip.origin_name, trip.destination_name);
+                    }
+                    for line in trip.leg_lines(options.stops) {
+                        println!("{}", line);
+                    }
+                }
+                let warnings = trip.warnings();
+                for warning in &warnings {
+                    if output.accessible {
+                        println!("{}: {}", tr(Text::Notice), warning);
+                    } else {
+                        println!("  💬 {}", warning);
+                    }
+                }
+                if trip.requires_supplement() {
+                    let line = supplement_line(trip);
+                    if output.accessible {
+                        println!("{}", line);
+                    } else {
+                        println!("  💶 {}", line);
+                    }
+                }
+                for (transfer, risk) in trip.transfer_risks(options.min_transfer) {
+                    let line = transfer_risk_line(&transfer, risk);
+                    if output.accessible {
+                        println!("{}", line);
+                    } else {
+                        println!("  ⚠️  {}", line);
+                    }
+                }
+                if options.co2
+                    && let Some(co2) = trip.co2()
+                {
+                    let saved = format!("{:.1}", f64::from(co2.saved_grams()) / 1000.0);
+                    let distance = format!("{:.0}", co2.distance_km);
+                    let line = trf(Text::Co2Saved, &[&saved, &distance]);
+                    if output.accessible {
+                        println!("{}", line);
+                    } else {
+                        println!("  🌱 {}", line);
+                    }
+                }
+                if options.bike
+                    && bikes_restricted_during(
+                        trip.departure_time.naive_local(),
+                        trip.arrival_time.naive_local(),
+                    )
+                {
+                    if output.accessible {
+                        println!("{}", tr(Text::BikesRestricted));
+                    } else {
+                        println!("  🚲 {}", tr(Text::BikesRestricted));
+                    }
+                }
+                if query.accessible {
+                    for note in trip.notes().iter().filter(|note| !warnings.contains(note)) {
+                        if output.accessible {
+                            println!("{}: {}", tr(Text::AccessibilityNote), note);
+                        } else {
+                            println!("  ♿ {}", note);
+                        }
+                    }
+                }
+                if options.cancelled == CancelledMode::Alternative && trip.is_cancelled() {
+                    match next_alternative(&trips, index) {
+                        Some(alternative) if output.accessible => println!(
+                            "{}: {}",
+                            tr(Text::Alternative),
+                            alternative.accessible_description()
+                        ),
+                        Some(alternative) => println!(
+                            "  ↪ {}: {}",
+                            tr(Text::Alternative),
+                            alternative.display_line(output.times, now)
+                        ),
+                        None => println!("  ↪ {}", tr(Text::NoAlternative)),
+                    }
+                }
+                if let Some(disruption) = likely_cause(trip, &disruptions) {
+                    let line = format!("{}: {}", tr(Text::LikelyCause), disruption.summary());
+                    if output.accessible {
+                        println!("{}", line);
+                    } else {
+                        println!("  ⚠️  {}", line);
+                    }
+                } else if !trip.is_disrupted()
+                    && let Some(typical) = typical_delay(&history, trip)
+                {
+                    if output.accessible {
+                        println!("{}", typical);
+                    } else {
+                        println!("  📈 {}", typical);
+                    }
+                }
+            }
+        }
+    }
     Ok(())
 }
+
+/// The station name for the header, or the group query and its size
+fn group_label(query: &str, stations: &[Station]) -> String {
+    match stations {
+        [station] => station.names.long.clone(),
+        _ => trf(Text::StationGroup, &[query.trim(), &stations.len().to_string()]),
+    }
+}
+
+/// The group member with this UIC code
+fn member<'a>(stations: &'a [Station], uic_code: Option<&str>) -> Option<&'a Station> {
+    stations.iter().find(|station| Some(station.id.uic_code.as_str()) == uic_code)
+}
+
+/// Journeys between every pair of group members, the earliest arrivals
+/// first. Pairs that fail are reported and skipped unless all of them fail.
+fn group_trips(
+    froms: &[Station],
+    tos: &[Station],
+    query: &TripQuery,
+) -> Result<Vec<Trip>, Box<dyn std::error::Error>> {
+    let mut found = Vec::new();
+    let mut failures = Vec::new();
+    let mut per_pair = 0;
+    for from in froms {
+        for to in tos.iter().filter(|to| to.id.uic_code != from.id.uic_code) {
+            match trips(from, to, query) {
+                Ok(pair) => {
+                    per_pair = per_pair.max(pair.len());
+                    found.extend(pair);
+                }
+                Err(e) => failures.push((from, to, e)),
+            }
+        }
+    }
+
+    if found.is_empty() && !failures.is_empty() {
+        return Err(failures.swap_remove(0).2);
+    }
+    for (from, to, e) in &failures {
+        eprintln!("⚠️  {} → {}: {}", from.names.long, to.names.long, e);
+    }
+    found.sort_by_key(|trip| (trip.effective_arrival(), trip.departure_time));
+    found.truncate(query.limit.unwrap_or(per_pair));
+    Ok(found)
+}
+
+/// Hand a URL to the desktop's default browser
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // Not `cmd /C start`: cmd splits the URL at every `&` of its query string
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+
+    command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Active disruptions, fetched only when at least one trip is delayed or cancelled.
+/// Failing to fetch them never fails the trip lookup itself.
+fn disruptions_for(trips: &[Trip]) -> Vec<Disruption> {
+    if !trips.iter().any(|trip| trip.is_disrupted()) {
+        return Vec::new();
+    }
+
+    active_disruptions().unwrap_or_else(|e| {
+        eprintln!("⚠️  Could not fetch disruptions: {}", e);
+        Vec::new()
+    })
+}
+
+/// e.g. "Tight transfer at Utrecht Centraal: 2 min to change"
+fn transfer_risk_line(transfer: &Transfer, risk: TransferRisk) -> String {
+    let spare = transfer.spare_minutes().to_string();
+    let delay = transfer.arrival_delay.to_string();
+    match risk {
+        TransferRisk::Tight => trf(Text::TightTransfer, &[&transfer.station, &spare]),
+        TransferRisk::Endangered => trf(
+            Text::EndangeredTransfer,
+            &[&transfer.station, &delay, &spare],
+        ),
+        TransferRisk::Missed => trf(Text::MissedTransfer, &[&transfer.station, &delay]),
+    }
+}
+
+/// e.g. "Supplement required for the ICD: €2.60"
+pub(crate) fn supplement_line(trip: &Trip) -> String {
+    let types = trip.supplement_types().join(", ");
+    match trip.supplement_cents() {
+        Some(cents) => trf(
+            Text::SupplementCost,
+            &[&types, &format!("{:.2}", f64::from(cents) / 100.0)],
+        ),
+        None => trf(Text::SupplementRequired, &[&types]),
+    }
+}
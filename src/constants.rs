@@ -1,632 +1,80 @@
-pub const STATIONS: &[(&str, i32)] = &[
-    ("Budapest", 551009),
-    ("Hamburg-Bergedorf", 8001063),
-    ("Hamburg Hbf", 8001071),
-    ("Hamburg-Harburg", 8001134),
-    ("Günzburg", 8002084),
-    ("Augsburg Hbf", 8002140),
-    ("Berlin Ostbahnhof", 8003004),
-    ("Berlin-Spandau", 8003025),
-    ("Frankfurt (Oder)", 8003171),
-    ("Berlin-Schönefeld Flugh.", 8003424),
-    ("Bad Schandau", 8006006),
-    ("Dresden Hbf", 8006050),
-    ("Dresden-Neustadt", 8006216),
-    ("Berlin Gesundbrunnen", 8007799),
-    ("Schwerte (Ruhr)", 8008016),
-    ("Hagen Hbf", 8008073),
-    ("Wuppertal-Vohwinkel", 8008082),
-    ("Düsseldorf Hbf", 8008094),
-    ("Ennepetal", 8008134),
-    ("Schwelm", 8008136),
-    ("Wuppertal-Oberbarmen", 8008138),
-    ("Wuppertal-Barmen", 8008142),
-    ("Wuppertal Hbf", 8008143),
-    ("Hamm (Westf.)", 8010002),
-    ("Dortmund Hbf", 8010053),
-    ("Lünen Hbf", 8010098),
-    ("Preussen", 8010100),
-    ("Dortmund-Derne", 8010101),
-    ("Dortmund-Kirchderne", 8010102),
-    ("Oberhausen Hbf", 8010263),
-    ("Oberhausen-Sterkrade", 8010266),
-    ("Wesel", 8010275),
-    ("Praest", 8010286),
-    ("Emmerich", 8010288),
-    ("Duisburg Hbf", 8010316),
-    ("Unna", 8010382),
-    ("Holzwickede", 8010384),
-    ("Bönen", 8010385),
-    ("Frankfurt (Main) Süd", 8011065),
-    ("Frankfurt (M) Hbf", 8011068),
-    ("Wolfsburg Hbf", 8013017),
-    ("Hannover Hbf", 8013552),
-    ("Minden (Westf)", 8013578),
-    ("Bad Oeynhausen", 8013585),
-    ("Bielefeld Hbf", 8013597),
-    ("Mannheim Hbf", 8014008),
-    ("Karlsruhe Hbf", 8014228),
-    ("Baden-Baden", 8014277),
-    ("Offenburg", 8014309),
-    ("Ringsheim/Europa-Park", 8014325),
-    ("Freiburg (Breisgau) Hbf", 8014350),
-    ("Basel Bad Bf", 8014431),
-    ("Viersen", 8015122),
-    ("Dülken", 8015125),
-    ("Boisheim", 8015128),
-    ("Breyell", 8015129),
-    ("Kaldenkirchen", 8015130),
-    ("Neuss Hbf", 8015149),
-    ("Mönchengladbach Hbf", 8015165),
-    ("Herzogenrath", 8015190),
-    ("Kohlscheid", 8015195),
-    ("Aachen West", 8015199),
-    ("Köln-Ehrenfeld", 8015321),
-    ("Aachen Hbf", 8015345),
-    ("Köln Hbf", 8015458),
-    ("Köln West", 8015468),
-    ("Bonn Hbf", 8015485),
-    ("Köln-Mülheim", 8015541),
-    ("Köln Messe/Deutz", 8015561),
-    ("Troisdorf", 8015572),
-    ("Bonn-Beuel", 8015577),
-    ("Siegburg/Bonn", 8015588),
-    ("Koblenz Hbf", 8019023),
-    ("Mainz Hbf", 8019051),
-    ("Montabaur", 8019585),
-    ("Rosenheim", 8020174),
-    ("München Ost", 8020234),
-    ("München Hbf", 8020347),
-    ("Dülmen", 8021005),
-    ("Münster (Westf) Hbf", 8021011),
-    ("Osnabrück Hbf", 8021025),
-    ("Osnabrück Altstadt", 8021031),
-    ("Rheine", 8021067),
-    ("Leer (Ostfriesl)", 8021091),
-    ("Bork (Westf)", 8021111),
-    ("Selm", 8021112),
-    ("Lüdinghausen", 8021113),
-    ("Lette (Kr Coesfeld)", 8021117),
-    ("Coesfeld (Westf)", 8021119),
-    ("Selm-Beifang", 8021120),
-    ("Rosendahl-Holtwick", 8021121),
-    ("Legden", 8021122),
-    ("Ahaus", 8021123),
-    ("Epe (Westf)", 8021126),
-    ("Gronau (Westf.)", 8021128),
-    ("Steinfurt-Burgsteinfurt", 8021143),
-    ("Münster-Häger", 8021147),
-    ("Altenberge", 8021148),
-    ("Nordwalde", 8021149),
-    ("Steinfurt-Borghorst", 8021150),
-    ("Metelen Land", 8021151),
-    ("Ochtrup", 8021152),
-    ("Bad Bentheim", 8021156),
-    ("Ibbenbüren", 8021162),
-    ("Bünde (Westf)", 8021173),
-    ("Weener", 8021239),
-    ("Nürnberg Hbf", 8022193),
-    ("Würzburg Hbf", 8022534),
-    ("Aschaffenburg", 8022610),
-    ("Münster (W) Zentrum Nord", 8023783),
-    ("Stendal", 8024062),
-    ("Rathenow", 8024069),
-    ("Regensburg Hbf", 8026294),
-    ("Passau Hbf", 8026506),
-    ("Stuttgart Hbf", 8029034),
-    ("Plochingen", 8029060),
-    ("Göppingen", 8029066),
-    ("Geislingen", 8029086),
-    ("Ulm Hbf", 8029103),
-    ("Aachen Schanz", 8031372),
-    ("Limburg Süd", 8032572),
-    ("Emmerich-Elten", 8033415),
-    ("Düsseldorf Flughafen", 8039904),
-    ("Frankfurt Flughafen Fernb", 8061676),
-    ("Steinfurt-Grottenkamp", 8064371),
-    ("Berlin Hbf", 8065969),
-    ("Berlin Südkreuz", 8065971),
-    ("Brussel-Noord", 8812005),
-    ("Brussel-Centraal", 8813003),
-    ("Brussel-Zuid", 8814001),
-    ("Brussels Airport-Zaventem", 8819406),
-    ("Antwerpen-Centraal", 8821006),
-    ("Antwerpen-Luchtbal", 8821063),
-    ("Ekeren", 8821071),
-    ("Antwerpen-Noorderdokken", 8821089),
-    ("Noorderkempen", 8821105),
-    ("Antwerpen-Berchem", 8821121),
-    ("Mortsel-Deurnesteenweg", 8821154),
-    ("Antwerpen-Zuid", 8821196),
-    ("Mortsel-Oude God", 8821238),
-    ("Kontich", 8821311),
-    ("Hove", 8821337),
-    ("Essen", 8821402),
-    ("Wildert", 8821436),
-    ("Kalmthout", 8821444),
-    ("Kijkuit", 8821451),
-    ("Heide (B)", 8821519),
-    ("Kapellen", 8821535),
-    ("Sint-Mariaburg", 8821543),
-    ("Mechelen", 8822004),
-    ("Duffel", 8822210),
-    ("Sint-Katelijne-Waver", 8822228),
-    ("Puurs", 8822715),
-    ("Boom", 8822814),
-    ("Ruisbroek-Sauvegarde", 8822848),
-    ("Hoboken-Polder", 8824158),
-    ("Hemiksem", 8824224),
-    ("Schelle", 8824232),
-    ("Niel", 8824240),
-    ("Diepenbeek", 8831112),
-    ("Bilzen", 8831138),
-    ("Tongeren", 8831310),
-    ("Liège-Guillemins", 8841004),
-    ("Herstal", 8841608),
-    ("Milmort", 8841665),
-    ("Liers", 8841673),
-    ("Glons", 8841731),
-    ("Bressoux", 8843901),
-    ("Visé", 8846201),
-    ("Merelbeke", 8893013),
-    ("Lokeren", 8894201),
-    ("Sinaai", 8894425),
-    ("Belsele", 8894433),
-    ("Sint-Niklaas", 8894508),
-    ("Nieuwkerken-Waas", 8894714),
-    ("Beveren", 8894748),
-    ("Melsele", 8894755),
-    ("Zwijndrecht (B)", 8894821),
-    ("Usti nad Labem", 5453179),
-    ("Decin hl.n.", 5455659),
-    ("Praha hl.n.", 5457076),
-    ("Praha-Holesovice", 5457256),
-    ("London St. Pancras Int.", 7015400),
-    ("Ebbsfleet International", 7015440),
-    ("Ashford International", 7054660),
-    ("Göteborg Central", 7401318),
-    ("Wien Westbahnhof", 8101001),
-    ("Wien Hbf", 8101003),
-    ("Wien Hütteldorf", 8101008),
-    ("St.Pölten Hbf", 8101032),
-    ("Amstetten NÖ", 8101049),
-    ("Linz Hbf", 8101073),
-    ("Wels Hbf", 8101081),
-    ("Salzburg Hbf", 8101114),
-    ("Bischofshofen", 8101134),
-    ("St. Johann im Pongau", 8101136),
-    ("Schwarzach-St. Veit", 8101137),
-    ("Zell am See", 8101145),
-    ("Saalfelden", 8101148),
-    ("Fieberbrunn", 8101155),
-    ("St. Johann in Tirol", 8101157),
-    ("Kitzbühel", 8101160),
-    ("Hopfgarten", 8101168),
-    ("Wörgl Hbf", 8101170),
-    ("Jenbach", 8101176),
-    ("Innsbruck Hbf", 8101187),
-    ("Bludenz", 8101230),
-    ("Kufstein", 8102184),
-    ("Como S. Giovanni", 8301307),
-    ("Milano Porta Garibaldi", 8301645),
-    ("Livorno Centrale", 8306725),
-    ("Aalten", 8400045),
-    ("Abcoude", 8400047),
-    ("Akkrum", 8400049),
-    ("Alkmaar", 8400050),
-    ("Almelo", 8400051),
-    ("Alkmaar Noord", 8400052),
-    ("Alphen a/d Rijn", 8400053),
-    ("Amersfoort Schothorst", 8400054),
-    ("Amersfoort Centraal", 8400055),
-    ("Amsterdam RAI", 8400056),
-    ("Amsterdam Amstel", 8400057),
-    ("Amsterdam Centraal", 8400058),
-    ("Amsterdam Sloterdijk", 8400059),
-    ("Amsterdam Muiderpoort", 8400060),
-    ("Amsterdam Zuid", 8400061),
-    ("Anna Paulowna", 8400065),
-    ("Apeldoorn", 8400066),
-    ("Appingedam", 8400067),
-    ("Arkel", 8400068),
-    ("Arnemuiden", 8400069),
-    ("Arnhem Centraal", 8400071),
-    ("Arnhem Velperpoort", 8400072),
-    ("Assen", 8400073),
-    ("Amsterdam Bijlmer ArenA", 8400074),
-    ("Arnhem Presikhaaf", 8400075),
-    ("Amsterdam Lelylaan", 8400079),
-    ("Almere Centrum", 8400080),
-    ("Almere Buiten", 8400081),
-    ("Almere Muziekwijk", 8400082),
-    ("Baarn", 8400086),
-    ("Baflo", 8400089),
-    ("Barendrecht", 8400090),
-    ("Barneveld Centrum", 8400091),
-    ("Barneveld Noord", 8400092),
-    ("Bedum", 8400094),
-    ("Beek-Elsloo", 8400096),
-    ("Beesd", 8400097),
-    ("Beilen", 8400100),
-    ("Almere Parkwijk", 8400104),
-    ("Bergen op Zoom", 8400108),
-    ("Best", 8400112),
-    ("Beverwijk", 8400113),
-    ("Bilthoven", 8400114),
-    ("Barneveld Zuid", 8400115),
-    ("Blerick", 8400117),
-    ("Bloemendaal", 8400118),
-    ("Bodegraven", 8400121),
-    ("Borne", 8400124),
-    ("Boskoop", 8400125),
-    ("Boskoop Snijdelwijk", 8400126),
-    ("Bovenkarspel-Grootebroek", 8400127),
-    ("Boxmeer", 8400128),
-    ("Boxtel", 8400129),
-    ("Breda", 8400131),
-    ("Breda-Prinsenbeek", 8400132),
-    ("Breukelen", 8400133),
-    ("Lansingerland-Zoetermeer", 8400135),
-    ("Brummen", 8400136),
-    ("Buitenpost", 8400139),
-    ("Bunde", 8400140),
-    ("Bunnik", 8400141),
-    ("Bussum Zuid", 8400145),
-    ("Capelle Schollevaar", 8400147),
-    ("Castricum", 8400151),
-    ("Chevremont", 8400152),
-    ("Coevorden", 8400153),
-    ("Culemborg", 8400154),
-    ("Cuijk", 8400155),
-    ("Dalen", 8400161),
-    ("Diemen Zuid", 8400163),
-    ("Daarlerveen", 8400164),
-    ("Diemen", 8400165),
-    ("Delft Campus", 8400166),
-    ("Dalfsen", 8400167),
-    ("Deinum", 8400168),
-    ("Delden", 8400169),
-    ("Delft", 8400170),
-    ("Delfzijl", 8400171),
-    ("Deurne", 8400172),
-    ("Deventer", 8400173),
-    ("Didam", 8400174),
-    ("Delfzijl West", 8400175),
-    ("Dieren", 8400176),
-    ("Doetinchem", 8400177),
-    ("Den Dolder", 8400179),
-    ("Dordrecht", 8400180),
-    ("Dordrecht Zuid", 8400181),
-    ("Driebergen-Zeist", 8400182),
-    ("Driehuis", 8400183),
-    ("Doetinchem De Huet", 8400184),
-    ("Deventer Colmschate", 8400185),
-    ("Dordrecht Stadspolders", 8400186),
-    ("De Vink", 8400188),
-    ("Dronryp", 8400192),
-    ("Duiven", 8400193),
-    ("Duivendrecht", 8400194),
-    ("Echt", 8400195),
-    ("Eindhoven Strijp-S", 8400196),
-    ("Dronten", 8400198),
-    ("Ede-Wageningen", 8400200),
-    ("Ede Centrum", 8400201),
-    ("Eemshaven", 8400202),
-    ("Eindhoven Centraal", 8400206),
-    ("Elst", 8400207),
-    ("Emmen", 8400208),
-    ("Enkhuizen", 8400210),
-    ("Enschede", 8400212),
-    ("Ermelo", 8400216),
-    ("Enschede De Eschmarke", 8400217),
-    ("Etten-Leur", 8400218),
-    ("Eijsden", 8400219),
-    ("Enschede Kennispark", 8400221),
-    ("Almere Oostvaarders", 8400226),
-    ("Arnhem Zuid", 8400227),
-    ("Amersfoort Vathorst", 8400228),
-    ("Apeldoorn Osseveld", 8400229),
-    ("Bovenkarspel Flora", 8400230),
-    ("Amsterdam Holendrecht", 8400231),
-    ("Franeker", 8400232),
-    ("Apeldoorn De Maten", 8400233),
-    ("Eygelshoven Markt", 8400234),
-    ("Amsterdam Science Park", 8400235),
-    ("Boven-Hardinxveld", 8400236),
-    ("Groningen Europapark", 8400238),
-    ("Gaanderen", 8400241),
-    ("Helmond Brandevoort", 8400242),
-    ("Geldermalsen", 8400244),
-    ("Geldrop", 8400245),
-    ("Geleen Oost", 8400246),
-    ("Geleen-Lutterade", 8400248),
-    ("Gilze-Rijen", 8400251),
-    ("Goes", 8400253),
-    ("Goor", 8400254),
-    ("Gorinchem", 8400256),
-    ("Gouda Goverwelle", 8400257),
-    ("Gouda", 8400258),
-    ("Gramsbergen", 8400259),
-    ("Glanerbrug", 8400262),
-    ("Groningen", 8400263),
-    ("Groningen Noord", 8400264),
-    ("Grou-Jirnsum", 8400266),
-    ("Grijpskerk", 8400267),
-    ("Haarlem Spaarnwoude", 8400270),
-    ("Den Haag Mariahoeve", 8400278),
-    ("Den Haag Moerwijk", 8400279),
-    ("Den Haag HS", 8400280),
-    ("Den Haag Centraal", 8400282),
-    ("Haarlem", 8400285),
-    ("Hurdegaryp", 8400292),
-    ("Hardenberg", 8400293),
-    ("Harderwijk", 8400294),
-    ("Hardinxveld-Giessendam", 8400295),
-    ("Harlingen", 8400296),
-    ("Haren", 8400297),
-    ("Harlingen Haven", 8400298),
-    ("Helmond Brouwhuis", 8400299),
-    ("Helmond 't Hout", 8400300),
-    ("Heemstede-Aerdenhout", 8400302),
-    ("Den Helder Zuid", 8400303),
-    ("Heerenveen", 8400305),
-    ("Heerhugowaard", 8400306),
-    ("Heerlen", 8400307),
-    ("Heeze", 8400308),
-    ("Heiloo", 8400309),
-    ("Heino", 8400310),
-    ("Den Helder", 8400311),
-    ("Hengelo Oost", 8400312),
-    ("Helmond", 8400313),
-    ("Hemmen-Dodewaard", 8400315),
-    ("Hengelo", 8400316),
-    ("Heemskerk", 8400317),
-    ("'s-Hertogenbosch", 8400319),
-    ("'s-Hertogenbosch Oost", 8400320),
-    ("Hilversum", 8400322),
-    ("Hindeloopen", 8400323),
-    ("Hilversum Media Park", 8400324),
-    ("Hoensbroek", 8400326),
-    ("Hollandsche Rading", 8400327),
-    ("Holten", 8400328),
-    ("Hillegom", 8400329),
-    ("Hoogeveen", 8400330),
-    ("Hoogezand-Sappemeer", 8400331),
-    ("Hoofddorp", 8400332),
-    ("Hoogkarspel", 8400334),
-    ("Houten Castellum", 8400335),
-    ("Hoorn Kersenboogerd", 8400336),
-    ("Hoorn", 8400337),
-    ("Eygelshoven", 8400338),
-    ("Horst-Sevenum", 8400339),
-    ("Houten", 8400340),
-    ("Houthem-St. Gerlach", 8400341),
-    ("Kampen", 8400353),
-    ("Kapelle-Biezelinge", 8400354),
-    ("Kerkrade Centrum", 8400355),
-    ("Kesteren", 8400359),
-    ("Kampen Zuid", 8400360),
-    ("Klarenbeek", 8400361),
-    ("Klimmen-Ransdaal", 8400362),
-    ("Koog aan de Zaan", 8400363),
-    ("Zaandijk Zaanse Schans", 8400364),
-    ("Koudum-Molkwerum", 8400366),
-    ("Krabbendijke", 8400367),
-    ("Krommenie-Assendelft", 8400368),
-    ("Kropswolde", 8400369),
-    ("Kruiningen-Yerseke", 8400370),
-    ("Den Haag Ypenburg", 8400379),
-    ("Den Haag Laan v NOI", 8400380),
-    ("Lage Zwaluwe", 8400382),
-    ("Leiden Lammenschans", 8400384),
-    ("Leerdam", 8400386),
-    ("Leeuwarden", 8400387),
-    ("'t Harde", 8400388),
-    ("Leeuwarden Camminghaburen", 8400389),
-    ("Leiden Centraal", 8400390),
-    ("Lelystad Centrum", 8400394),
-    ("Lichtenvoorde-Groenlo", 8400395),
-    ("Lochem", 8400399),
-    ("Halfweg-Zwanenburg", 8400400),
-    ("Loppersum", 8400403),
-    ("Heerlen Woonboulevard", 8400404),
-    ("Lunteren", 8400405),
-    ("Emmen Zuid", 8400407),
-    ("Maarn", 8400417),
-    ("Maarssen", 8400419),
-    ("Maastricht", 8400424),
-    ("Maastricht Randwyck", 8400426),
-    ("Mantgum", 8400427),
-    ("Mariënberg", 8400428),
-    ("Martenshoek", 8400430),
-    ("Maarheeze", 8400432),
-    ("Hengelo Gezondheidspark", 8400433),
-    ("Meerssen", 8400434),
-    ("Meppel", 8400435),
-    ("Middelburg", 8400436),
-    ("Hardinxveld Blauwe Zoom", 8400438),
-    ("Mook-Molenhoek", 8400446),
-    ("Maastricht Noord", 8400449),
-    ("Almere Poort", 8400450),
-    ("Nijmegen Lent", 8400451),
-    ("Naarden-Bussum", 8400452),
-    ("Nieuw Amsterdam", 8400454),
-    ("Nieuwerkerk a/d IJssel", 8400455),
-    ("Bad Nieuweschans", 8400457),
-    ("Nieuw Vennep", 8400460),
-    ("Nunspeet", 8400466),
-    ("Nuth", 8400467),
-    ("Nijmegen Heyendaal", 8400468),
-    ("Nijkerk", 8400469),
-    ("Nijmegen", 8400470),
-    ("Nijverdal", 8400472),
-    ("Nijmegen Dukenburg", 8400475),
-    ("Nijmegen Goffert", 8400477),
-    ("Obdam", 8400480),
-    ("Oisterwijk", 8400482),
-    ("Oldenzaal", 8400483),
-    ("Olst", 8400486),
-    ("Ommen", 8400487),
-    ("Oosterbeek", 8400489),
-    ("Hoevelaken", 8400490),
-    ("Opheusden", 8400494),
-    ("Oss", 8400495),
-    ("Oss West", 8400496),
-    ("Sliedrecht Baanhoek", 8400497),
-    ("Oudenbosch", 8400498),
-    ("Purmerend Weidevenne", 8400499),
-    ("Overveen", 8400501),
-    ("Rijswijk", 8400505),
-    ("Purmerend Overwhere", 8400506),
-    ("Rotterdam Alexander", 8400507),
-    ("Purmerend", 8400508),
-    ("Putten", 8400509),
-    ("Raalte", 8400513),
-    ("Ravenstein", 8400515),
-    ("Rhenen", 8400517),
-    ("Reuver", 8400518),
-    ("Rheden", 8400519),
-    ("Almelo de Riet", 8400520),
-    ("Rilland-Bath", 8400521),
-    ("Roermond", 8400523),
-    ("Rosmalen", 8400524),
-    ("Roodeschool", 8400525),
-    ("Roosendaal", 8400526),
-    ("Rotterdam Blaak", 8400529),
-    ("Rotterdam Centraal", 8400530),
-    ("Rotterdam Noord", 8400531),
-    ("Rotterdam Zuid", 8400533),
-    ("Rotterdam Stadion", 8400534),
-    ("Ruurlo", 8400537),
-    ("Rijssen", 8400538),
-    ("Rotterdam Lombardijen", 8400542),
-    ("Santpoort Noord", 8400543),
-    ("Santpoort Zuid", 8400544),
-    ("Sauwerd", 8400547),
-    ("Landgraaf", 8400548),
-    ("Schagen", 8400549),
-    ("Scheemda", 8400551),
-    ("Schiedam Centrum", 8400553),
-    ("Schinnen", 8400554),
-    ("Schin op Geul", 8400555),
-    ("Schiphol Airport", 8400561),
-    ("Sittard", 8400564),
-    ("Sliedrecht", 8400565),
-    ("Sneek", 8400566),
-    ("Soest", 8400567),
-    ("Soestdijk", 8400569),
-    ("Hilversum Sportpark", 8400570),
-    ("Soest Zuid", 8400571),
-    ("Spaubeek", 8400572),
-    ("Sneek Noord", 8400574),
-    ("Stavoren", 8400575),
-    ("Stedum", 8400576),
-    ("Steenwijk", 8400578),
-    ("Susteren", 8400582),
-    ("Swalmen", 8400583),
-    ("Tegelen", 8400591),
-    ("Terborg", 8400592),
-    ("Tilburg Universiteit", 8400594),
-    ("Tiel", 8400596),
-    ("Tilburg", 8400597),
-    ("Tilburg Reeshof", 8400598),
-    ("Twello", 8400599),
-    ("Tiel Passewaaij", 8400600),
-    ("Sassenheim", 8400603),
-    ("Utrecht Vaartsche Rijn", 8400606),
-    ("Utrecht Leidsche Rijn", 8400607),
-    ("Utrecht Zuilen", 8400613),
-    ("Utrecht Terwijde", 8400614),
-    ("Uitgeest", 8400615),
-    ("Uithuizen", 8400617),
-    ("Uithuizermeeden", 8400618),
-    ("Usquert", 8400619),
-    ("Utrecht Overvecht", 8400620),
-    ("Utrecht Centraal", 8400621),
-    ("Utrecht Lunetten", 8400623),
-    ("Utrecht Maliebaan", 8400624),
-    ("Veenendaal Centrum", 8400627),
-    ("Veenendaal West", 8400628),
-    ("Valkenburg", 8400632),
-    ("Varsseveld", 8400635),
-    ("Veendam", 8400636),
-    ("Veenendaal-De Klomp", 8400637),
-    ("Feanwâlden", 8400638),
-    ("Velp", 8400640),
-    ("Venlo", 8400644),
-    ("Venray", 8400646),
-    ("Vierlingsbeek", 8400647),
-    ("Vleuten", 8400651),
-    ("Vlissingen", 8400652),
-    ("Vlissingen Souburg", 8400653),
-    ("Voorhout", 8400655),
-    ("Voerendaal", 8400656),
-    ("Voorburg", 8400658),
-    ("Voorschoten", 8400659),
-    ("Vorden", 8400661),
-    ("Vriezenveen", 8400664),
-    ("Vroomshoop", 8400666),
-    ("Vught", 8400667),
-    ("Voorst-Empe", 8400668),
-    ("Waddinxveen Noord", 8400674),
-    ("Waddinxveen", 8400675),
-    ("Waddinxveen Triangel", 8400677),
-    ("Warffum", 8400679),
-    ("Weert", 8400684),
-    ("Weesp", 8400685),
-    ("Wehl", 8400686),
-    ("Westervoort", 8400688),
-    ("Wezep", 8400690),
-    ("Wierden", 8400691),
-    ("Winschoten", 8400696),
-    ("Winsum", 8400697),
-    ("Winterswijk", 8400698),
-    ("Winterswijk West", 8400700),
-    ("Woerden", 8400702),
-    ("Wolfheze", 8400704),
-    ("Wolvega", 8400705),
-    ("Workum", 8400706),
-    ("Wormerveer", 8400707),
-    ("Wijchen", 8400710),
-    ("Wijhe", 8400711),
-    ("IJlst", 8400722),
-    ("Zaandam Kogerveld", 8400728),
-    ("Zaandam", 8400731),
-    ("Zaltbommel", 8400732),
-    ("Zandvoort aan Zee", 8400733),
-    ("Zetten-Andelst", 8400734),
-    ("Zevenaar", 8400735),
-    ("Zevenbergen", 8400737),
-    ("Zoetermeer Oost", 8400740),
-    ("Zoetermeer", 8400741),
-    ("Zuidbroek", 8400742),
-    ("Zuidhorn", 8400743),
-    ("Zutphen", 8400744),
-    ("De Westereen", 8400745),
-    ("Zwolle Stadshagen", 8400746),
-    ("Zwolle", 8400747),
-    ("Zwijndrecht", 8400752),
-    ("Basel SBB", 8500010),
-    ("Zürich HB", 8503000),
-    ("Baden (CH)", 8503504),
-    ("Chur", 8509000),
-    ("Landquart", 8509002),
-    ("Odense", 8600512),
-    ("Ringsted", 8600611),
-    ("Lille Europe", 8722326),
-    ("Paris-Nord", 8727100),
-    ("Airport Charles de Gaulle", 8727149),
-    ("Lille Flandres", 8728600),
-    ("Avignon TGV", 8731896),
-    ("Aix-en-Provence TGV", 8731901),
-    ("Marne-la-Vallée-Chessy", 8738264),
-    ("Lourdes", 8767133),
-    ("Chambéry", 8774100),
-    ("Albertville", 8774164),
-    ("Moutiers-Salins-Brides", 8774172),
-    ("Aime-la-Plagne", 8774176),
-    ("Landry", 8774177),
-    ("Bourg-St-Maurice", 8774179),
-    ("Marseille-St-Charles", 8775100),
-    ("Valence TGV", 8776302),
+use crate::stations::models::StationInfo;
+
+// `STATION_INFO`, `STATIONS` and `STATION_CODES`: generated by build.rs from
+// data/stations.csv, which is the place to add or correct a station
+include!(concat!(env!("OUT_DIR"), "/stations.rs"));
+
+/// City meta-stations: a city name accepted wherever a station is queried
+/// and the member stations it stands for, by their name in `STATIONS`
+pub const CITY_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "Amsterdam",
+        &[
+            "Amsterdam Centraal",
+            "Amsterdam Zuid",
+            "Amsterdam Sloterdijk",
+            "Amsterdam Amstel",
+        ],
+    ),
+    (
+        "Rotterdam",
+        &["Rotterdam Centraal", "Rotterdam Blaak", "Rotterdam Alexander"],
+    ),
+    (
+        "Den Haag",
+        &["Den Haag Centraal", "Den Haag HS", "Den Haag Laan v NOI"],
+    ),
+    (
+        "Utrecht",
+        &[
+            "Utrecht Centraal",
+            "Utrecht Vaartsche Rijn",
+            "Utrecht Overvecht",
+            "Utrecht Leidsche Rijn",
+        ],
+    ),
+];
+
+/// Colloquial station names and the station they mean, compared through
+/// `fuzzy::fold()`. `NSTIMES_SYNONYMS` adds to (and overrides) these.
+pub const STATION_SYNONYMS: &[(&str, &str)] = &[
+    ("Den Bosch", "'s-Hertogenbosch"),
+    ("Den Bosch Oost", "'s-Hertogenbosch Oost"),
+    ("HS", "Den Haag HS"),
+    ("Hollands Spoor", "Den Haag HS"),
+    ("Den Haag Hollands Spoor", "Den Haag HS"),
+    ("Laan van NOI", "Den Haag Laan v NOI"),
+    ("The Hague", "Den Haag Centraal"),
+    ("A'dam", "Amsterdam Centraal"),
+    ("Mokum", "Amsterdam Centraal"),
+    ("Zuidas", "Amsterdam Zuid"),
+    ("R'dam", "Rotterdam Centraal"),
+    ("Dordt", "Dordrecht"),
+    ("Schiphol", "Schiphol Airport"),
+];
+
+/// Busy intercity routes that `cache warm` prices when given no routes file
+pub const BUSIEST_ROUTES: &[(&str, &str)] = &[
+    ("Amsterdam Centraal", "Utrecht Centraal"),
+    ("Amsterdam Centraal", "Schiphol Airport"),
+    ("Amsterdam Centraal", "Rotterdam Centraal"),
+    ("Amsterdam Centraal", "Den Haag Centraal"),
+    ("Amsterdam Centraal", "Haarlem"),
+    ("Amsterdam Centraal", "Leiden Centraal"),
+    ("Amsterdam Centraal", "Eindhoven Centraal"),
+    ("Amsterdam Zuid", "Schiphol Airport"),
+    ("Utrecht Centraal", "Rotterdam Centraal"),
+    ("Utrecht Centraal", "Den Haag Centraal"),
+    ("Utrecht Centraal", "Amersfoort Centraal"),
+    ("Utrecht Centraal", "Arnhem Centraal"),
+    ("Utrecht Centraal", "'s-Hertogenbosch"),
+    ("Utrecht Centraal", "Eindhoven Centraal"),
+    ("Utrecht Centraal", "Zwolle"),
+    ("Rotterdam Centraal", "Den Haag Centraal"),
+    ("Rotterdam Centraal", "Schiphol Airport"),
+    ("Den Haag Centraal", "Leiden Centraal"),
+    ("Leiden Centraal", "Schiphol Airport"),
+    ("Zwolle", "Groningen"),
+    ("Arnhem Centraal", "Nijmegen"),
+    ("Breda", "Rotterdam Centraal"),
 ];
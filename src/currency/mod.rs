@@ -0,0 +1,5 @@
+pub mod models;
+pub mod service;
+
+pub use models::ExchangeRates;
+pub use service::*;
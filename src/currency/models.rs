@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// ECB euro foreign exchange reference rates for a single day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRates {
+    /// Reference date published by the ECB (YYYY-MM-DD)
+    pub date: String,
+    /// Units of foreign currency per 1 EUR, keyed by ISO code (e.g. "GBP")
+    pub rates: HashMap<String, f64>,
+}
+
+impl ExchangeRates {
+    /// Convert an amount in euro cents to the given currency
+    pub fn convert(&self, cents: i32, currency: &str) -> Option<f64> {
+        let rate = self.rates.get(&currency.to_uppercase())?;
+        Some(cents as f64 / 100.0 * rate)
+    }
+}
+
+/// Display symbol for a currency, falling back to its ISO code
+pub fn currency_symbol(currency: &str) -> String {
+    match currency.to_uppercase().as_str() {
+        "GBP" => "£".to_string(),
+        "USD" => "$".to_string(),
+        "JPY" => "¥".to_string(),
+        "CHF" => "CHF ".to_string(),
+        other => format!("{} ", other),
+    }
+}
@@ -0,0 +1,113 @@
+use super::models::ExchangeRates;
+use crate::cache::{read_owned_file, user_cache_dir};
+use chrono::Local;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+
+/// Get today's ECB reference rates, reusing the copy cached on disk if it
+/// was fetched today
+pub fn get_rates() -> Result<ExchangeRates, Box<dyn std::error::Error>> {
+    let path = rates_cache_path();
+    let today = Local::now().format("%Y-%m-%d").to_string();
+
+    if let Some(path) = &path
+        && let Some(content) = read_owned_file(path)
+        && let Ok(cached) = serde_json::from_str::<CachedRates>(&content)
+        && cached.fetched_on == today
+    {
+        return Ok(cached.rates);
+    }
+
+    let body = ureq::get(ECB_DAILY_URL)
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    let rates = parse_ecb_xml(&body).ok_or("Failed to parse ECB exchange rates")?;
+
+    let cached = CachedRates {
+        fetched_on: today,
+        rates: rates.clone(),
+    };
+    if let Some(path) = &path
+        && let Ok(json) = serde_json::to_string(&cached)
+    {
+        let _ = path.parent().map(fs::create_dir_all);
+        let _ = fs::write(path, json);
+    }
+
+    Ok(rates)
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedRates {
+    fetched_on: String,
+    rates: ExchangeRates,
+}
+
+/// ecb-rates.json in the user's cache directory, None without one
+fn rates_cache_path() -> Option<PathBuf> {
+    Some(user_cache_dir()?.join("ecb-rates.json"))
+}
+
+/// Parse the ECB daily XML feed, which lists rates as
+/// `<Cube time='2024-06-01'>` containing `<Cube currency='USD' rate='1.0845'/>`
+fn parse_ecb_xml(xml: &str) -> Option<ExchangeRates> {
+    let date = attribute(xml, "time")?;
+    let mut rates = HashMap::new();
+
+    for element in xml.split("<Cube").skip(1) {
+        if let (Some(currency), Some(rate)) = (attribute(element, "currency"), attribute(element, "rate"))
+            && let Ok(rate) = rate.parse::<f64>()
+        {
+            rates.insert(currency, rate);
+        }
+    }
+
+    if rates.is_empty() {
+        return None;
+    }
+    Some(ExchangeRates { date, rates })
+}
+
+fn attribute(element: &str, name: &str) -> Option<String> {
+    let start = element.find(&format!("{}='", name))? + name.len() + 2;
+    let end = element[start..].find('\'')? + start;
+    Some(element[start..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+	<gesmes:subject>Reference rates</gesmes:subject>
+	<Cube>
+		<Cube time='2024-06-03'>
+			<Cube currency='USD' rate='1.0842'/>
+			<Cube currency='GBP' rate='0.85'/>
+		</Cube>
+	</Cube>
+</gesmes:Envelope>"#;
+
+    #[test]
+    fn test_parse_ecb_xml() {
+        let rates = parse_ecb_xml(SAMPLE).unwrap();
+        assert_eq!(rates.date, "2024-06-03");
+        assert_eq!(rates.rates.len(), 2);
+        assert_eq!(rates.rates["USD"], 1.0842);
+
+        // 9.40 EUR in GBP
+        let converted = rates.convert(940, "gbp").unwrap();
+        assert!((converted - 7.99).abs() < 1e-9);
+        assert!(rates.convert(940, "XYZ").is_none());
+    }
+
+    #[test]
+    fn test_parse_invalid_feed() {
+        assert!(parse_ecb_xml("<html>maintenance</html>").is_none());
+    }
+}
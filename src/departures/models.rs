@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct DeparturesResponse {
+    pub payload: DeparturesPayload,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeparturesPayload {
+    #[serde(default)]
+    pub departures: Vec<DepartureRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepartureRaw {
+    pub direction: String,
+    pub planned_date_time: String,
+    pub actual_date_time: Option<String>,
+    pub planned_track: Option<String>,
+    pub actual_track: Option<String>,
+    pub product: DepartureProductRaw,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub route_stations: Vec<RouteStationRaw>,
+    #[serde(default)]
+    pub messages: Vec<DepartureMessageRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepartureProductRaw {
+    pub category_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStationRaw {
+    pub medium_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DepartureMessageRaw {
+    pub message: String,
+}
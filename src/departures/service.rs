@@ -0,0 +1,281 @@
+use crate::client::{Endpoint, client};
+use crate::departures::models::{DepartureRaw, DeparturesResponse};
+use crate::i18n::status_label;
+use crate::output::{countdown, CsvRow, TableRow, TemplateFields, TimeDisplay, Value};
+use chrono::{DateTime, FixedOffset, Local};
+use colored::*;
+use serde::Serialize;
+use std::fmt;
+
+/// A train leaving a station, as shown on the departure boards
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Departure {
+    /// Final destination, e.g. "Amersfoort Schothorst"
+    pub direction: String,
+    /// Train category code, e.g. "IC"
+    pub train_type: String,
+    #[cfg_attr(feature = "server", schema(value_type = String, format = DateTime))]
+    pub planned: DateTime<FixedOffset>,
+    #[cfg_attr(feature = "server", schema(value_type = Option<String>, format = DateTime))]
+    pub actual: Option<DateTime<FixedOffset>>,
+    /// Departure track, the actual one when it changed
+    pub track: Option<String>,
+    pub cancelled: bool,
+    /// Stations the train calls at on the way
+    pub via: Vec<String>,
+    pub messages: Vec<String>,
+}
+
+/// Why a departure from the NS API could not be converted
+#[derive(Debug)]
+pub enum DepartureError {
+    InvalidTime(String),
+}
+
+impl fmt::Display for DepartureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepartureError::InvalidTime(txt) => write!(f, "invalid date/time `{}`", txt),
+        }
+    }
+}
+
+impl std::error::Error for DepartureError {}
+
+impl TryFrom<DepartureRaw> for Departure {
+    type Error = DepartureError;
+
+    fn try_from(raw: DepartureRaw) -> Result<Self, DepartureError> {
+        let parse_time = |txt: &str| {
+            DateTime::parse_from_str(txt, "%Y-%m-%dT%H:%M:%S%z")
+                .map_err(|_| DepartureError::InvalidTime(txt.to_string()))
+        };
+
+        Ok(Departure {
+            direction: raw.direction,
+            train_type: raw.product.category_code,
+            planned: parse_time(&raw.planned_date_time)?,
+            actual: raw.actual_date_time.as_deref().map(parse_time).transpose()?,
+            track: raw.actual_track.or(raw.planned_track),
+            cancelled: raw.cancelled,
+            via: raw
+                .route_stations
+                .into_iter()
+                .map(|s| s.medium_name)
+                .collect(),
+            messages: raw.messages.into_iter().map(|m| m.message).collect(),
+        })
+    }
+}
+
+impl Departure {
+    /// Delay in whole minutes, 0 when on time or unknown
+    pub fn delay_minutes(&self) -> i64 {
+        self.actual
+            .map(|actual| actual.signed_duration_since(self.planned).num_minutes())
+            .unwrap_or(0)
+    }
+
+    /// NS status of the departure, "CANCELLED" or "NORMAL"
+    pub fn status(&self) -> &'static str {
+        if self.cancelled { "CANCELLED" } else { "NORMAL" }
+    }
+
+    /// e.g. "08:12+3 IC Amersfoort Schothorst tr.5", cancelled ones struck
+    /// through and labelled
+    pub fn display_line(&self, times: TimeDisplay, now: DateTime<FixedOffset>) -> String {
+        let clock = self.planned.format("%H:%M").to_string();
+        let relative = countdown(self.actual.unwrap_or(self.planned), now);
+        let time = match times {
+            TimeDisplay::Absolute => clock,
+            TimeDisplay::Relative => relative,
+            TimeDisplay::Both => format!("{} ({})", clock, relative),
+        };
+        let delay = match self.delay_minutes() {
+            delay if delay > 0 => format!("+{}", delay).red().to_string(),
+            _ => String::new(),
+        };
+        let line = format!(
+            "{}{} {} {} tr.{}",
+            time,
+            delay,
+            self.train_type,
+            self.direction.bold(),
+            self.track.as_deref().unwrap_or("?")
+        );
+        if self.cancelled {
+            format!("{} ({})", line.strikethrough(), status_label(self.status()).red())
+        } else {
+            line
+        }
+    }
+
+    /// The departure as one plain sentence for screen readers
+    pub fn accessible_description(&self) -> String {
+        let delay = match self.delay_minutes() {
+            minutes if minutes <= 0 => String::new(),
+            1 => ", delayed 1 minute".to_string(),
+            minutes => format!(", delayed {} minutes", minutes),
+        };
+        let platform = match &self.track {
+            Some(track) => format!(" from platform {}", track),
+            None => String::new(),
+        };
+        let status = if self.cancelled {
+            format!(", {}", status_label(self.status()))
+        } else {
+            String::new()
+        };
+        format!(
+            "Train type {} to {}, departs {}{}{}{}",
+            self.train_type,
+            self.direction,
+            self.planned.format("%H:%M"),
+            platform,
+            delay,
+            status
+        )
+    }
+}
+
+impl TemplateFields for Departure {
+    fn field(&self, name: &str) -> Option<Value> {
+        Some(match name {
+            "direction" => Value::Text(self.direction.clone()),
+            "type" => Value::Text(self.train_type.clone()),
+            "track" => Value::Text(self.track.clone().unwrap_or_default()),
+            "status" => Value::Text(self.status().to_string()),
+            "departure" => Value::Time(self.planned),
+            "actual_departure" => Value::Time(self.actual.unwrap_or(self.planned)),
+            "departure_delay" => Value::Text(match self.delay_minutes() {
+                minutes if minutes > 0 => format!("+{}", minutes),
+                _ => String::new(),
+            }),
+            "departure_in" => Value::Text(countdown(
+                self.actual.unwrap_or(self.planned),
+                Local::now().fixed_offset(),
+            )),
+            "via" => Value::Text(self.via.join(", ")),
+            _ => return None,
+        })
+    }
+}
+
+impl TableRow for Departure {
+    fn headers() -> &'static [&'static str] {
+        &["Departure", "Type", "Direction", "Track", "Status"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let departure = match self.delay_minutes() {
+            minutes if minutes > 0 => format!("{} +{}", self.planned.format("%H:%M"), minutes),
+            _ => self.planned.format("%H:%M").to_string(),
+        };
+        vec![
+            departure,
+            self.train_type.clone(),
+            self.direction.clone(),
+            self.track.clone().unwrap_or_default(),
+            self.status().to_string(),
+        ]
+    }
+}
+
+impl CsvRow for Departure {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "direction",
+            "type",
+            "departure",
+            "actual_departure",
+            "departure_delay",
+            "track",
+            "status",
+            "via",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.direction.clone(),
+            self.train_type.clone(),
+            self.planned.to_rfc3339(),
+            self.actual.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.delay_minutes().max(0).to_string(),
+            self.track.clone().unwrap_or_default(),
+            self.status().to_string(),
+            self.via.join(", "),
+        ]
+    }
+}
+
+/// Fetch the next departures from a station
+pub fn departures(
+    uic_code: &str,
+    limit: usize,
+) -> Result<Vec<Departure>, Box<dyn std::error::Error>> {
+    let url = "https://gateway.apiportal.ns.nl/reisinformatie-api/api/v2/departures";
+
+    let request = client()
+        .get(url)?
+        .query("uicCode", uic_code)
+        .query("maxJourneys", limit.to_string());
+    let params = format!("uicCode={}&maxJourneys={}", uic_code, limit);
+    let body = client().send_cached(Endpoint::Departures, &params, request)?;
+
+    let response: DeparturesResponse = serde_json::from_str(&body)?;
+    Ok(convert_departures(response.payload.departures))
+}
+
+/// Convert the departures NS sent, skipping (and reporting) malformed ones
+/// so a single bad row doesn't empty the whole board
+fn convert_departures(raws: Vec<DepartureRaw>) -> Vec<Departure> {
+    raws.into_iter()
+        .filter_map(|raw| match Departure::try_from(raw) {
+            Ok(departure) => Some(departure),
+            Err(e) => {
+                eprintln!("⚠️  Skipping a departure from the NS API: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_departure() {
+        let body = r#"{"payload": {"departures": [{
+            "direction": "Amersfoort Schothorst",
+            "plannedDateTime": "2024-06-01T08:12:00+0200",
+            "actualDateTime": "2024-06-01T08:15:00+0200",
+            "plannedTrack": "5",
+            "product": {"categoryCode": "IC"},
+            "routeStations": [{"mediumName": "Utrecht C."}],
+            "messages": [{"message": "Let op: extra druk"}]
+        }]}}"#;
+
+        let response: DeparturesResponse = serde_json::from_str(body).unwrap();
+        let mut raws = response.payload.departures;
+        raws[0].actual_date_time = Some("08:15".to_string());
+        raws.extend(serde_json::from_str::<DeparturesResponse>(body).unwrap().payload.departures);
+
+        // The row with the bad time is skipped, the rest of the board stays
+        let departures = convert_departures(raws);
+        assert_eq!(departures.len(), 1);
+        let departure = &departures[0];
+        assert_eq!(departure.track.as_deref(), Some("5"));
+        assert_eq!(departure.delay_minutes(), 3);
+        assert_eq!(departure.via, ["Utrecht C."]);
+        assert!(!departure.cancelled);
+
+        let mut cancelled = departure.clone();
+        cancelled.cancelled = true;
+        assert!(cancelled.accessible_description().ends_with(", delayed 3 minutes, CANCELLED"));
+        let template = crate::output::Template::parse("{departure:%H:%M} {type} {status}").unwrap();
+        assert_eq!(template.render(&cancelled).unwrap(), "08:12 IC CANCELLED");
+    }
+}
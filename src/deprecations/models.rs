@@ -0,0 +1,43 @@
+/// A CLI name that was replaced but is still accepted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rename {
+    /// Long option, written with its dashes, e.g. `--return`
+    Flag {
+        old: &'static str,
+        new: &'static str,
+    },
+    /// Subcommand; the replacement may be a nested path such as `monitor watch`
+    Command {
+        old: &'static str,
+        new: &'static str,
+    },
+}
+
+impl Rename {
+    pub fn old_name(&self) -> &'static str {
+        match self {
+            Rename::Flag { old, .. } | Rename::Command { old, .. } => old,
+        }
+    }
+
+    pub fn new_name(&self) -> &'static str {
+        match self {
+            Rename::Flag { new, .. } | Rename::Command { new, .. } => new,
+        }
+    }
+
+    pub fn notice(&self) -> String {
+        format!(
+            "`{}` is deprecated and will be removed in a future release, use `{}` instead",
+            self.old_name(),
+            self.new_name()
+        )
+    }
+}
+
+/// Every renamed flag and command. Add a row here when renaming, nothing else
+/// is needed for the old spelling to keep working.
+pub const RENAMES: &[Rename] = &[Rename::Flag {
+    old: "--return",
+    new: "--round-trip",
+}];
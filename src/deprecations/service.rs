@@ -0,0 +1,146 @@
+use crate::deprecations::models::Rename;
+
+/// Arguments with deprecated names replaced, plus one notice per rename used
+#[derive(Debug, Default)]
+pub struct Rewritten {
+    pub args: Vec<String>,
+    pub notices: Vec<String>,
+}
+
+impl Rewritten {
+    /// Print the notices to stderr
+    pub fn warn(&self) {
+        for notice in &self.notices {
+            eprintln!("⚠️  {}", notice);
+        }
+    }
+}
+
+/// Replace deprecated flags and subcommands in `args` (program name first)
+/// before they reach clap. `takes_value` tells whether an option consumes the
+/// next argument, so option values are never mistaken for a subcommand.
+pub fn rewrite_args(
+    args: impl IntoIterator<Item = String>,
+    renames: &[Rename],
+    takes_value: impl Fn(&str) -> bool,
+) -> Rewritten {
+    let mut out = Rewritten::default();
+    let mut used: Vec<Rename> = Vec::new();
+    let mut args = args.into_iter();
+    let mut expect_value = false;
+    let mut escaped = false;
+    let mut seen_command = false;
+
+    let mut record = |rename: &Rename| {
+        if !used.contains(rename) {
+            used.push(*rename);
+        }
+    };
+
+    out.args.extend(args.next());
+
+    for arg in args {
+        if escaped || expect_value {
+            expect_value = false;
+            out.args.push(arg);
+            continue;
+        }
+        if arg == "--" {
+            escaped = true;
+            out.args.push(arg);
+            continue;
+        }
+
+        if arg.starts_with('-') {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg.as_str(), None),
+            };
+            let name = match renames
+                .iter()
+                .find(|r| matches!(r, Rename::Flag { old, .. } if *old == name))
+            {
+                Some(rename) => {
+                    record(rename);
+                    rename.new_name()
+                }
+                None => name,
+            };
+            expect_value = value.is_none() && takes_value(name);
+            out.args.push(match value {
+                Some(value) => format!("{}={}", name, value),
+                None => name.to_string(),
+            });
+            continue;
+        }
+
+        // Only the first positional is the subcommand, later ones are values
+        // such as station names
+        let rename = renames
+            .iter()
+            .find(|r| matches!(r, Rename::Command { old, .. } if *old == arg));
+        match rename {
+            Some(rename) if !seen_command => {
+                record(rename);
+                out.args
+                    .extend(rename.new_name().split_whitespace().map(str::to_string));
+            }
+            _ => out.args.push(arg),
+        }
+        seen_command = true;
+    }
+
+    out.notices = used.iter().map(Rename::notice).collect();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &[Rename] = &[
+        Rename::Flag {
+            old: "--return",
+            new: "--round-trip",
+        },
+        Rename::Command {
+            old: "watch",
+            new: "monitor watch",
+        },
+    ];
+
+    fn rewrite(args: &[&str]) -> Rewritten {
+        let args = args.iter().map(|arg| arg.to_string());
+        rewrite_args(args, TABLE, |name| name == "--cache")
+    }
+
+    #[test]
+    fn test_renamed_flag_warns_once() {
+        let rewritten = rewrite(&["cli", "price", "A", "B", "--return", "--return=true"]);
+        assert_eq!(
+            rewritten.args,
+            [
+                "cli",
+                "price",
+                "A",
+                "B",
+                "--round-trip",
+                "--round-trip=true"
+            ]
+        );
+        assert_eq!(rewritten.notices.len(), 1);
+    }
+
+    #[test]
+    fn test_renamed_command_only_in_command_position() {
+        let rewritten = rewrite(&["cli", "--cache", "watch", "watch", "A", "watch"]);
+        assert_eq!(
+            rewritten.args,
+            ["cli", "--cache", "watch", "monitor", "watch", "A", "watch"]
+        );
+
+        let escaped = rewrite(&["cli", "trip", "--", "--return"]);
+        assert_eq!(escaped.args, ["cli", "trip", "--", "--return"]);
+        assert!(escaped.notices.is_empty());
+    }
+}
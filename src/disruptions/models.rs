@@ -0,0 +1,39 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisruptionRaw {
+    pub title: String,
+    #[serde(default)]
+    pub publication_sections: Vec<PublicationSectionRaw>,
+    #[serde(default)]
+    pub timespans: Vec<TimespanRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicationSectionRaw {
+    pub section: SectionRaw,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SectionRaw {
+    #[serde(default)]
+    pub stations: Vec<SectionStationRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionStationRaw {
+    pub uic_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimespanRaw {
+    #[serde(default)]
+    pub cause: Option<LabelRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelRaw {
+    pub label: String,
+}
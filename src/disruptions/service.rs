@@ -0,0 +1,147 @@
+use crate::client::{Endpoint, client};
+use crate::disruptions::models::DisruptionRaw;
+use crate::trips::Trip;
+use serde::Serialize;
+
+/// An active disruption with the stations on its affected sections
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Disruption {
+    /// Affected route, e.g. "Utrecht Centraal - Amsterdam Centraal"
+    pub title: String,
+    /// Cause label from the first timespan, e.g. "seinstoring"
+    pub cause: Option<String>,
+    /// UIC codes of every station on the affected sections
+    #[serde(skip)]
+    pub station_uics: Vec<String>,
+}
+
+impl From<DisruptionRaw> for Disruption {
+    fn from(raw: DisruptionRaw) -> Self {
+        let cause = raw
+            .timespans
+            .into_iter()
+            .find_map(|timespan| timespan.cause)
+            .map(|cause| cause.label);
+        let station_uics = raw
+            .publication_sections
+            .into_iter()
+            .flat_map(|section| section.section.stations)
+            .map(|station| station.uic_code)
+            .collect();
+
+        Disruption {
+            title: raw.title,
+            cause,
+            station_uics,
+        }
+    }
+}
+
+impl Disruption {
+    /// Short description like "seinstoring (Utrecht Centraal - Amsterdam Centraal)"
+    pub fn summary(&self) -> String {
+        match &self.cause {
+            Some(cause) => format!("{} ({})", cause, self.title),
+            None => self.title.clone(),
+        }
+    }
+}
+
+/// Fetch the currently active disruptions and maintenance works
+pub fn active_disruptions() -> Result<Vec<Disruption>, Box<dyn std::error::Error>> {
+    let url = "https://gateway.apiportal.ns.nl/disruptions/v3";
+
+    let request = client().get(url)?.query("isActive", "true");
+    let body = client().send(Endpoint::Disruptions, request)?;
+
+    let raw: Vec<DisruptionRaw> = serde_json::from_str(&body)?;
+    Ok(raw.into_iter().map(Disruption::from).collect())
+}
+
+/// Active disruptions whose affected sections include a station
+pub fn disruptions_at(uic_code: &str) -> Result<Vec<Disruption>, Box<dyn std::error::Error>> {
+    Ok(active_disruptions()?
+        .into_iter()
+        .filter(|d| d.station_uics.iter().any(|uic| uic == uic_code))
+        .collect())
+}
+
+/// The disruption most likely responsible for a delayed or cancelled trip:
+/// one whose affected sections contain both ends of a leg, otherwise one
+/// touching any station along the journey
+pub fn likely_cause<'a>(trip: &Trip, disruptions: &'a [Disruption]) -> Option<&'a Disruption> {
+    if !trip.is_disrupted() {
+        return None;
+    }
+
+    let touches = |disruption: &Disruption, uic: &Option<String>| {
+        uic.as_ref()
+            .is_some_and(|uic| disruption.station_uics.contains(uic))
+    };
+
+    // Any leg whose both ends are affected, otherwise any station along the journey
+    disruptions
+        .iter()
+        .find(|d| {
+            trip.legs
+                .iter()
+                .any(|leg| touches(d, &leg.origin_uic) && touches(d, &leg.destination_uic))
+        })
+        .or_else(|| {
+            disruptions.iter().find(|d| {
+                trip.legs
+                    .iter()
+                    .any(|leg| touches(d, &leg.origin_uic) || touches(d, &leg.destination_uic))
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trips::service::test_trip;
+
+    fn disruption(title: &str, uics: &[&str]) -> Disruption {
+        Disruption {
+            title: title.to_string(),
+            cause: Some("seinstoring".to_string()),
+            station_uics: uics.iter().map(|u| u.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_likely_cause_prefers_sections_covering_both_ends() {
+        let mut trip = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:15:00+02:00");
+        let disruptions = [
+            disruption("Den Haag C - Leiden C", &["8400282", "8400390"]),
+            disruption("Den Haag C - Utrecht Centraal", &["8400282", "8400621"]),
+        ];
+
+        // On-time trips are never attributed a cause
+        assert!(likely_cause(&trip, &disruptions).is_none());
+
+        trip.status = "CANCELLED".to_string();
+        let cause = likely_cause(&trip, &disruptions).unwrap();
+        assert_eq!(
+            cause.summary(),
+            "seinstoring (Den Haag C - Utrecht Centraal)"
+        );
+    }
+
+    #[test]
+    fn test_parse_disruption() {
+        let json = r#"[{
+            "id": "7001234",
+            "type": "DISRUPTION",
+            "title": "Utrecht Centraal - Amsterdam Centraal",
+            "publicationSections": [{"section": {"stations": [{"uicCode": "8400621"}, {"uicCode": "8400058"}]}}],
+            "timespans": [{"cause": {"label": "seinstoring"}}]
+        }]"#;
+        let raw: Vec<DisruptionRaw> = serde_json::from_str(json).unwrap();
+        let disruption = Disruption::from(raw.into_iter().next().unwrap());
+
+        assert_eq!(disruption.cause.as_deref(), Some("seinstoring"));
+        assert_eq!(disruption.station_uics, vec!["8400621", "8400058"]);
+    }
+}
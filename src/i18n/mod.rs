@@ -0,0 +1,5 @@
+pub mod models;
+pub mod service;
+
+pub use models::{Lang, Text};
+pub use service::*;
@@ -0,0 +1,337 @@
+/// Language used for human-readable CLI output
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Lang {
+    #[default]
+    En,
+    Nl,
+}
+
+impl Lang {
+    /// Parse a language code such as "nl", "NL" or "nl_NL.UTF-8"
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.get(..2)?.to_lowercase().as_str() {
+            "en" => Some(Lang::En),
+            "nl" => Some(Lang::Nl),
+            _ => None,
+        }
+    }
+}
+
+/// Translatable labels and messages. Placeholders are written as `{}` and
+/// filled in order by `trf()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Text {
+    FindingJourney,
+    FindingJourneyVia,
+    StationGroup,
+    GettingPrices,
+    FirstJourney,
+    LastJourney,
+    NoJourneysThatDay,
+    DeparturesFrom,
+    OvFietsAt,
+    NoOvFiets,
+    BikesAvailable,
+    BikesUnknown,
+    TravelTimesBetween,
+    BestMeetingPoint,
+    MeetingAt,
+    BestOption,
+    PerAdult,
+    SingleJourney,
+    ReturnJourney,
+    AdultsTotal,
+    ChildrenTotal,
+    Discount,
+    DiscountType,
+    Operator,
+    NoPrices,
+    ComparingStations,
+    ChosenStations,
+    Cheapest,
+    Fastest,
+    NoJourneysForBoth,
+    Them,
+    You,
+    Wait,
+    ArriveTogether,
+    TheyWait,
+    YouWait,
+    LikelyCause,
+    TightTransfer,
+    SupplementRequired,
+    SupplementCost,
+    NoSupplement,
+    WithSupplement,
+    InternationalJourney,
+    NoInternationalFare,
+    FareUnits,
+    TicketPrice,
+    ConvertedPrice,
+    GroupProduct,
+    GroupNoProduct,
+    BikeTicket,
+    BikesRestricted,
+    FareHistory,
+    NoFareHistory,
+    AdvisorSummary,
+    UnpricedJourneys,
+    FullFareTotal,
+    BestSubscription,
+    FullFareCheapest,
+    TrajectVrijHint,
+    EndangeredTransfer,
+    MissedTransfer,
+    TypicalDelayWeekdays,
+    TypicalDelayWeekends,
+    Notice,
+    AccessibilityNote,
+    WroteTrips,
+    BrowserFailed,
+    WroteDepartureBoard,
+    WatchingRoute,
+    NoChangesRecorded,
+    FollowingTrip,
+    TripNoLongerListed,
+    TripIsCancelled,
+    TripDeparted,
+    PriceCacheAt,
+    CacheEntries,
+    CacheWarmed,
+    CacheExported,
+    CacheImported,
+    StationsSynced,
+    StationsMatch,
+    StationsChecked,
+    UnknownLogStation,
+    NoFareFor,
+    Co2Saved,
+    Direct,
+    Alternative,
+    NoAlternative,
+    OneTransfer,
+    Transfers,
+    Cancelled,
+    Disruption,
+    Maintenance,
+    AlternativeTransport,
+    NoStationsFound,
+    AmbiguousQuery,
+    RefineQuery,
+    NotAUicCode,
+    OnlyForeignMatches,
+    Error,
+}
+
+impl Text {
+    pub fn en(self) -> &'static str {
+        match self {
+            Text::FindingJourney => "Finding journey from {} to {}",
+            Text::FindingJourneyVia => "Finding journey from {} to {} via {}",
+            Text::StationGroup => "{} ({} stations)",
+            Text::GettingPrices => "Getting prices from {} to {}",
+            Text::FirstJourney => "First journey from {} to {} on {}",
+            Text::LastJourney => "Last journey from {} to {} on {}",
+            Text::NoJourneysThatDay => "No running journeys found on that day.",
+            Text::DeparturesFrom => "Departures from {}",
+            Text::OvFietsAt => "🚲 OV-fiets at {}",
+            Text::NoOvFiets => "No OV-fiets rental location at {}",
+            Text::BikesAvailable => "{} bikes available",
+            Text::BikesUnknown => "availability unknown",
+            Text::TravelTimesBetween => "🗺️  Fastest travel times between {} stations",
+            Text::BestMeetingPoint => "📍 Best meeting point: {}, at most {} away for everyone",
+            Text::MeetingAt => "Meeting at {}: they travel from {}, you travel from {}",
+            Text::BestOption => "Best option",
+            Text::PerAdult => "Per adult",
+            Text::SingleJourney => "Single",
+            Text::ReturnJourney => "Return",
+            Text::AdultsTotal => "{} × adult: €{}",
+            Text::ChildrenTotal => "{} × child (4-11): €{}",
+            Text::Discount => "Discount",
+            Text::DiscountType => "Discount type",
+            Text::Operator => "Operator",
+            Text::NoPrices => "No prices found for this route.",
+            Text::ComparingStations => "Comparing {} station pairs for {} to {}",
+            Text::ChosenStations => "{} → {} is the {} option",
+            Text::Cheapest => "cheapest",
+            Text::Fastest => "fastest",
+            Text::NoJourneysForBoth => "No journeys found for both parties.",
+            Text::Them => "Them",
+            Text::You => "You",
+            Text::Wait => "Wait",
+            Text::ArriveTogether => "arrive together",
+            Text::TheyWait => "they wait {} min",
+            Text::YouWait => "you wait {} min",
+            Text::LikelyCause => "Likely cause",
+            Text::TightTransfer => "Tight transfer at {}: {} min to change",
+            Text::SupplementRequired => "Supplement required for the {}",
+            Text::SupplementCost => "Supplement required for the {}: €{}",
+            Text::NoSupplement => "No supplement needed on the next journeys",
+            Text::WithSupplement => "+ €{} {} supplement: €{} in total",
+            Text::InternationalJourney => "International journey to {}, fares in euros",
+            Text::NoInternationalFare => "NS quotes no fare for this journey, book it at nsinternational.com",
+            Text::FareUnits => "Distance: {} fare units (tariefeenheden)",
+            Text::TicketPrice => "Ticket €{} ({})",
+            Text::ConvertedPrice => "≈ {}{} (from €{} at ECB rate {} of {}, charged in EUR)",
+            Text::GroupProduct => "Group of {}: {} applies, €{} per person (€{} in total)",
+            Text::GroupNoProduct => "Group of {}: no group product offered, {} applies at €{} per person (€{} in total)",
+            Text::BikeTicket => "+ €{} day bike ticket: €{} in total",
+            Text::FareHistory => "Fare history ({}, full fare):",
+            Text::NoFareHistory => "No fare history cached for this route yet",
+            Text::AdvisorSummary => "{} journeys over {} month(s)",
+            Text::UnpricedJourneys => "{} journeys left out without a known fare",
+            Text::FullFareTotal => "Full fare: €{}",
+            Text::BestSubscription => "{} would have been cheapest, saving €{}",
+            Text::FullFareCheapest => "Full fare would have been cheapest",
+            Text::TrajectVrijHint => "Traject Vrij {} - {} would cover {} of these journeys (€{} at full fare); look up its monthly fee on ns.nl and pass it with --traject-vrij",
+            Text::BikesRestricted => "No bikes on board in rush hour (weekdays 06:30-09:00 and 16:30-18:00, not in July and August), folding bikes excepted",
+            Text::EndangeredTransfer => "Transfer at {} at risk: incoming train +{}, {} min left to change",
+            Text::MissedTransfer => "Transfer at {} will likely be missed: incoming train +{}",
+            Text::TypicalDelayWeekdays => "usually +{} on weekdays around {}:00 ({} logged delays)",
+            Text::TypicalDelayWeekends => "usually +{} on weekends around {}:00 ({} logged delays)",
+            Text::Notice => "Notice",
+            Text::AccessibilityNote => "Accessibility",
+            Text::WroteTrips => "Wrote {} trips to {}",
+            Text::BrowserFailed => "Could not open a browser ({}), open the link above manually",
+            Text::WroteDepartureBoard => "Wrote the {} departure board to {}",
+            Text::WatchingRoute => "Watching {} every {}s, logging changes to {} (sinks: {})",
+            Text::NoChangesRecorded => "No changes recorded in {}",
+            Text::FollowingTrip => "Following {} every {}s",
+            Text::TripNoLongerListed => "The trip is no longer listed, keeping its last known state",
+            Text::TripIsCancelled => "{} is cancelled",
+            Text::TripDeparted => "{} has departed",
+            Text::PriceCacheAt => "Price cache: {}",
+            Text::CacheEntries => "Entries: {} ({} valid, {} expired)",
+            Text::CacheWarmed => "Cached {} prices for {} routes in {}",
+            Text::CacheExported => "Exported {} prices from {} to {}",
+            Text::CacheImported => "Imported {} into {}: {} added, {} updated, {} kept",
+            Text::StationsSynced => "Synced {} stations to {}",
+            Text::StationsMatch => "All {} local stations match the {} stations in the NS API",
+            Text::StationsChecked => "Checked {} local stations against {} stations in the NS API",
+            Text::UnknownLogStation => "Skipping journeys with unknown or ambiguous station '{}'",
+            Text::NoFareFor => "No fare for {} - {}: {}",
+            Text::Co2Saved => "{} kg CO₂ saved vs. driving {} km by car",
+            Text::Direct => "direct",
+            Text::Alternative => "Alternative",
+            Text::NoAlternative => "No running alternative among these journeys, try --later",
+            Text::OneTransfer => "1 transfer",
+            Text::Transfers => "{} transfers",
+            Text::Cancelled => "CANCELLED",
+            Text::Disruption => "DISRUPTION",
+            Text::Maintenance => "MAINTENANCE",
+            Text::AlternativeTransport => "ALTERNATIVE_TRANSPORT",
+            Text::NoStationsFound => "No stations found for your query",
+            Text::AmbiguousQuery => "Your query `{}` was ambiguous, multiple stations matched:",
+            Text::RefineQuery => "Multiple stations matched. Please refine your query.",
+            Text::NotAUicCode => "`{}` is not a UIC code, --uic takes codes such as 8400621",
+            Text::OnlyForeignMatches => "`{}` only matches stations abroad, pass --include-foreign to search those",
+            Text::Error => "Error",
+        }
+    }
+
+    pub fn nl(self) -> &'static str {
+        match self {
+            Text::FindingJourney => "Reis zoeken van {} naar {}",
+            Text::FindingJourneyVia => "Reis zoeken van {} naar {} via {}",
+            Text::StationGroup => "{} ({} stations)",
+            Text::GettingPrices => "Prijzen ophalen van {} naar {}",
+            Text::FirstJourney => "Eerste reis van {} naar {} op {}",
+            Text::LastJourney => "Laatste reis van {} naar {} op {}",
+            Text::NoJourneysThatDay => "Geen rijdende reizen gevonden op die dag.",
+            Text::DeparturesFrom => "Vertrektijden {}",
+            Text::OvFietsAt => "🚲 OV-fiets bij {}",
+            Text::NoOvFiets => "Geen OV-fiets verhuurlocatie bij {}",
+            Text::BikesAvailable => "{} fietsen beschikbaar",
+            Text::BikesUnknown => "beschikbaarheid onbekend",
+            Text::TravelTimesBetween => "🗺️  Snelste reistijden tussen {} stations",
+            Text::BestMeetingPoint => "📍 Beste ontmoetingsplek: {}, voor iedereen hooguit {} reizen",
+            Text::MeetingAt => "Afspreken in {}: zij reizen vanaf {}, jij reist vanaf {}",
+            Text::BestOption => "Beste optie",
+            Text::PerAdult => "Per volwassene",
+            Text::SingleJourney => "Enkele reis",
+            Text::ReturnJourney => "Retour",
+            Text::AdultsTotal => "{} × volwassene: €{}",
+            Text::ChildrenTotal => "{} × kind (4-11): €{}",
+            Text::Discount => "Korting",
+            Text::DiscountType => "Kortingstype",
+            Text::Operator => "Vervoerder",
+            Text::NoPrices => "Geen prijzen gevonden voor deze route.",
+            Text::ComparingStations => "{} stationscombinaties vergelijken voor {} naar {}",
+            Text::ChosenStations => "{} → {} is de {} optie",
+            Text::Cheapest => "goedkoopste",
+            Text::Fastest => "snelste",
+            Text::NoJourneysForBoth => "Geen reizen gevonden voor beide reizigers.",
+            Text::Them => "Zij",
+            Text::You => "Jij",
+            Text::Wait => "Wachten",
+            Text::ArriveTogether => "tegelijk aankomst",
+            Text::TheyWait => "zij wachten {} min",
+            Text::YouWait => "jij wacht {} min",
+            Text::LikelyCause => "Vermoedelijke oorzaak",
+            Text::TightTransfer => "Krappe overstap in {}: {} min om over te stappen",
+            Text::SupplementRequired => "Toeslag nodig voor de {}",
+            Text::SupplementCost => "Toeslag nodig voor de {}: €{}",
+            Text::NoSupplement => "Geen toeslag nodig op de eerstvolgende reizen",
+            Text::WithSupplement => "+ €{} toeslag {}: €{} totaal",
+            Text::InternationalJourney => "Internationale reis naar {}, prijzen in euro",
+            Text::NoInternationalFare => "NS geeft geen prijs voor deze reis, boek hem via nsinternational.com",
+            Text::FareUnits => "Afstand: {} tariefeenheden",
+            Text::TicketPrice => "Kaartje €{} ({})",
+            Text::ConvertedPrice => "≈ {}{} (van €{} tegen ECB-koers {} van {}, afgerekend in EUR)",
+            Text::GroupProduct => "Groep van {}: {} geldt, €{} per persoon (€{} totaal)",
+            Text::GroupNoProduct => "Groep van {}: geen groepsproduct aangeboden, {} geldt voor €{} per persoon (€{} totaal)",
+            Text::BikeTicket => "+ €{} dagkaart fiets: €{} totaal",
+            Text::FareHistory => "Prijsverloop ({}, vol tarief):",
+            Text::NoFareHistory => "Nog geen prijsverloop opgeslagen voor deze route",
+            Text::AdvisorSummary => "{} reizen in {} maand(en)",
+            Text::UnpricedJourneys => "{} reizen weggelaten zonder bekende prijs",
+            Text::FullFareTotal => "Vol tarief: €{}",
+            Text::BestSubscription => "{} was het voordeligst geweest, €{} bespaard",
+            Text::FullFareCheapest => "Vol tarief was het voordeligst geweest",
+            Text::TrajectVrijHint => "Traject Vrij {} - {} dekt {} van deze reizen (€{} vol tarief); zoek het maandbedrag op ns.nl op en geef het mee met --traject-vrij",
+            Text::BikesRestricted => "Geen fietsen mee in de spits (werkdagen 06:30-09:00 en 16:30-18:00, niet in juli en augustus), vouwfietsen wel",
+            Text::EndangeredTransfer => "Overstap in {} in gevaar: aankomende trein +{}, nog {} min om over te stappen",
+            Text::MissedTransfer => "Overstap in {} wordt waarschijnlijk gemist: aankomende trein +{}",
+            Text::TypicalDelayWeekdays => "meestal +{} op werkdagen rond {}:00 ({} gelogde vertragingen)",
+            Text::TypicalDelayWeekends => "meestal +{} in het weekend rond {}:00 ({} gelogde vertragingen)",
+            Text::Notice => "Let op",
+            Text::AccessibilityNote => "Toegankelijkheid",
+            Text::WroteTrips => "{} reizen naar {} geschreven",
+            Text::BrowserFailed => "Kon geen browser openen ({}), open de link hierboven zelf",
+            Text::WroteDepartureBoard => "Vertrekbord van {} naar {} geschreven",
+            Text::WatchingRoute => "{} elke {}s volgen, wijzigingen in {} (sinks: {})",
+            Text::NoChangesRecorded => "Geen wijzigingen vastgelegd in {}",
+            Text::FollowingTrip => "{} elke {}s volgen",
+            Text::TripNoLongerListed => "De reis staat er niet meer bij, de laatst bekende stand blijft staan",
+            Text::TripIsCancelled => "{} is geannuleerd",
+            Text::TripDeparted => "{} is vertrokken",
+            Text::PriceCacheAt => "Prijzencache: {}",
+            Text::CacheEntries => "Items: {} ({} geldig, {} verlopen)",
+            Text::CacheWarmed => "{} prijzen voor {} trajecten in {} opgeslagen",
+            Text::CacheExported => "{} prijzen uit {} naar {} geëxporteerd",
+            Text::CacheImported => "{} in {} geïmporteerd: {} toegevoegd, {} bijgewerkt, {} behouden",
+            Text::StationsSynced => "{} stations naar {} gesynchroniseerd",
+            Text::StationsMatch => "Alle {} lokale stations komen overeen met de {} stations in de NS API",
+            Text::StationsChecked => "{} lokale stations vergeleken met {} stations in de NS API",
+            Text::UnknownLogStation => "Reizen met onbekend of dubbelzinnig station '{}' overgeslagen",
+            Text::NoFareFor => "Geen tarief voor {} - {}: {}",
+            Text::Co2Saved => "{} kg CO₂ bespaard t.o.v. {} km met de auto",
+            Text::Direct => "direct",
+            Text::Alternative => "Alternatief",
+            Text::NoAlternative => "Geen rijdend alternatief bij deze reizen, probeer --later",
+            Text::OneTransfer => "1 overstap",
+            Text::Transfers => "{} overstappen",
+            Text::Cancelled => "RIJDT NIET",
+            Text::Disruption => "STORING",
+            Text::Maintenance => "WERKZAAMHEDEN",
+            Text::AlternativeTransport => "VERVANGEND VERVOER",
+            Text::NoStationsFound => "Geen stations gevonden voor je zoekopdracht",
+            Text::AmbiguousQuery => "Je zoekopdracht `{}` is niet eenduidig, meerdere stations gevonden:",
+            Text::RefineQuery => "Meerdere stations gevonden. Maak je zoekopdracht specifieker.",
+            Text::NotAUicCode => "`{}` is geen UIC-code, --uic verwacht codes zoals 8400621",
+            Text::OnlyForeignMatches => "`{}` komt alleen overeen met stations in het buitenland, gebruik --include-foreign om die te doorzoeken",
+            Text::Error => "Fout",
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::models::{Lang, Text};
+use std::env;
+use std::sync::OnceLock;
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Select the output language for the rest of the process. An explicit choice
+/// wins, then `NSTIMES_LANG`, then English. Only the first call has effect.
+pub fn set_lang(choice: Option<Lang>) {
+    let lang = choice
+        .or_else(|| env::var("NSTIMES_LANG").ok().and_then(|v| Lang::from_code(&v)))
+        .unwrap_or_default();
+    let _ = LANG.set(lang);
+}
+
+/// The active output language (English until `set_lang()` is called)
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// Translate a label into the active language
+pub fn tr(text: Text) -> &'static str {
+    translate(lang(), text)
+}
+
+/// Translate a message and fill its `{}` placeholders in order
+pub fn trf(text: Text, args: &[&str]) -> String {
+    fill(tr(text), args)
+}
+
+/// Human label for an NS trip status such as "CANCELLED"
+pub fn status_label(status: &str) -> String {
+    let text = match status {
+        "CANCELLED" => Text::Cancelled,
+        "DISRUPTION" => Text::Disruption,
+        "MAINTENANCE" => Text::Maintenance,
+        "ALTERNATIVE_TRANSPORT" => Text::AlternativeTransport,
+        other => return other.to_string(),
+    };
+    tr(text).to_string()
+}
+
+fn translate(lang: Lang, text: Text) -> &'static str {
+    match lang {
+        Lang::En => text.en(),
+        Lang::Nl => text.nl(),
+    }
+}
+
+fn fill(template: &str, args: &[&str]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut pieces = template.split("{}").peekable();
+
+    while let Some(piece) = pieces.next() {
+        out.push_str(piece);
+        if pieces.peek().is_some() {
+            out.push_str(args.next().copied().unwrap_or_default());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_and_translate() {
+        assert_eq!(
+            fill(translate(Lang::Nl, Text::FindingJourney), &["Den Haag C", "Utrecht Centraal"]),
+            "Reis zoeken van Den Haag C naar Utrecht Centraal"
+        );
+        assert_eq!(fill(translate(Lang::En, Text::TheyWait), &["4"]), "they wait 4 min");
+        assert_eq!(Lang::from_code("nl_NL.UTF-8"), Some(Lang::Nl));
+        assert_eq!(Lang::from_code("de"), None);
+    }
+}
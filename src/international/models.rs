@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+/// Country a station lies in, as told by the first two digits of its UIC code
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Country {
+    Netherlands,
+    Belgium,
+    Luxembourg,
+    Germany,
+    France,
+    Switzerland,
+    Austria,
+    Italy,
+    Denmark,
+    Sweden,
+    CzechRepublic,
+    Hungary,
+    UnitedKingdom,
+}
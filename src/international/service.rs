@@ -0,0 +1,107 @@
+use crate::i18n::{lang, Lang};
+use crate::international::models::Country;
+use crate::stations::models::Station;
+
+impl Country {
+    /// The country of a station by its UIC code, `None` for countries NS does not run to
+    pub fn of(station: &Station) -> Option<Country> {
+        Some(match station.id.uic_code.get(..2)? {
+            "84" => Country::Netherlands,
+            "88" => Country::Belgium,
+            "82" => Country::Luxembourg,
+            "80" => Country::Germany,
+            "87" => Country::France,
+            "85" => Country::Switzerland,
+            "81" => Country::Austria,
+            "83" => Country::Italy,
+            "86" => Country::Denmark,
+            "74" => Country::Sweden,
+            "54" => Country::CzechRepublic,
+            "55" => Country::Hungary,
+            "70" => Country::UnitedKingdom,
+            _ => return None,
+        })
+    }
+
+    /// Name in the active output language
+    pub fn name(&self) -> &'static str {
+        let (en, nl) = match self {
+            Country::Netherlands => ("the Netherlands", "Nederland"),
+            Country::Belgium => ("Belgium", "België"),
+            Country::Luxembourg => ("Luxembourg", "Luxemburg"),
+            Country::Germany => ("Germany", "Duitsland"),
+            Country::France => ("France", "Frankrijk"),
+            Country::Switzerland => ("Switzerland", "Zwitserland"),
+            Country::Austria => ("Austria", "Oostenrijk"),
+            Country::Italy => ("Italy", "Italië"),
+            Country::Denmark => ("Denmark", "Denemarken"),
+            Country::Sweden => ("Sweden", "Zweden"),
+            Country::CzechRepublic => ("the Czech Republic", "Tsjechië"),
+            Country::Hungary => ("Hungary", "Hongarije"),
+            Country::UnitedKingdom => ("the United Kingdom", "het Verenigd Koninkrijk"),
+        };
+        match lang() {
+            Lang::En => en,
+            Lang::Nl => nl,
+        }
+    }
+
+    /// ISO 3166 code, e.g. "GB", as in the country column of data/stations.csv
+    pub fn iso_code(&self) -> &'static str {
+        match self {
+            Country::Netherlands => "NL",
+            Country::Belgium => "BE",
+            Country::Luxembourg => "LU",
+            Country::Germany => "DE",
+            Country::France => "FR",
+            Country::Switzerland => "CH",
+            Country::Austria => "AT",
+            Country::Italy => "IT",
+            Country::Denmark => "DK",
+            Country::Sweden => "SE",
+            Country::CzechRepublic => "CZ",
+            Country::Hungary => "HU",
+            Country::UnitedKingdom => "GB",
+        }
+    }
+
+    /// ISO code of the local currency, e.g. "GBP"
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Country::Switzerland => "CHF",
+            Country::Denmark => "DKK",
+            Country::Sweden => "SEK",
+            Country::CzechRepublic => "CZK",
+            Country::Hungary => "HUF",
+            Country::UnitedKingdom => "GBP",
+            _ => "EUR",
+        }
+    }
+}
+
+/// The foreign country a journey crosses into (or starts in), `None` for domestic journeys
+pub fn abroad(from: &Station, to: &Station) -> Option<Country> {
+    [to, from]
+        .into_iter()
+        .filter_map(Country::of)
+        .find(|country| *country != Country::Netherlands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stations::pick_station_local;
+
+    #[test]
+    fn test_abroad() {
+        let utrecht = pick_station_local("Utrecht Centraal").unwrap();
+        let london = pick_station_local("London St. Pancras Int.").unwrap();
+        let brussels = pick_station_local("Brussel-Zuid").unwrap();
+
+        assert_eq!(Country::of(&utrecht), Some(Country::Netherlands));
+        assert_eq!(abroad(&utrecht, &utrecht), None);
+        assert_eq!(abroad(&utrecht, &london).map(|c| c.currency()), Some("GBP"));
+        assert_eq!(abroad(&brussels, &utrecht), Some(Country::Belgium));
+        assert_eq!(abroad(&brussels, &london), Some(Country::UnitedKingdom));
+    }
+}
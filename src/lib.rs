@@ -1,6 +1,22 @@
+pub mod advisor;
 pub mod cache;
+pub mod client;
+#[cfg(feature = "cli")]
 pub mod commands;
 pub mod constants;
+pub mod currency;
+pub mod departures;
+pub mod deprecations;
+pub mod disruptions;
+pub mod i18n;
+pub mod international;
+pub mod matrix;
+pub mod migrations;
+pub mod monitor;
+pub mod output;
+pub mod places;
 pub mod prices;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod stations;
 pub mod trips;
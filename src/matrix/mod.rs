@@ -0,0 +1,4 @@
+pub mod models;
+pub mod service;
+
+pub use service::*;
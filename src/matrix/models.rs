@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Fastest travel times between every ordered pair of a set of stations
+#[derive(Debug, Clone, Serialize)]
+pub struct TravelMatrix {
+    pub stations: Vec<String>,
+    /// `minutes[i][j]`: the fastest journey from station i to station j,
+    /// `None` on the diagonal and where no journey was found
+    pub minutes: Vec<Vec<Option<i64>>>,
+    /// Pairs whose journeys could not be fetched
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<MatrixFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixFailure {
+    pub from: String,
+    pub to: String,
+    pub error: String,
+}
+
+/// A fastest travel time in the travel-time cache
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TravelTimeEntry {
+    pub minutes: i64,
+    pub fetched_at: DateTime<Utc>,
+}
@@ -0,0 +1,253 @@
+use crate::cache::{read_owned_file, user_cache_dir};
+use crate::matrix::models::{MatrixFailure, TravelMatrix, TravelTimeEntry};
+use crate::stations::models::Station;
+use crate::trips::{trips, Trip, TripQuery};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Most journey searches a matrix runs at the same time
+pub const MAX_JOBS: usize = 8;
+
+/// How long a cached travel time is used before it is fetched again
+const CACHE_TTL: Duration = Duration::days(1);
+
+/// Travel-time cache file: `NS_MATRIX_CACHE`, or travel-times.json in the
+/// user's cache directory; None without either
+pub fn travel_time_cache_path() -> Option<PathBuf> {
+    match std::env::var_os("NS_MATRIX_CACHE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(user_cache_dir()?.join("travel-times.json")),
+    }
+}
+
+/// Fastest travel times by station pair, so a repeated matrix only searches
+/// the pairs it has not seen within `CACHE_TTL`
+pub struct TravelTimeCache {
+    path: PathBuf,
+    /// Key format: "from_uic-to_uic"
+    entries: Mutex<HashMap<String, TravelTimeEntry>>,
+}
+
+impl TravelTimeCache {
+    /// Load the cache at `path`; a missing or unreadable file, or one owned
+    /// by another user, starts empty
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = match read_owned_file(&path) {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("⚠️  Failed to parse travel-time cache, starting fresh: {}", e);
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    fn key(from: &Station, to: &Station) -> String {
+        format!("{}-{}", from.id.uic_code, to.id.uic_code)
+    }
+
+    /// The cached travel time from `from` to `to`, unless older than `CACHE_TTL`
+    pub fn get(&self, from: &Station, to: &Station, now: DateTime<Utc>) -> Option<i64> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&Self::key(from, to))
+            .filter(|entry| now - entry.fetched_at < CACHE_TTL)
+            .map(|entry| entry.minutes)
+    }
+
+    pub fn insert(&self, from: &Station, to: &Station, minutes: i64, now: DateTime<Utc>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                Self::key(from, to),
+                TravelTimeEntry {
+                    minutes,
+                    fetched_at: now,
+                },
+            );
+        }
+    }
+
+    /// Write the cache back to its file, dropping expired entries
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let mut entries = self.entries.lock().map_err(|_| "travel-time cache lock poisoned")?;
+        entries.retain(|_, entry| now - entry.fetched_at < CACHE_TTL);
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&*entries)?)?;
+        Ok(())
+    }
+}
+
+/// Minutes of the fastest journey that still runs
+pub fn fastest_minutes(trips: &[Trip]) -> Option<i64> {
+    trips
+        .iter()
+        .filter(|trip| !trip.is_cancelled())
+        .map(|trip| trip.duration().num_minutes())
+        .min()
+}
+
+/// `f` applied to every item on at most `jobs` threads (1 to `MAX_JOBS`),
+/// the results in the order of `items`
+pub fn bounded_map<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, MAX_JOBS).min(items.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    if let Ok(mut results) = results.lock() {
+                        results[index] = Some(result);
+                    }
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Search the fastest journey between every ordered pair of `stations`, at
+/// most `jobs` searches at a time, filling `cache` and, unless `refresh`,
+/// reusing the travel times it holds
+pub fn travel_matrix(
+    stations: &[Station],
+    jobs: usize,
+    cache: Option<&TravelTimeCache>,
+    refresh: bool,
+) -> TravelMatrix {
+    let now = Utc::now();
+    let pairs: Vec<(usize, usize)> = (0..stations.len())
+        .flat_map(|i| (0..stations.len()).map(move |j| (i, j)))
+        .filter(|(i, j)| i != j)
+        .collect();
+
+    let outcomes = bounded_map(&pairs, jobs, |&(i, j)| {
+        let (from, to) = (&stations[i], &stations[j]);
+        let cached = cache.filter(|_| !refresh).and_then(|cache| cache.get(from, to, now));
+        if let Some(minutes) = cached {
+            return Ok(Some(minutes));
+        }
+        let found = trips(from, to, &TripQuery::default()).map_err(|e| e.to_string())?;
+        let minutes = fastest_minutes(&found);
+        if let (Some(cache), Some(minutes)) = (cache, minutes) {
+            cache.insert(from, to, minutes, now);
+        }
+        Ok(minutes)
+    });
+
+    let mut matrix = TravelMatrix {
+        stations: stations.iter().map(|station| station.names.long.clone()).collect(),
+        minutes: vec![vec![None; stations.len()]; stations.len()],
+        failures: Vec::new(),
+    };
+    for (&(i, j), outcome) in pairs.iter().zip(outcomes) {
+        match outcome {
+            Ok(minutes) => matrix.minutes[i][j] = minutes,
+            Err(error) => matrix.failures.push(MatrixFailure {
+                from: matrix.stations[i].clone(),
+                to: matrix.stations[j].clone(),
+                error,
+            }),
+        }
+    }
+    matrix
+}
+
+impl TravelMatrix {
+    /// The station everyone else reaches soonest: the one with the shortest
+    /// longest journey towards it, and that journey's minutes. Only stations
+    /// every other station has a travel time to are considered.
+    pub fn best_meeting_point(&self) -> Option<(usize, i64)> {
+        (0..self.stations.len())
+            .filter_map(|j| {
+                let worst = (0..self.stations.len())
+                    .filter(|&i| i != j)
+                    .map(|i| self.minutes[i][j])
+                    .collect::<Option<Vec<i64>>>()?
+                    .into_iter()
+                    .max()?;
+                Some((j, worst))
+            })
+            .min_by_key(|&(j, worst)| (worst, j))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stations::pick_station_local;
+
+    #[test]
+    fn test_bounded_map_keeps_order_and_limit() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<usize> = (0..40).collect();
+        let doubled = bounded_map(&items, 3, |n| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            running.fetch_sub(1, Ordering::SeqCst);
+            n * 2
+        });
+        assert_eq!(doubled, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(bounded_map(&[] as &[usize], 4, |n| *n).is_empty());
+    }
+
+    #[test]
+    fn test_best_meeting_point() {
+        let matrix = TravelMatrix {
+            stations: vec!["Amsterdam".into(), "Utrecht".into(), "Zwolle".into()],
+            minutes: vec![
+                vec![None, Some(27), Some(70)],
+                vec![Some(27), None, Some(60)],
+                vec![Some(70), Some(60), None],
+            ],
+            failures: Vec::new(),
+        };
+        assert_eq!(matrix.best_meeting_point(), Some((1, 60)));
+
+        let mut unknown = matrix.clone();
+        unknown.minutes[2][1] = None;
+        assert_eq!(unknown.best_meeting_point(), Some((0, 70)));
+    }
+
+    #[test]
+    fn test_cache_expires_entries() {
+        let cache = TravelTimeCache::open(std::env::temp_dir().join("nstimes-matrix-test.json"));
+        let from = pick_station_local("Utrecht Centraal").unwrap();
+        let to = pick_station_local("Zwolle").unwrap();
+        let now = Utc::now();
+        cache.insert(&from, &to, 60, now - Duration::hours(2));
+        assert_eq!(cache.get(&from, &to, now), Some(60));
+        assert_eq!(cache.get(&to, &from, now), None);
+        assert_eq!(cache.get(&from, &to, now + CACHE_TTL), None);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::cache::CACHE_VERSION;
+use std::fmt;
+
+/// What the startup migrations changed on disk
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// One human-readable line per applied migration
+    pub changes: Vec<String>,
+    /// Copies of files taken before they were rewritten
+    pub backups: Vec<String>,
+}
+
+impl MigrationReport {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Print a summary to stderr, nothing when no migration ran
+    pub fn log(&self) {
+        for change in &self.changes {
+            eprintln!("🔧 {}", change);
+        }
+        for backup in &self.backups {
+            eprintln!("💾 Backup kept at {}", backup);
+        }
+    }
+}
+
+/// A cache file written by a newer build, in a format this one can't read
+#[derive(Debug)]
+pub struct NewerCacheVersion(pub u32);
+
+impl fmt::Display for NewerCacheVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cache format v{} is newer than this build reads (v{}), upgrade nstimes to use it",
+            self.0, CACHE_VERSION
+        )
+    }
+}
+
+impl std::error::Error for NewerCacheVersion {}
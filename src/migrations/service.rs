@@ -0,0 +1,257 @@
+use super::models::{MigrationReport, NewerCacheVersion};
+use crate::cache::backend::{read_json, write_file, GZIP_MAGIC};
+use crate::cache::models::CacheEntry;
+use crate::cache::{CacheFile, PriceKey, CACHE_VERSION, KV_PREFIX, REDIS_SCHEME};
+use crate::stations::station_table;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// One cache format upgrade: the file of one version in, the next version
+/// out, with a line per change made
+type CacheUpgrade = fn(Value, &mut Vec<String>) -> Result<Value, Box<dyn std::error::Error>>;
+
+/// `CACHE_UPGRADES[n]` turns a cache file of version `n` into version
+/// `n + 1`; one entry per `CACHE_VERSION` bump
+const CACHE_UPGRADES: &[CacheUpgrade] = &[upgrade_v0, upgrade_v1];
+
+/// Bring legacy on-disk artifacts up to date before anything opens them.
+/// Every file is backed up before it is rewritten, and failures are reported
+/// as warnings so a broken migration never blocks startup.
+pub fn run(cache_path: Option<&str>) -> MigrationReport {
+    let mut report = MigrationReport::default();
+
+    if let Some(path) = cache_path
+        && !path.starts_with(KV_PREFIX)
+        && !path.starts_with(REDIS_SCHEME)
+        && let Err(e) = migrate_cache_file(Path::new(path), &mut report)
+    {
+        eprintln!("⚠️  Failed to migrate cache file {}: {}", path, e);
+    }
+
+    report
+}
+
+/// Upgrade a cache file of an older format version in place, and keep a
+/// copy of cache files that can no longer be parsed (they would otherwise
+/// be replaced by an empty cache on the next save). Files of a newer
+/// version are left for the cache to refuse.
+fn migrate_cache_file(
+    path: &Path,
+    report: &mut MigrationReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read(path)?;
+    let Some(file) = read_json(&content).ok().filter(|file| cache_version(file).is_some()) else {
+        let backup = backup(path)?;
+        report
+            .changes
+            .push(format!("Cache file {} is in an unknown format, starting fresh", path.display()));
+        report.backups.push(backup);
+        return Ok(());
+    };
+    let version = cache_version(&file).unwrap_or(CACHE_VERSION);
+    if version >= CACHE_VERSION {
+        return Ok(());
+    }
+
+    let mut changes = Vec::new();
+    let mut upgraded = upgrade_cache(file, &mut changes)?;
+    let entries: HashMap<String, CacheEntry> = serde_json::from_value(upgraded["entries"].take())?;
+    let backup = backup(path)?;
+    write_file(path, &CacheFile::new(&entries), content.starts_with(&GZIP_MAGIC))?;
+    report.changes.push(format!(
+        "Upgraded cache file {} from format v{} to v{}",
+        path.display(),
+        version,
+        CACHE_VERSION
+    ));
+    let path = path.display();
+    report.changes.extend(changes.into_iter().map(|change| format!("{} in {}", change, path)));
+    report.backups.push(backup);
+    Ok(())
+}
+
+/// Format version of a cache file: its `version`, 0 for a bare map of
+/// entries, None when it is no cache file at all
+fn cache_version(file: &Value) -> Option<u32> {
+    let file = file.as_object()?;
+    match (file.get("version"), file.get("entries")) {
+        (Some(version), Some(Value::Object(_))) => version.as_u64()?.try_into().ok(),
+        _ => Some(0),
+    }
+}
+
+/// Bring a cache file of any older format version to `CACHE_VERSION`, one
+/// upgrade at a time, noting what changed in `changes`. Fails for files of
+/// a newer version.
+pub fn upgrade_cache(
+    mut file: Value,
+    changes: &mut Vec<String>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let version = cache_version(&file).ok_or("not a cache file")?;
+    if version > CACHE_VERSION {
+        return Err(NewerCacheVersion(version).into());
+    }
+    for upgrade in &CACHE_UPGRADES[version as usize..] {
+        file = upgrade(file, changes)?;
+    }
+    Ok(file)
+}
+
+/// v0 → v1: wrap the bare map of entries with the version, rewrite keys
+/// into the current `PriceKey` form and start the price history of entries
+/// written before it was kept
+fn upgrade_v0(file: Value, changes: &mut Vec<String>) -> Result<Value, Box<dyn std::error::Error>> {
+    let entries: HashMap<String, CacheEntry> = serde_json::from_value(file)?;
+
+    let mut migrated = HashMap::with_capacity(entries.len());
+    let mut rewritten = 0;
+    let mut seeded = 0;
+    for (key, mut entry) in entries {
+        if entry.history.is_empty()
+            && let Some(year) = entry.tariff_year()
+        {
+            seeded += 1;
+            entry.record(entry.price_cents, year);
+        }
+        match canonical_key(&key, entry.travel_class) {
+            Some(canonical) if canonical != key => {
+                rewritten += 1;
+                migrated.entry(canonical).or_insert(entry);
+            }
+            _ => {
+                migrated.insert(key, entry);
+            }
+        }
+    }
+
+    if rewritten > 0 {
+        changes.push(format!("Rewrote {} legacy cache key(s)", rewritten));
+    }
+    if seeded > 0 {
+        changes.push(format!("Started the price history of {} cache entries", seeded));
+    }
+    Ok(json!({ "version": 1, "entries": migrated }))
+}
+
+/// v1 → v2: entries may now be `no_prices_until` markers, which older
+/// builds would read as free fares; existing entries stay as they are
+fn upgrade_v1(mut file: Value, _: &mut Vec<String>) -> Result<Value, Box<dyn std::error::Error>> {
+    file["version"] = json!(2);
+    Ok(file)
+}
+
+/// Current key for a stored legacy "from-to-class[-discount]" key, found by
+/// splitting the pair at the hyphen where both halves are known station names
+/// (names like "Hamburg-Harburg" contain hyphens themselves). Legacy keys
+/// only ever held single fares for one adult. None when the key can't be read.
+fn canonical_key(key: &str, travel_class: u8) -> Option<String> {
+    if let Some(current) = PriceKey::parse(key) {
+        return Some(current.to_string());
+    }
+    let class = format!("-{}", travel_class);
+    let (pair, discount) = match key.strip_suffix(&class) {
+        Some(pair) => (pair, None),
+        None => {
+            let (rest, discount) = key.rsplit_once('-')?;
+            (rest.strip_suffix(&class)?, Some(discount))
+        }
+    };
+    let is_station = |name: &str| station_table().iter().any(|(station, _)| *station == name);
+
+    pair.match_indices('-')
+        .map(|(i, _)| (&pair[..i], &pair[i + 1..]))
+        .find(|(from, to)| is_station(from) && is_station(to))
+        .map(|(from, to)| {
+            PriceKey::new(from, to, travel_class)
+                .with_discount(discount)
+                .to_string()
+        })
+}
+
+/// Copy `path` to `<path>.bak` and return the backup location
+fn backup(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let backup = format!("{}.bak", path.display());
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::PriceCache;
+    use std::env;
+
+    #[test]
+    fn test_canonical_key() {
+        let key = PriceKey::new("Amsterdam Centraal", "Utrecht Centraal", 2).to_string();
+        assert_eq!(canonical_key("Utrecht Centraal-Amsterdam Centraal-2", 2), Some(key.clone()));
+        assert_eq!(canonical_key("Amsterdam Centraal-Utrecht Centraal-2", 2), Some(key.clone()));
+        assert_eq!(canonical_key(&key, 2), Some(key));
+        assert_eq!(
+            canonical_key("Hamburg-Harburg-Amsterdam Centraal-1", 1),
+            Some(PriceKey::new("Amsterdam Centraal", "Hamburg-Harburg", 1).to_string())
+        );
+        assert_eq!(
+            canonical_key("Amsterdam Centraal-Utrecht Centraal-2-DISCOUNT_40_PERCENT", 2),
+            Some(
+                PriceKey::new("Amsterdam Centraal", "Utrecht Centraal", 2)
+                    .with_discount(Some("DISCOUNT_40_PERCENT"))
+                    .to_string()
+            )
+        );
+        assert_eq!(canonical_key("Nowhere-Somewhere-2", 2), None);
+    }
+
+    #[test]
+    fn test_migrate_cache_file() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_migrate_cache.json");
+        let entry = r#"{"price_cents": 940, "travel_class": 2, "expires_at": "2099-01-01"}"#;
+        fs::write(&path, format!(r#"{{"Utrecht Centraal-Amsterdam Centraal-2": {}}}"#, entry))?;
+
+        let report = run(Some(path.to_str().unwrap()));
+        assert_eq!(report.changes.len(), 3);
+        let upgraded = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        assert_eq!(cache_version(&upgraded), Some(CACHE_VERSION));
+
+        let cache = PriceCache::new(&path)?;
+        let key = PriceKey::new("Amsterdam Centraal", "Utrecht Centraal", 2);
+        assert_eq!(cache.get(&key), Some(940));
+        let history = cache.history(&key);
+        assert_eq!((history.len(), history[0].tariff_year), (1, 2098));
+
+        // Already migrated files are left alone
+        assert!(run(Some(path.to_str().unwrap())).is_empty());
+
+        let _ = fs::remove_file(&report.backups[0]);
+        let _ = fs::remove_file(&path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrade_cache_versions() {
+        let entry = json!({"price_cents": 940, "travel_class": 2, "expires_at": "2099-01-01"});
+        let key = PriceKey::new("Amsterdam Centraal", "Utrecht Centraal", 2).to_string();
+        let current = json!({ "version": CACHE_VERSION, "entries": { key.clone(): entry } });
+
+        let mut changes = Vec::new();
+        let upgraded = upgrade_cache(json!({ key.clone(): entry }), &mut changes).unwrap();
+        assert_eq!(upgraded["version"], CACHE_VERSION);
+        assert_eq!(upgraded["entries"][&key]["price_cents"], 940);
+        assert_eq!(changes, ["Started the price history of 1 cache entries"]);
+
+        let mut changes = Vec::new();
+        assert_eq!(upgrade_cache(current.clone(), &mut changes).unwrap(), current);
+        assert!(changes.is_empty());
+
+        let newer = json!({ "version": CACHE_VERSION + 1, "entries": {} });
+        let error = upgrade_cache(newer, &mut Vec::new()).unwrap_err();
+        assert!(error.is::<NewerCacheVersion>());
+        assert!(upgrade_cache(json!([1, 2]), &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod models;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod registry;
+pub mod service;
+pub mod sinks;
+
+pub use models::{Change, ChangeEvent, JourneyStatus, MonitoredRoute, Refresh, Urgency};
+pub use registry::MonitorRegistry;
+pub use service::*;
+pub use sinks::{parse_sink, redact_sink, Sink};
@@ -0,0 +1,98 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use super::sinks::redact_sink;
+use std::fmt;
+
+/// A single difference between two observations of the same journey
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// Departure delay in minutes went from `from` to `to`
+    Delay { from: i64, to: i64 },
+    /// Departure track changed
+    Track { from: String, to: String },
+    /// The journey got cancelled
+    Cancelled,
+    /// Any other NS status change (e.g. NORMAL -> DISRUPTION)
+    Status { from: String, to: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Delay { from, to } => write!(f, "delay +{} → +{}", from, to),
+            Change::Track { from, to } => write!(f, "platform {} → {}", from, to),
+            Change::Cancelled => write!(f, "cancelled"),
+            Change::Status { from, to } => write!(f, "status {} → {}", from, to),
+        }
+    }
+}
+
+/// A route watched by the API server, persisted in the `--monitors` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct MonitoredRoute {
+    #[cfg_attr(feature = "server", schema(example = 1))]
+    pub id: u64,
+    /// Origin station as resolved at creation, e.g. "Den Haag C"
+    #[cfg_attr(feature = "server", schema(example = "Den Haag C"))]
+    pub from: String,
+    #[cfg_attr(feature = "server", schema(example = "Amersfoort C"))]
+    pub to: String,
+    /// Seconds between refreshes
+    #[cfg_attr(feature = "server", schema(example = 60))]
+    pub interval_secs: u64,
+    /// Sink specs as accepted by `monitor watch --sink` (webhooks are http(s) sinks)
+    #[cfg_attr(feature = "server", schema(example = json!(["https://example.com/hook#secret=s3cr3t"])))]
+    pub sinks: Vec<String>,
+}
+
+impl MonitoredRoute {
+    /// The route with the credentials of its sinks masked, as the admin API lists it
+    pub fn redacted(&self) -> Self {
+        Self {
+            sinks: self.sinks.iter().map(|spec| redact_sink(spec)).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// How urgently a change is announced, see `escalation()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Urgency {
+    Normal,
+    /// Raised as a critical desktop notification
+    Critical,
+}
+
+/// State of one watched journey at a refresh
+#[derive(Debug, Clone, Serialize)]
+pub struct JourneyStatus {
+    pub journey: String,
+    pub departure: DateTime<FixedOffset>,
+    pub track: String,
+    pub status: String,
+    /// Departure delay in minutes, 0 when on time
+    pub delay: i64,
+}
+
+/// Everything a monitor poll produced, handed to every configured sink
+#[derive(Debug, Clone, Serialize)]
+pub struct Refresh {
+    pub refreshed_at: DateTime<FixedOffset>,
+    /// e.g. "Den Haag C → Amersfoort C"
+    pub route: String,
+    pub journeys: Vec<JourneyStatus>,
+    /// Changes since the previous refresh
+    pub changes: Vec<ChangeEvent>,
+}
+
+/// A change together with when it was seen and which journey it belongs to,
+/// stored one per line in the monitor log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub observed_at: DateTime<FixedOffset>,
+    /// Journey identity, see `journey_key()`
+    pub journey: String,
+    pub change: Change,
+}
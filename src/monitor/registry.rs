@@ -0,0 +1,202 @@
+use super::models::MonitoredRoute;
+use super::service::{EventLog, run_watch};
+use super::sinks::{parse_sink, Sink};
+use crate::cache::backend::write_file;
+use crate::stations::models::Station;
+use crate::stations::{StationLookupResult, lookup_station_local};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Shortest refresh interval accepted for server-side monitors
+pub const MIN_INTERVAL_SECS: u64 = 10;
+
+/// Each route with the flag that stops its watcher thread
+type Routes = Vec<(MonitoredRoute, Arc<AtomicBool>)>;
+
+/// Routes monitored by the API server. The list is persisted to a JSON file so
+/// a restart resumes the same monitors; each route runs on its own thread.
+pub struct MonitorRegistry {
+    path: PathBuf,
+    routes: Mutex<Routes>,
+}
+
+impl MonitorRegistry {
+    /// Load the persisted routes (if any) and start watching them
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        let saved: Vec<MonitoredRoute> = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Vec::new()
+        };
+
+        let registry = Self {
+            path,
+            routes: Mutex::new(Vec::new()),
+        };
+        for route in saved {
+            let stop = registry.start(&route)?;
+            registry.lock()?.push((route, stop));
+        }
+        Ok(registry)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn list(&self) -> Vec<MonitoredRoute> {
+        self.lock()
+            .map(|routes| routes.iter().map(|(route, _)| route.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Validate, persist and start a new monitored route
+    pub fn add(
+        &self,
+        from: &str,
+        to: &str,
+        interval_secs: u64,
+        sinks: Vec<String>,
+    ) -> Result<MonitoredRoute, Box<dyn std::error::Error>> {
+        if interval_secs < MIN_INTERVAL_SECS {
+            return Err(format!("interval_secs must be at least {}", MIN_INTERVAL_SECS).into());
+        }
+        for spec in &sinks {
+            remote_sink(spec)?;
+        }
+
+        let mut routes = self.lock()?;
+        let route = MonitoredRoute {
+            id: routes.iter().map(|(route, _)| route.id).max().unwrap_or(0) + 1,
+            from: resolve(from)?.names.long,
+            to: resolve(to)?.names.long,
+            interval_secs,
+            sinks,
+        };
+
+        let stop = self.start(&route)?;
+        routes.push((route.clone(), stop));
+        self.save(&routes)?;
+        Ok(route)
+    }
+
+    /// Stop and forget a route, returns false when the id is unknown
+    pub fn remove(&self, id: u64) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut routes = self.lock()?;
+        let Some(index) = routes.iter().position(|(route, _)| route.id == id) else {
+            return Ok(false);
+        };
+
+        let (_, stop) = routes.remove(index);
+        stop.store(true, Ordering::Relaxed);
+        self.save(&routes)?;
+        Ok(true)
+    }
+
+    fn start(&self, route: &MonitoredRoute) -> Result<Arc<AtomicBool>, Box<dyn std::error::Error>> {
+        let from = resolve(&route.from)?;
+        let to = resolve(&route.to)?;
+        let sinks = route
+            .sinks
+            .iter()
+            .map(|spec| remote_sink(spec))
+            .collect::<Result<Vec<_>, _>>()?;
+        let interval = Duration::from_secs(route.interval_secs);
+        let log = EventLog::open_default()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            run_watch(&from, &to, interval, &log, &sinks, &flag);
+        });
+        Ok(stop)
+    }
+
+    /// Write the list through a temporary file and a rename, so a crash
+    /// mid-write can't leave a truncated file that fails the next `open()`
+    fn save(&self, routes: &Routes) -> Result<(), Box<dyn std::error::Error>> {
+        let list: Vec<&MonitoredRoute> = routes.iter().map(|(route, _)| route).collect();
+        write_file(&self.path, &list, false)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Routes>, Box<dyn std::error::Error>> {
+        self.routes
+            .lock()
+            .map_err(|_| "Failed to lock monitor registry".into())
+    }
+}
+
+/// Parse a sink of a server-side monitor. Only sinks that deliver over the
+/// network are accepted: `stdout` and `json:<path>` would let an admin
+/// client write to the server's terminal or to any file it can write
+fn remote_sink(spec: &str) -> Result<Box<dyn Sink>, Box<dyn std::error::Error>> {
+    if !["http://", "https://", "mqtt://"].iter().any(|scheme| spec.starts_with(scheme)) {
+        return Err(format!(
+            "❌ Unsupported monitor sink '{}', expected an http(s) or mqtt:// URL",
+            spec
+        )
+        .into());
+    }
+    parse_sink(spec)
+}
+
+fn resolve(query: &str) -> Result<Station, Box<dyn std::error::Error>> {
+    match lookup_station_local(query) {
+        StationLookupResult::Single(station) => Ok(station),
+        StationLookupResult::None => Err(format!("No stations found for '{}'", query).into()),
+        StationLookupResult::Multiple(_) => {
+            Err(format!("Multiple stations matched '{}', please refine it", query).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_registry_validates_and_persists() -> Result<(), Box<dyn std::error::Error>> {
+        let path = env::temp_dir().join("test_monitor_registry.json");
+        let _ = fs::remove_file(&path);
+        let registry = MonitorRegistry::open(&path)?;
+
+        assert!(
+            registry
+                .add("Den Haag C", "Amersfoort C", 5, Vec::new())
+                .is_err()
+        );
+        assert!(
+            registry
+                .add("Den Haag C", "Amersfoort C", 60, vec!["mqtt://x".into()])
+                .is_err()
+        );
+        for sink in ["stdout", "json:/etc/nstimes.json"] {
+            assert!(
+                registry
+                    .add("Den Haag C", "Amersfoort C", 60, vec![sink.into()])
+                    .is_err()
+            );
+        }
+        assert!(
+            registry
+                .add("Nowhere", "Amersfoort C", 60, Vec::new())
+                .is_err()
+        );
+
+        // Stop the thread right away so the test doesn't poll the API
+        let route = registry.add("Den Haag C", "Amersfoort C", 3600, Vec::new())?;
+        assert!(registry.remove(route.id)?);
+        assert!(!registry.remove(route.id)?);
+
+        let saved: Vec<MonitoredRoute> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        assert!(saved.is_empty());
+
+        let _ = fs::remove_file(&path);
+        Ok(())
+    }
+}
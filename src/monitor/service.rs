@@ -0,0 +1,473 @@
+use super::models::{Change, ChangeEvent, JourneyStatus, Refresh, Urgency};
+use super::sinks::Sink;
+use crate::cache::{read_owned_file, user_cache_dir};
+use crate::i18n::{trf, Text};
+use crate::stations::models::Station;
+use crate::trips::{trips, Trip, TripQuery};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, Timelike, Weekday};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Stable identity of a journey across polls: route plus planned departure
+pub fn journey_key(trip: &Trip) -> String {
+    format!(
+        "{} → {} {}",
+        trip.origin_name,
+        trip.destination_name,
+        trip.departure_time.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// Route and planned departure back out of a `journey_key()`
+fn split_journey_key(key: &str) -> Option<(&str, NaiveDateTime)> {
+    let (rest, time) = key.rsplit_once(' ')?;
+    let (route, date) = rest.rsplit_once(' ')?;
+    let departure =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").ok()?;
+    Some((route, departure))
+}
+
+/// Everything that changed between two observations of the same journey
+pub fn diff(before: &Trip, after: &Trip) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let (old_delay, new_delay) = (
+        before.departure_delay().unwrap_or(0),
+        after.departure_delay().unwrap_or(0),
+    );
+    if old_delay != new_delay {
+        changes.push(Change::Delay {
+            from: old_delay,
+            to: new_delay,
+        });
+    }
+
+    if before.track != after.track {
+        changes.push(Change::Track {
+            from: before.track.clone(),
+            to: after.track.clone(),
+        });
+    }
+
+    if before.status != after.status {
+        if after.status == "CANCELLED" {
+            changes.push(Change::Cancelled);
+        } else {
+            changes.push(Change::Status {
+                from: before.status.clone(),
+                to: after.status.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Snapshot of a trip for monitor sinks
+pub fn journey_status(trip: &Trip) -> JourneyStatus {
+    JourneyStatus {
+        journey: journey_key(trip),
+        departure: trip.departure_time,
+        track: trip.track.clone(),
+        status: trip.status.clone(),
+        delay: trip.departure_delay().unwrap_or(0),
+    }
+}
+
+/// Tracks the last observation of every watched journey and turns each new
+/// poll into change events
+#[derive(Default)]
+pub struct Watcher {
+    last_seen: HashMap<String, Trip>,
+}
+
+impl Watcher {
+    /// Record a poll; journeys seen for the first time produce no events
+    pub fn observe(&mut self, trips: &[Trip], now: DateTime<FixedOffset>) -> Vec<ChangeEvent> {
+        let mut events = Vec::new();
+
+        for trip in trips {
+            let key = journey_key(trip);
+            if let Some(previous) = self.last_seen.get(&key) {
+                events.extend(diff(previous, trip).into_iter().map(|change| ChangeEvent {
+                    observed_at: now,
+                    journey: key.clone(),
+                    change,
+                }));
+            }
+            self.last_seen.insert(key, trip.clone());
+        }
+
+        events
+    }
+}
+
+/// Tracks one specific journey, identified by its planned departure, across polls
+pub struct Follower {
+    departure: DateTime<FixedOffset>,
+    last_seen: Option<Trip>,
+}
+
+impl Follower {
+    pub fn new(departure: DateTime<FixedOffset>) -> Self {
+        Self {
+            departure,
+            last_seen: None,
+        }
+    }
+
+    /// The journey as seen in the latest poll that contained it
+    pub fn current(&self) -> Option<&Trip> {
+        self.last_seen.as_ref()
+    }
+
+    /// Find the journey in a poll and return what changed since it was last
+    /// seen; `None` when the poll does not contain it, no changes the first time
+    pub fn observe(&mut self, trips: &[Trip]) -> Option<Vec<Change>> {
+        let trip = trips
+            .iter()
+            .find(|trip| same_minute(trip.departure_time, self.departure))?;
+        let changes = match &self.last_seen {
+            Some(previous) => diff(previous, trip),
+            None => Vec::new(),
+        };
+        self.last_seen = Some(trip.clone());
+        Some(changes)
+    }
+}
+
+fn same_minute(a: DateTime<FixedOffset>, b: DateTime<FixedOffset>) -> bool {
+    a.timestamp() / 60 == b.timestamp() / 60
+}
+
+/// Poll the trips between two stations until `stop` is set: log every change
+/// and hand each refresh to all sinks. A failing sink never stops the others.
+pub fn run_watch(
+    from: &Station,
+    to: &Station,
+    interval: Duration,
+    log: &EventLog,
+    sinks: &[Box<dyn Sink>],
+    stop: &AtomicBool,
+) {
+    let route = format!("{} → {}", from.names.long, to.names.long);
+    let mut watcher = Watcher::default();
+
+    while !stop.load(Ordering::Relaxed) {
+        match trips(from, to, &TripQuery::default()) {
+            Ok(trips) => {
+                let now = Local::now().fixed_offset();
+                let refresh = Refresh {
+                    refreshed_at: now,
+                    route: route.clone(),
+                    journeys: trips.iter().map(journey_status).collect(),
+                    changes: watcher.observe(&trips, now),
+                };
+                if let Err(e) = log.append(&refresh.changes) {
+                    eprintln!("⚠️  Failed to write monitor log: {}", e);
+                }
+
+                for sink in sinks {
+                    if let Err(e) = sink.publish(&refresh) {
+                        eprintln!("⚠️  Sink {} failed: {}", sink.describe(), e);
+                    }
+                }
+            }
+            Err(e) => eprintln!("⚠️  Failed to refresh trips for {}: {}", route, e),
+        }
+
+        // Sleep in small steps so a stop request is picked up quickly
+        let mut waited = Duration::ZERO;
+        while waited < interval && !stop.load(Ordering::Relaxed) {
+            let step = Duration::from_secs(1).min(interval - waited);
+            std::thread::sleep(step);
+            waited += step;
+        }
+    }
+}
+
+/// Append-only JSON lines file with every change seen by `monitor watch`
+pub struct EventLog {
+    path: PathBuf,
+}
+
+impl EventLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// monitor.jsonl in the user's cache directory, shared by `monitor watch`,
+    /// `monitor history` and `follow`; None without a cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        Some(user_cache_dir()?.join("monitor.jsonl"))
+    }
+
+    /// The log at `default_path()`
+    pub fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
+        match Self::default_path() {
+            Some(path) => Ok(Self::new(path)),
+            None => {
+                Err("No cache directory for the monitor log, set XDG_CACHE_HOME or pass --log".into())
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, events: &[ChangeEvent]) -> Result<(), Box<dyn std::error::Error>> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        for event in events {
+            writeln!(file, "{}", serde_json::to_string(event)?)?;
+        }
+        Ok(())
+    }
+
+    /// All logged events in the order they were observed; unreadable lines
+    /// are skipped, and so is a log owned by another user
+    pub fn load(&self) -> Result<Vec<ChangeEvent>, Box<dyn std::error::Error>> {
+        let Some(content) = read_owned_file(&self.path) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// Fewest logged journeys before a typical delay is reported
+const MIN_SAMPLES: usize = 3;
+
+/// Delay a route usually has in the same kind of day and departure hour
+#[derive(Debug, PartialEq)]
+pub struct TypicalDelay {
+    /// Median final delay in minutes over the matching logged journeys
+    pub minutes: i64,
+    pub samples: usize,
+    pub weekday: bool,
+    pub hour: u32,
+}
+
+/// In the current language
+impl fmt::Display for TypicalDelay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = if self.weekday {
+            Text::TypicalDelayWeekdays
+        } else {
+            Text::TypicalDelayWeekends
+        };
+        let args = [
+            self.minutes.to_string(),
+            format!("{:02}", self.hour),
+            self.samples.to_string(),
+        ];
+        f.write_str(&trf(text, &[&args[0], &args[1], &args[2]]))
+    }
+}
+
+fn is_weekday(day: Weekday) -> bool {
+    !matches!(day, Weekday::Sat | Weekday::Sun)
+}
+
+/// Predict the delay of a trip from the monitor change log: the median of the
+/// last known delay of every logged journey on the same route, departing in the
+/// same hour on the same kind of day (weekday/weekend). Only journeys that were
+/// ever delayed are logged, so this describes how late the train is when it is late.
+pub fn typical_delay(events: &[ChangeEvent], trip: &Trip) -> Option<TypicalDelay> {
+    let route = format!("{} → {}", trip.origin_name, trip.destination_name);
+    let weekday = is_weekday(trip.departure_time.weekday());
+    let hour = trip.departure_time.hour();
+
+    // Final delay per journey, events are logged in observation order
+    let mut final_delays: HashMap<&str, i64> = HashMap::new();
+    for event in events {
+        let Change::Delay { to, .. } = event.change else {
+            continue;
+        };
+        let Some((event_route, departure)) = split_journey_key(&event.journey) else {
+            continue;
+        };
+        if event_route == route
+            && is_weekday(departure.weekday()) == weekday
+            && departure.hour() == hour
+        {
+            final_delays.insert(&event.journey, to);
+        }
+    }
+
+    let mut delays: Vec<i64> = final_delays.into_values().collect();
+    if delays.len() < MIN_SAMPLES {
+        return None;
+    }
+    delays.sort_unstable();
+    let minutes = delays[delays.len() / 2];
+
+    (minutes > 0).then_some(TypicalDelay {
+        minutes,
+        samples: delays.len(),
+        weekday,
+        hour,
+    })
+}
+
+/// Delay in minutes from which a change of a followed journey is escalated
+const ESCALATE_DELAY_MINUTES: i64 = 10;
+
+/// Logged changes of one journey from which every further change is escalated
+const ESCALATE_CHANGES: usize = 3;
+
+/// How urgently `follow` announces a change of `journey`, given the changes
+/// logged before it: critical for a cancellation, a delay of
+/// `ESCALATE_DELAY_MINUTES` or more, or once the journey already changed
+/// `ESCALATE_CHANGES` times (0→3, 3→7 and a track change), normal otherwise
+pub fn escalation(history: &[ChangeEvent], journey: &str, change: &Change) -> Urgency {
+    let changes = history.iter().filter(|event| event.journey == journey).count();
+    let critical = match change {
+        Change::Cancelled => true,
+        Change::Delay { to, .. } => *to >= ESCALATE_DELAY_MINUTES,
+        _ => false,
+    };
+    if critical || changes >= ESCALATE_CHANGES {
+        Urgency::Critical
+    } else {
+        Urgency::Normal
+    }
+}
+
+/// Group events per journey, keeping each journey's events chronological
+pub fn history_by_journey(events: &[ChangeEvent]) -> Vec<(&str, Vec<&ChangeEvent>)> {
+    let mut grouped: Vec<(&str, Vec<&ChangeEvent>)> = Vec::new();
+
+    for event in events {
+        match grouped.iter_mut().find(|(journey, _)| *journey == event.journey) {
+            Some((_, list)) => list.push(event),
+            None => grouped.push((&event.journey, vec![event])),
+        }
+    }
+    for (_, list) in &mut grouped {
+        list.sort_by_key(|event| event.observed_at);
+    }
+
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trips::service::test_trip;
+
+    #[test]
+    fn test_watcher_records_changes() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T08:00:00+02:00").unwrap();
+        let mut watcher = Watcher::default();
+        let mut trip = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:15:00+02:00");
+
+        assert!(watcher.observe(std::slice::from_ref(&trip), now).is_empty());
+
+        trip.actual_departure_time =
+            Some(DateTime::parse_from_rfc3339("2024-06-01T08:33:00+02:00").unwrap());
+        trip.track = "7b".to_string();
+        let events = watcher.observe(std::slice::from_ref(&trip), now);
+        let changes: Vec<String> = events.iter().map(|e| e.change.to_string()).collect();
+        assert_eq!(changes, vec!["delay +0 → +3", "platform 5 → 7b"]);
+
+        trip.status = "CANCELLED".to_string();
+        let events = watcher.observe(std::slice::from_ref(&trip), now);
+        assert_eq!(events[0].change, Change::Cancelled);
+        assert_eq!(events[0].journey, "Den Haag C → Utrecht Centraal 2024-06-01 08:30");
+    }
+
+    #[test]
+    fn test_follower_matches_planned_departure() {
+        let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let earlier = test_trip("2024-06-01T08:12:00+02:00", "2024-06-01T09:00:00+02:00");
+        let mut followed = test_trip("2024-06-01T08:42:00+02:00", "2024-06-01T09:30:00+02:00");
+        let mut follower = Follower::new(time("2024-06-01T08:42:00+02:00"));
+
+        assert_eq!(follower.observe(std::slice::from_ref(&earlier)), None);
+        assert_eq!(
+            follower.observe(&[earlier.clone(), followed.clone()]),
+            Some(vec![])
+        );
+
+        followed.actual_departure_time = Some(time("2024-06-01T08:47:00+02:00"));
+        let changes = follower.observe(&[earlier, followed]).unwrap();
+        assert_eq!(changes, vec![Change::Delay { from: 0, to: 5 }]);
+        assert_eq!(follower.current().unwrap().departure_delay(), Some(5));
+    }
+
+    #[test]
+    fn test_escalation() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T08:00:00+02:00").unwrap();
+        let journey = "Den Haag C → Utrecht Centraal 2024-06-01 08:30";
+        let event = |journey: &str, change: Change| ChangeEvent {
+            observed_at: now,
+            journey: journey.to_string(),
+            change,
+        };
+        let small = Change::Delay { from: 0, to: 3 };
+        let mut history = vec![event(journey, small.clone())];
+
+        assert_eq!(escalation(&history, journey, &small), Urgency::Normal);
+        assert_eq!(
+            escalation(&history, journey, &Change::Delay { from: 3, to: 12 }),
+            Urgency::Critical
+        );
+        assert_eq!(escalation(&history, journey, &Change::Cancelled), Urgency::Critical);
+
+        // Other journeys don't count towards the change history
+        history.push(event("Den Haag C → Utrecht Centraal 2024-06-01 08:00", small.clone()));
+        history.push(event(journey, Change::Delay { from: 3, to: 7 }));
+        assert_eq!(escalation(&history, journey, &small), Urgency::Normal);
+        let track = Change::Track { from: "5".to_string(), to: "7b".to_string() };
+        history.push(event(journey, track.clone()));
+        assert_eq!(escalation(&history, journey, &track), Urgency::Critical);
+    }
+
+    #[test]
+    fn test_typical_delay() {
+        let now = DateTime::parse_from_rfc3339("2024-06-10T08:00:00+02:00").unwrap();
+        let delay = |day: &str, to: i64| ChangeEvent {
+            observed_at: now,
+            journey: format!("Den Haag C → Utrecht Centraal 2024-06-{} 08:30", day),
+            change: Change::Delay { from: 0, to },
+        };
+        // Mon 3 → 5, Tue 4, Wed 2, and a Saturday that must be ignored
+        let events = [
+            delay("03", 3),
+            delay("03", 5),
+            delay("04", 4),
+            delay("05", 2),
+            delay("08", 12),
+        ];
+
+        // Monday 08:12, same route and hour
+        let trip = test_trip("2024-06-10T08:12:00+02:00", "2024-06-10T09:00:00+02:00");
+        let typical = typical_delay(&events, &trip).unwrap();
+        assert_eq!(
+            typical.to_string(),
+            "usually +4 on weekdays around 08:00 (3 logged delays)"
+        );
+
+        assert!(typical_delay(&events[..3], &trip).is_none());
+    }
+}
@@ -0,0 +1,41 @@
+use std::io::IsTerminal;
+
+/// When to emit ANSI colors
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ColorChoice {
+    /// Color only when stdout is a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Configure the `colored` crate globally for every formatter
+pub fn apply_color_choice(choice: ColorChoice) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = should_color(choice, no_color, std::io::stdout().is_terminal());
+    colored::control::set_override(enabled);
+}
+
+fn should_color(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color && is_terminal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_color() {
+        assert!(should_color(ColorChoice::Auto, false, true));
+        assert!(!should_color(ColorChoice::Auto, true, true));
+        assert!(!should_color(ColorChoice::Auto, false, false));
+        assert!(should_color(ColorChoice::Always, true, false));
+        assert!(!should_color(ColorChoice::Never, false, true));
+    }
+}
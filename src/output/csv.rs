@@ -0,0 +1,61 @@
+/// Implemented by structs that can be written as a CSV row
+pub trait CsvRow {
+    /// Column names, in the same order as `row()`
+    fn headers() -> &'static [&'static str];
+    fn row(&self) -> Vec<String>;
+}
+
+/// Render items as CSV with a header row
+pub fn to_csv<T: CsvRow>(items: &[T]) -> String {
+    let mut out = write_line(T::headers().iter().map(|h| h.to_string()));
+    for item in items {
+        out.push_str(&write_line(item.row().into_iter()));
+    }
+    out
+}
+
+fn write_line(fields: impl Iterator<Item = String>) -> String {
+    let mut line = fields.map(|f| escape(&f)).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+/// Quote a field if it contains a separator, quote or newline (RFC 4180)
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(&'static str, &'static str);
+
+    impl CsvRow for Row {
+        fn headers() -> &'static [&'static str] {
+            &["name", "note"]
+        }
+
+        fn row(&self) -> Vec<String> {
+            vec![self.0.to_string(), self.1.to_string()]
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(&[Row("Den Haag C", "plain"), Row("Frankfurt (Oder)", "a, \"b\"")]);
+        assert_eq!(
+            csv,
+            "name,note\nDen Haag C,plain\nFrankfurt (Oder),\"a, \"\"b\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_to_csv_empty_has_header() {
+        assert_eq!(to_csv::<Row>(&[]), "name,note\n");
+    }
+}
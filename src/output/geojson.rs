@@ -0,0 +1,179 @@
+use crate::stations::models::StationInfo;
+use crate::trips::{Coordinates, Trip};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A `bbox=minLng,minLat,maxLng,maxLat` filter in degrees, the GeoJSON order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lng: f64,
+    pub min_lat: f64,
+    pub max_lng: f64,
+    pub max_lat: f64,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        (self.min_lat..=self.max_lat).contains(&lat) && (self.min_lng..=self.max_lng).contains(&lng)
+    }
+}
+
+impl FromStr for BoundingBox {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid bbox '{}', expected minLng,minLat,maxLng,maxLat", s);
+        let corners: Vec<f64> = s
+            .split(',')
+            .map(|value| value.trim().parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        let [min_lng, min_lat, max_lng, max_lat] = corners[..] else {
+            return Err(invalid());
+        };
+        if min_lng > max_lng || min_lat > max_lat {
+            return Err(invalid());
+        }
+        Ok(Self {
+            min_lng,
+            min_lat,
+            max_lng,
+            max_lat,
+        })
+    }
+}
+
+/// Build a GeoJSON FeatureCollection with a LineString per journey leg and
+/// a Point per station, ready to drop onto a Leaflet/QGIS map
+pub fn trips_to_geojson(trips: &[Trip]) -> Value {
+    let mut features = Vec::new();
+    let mut stations: Vec<(&str, Coordinates)> = Vec::new();
+
+    for (index, trip) in trips.iter().enumerate() {
+        for leg in &trip.legs {
+            let (Some(origin), Some(destination)) = (leg.origin_location, leg.destination_location)
+            else {
+                continue;
+            };
+
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": [position(origin), position(destination)],
+                },
+                "properties": {
+                    "trip": index,
+                    "origin": leg.origin_name,
+                    "destination": leg.destination_name,
+                    "departure": leg.departure_time.to_rfc3339(),
+                    "arrival": leg.arrival_time.to_rfc3339(),
+                    "type": leg.train_type,
+                    "status": trip.status,
+                },
+            }));
+
+            for station in [(leg.origin_name.as_str(), origin), (leg.destination_name.as_str(), destination)] {
+                if !stations.iter().any(|(name, _)| *name == station.0) {
+                    stations.push(station);
+                }
+            }
+        }
+    }
+
+    for (name, location) in stations {
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": position(location),
+            },
+            "properties": { "name": name },
+        }));
+    }
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Build a GeoJSON FeatureCollection with a Point per station that has
+/// coordinates, inside `bbox` when one is given: a map's station layer.
+/// `facilities` counts the facilities of a station by kind, when known.
+pub fn stations_to_geojson(
+    stations: &[StationInfo],
+    bbox: Option<BoundingBox>,
+    facilities: impl Fn(i32) -> Option<BTreeMap<String, usize>>,
+) -> Value {
+    let features: Vec<Value> = stations
+        .iter()
+        .filter_map(|station| Some((station, station.coordinates?)))
+        .filter(|(_, (lat, lng))| bbox.is_none_or(|bbox| bbox.contains(*lat, *lng)))
+        .map(|(station, (lat, lng))| {
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": position(Coordinates { lat, lng }),
+                },
+                "properties": {
+                    "name": station.name,
+                    "code": station.code,
+                    "uic_code": station.uic_code,
+                    "country": station.country,
+                    "type": station.station_type,
+                    "facilities": facilities(station.uic_code),
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// GeoJSON positions are [longitude, latitude]
+fn position(location: Coordinates) -> Value {
+    json!([location.lng, location.lat])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stations_to_geojson() {
+        let station = |name, uic_code, coordinates| StationInfo {
+            name,
+            uic_code,
+            code: None,
+            country: "NL",
+            coordinates,
+            station_type: None,
+        };
+        let stations = [
+            station("Utrecht Centraal", 8400621, Some((52.0894, 5.11))),
+            station("Maastricht", 8400424, Some((50.8497, 5.7056))),
+            station("Unplaced", 8400999, None),
+        ];
+        let toilets = |uic| (uic == 8400621).then(|| BTreeMap::from([("Toilet".to_string(), 2)]));
+
+        let all = stations_to_geojson(&stations, None, toilets);
+        assert_eq!(all["features"].as_array().map(Vec::len), Some(2));
+        assert_eq!(all["features"][0]["geometry"]["coordinates"], json!([5.11, 52.0894]));
+        assert_eq!(all["features"][0]["properties"]["facilities"]["Toilet"], 2);
+        assert!(all["features"][1]["properties"]["facilities"].is_null());
+
+        let randstad: BoundingBox = "4.0,51.7,5.5,52.6".parse().unwrap();
+        let filtered = stations_to_geojson(&stations, Some(randstad), toilets);
+        assert_eq!(filtered["features"].as_array().map(Vec::len), Some(1));
+        assert_eq!(filtered["features"][0]["properties"]["name"], "Utrecht Centraal");
+
+        for invalid in ["4.0,51.7,5.5", "4.0,51.7,5.5,north", "5.5,51.7,4.0,52.6"] {
+            assert!(invalid.parse::<BoundingBox>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::departures::Departure;
+use chrono::{DateTime, FixedOffset};
+
+const STYLE: &str = "body{margin:0;background:#003082;color:#fff;font-family:'Frutiger',Arial,sans-serif}\
+h1{margin:0;padding:16px 24px;background:#ffc917;color:#003082;font-size:28px}\
+table{width:100%;border-collapse:collapse;font-size:22px}\
+th{text-align:left;padding:8px 24px;color:#ffc917;font-weight:normal}\
+td{padding:10px 24px;border-top:1px solid #1a4a9a;vertical-align:top}\
+.delay{color:#ff6b6b;margin-left:6px}\
+.via{display:block;font-size:15px;color:#b8c7e6}\
+.message{display:block;font-size:15px;color:#ffc917}\
+.cancelled td{text-decoration:line-through;color:#b8c7e6}\
+footer{padding:12px 24px;font-size:14px;color:#b8c7e6}";
+
+/// Render a standalone HTML departure board, optionally reloading itself
+/// every `refresh_secs` seconds
+pub fn departures_to_html(
+    station: &str,
+    departures: &[Departure],
+    refresh_secs: Option<u64>,
+    generated_at: DateTime<FixedOffset>,
+) -> String {
+    let mut html =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    if let Some(secs) = refresh_secs {
+        html.push_str(&format!(
+            "<meta http-equiv=\"refresh\" content=\"{}\">\n",
+            secs
+        ));
+    }
+    html.push_str(&format!(
+        "<title>Departures {}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(station),
+        STYLE,
+        escape(station)
+    ));
+
+    html.push_str(
+        "<table>\n<tr><th>Time</th><th>Destination</th><th>Track</th><th>Train</th></tr>\n",
+    );
+    for departure in departures {
+        let delay = match departure.delay_minutes() {
+            delay if delay > 0 => format!("<span class=\"delay\">+{}</span>", delay),
+            _ => String::new(),
+        };
+        let via = if departure.via.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<span class=\"via\">via {}</span>",
+                escape(&departure.via.join(", "))
+            )
+        };
+        let messages: String = departure
+            .messages
+            .iter()
+            .map(|message| format!("<span class=\"message\">{}</span>", escape(message)))
+            .collect();
+
+        html.push_str(&format!(
+            "<tr{}><td>{}{}</td><td>{}{}{}</td><td>{}</td><td>{}</td></tr>\n",
+            if departure.cancelled {
+                " class=\"cancelled\""
+            } else {
+                ""
+            },
+            departure.planned.format("%H:%M"),
+            delay,
+            escape(&departure.direction),
+            via,
+            messages,
+            escape(departure.track.as_deref().unwrap_or("-")),
+            escape(&departure.train_type)
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(&format!(
+        "<footer>Updated {} · nstimes</footer>\n</body>\n</html>\n",
+        generated_at.format("%Y-%m-%d %H:%M")
+    ));
+    html
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_departure_board() {
+        let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let departure = Departure {
+            direction: "Amersfoort <Schothorst>".to_string(),
+            train_type: "IC".to_string(),
+            planned: time("2024-06-01T08:12:00+02:00"),
+            actual: Some(time("2024-06-01T08:15:00+02:00")),
+            track: Some("5b".to_string()),
+            cancelled: true,
+            via: vec!["Utrecht C.".to_string()],
+            messages: vec!["Let op: extra druk".to_string()],
+        };
+
+        let html = departures_to_html(
+            "Den Haag C",
+            &[departure],
+            Some(30),
+            time("2024-06-01T08:00:00+02:00"),
+        );
+        assert!(html.contains("<meta http-equiv=\"refresh\" content=\"30\">"));
+        assert!(html.contains("<tr class=\"cancelled\"><td>08:12<span class=\"delay\">+3</span>"));
+        assert!(html.contains("Amersfoort &lt;Schothorst&gt;"));
+        assert!(html.contains("via Utrecht C."));
+
+        let html = departures_to_html("Den Haag C", &[], None, time("2024-06-01T08:00:00+02:00"));
+        assert!(!html.contains("refresh"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::trips::Trip;
+use chrono::{DateTime, FixedOffset, Utc};
+
+/// Render trips as an iCalendar (RFC 5545) document with one VEVENT per trip
+pub fn trips_to_ical(trips: &[Trip]) -> String {
+    let stamp = format_utc(Utc::now().fixed_offset());
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//nstimes//NS trips//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for trip in trips {
+        let summary = format!(
+            "{} {} → {} (tr. {})",
+            trip.train_type, trip.origin_name, trip.destination_name, trip.track
+        );
+        let mut description = format!(
+            "Departure {} from track {}\nArrival {} at {}",
+            trip.departure_time.format("%H:%M"),
+            trip.track,
+            trip.arrival_time.format("%H:%M"),
+            trip.destination_name
+        );
+        for transfer in trip.transfer_points() {
+            description.push_str(&format!(
+                "\nChange at {} ({} min, track {})",
+                transfer.station, transfer.minutes, transfer.departure_track
+            ));
+        }
+        for warning in trip.warnings() {
+            description.push_str(&format!("\nNote: {}", warning));
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-{}-{}@nstimes",
+            trip.departure_time.timestamp(),
+            slug(&trip.origin_name),
+            slug(&trip.destination_name)
+        ));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", format_utc(trip.departure_time)));
+        lines.push(format!("DTEND:{}", format_utc(trip.arrival_time)));
+        lines.push(format!("SUMMARY:{}", escape(&summary)));
+        lines.push(format!(
+            "LOCATION:{}",
+            escape(&format!("{}, track {}", trip.origin_name, trip.track))
+        ));
+        lines.push(format!("DESCRIPTION:{}", escape(&description)));
+        if trip.status == "CANCELLED" {
+            lines.push("STATUS:CANCELLED".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn format_utc(time: DateTime<FixedOffset>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn slug(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Escape TEXT values: backslash, semicolon, comma and newlines
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets and terminate it with CRLF
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("Den Haag C, tr. 5; IC\nnext"),
+            "Den Haag C\\, tr. 5\\; IC\\nnext"
+        );
+    }
+
+    #[test]
+    fn test_fold_long_lines() {
+        let folded = fold(&"a".repeat(100));
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "a".repeat(25)));
+    }
+}
@@ -0,0 +1,21 @@
+pub mod color;
+pub mod csv;
+pub mod geojson;
+pub mod html;
+pub mod ical;
+pub mod models;
+pub mod relative;
+pub mod table;
+pub mod template;
+pub mod waybar;
+
+pub use color::{apply_color_choice, ColorChoice};
+pub use csv::{to_csv, CsvRow};
+pub use geojson::{stations_to_geojson, trips_to_geojson, BoundingBox};
+pub use html::departures_to_html;
+pub use ical::trips_to_ical;
+pub use models::{OutputFormat, OutputOptions};
+pub use relative::{countdown, TimeDisplay};
+pub use table::{terminal_width, to_table, TableRow, TableStyle};
+pub use template::{Template, TemplateFields, Value};
+pub use waybar::{departures_to_waybar, prices_to_waybar, trips_to_waybar};
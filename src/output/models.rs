@@ -0,0 +1,47 @@
+use crate::output::{TableStyle, Template, TimeDisplay};
+
+/// How command results are written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Human-readable terminal output
+    #[default]
+    Text,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Waybar/i3blocks JSON status module for the next departure
+    Waybar,
+    /// Column-aligned table sized to the terminal width
+    Table,
+    /// JSON array with one object per trip or price
+    Json,
+}
+
+/// Output settings shared by all commands
+#[derive(Default)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    /// Custom per-item template, overrides `format` when set
+    pub template: Option<Template>,
+    /// Spell out symbols and colors as words for screen readers
+    pub accessible: bool,
+    /// Clock times, countdowns or both for departures
+    pub times: TimeDisplay,
+    /// Draw tables with unicode box borders
+    pub boxed: bool,
+}
+
+impl OutputOptions {
+    /// Whether decorative lines (headers, blank lines) should be printed
+    pub fn is_human(&self) -> bool {
+        self.template.is_none() && matches!(self.format, OutputFormat::Text | OutputFormat::Table)
+    }
+
+    pub fn table_style(&self) -> TableStyle {
+        if self.boxed {
+            TableStyle::Boxed
+        } else {
+            TableStyle::Plain
+        }
+    }
+}
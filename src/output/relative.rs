@@ -0,0 +1,46 @@
+use chrono::{DateTime, FixedOffset};
+
+/// How departure times are shown in human output
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TimeDisplay {
+    /// Clock times like "08:30"
+    #[default]
+    Absolute,
+    /// Countdowns like "in 7 min"
+    Relative,
+    /// Clock time followed by the countdown, e.g. "08:30 (in 7 min)"
+    Both,
+}
+
+/// Countdown from `now` to `target`, e.g. "in 7 min", "in 1 h 5 min", "now"
+pub fn countdown(target: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> String {
+    let minutes = target.signed_duration_since(now).num_minutes();
+
+    match minutes {
+        0 => "now".to_string(),
+        m if m < 0 => format!("{} min ago", -m),
+        m if m < 60 => format!("in {} min", m),
+        m if m % 60 == 0 => format!("in {} h", m / 60),
+        m => format!("in {} h {} min", m / 60, m % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T08:00:00+02:00").unwrap();
+        let at = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+
+        assert_eq!(countdown(at("2024-06-01T08:00:30+02:00"), now), "now");
+        assert_eq!(countdown(at("2024-06-01T08:07:00+02:00"), now), "in 7 min");
+        assert_eq!(countdown(at("2024-06-01T09:05:00+02:00"), now), "in 1 h 5 min");
+        assert_eq!(countdown(at("2024-06-01T10:00:00+02:00"), now), "in 2 h");
+        assert_eq!(countdown(at("2024-06-01T07:58:00+02:00"), now), "2 min ago");
+        // Timezone offsets are taken into account
+        assert_eq!(countdown(at("2024-06-01T06:10:00+00:00"), now), "in 10 min");
+    }
+}
@@ -0,0 +1,147 @@
+/// Implemented by structs that can be rendered as a table row
+pub trait TableRow {
+    /// Column headers, in the same order as `cells()`
+    fn headers() -> &'static [&'static str];
+    fn cells(&self) -> Vec<String>;
+}
+
+/// Border style for tables
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableStyle {
+    /// Space-separated columns with a dashed header underline
+    Plain,
+    /// Unicode box drawing borders
+    Boxed,
+}
+
+/// Render items as a column-aligned table that fits within `max_width`
+/// characters, truncating the widest columns with "…" when needed
+pub fn to_table<T: TableRow>(items: &[T], style: TableStyle, max_width: usize) -> String {
+    let headers: Vec<String> = T::headers().iter().map(|h| h.to_string()).collect();
+    let rows: Vec<Vec<String>> = items.iter().map(|item| item.cells()).collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| width(h)).collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(width(cell));
+        }
+    }
+    shrink_to_fit(&mut widths, style, max_width);
+
+    let mut out = String::new();
+    match style {
+        TableStyle::Plain => {
+            out.push_str(&plain_line(&headers, &widths));
+            let underline: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            out.push_str(&plain_line(&underline, &widths));
+            for row in &rows {
+                out.push_str(&plain_line(row, &widths));
+            }
+        }
+        TableStyle::Boxed => {
+            out.push_str(&border(&widths, '┌', '┬', '┐'));
+            out.push_str(&boxed_line(&headers, &widths));
+            out.push_str(&border(&widths, '├', '┼', '┤'));
+            for row in &rows {
+                out.push_str(&boxed_line(row, &widths));
+            }
+            out.push_str(&border(&widths, '└', '┴', '┘'));
+        }
+    }
+    out
+}
+
+/// Terminal width from `COLUMNS`, defaulting to 100
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(100)
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn shrink_to_fit(widths: &mut [usize], style: TableStyle, max_width: usize) {
+    // Plain: two spaces between columns. Boxed: "│ " + " │ " separators + " │"
+    let overhead = match style {
+        TableStyle::Plain => 2 * widths.len().saturating_sub(1),
+        TableStyle::Boxed => 3 * widths.len() + 1,
+    };
+
+    while widths.iter().sum::<usize>() + overhead > max_width {
+        let Some(widest) = widths.iter_mut().max() else {
+            return;
+        };
+        if *widest <= 3 {
+            return;
+        }
+        *widest -= 1;
+    }
+}
+
+fn fit(cell: &str, width: usize) -> String {
+    if self::width(cell) <= width {
+        return format!("{:<width$}", cell, width = width);
+    }
+    let truncated: String = cell.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", truncated)
+}
+
+fn plain_line(cells: &[String], widths: &[usize]) -> String {
+    let line: Vec<String> = cells.iter().zip(widths).map(|(c, w)| fit(c, *w)).collect();
+    format!("{}\n", line.join("  ").trim_end())
+}
+
+fn boxed_line(cells: &[String], widths: &[usize]) -> String {
+    let line: Vec<String> = cells.iter().zip(widths).map(|(c, w)| fit(c, *w)).collect();
+    format!("│ {} │\n", line.join(" │ "))
+}
+
+fn border(widths: &[usize], left: char, middle: char, right: char) -> String {
+    let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+    format!("{}{}{}\n", left, segments.join(&middle.to_string()), right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(&'static str, &'static str);
+
+    impl TableRow for Row {
+        fn headers() -> &'static [&'static str] {
+            &["Dep", "Station"]
+        }
+
+        fn cells(&self) -> Vec<String> {
+            vec![self.0.to_string(), self.1.to_string()]
+        }
+    }
+
+    #[test]
+    fn test_plain_table_aligns_columns() {
+        let table = to_table(&[Row("08:30", "Den Haag C"), Row("9:01", "Gouda")], TableStyle::Plain, 80);
+        assert_eq!(
+            table,
+            "Dep    Station\n-----  ----------\n08:30  Den Haag C\n9:01   Gouda\n"
+        );
+    }
+
+    #[test]
+    fn test_boxed_table() {
+        let table = to_table(&[Row("08:30", "Gouda")], TableStyle::Boxed, 80);
+        assert_eq!(
+            table,
+            "┌───────┬─────────┐\n│ Dep   │ Station │\n├───────┼─────────┤\n│ 08:30 │ Gouda   │\n└───────┴─────────┘\n"
+        );
+    }
+
+    #[test]
+    fn test_truncates_to_width() {
+        let table = to_table(&[Row("08:30", "Amsterdam Centraal")], TableStyle::Plain, 20);
+        assert!(table.lines().all(|line| line.chars().count() <= 20));
+        assert!(table.contains("Amsterdam Ce…"));
+    }
+}
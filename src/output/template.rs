@@ -0,0 +1,201 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset};
+use std::fmt::{self, Write};
+
+/// A value a template placeholder can resolve to
+pub enum Value {
+    Text(String),
+    Time(DateTime<FixedOffset>),
+}
+
+/// Implemented by structs that can be rendered through a `Template`
+pub trait TemplateFields {
+    /// Resolve a placeholder name to a value, or None if the field is unknown
+    fn field(&self, name: &str) -> Option<Value>;
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field { name: String, spec: Option<String> },
+}
+
+/// A parsed output template such as `{departure:%H:%M} tr.{track} {type}`
+///
+/// Placeholders are `{name}` or `{name:spec}`, where `spec` is a chrono
+/// strftime format for time fields. Use `{{` and `}}` for literal braces.
+#[derive(Debug)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    UnclosedPlaceholder,
+    UnmatchedBrace,
+    EmptyPlaceholder,
+    UnknownField(String),
+    /// A `{name:spec}` whose strftime spec chrono can't format
+    InvalidTimeFormat(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnclosedPlaceholder => write!(f, "unclosed `{{` in format string"),
+            TemplateError::UnmatchedBrace => write!(f, "unmatched `}}` in format string"),
+            TemplateError::EmptyPlaceholder => write!(f, "empty `{{}}` placeholder in format string"),
+            TemplateError::UnknownField(name) => write!(f, "unknown format field `{}`", name),
+            TemplateError::InvalidTimeFormat(spec) => {
+                write!(f, "invalid time format `{}` in format string", spec)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace),
+                '{' => {
+                    let mut inner = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inner.push(c),
+                            None => return Err(TemplateError::UnclosedPlaceholder),
+                        }
+                    }
+
+                    let (name, spec) = match inner.split_once(':') {
+                        Some((name, spec)) => (name.trim(), Some(spec.to_string())),
+                        None => (inner.trim(), None),
+                    };
+                    if name.is_empty() {
+                        return Err(TemplateError::EmptyPlaceholder);
+                    }
+                    if let Some(spec) = &spec
+                        && StrftimeItems::new(spec).any(|item| item == Item::Error)
+                    {
+                        return Err(TemplateError::InvalidTimeFormat(spec.clone()));
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field {
+                        name: name.to_string(),
+                        spec,
+                    });
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Render the template against a value, failing on unknown fields
+    pub fn render(&self, item: &impl TemplateFields) -> Result<String, TemplateError> {
+        let mut out = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field { name, spec } => {
+                    let value = item
+                        .field(name)
+                        .ok_or_else(|| TemplateError::UnknownField(name.clone()))?;
+                    match value {
+                        Value::Text(text) => out.push_str(&text),
+                        Value::Time(time) => {
+                            let spec = spec.as_deref().unwrap_or("%H:%M");
+                            // Formatting fails instead of panicking on a spec parse() let through
+                            write!(out, "{}", time.format(spec))
+                                .map_err(|_| TemplateError::InvalidTimeFormat(spec.to_string()))?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sample;
+
+    impl TemplateFields for Sample {
+        fn field(&self, name: &str) -> Option<Value> {
+            match name {
+                "track" => Some(Value::Text("5b".to_string())),
+                "departure" => Some(Value::Time(
+                    DateTime::parse_from_rfc3339("2024-06-01T08:30:00+02:00").unwrap(),
+                )),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_fields_and_specs() {
+        let template = Template::parse("{departure:%H:%M} tr.{track} {departure:%d-%m}").unwrap();
+        assert_eq!(template.render(&Sample).unwrap(), "08:30 tr.5b 01-06");
+
+        // Time fields default to HH:MM
+        let template = Template::parse("{departure}").unwrap();
+        assert_eq!(template.render(&Sample).unwrap(), "08:30");
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let template = Template::parse("{{{track}}}").unwrap();
+        assert_eq!(template.render(&Sample).unwrap(), "{5b}");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Template::parse("{track").unwrap_err(),
+            TemplateError::UnclosedPlaceholder
+        );
+        assert_eq!(Template::parse("track}").unwrap_err(), TemplateError::UnmatchedBrace);
+        assert_eq!(Template::parse("{}").unwrap_err(), TemplateError::EmptyPlaceholder);
+
+        assert_eq!(
+            Template::parse("{departure:%Q}").unwrap_err(),
+            TemplateError::InvalidTimeFormat("%Q".to_string())
+        );
+        // A spec on a text field is never used as a time format
+        assert!(Template::parse("{track:%H}").is_ok());
+
+        let template = Template::parse("{platform}").unwrap();
+        assert_eq!(
+            template.render(&Sample).unwrap_err(),
+            TemplateError::UnknownField("platform".to_string())
+        );
+    }
+}
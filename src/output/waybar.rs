@@ -0,0 +1,203 @@
+use crate::departures::Departure;
+use crate::prices::models::Price;
+use crate::trips::Trip;
+use chrono::{DateTime, FixedOffset, Local};
+use serde_json::json;
+
+/// Status-bar module output for the next departure, in the JSON shape waybar
+/// (and i3blocks with `format=json`) expects: `{"text", "tooltip", "class"}`
+pub fn trips_to_waybar(trips: &[Trip]) -> String {
+    next_departure_waybar(trips, Local::now().fixed_offset())
+}
+
+fn next_departure_waybar(trips: &[Trip], now: DateTime<FixedOffset>) -> String {
+    let next = trips
+        .iter()
+        .find(|t| t.actual_departure_time.unwrap_or(t.departure_time) >= now)
+        .or(trips.first());
+
+    let Some(next) = next else {
+        return waybar_json("no trains", "No trips found", "none");
+    };
+
+    let mut text = format!("{} {}", next.departure_time.format("%H:%M"), next.train_type);
+    if let Some(delay) = next.departure_delay() {
+        text.push_str(&format!(" +{}", delay));
+    }
+    text.push_str(&format!(" tr.{}", next.track));
+
+    let class = if next.status == "CANCELLED" {
+        "cancelled"
+    } else if next.departure_delay().is_some() {
+        "delayed"
+    } else {
+        "on-time"
+    };
+
+    let tooltip = trips
+        .iter()
+        .map(|t| {
+            let delay = t.departure_delay().map(|d| format!(" +{}", d)).unwrap_or_default();
+            let status = if t.status == "NORMAL" {
+                String::new()
+            } else {
+                format!(" ({})", t.status)
+            };
+            format!(
+                "{}{} {} → {} {} tr.{}{}",
+                t.departure_time.format("%H:%M"),
+                delay,
+                t.origin_name,
+                t.destination_name,
+                t.train_type,
+                t.track,
+                status
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    waybar_json(&text, &tooltip, class)
+}
+
+/// Status-bar module output for the next train on a station's departure board
+pub fn departures_to_waybar(departures: &[Departure]) -> String {
+    next_board_departure_waybar(departures, Local::now().fixed_offset())
+}
+
+fn next_board_departure_waybar(departures: &[Departure], now: DateTime<FixedOffset>) -> String {
+    let next = departures
+        .iter()
+        .find(|d| !d.cancelled && d.actual.unwrap_or(d.planned) >= now)
+        .or(departures.first());
+
+    let Some(next) = next else {
+        return waybar_json("no trains", "No departures found", "none");
+    };
+
+    let line = |d: &Departure| {
+        let delay = match d.delay_minutes() {
+            minutes if minutes > 0 => format!(" +{}", minutes),
+            _ => String::new(),
+        };
+        format!(
+            "{}{} {} {} tr.{}",
+            d.planned.format("%H:%M"),
+            delay,
+            d.train_type,
+            d.direction,
+            d.track.as_deref().unwrap_or("?")
+        )
+    };
+    let class = if next.cancelled {
+        "cancelled"
+    } else if next.delay_minutes() > 0 {
+        "delayed"
+    } else {
+        "on-time"
+    };
+    let tooltip = departures
+        .iter()
+        .map(|d| {
+            if d.cancelled {
+                format!("{} ({})", line(d), d.status())
+            } else {
+                line(d)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    waybar_json(&line(next), &tooltip, class)
+}
+
+/// Status-bar module output for the cheapest price on a route
+pub fn prices_to_waybar(prices: &[Price]) -> String {
+    let Some(cheapest) = prices.iter().min_by_key(|p| p.total_price_in_cents) else {
+        return waybar_json("no price", "No prices found", "none");
+    };
+
+    let tooltip = prices
+        .iter()
+        .map(|p| {
+            format!(
+                "€{:.2} {} ({})",
+                p.total_price_in_cents as f64 / 100.0,
+                p.display_name,
+                p.class_label()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    waybar_json(
+        &format!("€{:.2}", cheapest.total_price_in_cents as f64 / 100.0),
+        &tooltip,
+        "price",
+    )
+}
+
+fn waybar_json(text: &str, tooltip: &str, class: &str) -> String {
+    json!({ "text": text, "tooltip": tooltip, "class": class }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trips::service::test_trip;
+
+    fn trip(departure: &str, actual: Option<&str>) -> Trip {
+        let mut trip = test_trip(departure, departure);
+        trip.actual_departure_time = actual.map(|t| DateTime::parse_from_rfc3339(t).unwrap());
+        trip
+    }
+
+    #[test]
+    fn test_next_departure_skips_departed_trains() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T08:20:00+02:00").unwrap();
+        let trips = [
+            trip("2024-06-01T08:10:00+02:00", None),
+            trip("2024-06-01T08:25:00+02:00", Some("2024-06-01T08:29:00+02:00")),
+        ];
+
+        let output: serde_json::Value =
+            serde_json::from_str(&next_departure_waybar(&trips, now)).unwrap();
+        assert_eq!(output["text"], "08:25 IC +4 tr.5");
+        assert_eq!(output["class"], "delayed");
+    }
+
+    #[test]
+    fn test_next_board_departure_skips_cancelled_trains() {
+        let at = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let departure = |planned: &str, cancelled: bool| Departure {
+            direction: "Amersfoort Schothorst".to_string(),
+            train_type: "IC".to_string(),
+            planned: at(planned),
+            actual: None,
+            track: Some("5".to_string()),
+            cancelled,
+            via: Vec::new(),
+            messages: Vec::new(),
+        };
+        let board = [
+            departure("2024-06-01T08:25:00+02:00", true),
+            departure("2024-06-01T08:40:00+02:00", false),
+        ];
+
+        let now = at("2024-06-01T08:20:00+02:00");
+        let output: serde_json::Value =
+            serde_json::from_str(&next_board_departure_waybar(&board, now)).unwrap();
+        assert_eq!(output["text"], "08:40 IC Amersfoort Schothorst tr.5");
+        assert_eq!(output["class"], "on-time");
+        let tooltip = output["tooltip"].as_str().unwrap();
+        assert!(tooltip.starts_with("08:25 IC Amersfoort Schothorst tr.5 (CANCELLED)\n"));
+    }
+
+    #[test]
+    fn test_no_trips() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T08:20:00+02:00").unwrap();
+        let output: serde_json::Value =
+            serde_json::from_str(&next_departure_waybar(&[], now)).unwrap();
+        assert_eq!(output["class"], "none");
+    }
+}
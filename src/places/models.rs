@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct PlacesResponse {
+    #[serde(default)]
+    pub payload: Vec<PlaceTypeRaw>,
+}
+
+/// One kind of place at a station, e.g. "OV-fiets" or "Toilet"
+#[derive(Debug, Deserialize)]
+pub struct PlaceTypeRaw {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+    #[serde(default)]
+    pub locations: Vec<LocationRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocationRaw {
+    pub name: String,
+    #[serde(default)]
+    pub extra: LocationExtraRaw,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationExtraRaw {
+    /// OV-fiets bikes available right now; NS sends it as a string
+    pub rental_bikes: Option<String>,
+}
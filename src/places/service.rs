@@ -0,0 +1,89 @@
+use crate::client::{Endpoint, client};
+use crate::places::models::PlacesResponse;
+use serde::Serialize;
+
+/// A facility at a station, e.g. a toilet or a shop
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct Facility {
+    /// Kind of facility as named by NS, e.g. "Toilet"
+    pub kind: String,
+    pub name: String,
+}
+
+/// An OV-fiets rental location with its current stock
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct BikeRental {
+    pub name: String,
+    /// Bikes available now, `None` when NS doesn't report it
+    pub available: Option<u32>,
+}
+
+fn places(path: &str, station_code: &str) -> Result<PlacesResponse, Box<dyn std::error::Error>> {
+    let url = format!("https://gateway.apiportal.ns.nl/places-api/v2/{}", path);
+
+    let request = client()
+        .get(&url)?
+        .query("station_code", station_code.to_lowercase());
+    let body = client().send(Endpoint::Places, request)?;
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Everything the places API lists at a station except the OV-fiets locations
+pub fn facilities(station_code: &str) -> Result<Vec<Facility>, Box<dyn std::error::Error>> {
+    let response = places("places", station_code)?;
+
+    Ok(response
+        .payload
+        .into_iter()
+        .filter(|place| place.kind != "ovfiets")
+        .flat_map(|place| {
+            let kind = place.name;
+            place.locations.into_iter().map(move |location| Facility {
+                kind: kind.clone(),
+                name: location.name,
+            })
+        })
+        .collect())
+}
+
+/// OV-fiets rental locations at a station with the bikes available now
+pub fn bike_rentals(station_code: &str) -> Result<Vec<BikeRental>, Box<dyn std::error::Error>> {
+    let response = places("ovfiets", station_code)?;
+    Ok(rentals(response))
+}
+
+fn rentals(response: PlacesResponse) -> Vec<BikeRental> {
+    response
+        .payload
+        .into_iter()
+        .flat_map(|place| place.locations)
+        .map(|location| BikeRental {
+            name: location.name,
+            available: location
+                .extra
+                .rental_bikes
+                .and_then(|count| count.parse().ok()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rentals_parse_string_counts() {
+        let body = r#"{"payload": [{"type": "ovfiets", "name": "OV-fiets", "locations": [
+            {"name": "Utrecht Centraal Jaarbeursplein", "extra": {"rentalBikes": "143"}},
+            {"name": "Utrecht Centraal Stationsplein", "extra": {}}
+        ]}]}"#;
+
+        let response: PlacesResponse = serde_json::from_str(body).unwrap();
+        let rentals = rentals(response);
+        assert_eq!(rentals[0].available, Some(143));
+        assert_eq!(rentals[1].available, None);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod models;
 pub mod service;
 
+pub use models::{CityPick, ClassComparison, Discount, FareQuery, Passengers};
 pub use service::*;
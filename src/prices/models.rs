@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
 pub struct PriceApiResponse {
@@ -8,10 +9,14 @@ pub struct PriceApiResponse {
 #[derive(Debug, Deserialize)]
 pub struct PricesResponse {
     pub prices: Vec<Price>,
+    /// Fare-unit distance of the route (tariefeenheden), which domestic fares
+    /// and subscriptions are based on; absent abroad and for cached prices
+    #[serde(default, rename = "tariefEenheden", alias = "tariffUnits")]
+    pub fare_units: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
 pub struct Price {
     pub total_price_in_cents: i32,
     pub price_per_adult_in_cents: i32,
@@ -24,4 +29,99 @@ pub struct Price {
     pub display_name: String,
     #[serde(default)]
     pub is_best_option: bool,
+    /// "single" or "return", set for the products listed by `price --all-products`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub travel_type: Option<String>,
+    /// Intercity direct / ICE supplement of the next fast journey, set by `price --supplement`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplement_in_cents: Option<i32>,
+}
+
+/// One product priced in both classes, a row of `price --compare-class`
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ClassComparison {
+    pub display_name: String,
+    pub second_class_in_cents: Option<i32>,
+    pub first_class_in_cents: Option<i32>,
+    /// What 1st class costs on top of 2nd class
+    pub difference_in_cents: Option<i32>,
+}
+
+/// Who is travelling (`price --adults/--children`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Passengers {
+    pub adults: u32,
+    /// Children aged 4 to 11; younger children travel free and need no ticket
+    pub children: u32,
+}
+
+impl Default for Passengers {
+    fn default() -> Self {
+        Passengers {
+            adults: 1,
+            children: 0,
+        }
+    }
+}
+
+/// Subscription whose discount the quoted price should include (`price --discount`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Discount {
+    /// Full fare
+    #[default]
+    None,
+    /// 40% off-peak
+    DalVoordeel,
+    /// 20% at peak hours, 40% off-peak
+    AltijdVoordeel,
+    /// Free off-peak
+    DalVrij,
+    /// Free at weekends, 40% off-peak on weekdays
+    WeekendVrij,
+    /// Always free
+    AltijdVrij,
+}
+
+/// Everything about a fare beyond the route, class and single/return
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FareQuery {
+    pub passengers: Passengers,
+    pub discount: Discount,
+    /// Price the journey as one ticket when part of it is run by other carriers
+    pub joint: bool,
+    /// Depart at this moment instead of now (`plannedDepartureTime`), for date-dependent
+    /// products and promotions
+    pub planned_departure_time: Option<DateTime<FixedOffset>>,
+}
+
+/// How `price` chooses among the member stations of a city (`--city-pick`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CityPick {
+    /// Lowest total price
+    #[default]
+    Cheapest,
+    /// Shortest travel time of the next journey
+    Fastest,
+}
+
+/// A route and class `cache warm` could not price
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmFailure {
+    pub from: String,
+    pub to: String,
+    /// Price API travel class, e.g. "FIRST_CLASS"
+    pub class: String,
+    pub error: String,
+}
+
+/// Outcome of `warm_cache()`
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmReport {
+    pub routes: usize,
+    /// Prices now in the cache, fetched or already there
+    pub cached: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<WarmFailure>,
 }
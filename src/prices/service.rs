@@ -1,17 +1,64 @@
-use crate::cache::PriceCache;
-use crate::prices::models::PriceApiResponse;
+use crate::cache::{CachedPrice, PriceCache, PriceKey};
+use crate::client::{client, Endpoint};
+use crate::output::{CsvRow, TableRow, TemplateFields, Value};
+use crate::matrix::bounded_map;
+use crate::prices::models::{
+    ClassComparison, Discount, FareQuery, Passengers, Price, PriceApiResponse, WarmFailure,
+    WarmReport,
+};
+use crate::stations::pick_station_local;
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use std::iter::once;
+use std::collections::HashMap;
 use crate::stations::models::Station;
-use std::env;
 
+/// The fares of a route, with `fare.discount` applied. The price API only
+/// quotes full fares, so the subscription discount is computed here from
+/// the moment of travel (now by default) and the cache holds full fares.
 pub fn get_prices(
     from: &Station,
     to: &Station,
     travel_class: Option<&str>,
     travel_type: Option<&str>,
+    fare: &FareQuery,
     cache: Option<&PriceCache>,
 ) -> Result<PriceApiResponse, Box<dyn std::error::Error>> {
-    // Only use cache for single trips (not return trips)
-    let use_cache = cache.is_some() && travel_type.unwrap_or("single") == "single";
+    let mut response = get_full_prices(from, to, travel_class, travel_type, fare, cache)?;
+    let travel_time = match fare.planned_departure_time {
+        Some(time) => time.naive_local(),
+        None => Local::now().naive_local(),
+    };
+    apply_discount(&mut response, fare.discount, travel_time);
+    Ok(response)
+}
+
+/// Lower every price of `response` to what it costs with `discount` at `at`,
+/// noting the difference in `discount_in_cents`
+fn apply_discount(response: &mut PriceApiResponse, discount: Discount, at: NaiveDateTime) {
+    for price in &mut response.payload.prices {
+        let full = price.total_price_in_cents;
+        price.total_price_in_cents = discount.apply(full, at);
+        price.price_per_adult_in_cents = discount.apply(price.price_per_adult_in_cents, at);
+        let saved = full - price.total_price_in_cents;
+        if saved > 0 {
+            price.discount_in_cents = Some(price.discount_in_cents.unwrap_or(0) + saved);
+        }
+    }
+}
+
+fn get_full_prices(
+    from: &Station,
+    to: &Station,
+    travel_class: Option<&str>,
+    travel_type: Option<&str>,
+    fare: &FareQuery,
+    cache: Option<&PriceCache>,
+) -> Result<PriceApiResponse, Box<dyn std::error::Error>> {
+    // Only use cache for undated trips: promotions depend on the moment of
+    // travel. Passengers and joint journeys are part of the key, so group
+    // fares are cached apart from single ones
+    let use_cache = cache.is_some() && fare.planned_departure_time.is_none();
+    let passengers = fare.passengers;
 
     // Convert travel_class string to u8 for cache lookup
     let class_num = match travel_class.unwrap_or("SECOND_CLASS") {
@@ -19,52 +66,499 @@ pub fn get_prices(
         _ => 2,
     };
 
+    let cache_key = PriceKey {
+        travel_type: travel_type.unwrap_or("single").to_string(),
+        adults: passengers.adults,
+        children: passengers.children,
+        joint: fare.joint,
+        ..PriceKey::new(&from.names.long, &to.names.long, class_num)
+    };
+
+    // A cached price is the total; for groups the price per adult is stored with it
+    let cached_response = |cache: &PriceCache, total: u32| {
+        let mut response = create_cached_response(total, travel_class.unwrap_or("SECOND_CLASS"));
+        if passengers != Passengers::default()
+            && let Some(per_adult) = cache.per_adult(&cache_key)
+        {
+            response.payload.prices[0].price_per_adult_in_cents = per_adult as i32;
+        }
+        response
+    };
+
     // Check cache first
-    if use_cache {
-        if let Some(cached_price) = cache.unwrap().get(&from.names.long, &to.names.long, class_num) {
-            // Return a mock response with the cached price
-            return Ok(create_cached_response(
-                cached_price,
-                travel_class.unwrap_or("SECOND_CLASS"),
-            ));
+    if use_cache
+        && let Some(cache) = cache
+        && let Some(cached) = cache.lookup(&cache_key)
+    {
+        // Return a mock response with the cached price, or none for routes without prices
+        return Ok(match cached {
+            CachedPrice::Price(price) => cached_response(cache, price),
+            CachedPrice::NoPrices => no_prices_response(),
+        });
+    }
+
+    // The price API keeps blowing its latency budget, or stale-while-revalidate
+    // is on - serve an expired entry rather than wait
+    if use_cache
+        && let Some(cache) = cache
+        && (cache.stale_while_revalidate() || client().is_degraded(Endpoint::Price))
+        && let Some(stale_price) = cache.get_stale(&cache_key)
+    {
+        if cache.stale_while_revalidate() {
+            let (from, to, fare, key) = (from.clone(), to.clone(), *fare, cache_key.clone());
+            let travel_class = travel_class.map(str::to_string);
+            let travel_type = travel_type.map(str::to_string);
+            cache.revalidate(
+                &cache_key,
+                Box::new(move |cache: &PriceCache| {
+                    let response = fetch_prices(
+                        &from,
+                        &to,
+                        travel_class.as_deref(),
+                        travel_type.as_deref(),
+                        &fare,
+                    )?;
+                    cache.record_fetch();
+                    store_prices(cache, &key, &response);
+                    Ok(())
+                }),
+            );
         }
+        return Ok(cached_response(cache, stale_price));
     }
 
     // Cache miss or caching disabled - fetch from API
-    let url = "https://gateway.apiportal.ns.nl/reisinformatie-api/api/v3/price";
+    let response = fetch_prices(from, to, travel_class, travel_type, fare)?;
+    if let Some(cache) = cache {
+        cache.record_fetch();
+        if use_cache {
+            store_prices(cache, &cache_key, &response);
+        }
+    }
+    Ok(response)
+}
 
-    let ns_api_token = env::var("NS_API_TOKEN").map_err(|_| "NS_API_TOKEN not found")?;
+/// Ask the price API for the full fares of a route
+fn fetch_prices(
+    from: &Station,
+    to: &Station,
+    travel_class: Option<&str>,
+    travel_type: Option<&str>,
+    fare: &FareQuery,
+) -> Result<PriceApiResponse, Box<dyn std::error::Error>> {
+    let passengers = fare.passengers;
+    let url = "https://gateway.apiportal.ns.nl/reisinformatie-api/api/v3/price";
 
-    let request = ureq::get(url)
-        .header("Cache-Control", "no-cache")
-        .header("Ocp-Apim-Subscription-Key", &ns_api_token)
+    let mut request = client()
+        .get(url)?
         .query("fromStation", &from.id.uic_code)
         .query("toStation", &to.id.uic_code)
         .query("travelClass", travel_class.unwrap_or("SECOND_CLASS"))
         .query("travelType", travel_type.unwrap_or("single"))
-        .query("isJointJourney", "false")
-        .query("adults", "1")
-        .query("children", "0");
+        .query("isJointJourney", fare.joint.to_string())
+        .query("adults", passengers.adults.to_string())
+        .query("children", passengers.children.to_string());
+    if let Some(time) = fare.planned_departure_time {
+        request = request.query("plannedDepartureTime", time.to_rfc3339());
+    }
 
-    let body: String = request.call()?.body_mut().read_to_string()?;
+    let body = client().send(Endpoint::Price, request)?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Update the cache with the first price of `response`, or remember for a
+/// while that the route has no prices
+fn store_prices(cache: &PriceCache, key: &PriceKey, response: &PriceApiResponse) {
+    let _ = match response.payload.prices.first() {
+        Some(first_price) => cache.set_fare(
+            key,
+            first_price.total_price_in_cents as u32,
+            first_price.price_per_adult_in_cents as u32,
+        ),
+        None => cache.set_no_prices(key),
+    };
+}
 
-    let response: PriceApiResponse = serde_json::from_str(&body)?;
+/// What `Discount::rate_at()` gives when the full fare applies
+const NO_DISCOUNT: &str = "NO_DISCOUNT";
 
-    // Update cache with the first price if available
-    if use_cache {
-        if let Some(first_price) = response.payload.prices.first() {
-            let _ = cache
-                .unwrap()
-                .set(&from.names.long, &to.names.long, class_num, first_price.total_price_in_cents as u32);
+/// Read a routes file of `from,to` lines such as `Den Haag C,Utrecht Centraal`.
+/// Blank lines and `#` comments are skipped
+pub fn parse_routes(text: &str) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let mut routes = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
+        let Some((from, to)) = line.split_once(',').filter(|(from, to)| {
+            !from.trim().is_empty() && !to.trim().is_empty() && !to.contains(',')
+        }) else {
+            return Err(format!("❌ Line {} of the routes file needs from,to: {}", number + 1, line)
+                .into());
+        };
+        routes.push((from.trim().to_string(), to.trim().to_string()));
     }
+    Ok(routes)
+}
 
-    Ok(response)
+/// Store the full single fare of every route in both classes in `cache`,
+/// `jobs` routes at a time (1 to 8). Prices still valid in the cache are
+/// kept; routes whose stations are unknown or whose price fails are reported
+pub fn warm_cache(routes: &[(String, String)], jobs: usize, cache: &PriceCache) -> WarmReport {
+    let results = bounded_map(routes, jobs, |(from, to)| {
+        let stations =
+            pick_station_local(from).and_then(|from| Ok((from, pick_station_local(to)?)));
+        ["SECOND_CLASS", "FIRST_CLASS"].map(|class| {
+            let error = match &stations {
+                Ok((from_station, to_station)) => {
+                    let fare = FareQuery::default();
+                    get_prices(from_station, to_station, Some(class), None, &fare, Some(cache))
+                        .err()
+                        .map(|e| e.to_string())
+                }
+                Err(e) => Some(e.to_string()),
+            };
+            error.map(|error| WarmFailure {
+                from: from.clone(),
+                to: to.clone(),
+                class: class.to_string(),
+                error,
+            })
+        })
+    });
+
+    let failures: Vec<WarmFailure> = results.into_iter().flatten().flatten().collect();
+    WarmReport {
+        routes: routes.len(),
+        cached: routes.len() * 2 - failures.len(),
+        failures,
+    }
+}
+
+/// Whether NS counts `at` as peak hours: weekdays 06:30-09:00 and 16:00-18:30
+pub fn is_peak(at: NaiveDateTime) -> bool {
+    let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).expect("valid time");
+    let weekday = !matches!(at.weekday(), Weekday::Sat | Weekday::Sun);
+    let t = at.time();
+    weekday && ((time(6, 30)..time(9, 0)).contains(&t) || (time(16, 0)..time(18, 30)).contains(&t))
+}
+
+/// Flat NS tariff of the day bicycle ticket (Dagkaart Fiets), 2025 tariff;
+/// the price API does not quote it
+pub const BIKE_DAY_TICKET_CENTS: i32 = 885;
+
+/// The daily windows of the bike ban, `(start, end)` as `(hour, minute)`.
+/// Narrower than the peak hours of the fares
+const BIKE_BAN_WINDOWS: [((u32, u32), (u32, u32)); 2] = [((6, 30), (9, 0)), ((16, 30), (18, 0))];
+
+/// Whether bikes are banned from the train at `at`: weekdays 06:30-09:00 and
+/// 16:30-18:00, except in July and August. Folding bikes are always allowed
+pub fn bikes_restricted(at: NaiveDateTime) -> bool {
+    let time = |(h, m)| NaiveTime::from_hms_opt(h, m, 0).expect("valid time");
+    let weekday = !matches!(at.weekday(), Weekday::Sat | Weekday::Sun);
+    weekday
+        && !matches!(at.month(), 7 | 8)
+        && BIKE_BAN_WINDOWS
+            .iter()
+            .any(|&(start, end)| (time(start)..time(end)).contains(&at.time()))
+}
+
+/// Whether a ride from `start` to `end` runs into the bike ban at any moment
+pub fn bikes_restricted_during(start: NaiveDateTime, end: NaiveDateTime) -> bool {
+    let window_starts = [start.date(), end.date()].into_iter().flat_map(|date| {
+        BIKE_BAN_WINDOWS
+            .into_iter()
+            .map(move |((h, m), _)| date.and_hms_opt(h, m, 0).expect("valid time"))
+    });
+    once(start)
+        .chain(window_starts.filter(|at| (start..=end).contains(at)))
+        .any(bikes_restricted)
+}
+
+impl Discount {
+    /// Every subscription, without `Discount::None`
+    pub const SUBSCRIPTIONS: [Discount; 5] = [
+        Discount::DalVoordeel,
+        Discount::AltijdVoordeel,
+        Discount::DalVrij,
+        Discount::WeekendVrij,
+        Discount::AltijdVrij,
+    ];
+
+    /// Product name as NS sells it
+    pub fn label(&self) -> &'static str {
+        match self {
+            Discount::None => "Full fare",
+            Discount::DalVoordeel => "Dal Voordeel",
+            Discount::AltijdVoordeel => "Altijd Voordeel",
+            Discount::DalVrij => "Dal Vrij",
+            Discount::WeekendVrij => "Weekend Vrij",
+            Discount::AltijdVrij => "Altijd Vrij",
+        }
+    }
+
+    /// Monthly fee in the 2024 tariff year; the price API does not quote
+    /// subscriptions, update these with the yearly tariff change
+    pub fn monthly_fee_cents(&self) -> i32 {
+        match self {
+            Discount::None => 0,
+            Discount::DalVoordeel => 560,
+            Discount::AltijdVoordeel => 2620,
+            Discount::DalVrij => 11900,
+            Discount::WeekendVrij => 3820,
+            Discount::AltijdVrij => 39300,
+        }
+    }
+
+    /// What a journey with full fare `full_cents` costs with this subscription at `at`
+    pub fn apply(&self, full_cents: i32, at: NaiveDateTime) -> i32 {
+        match self.rate_at(at) {
+            "FREE" => 0,
+            "DISCOUNT_40_PERCENT" => (f64::from(full_cents) * 0.6).round() as i32,
+            "DISCOUNT_20_PERCENT" => (f64::from(full_cents) * 0.8).round() as i32,
+            _ => full_cents,
+        }
+    }
+
+    /// The discount this subscription gives for travelling at `at`:
+    /// `FREE`, `DISCOUNT_40_PERCENT`, `DISCOUNT_20_PERCENT` or `NO_DISCOUNT`
+    pub fn rate_at(&self, at: NaiveDateTime) -> &'static str {
+        let peak = is_peak(at);
+        let weekend = matches!(at.weekday(), Weekday::Sat | Weekday::Sun);
+        match self {
+            Discount::None => NO_DISCOUNT,
+            Discount::DalVoordeel if peak => NO_DISCOUNT,
+            Discount::DalVoordeel => "DISCOUNT_40_PERCENT",
+            Discount::AltijdVoordeel if peak => "DISCOUNT_20_PERCENT",
+            Discount::AltijdVoordeel => "DISCOUNT_40_PERCENT",
+            Discount::DalVrij if peak => NO_DISCOUNT,
+            Discount::DalVrij => "FREE",
+            Discount::WeekendVrij if weekend => "FREE",
+            Discount::WeekendVrij if peak => NO_DISCOUNT,
+            Discount::WeekendVrij => "DISCOUNT_40_PERCENT",
+            Discount::AltijdVrij => "FREE",
+        }
+    }
+}
+
+/// Order products by single/return and class, the group holding the cheapest
+/// product first and the cheapest product first within each group
+pub fn sort_products(prices: &mut [Price]) {
+    let mut cheapest_in_group: HashMap<(Option<String>, String), i32> = HashMap::new();
+    for price in prices.iter() {
+        let cheapest = cheapest_in_group
+            .entry(price.product_group())
+            .or_insert(price.total_price_in_cents);
+        *cheapest = (*cheapest).min(price.total_price_in_cents);
+    }
+    prices.sort_by_cached_key(|price| {
+        let group = price.product_group();
+        (cheapest_in_group[&group], group, price.total_price_in_cents)
+    });
+}
+
+/// Lowest total price among the listed options
+pub fn cheapest(prices: &[Price]) -> Option<&Price> {
+    prices.iter().min_by_key(|price| price.total_price_in_cents)
+}
+
+/// Pair the products of both classes by name, in the order of the 2nd class
+/// prices; products only sold in one class get a row of their own
+pub fn compare_classes(second: &[Price], first: &[Price]) -> Vec<ClassComparison> {
+    let row = |name: &str, second: Option<i32>, first: Option<i32>| ClassComparison {
+        display_name: name.to_string(),
+        second_class_in_cents: second,
+        first_class_in_cents: first,
+        difference_in_cents: second.zip(first).map(|(second, first)| first - second),
+    };
+
+    let mut rows: Vec<ClassComparison> = second
+        .iter()
+        .map(|price| {
+            let first = first.iter().find(|p| p.display_name == price.display_name);
+            row(
+                &price.display_name,
+                Some(price.total_price_in_cents),
+                first.map(|p| p.total_price_in_cents),
+            )
+        })
+        .collect();
+    for price in first {
+        if !second.iter().any(|p| p.display_name == price.display_name) {
+            rows.push(row(&price.display_name, None, Some(price.total_price_in_cents)));
+        }
+    }
+    rows
+}
+
+impl TableRow for ClassComparison {
+    fn headers() -> &'static [&'static str] {
+        &["Product", "2nd class", "1st class", "Difference"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let euros = |cents: Option<i32>| match cents {
+            Some(cents) => format!("€{:.2}", cents as f64 / 100.0),
+            None => "-".to_string(),
+        };
+        vec![
+            self.display_name.clone(),
+            euros(self.second_class_in_cents),
+            euros(self.first_class_in_cents),
+            match self.difference_in_cents {
+                Some(cents) => format!("+€{:.2}", cents as f64 / 100.0),
+                None => "-".to_string(),
+            },
+        ]
+    }
+}
+
+impl CsvRow for ClassComparison {
+    fn headers() -> &'static [&'static str] {
+        &["name", "second_class_cents", "first_class_cents", "difference_cents"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let cents = |cents: Option<i32>| cents.map(|c| c.to_string()).unwrap_or_default();
+        vec![
+            self.display_name.clone(),
+            cents(self.second_class_in_cents),
+            cents(self.first_class_in_cents),
+            cents(self.difference_in_cents),
+        ]
+    }
+}
+
+/// The fare that applies to a group: a group product when NS offers one,
+/// otherwise the cheapest regular fare
+pub fn group_fare(prices: &[Price]) -> Option<&Price> {
+    prices
+        .iter()
+        .filter(|price| price.is_group_product())
+        .min_by_key(|price| price.total_price_in_cents)
+        .or_else(|| cheapest(prices))
+}
+
+impl Price {
+    /// Whether NS sells this product to groups, e.g. "Groepsretour"
+    pub fn is_group_product(&self) -> bool {
+        let name = self.display_name.to_lowercase();
+        name.contains("groep") || name.contains("group")
+    }
+
+    /// Single/return and travel class, the grouping of `price --all-products`
+    pub fn product_group(&self) -> (Option<String>, String) {
+        (self.travel_type.clone(), self.travel_class.clone())
+    }
+
+    /// What the children pay together: the total minus the adult fares
+    pub fn children_total_cents(&self, passengers: Passengers) -> i32 {
+        let adults = self.price_per_adult_in_cents * passengers.adults as i32;
+        (self.total_price_in_cents - adults).max(0)
+    }
+
+    /// The fare plus the supplement of the fast train, if one was looked up
+    pub fn total_with_supplement_cents(&self) -> Option<i32> {
+        self.supplement_in_cents
+            .map(|supplement| self.total_price_in_cents + supplement)
+    }
+
+    /// Human-readable travel class, e.g. "2nd class"
+    pub fn class_label(&self) -> &str {
+        match self.travel_class.as_str() {
+            "FIRST_CLASS" => "1st class",
+            "SECOND_CLASS" => "2nd class",
+            _ => &self.travel_class,
+        }
+    }
+}
+
+impl Price {
+    /// Plain-language description for screen readers, in a fixed order
+    pub fn accessible_description(&self) -> String {
+        let euros = |cents: i32| format!("{:.2} euro", cents as f64 / 100.0);
+
+        let mut parts = vec![
+            format!("Price {}", euros(self.total_price_in_cents)),
+            self.display_name.clone(),
+            self.class_label().to_string(),
+            format!("per adult {}", euros(self.price_per_adult_in_cents)),
+        ];
+        if let Some(discount) = self.discount_in_cents
+            && discount > 0
+        {
+            parts.push(format!("discount {}", euros(discount)));
+        }
+        if self.discount_type != "NONE" {
+            parts.push(format!("discount type {}", self.discount_type));
+        }
+        if let Some(supplement) = self.supplement_in_cents {
+            parts.push(format!("supplement {}", euros(supplement)));
+        }
+        if let Some(operator) = &self.operator_name {
+            parts.push(format!("operator {}", operator));
+        }
+        if self.is_best_option {
+            parts.push("best option".to_string());
+        }
+
+        parts.join(", ")
+    }
+}
+
+impl TemplateFields for Price {
+    fn field(&self, name: &str) -> Option<Value> {
+        let euros = |cents: i32| format!("{:.2}", cents as f64 / 100.0);
+
+        Some(Value::Text(match name {
+            "price" => euros(self.total_price_in_cents),
+            "price_cents" => self.total_price_in_cents.to_string(),
+            "per_adult" => euros(self.price_per_adult_in_cents),
+            "discount" => euros(self.discount_in_cents.unwrap_or(0)),
+            "discount_type" => self.discount_type.clone(),
+            "name" => self.display_name.clone(),
+            "class" => self.class_label().to_string(),
+            "supplement" => self.supplement_in_cents.map(euros).unwrap_or_default(),
+            "with_supplement" => self.total_with_supplement_cents().map(euros).unwrap_or_default(),
+            "operator" => self.operator_name.clone().unwrap_or_default(),
+            "best" => if self.is_best_option { "best" } else { "" }.to_string(),
+            _ => return None,
+        }))
+    }
+}
+
+impl CsvRow for Price {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "name",
+            "travel_class",
+            "total_price_cents",
+            "price_per_adult_cents",
+            "discount_cents",
+            "discount_type",
+            "operator",
+            "best_option",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.display_name.clone(),
+            self.travel_class.clone(),
+            self.total_price_in_cents.to_string(),
+            self.price_per_adult_in_cents.to_string(),
+            self.discount_in_cents.unwrap_or(0).to_string(),
+            self.discount_type.clone(),
+            self.operator_name.clone().unwrap_or_default(),
+            self.is_best_option.to_string(),
+        ]
+    }
 }
 
 /// Create a cached response with minimal data
 fn create_cached_response(price_cents: u32, travel_class: &str) -> PriceApiResponse {
-    use crate::prices::models::{Price, PricesResponse};
+    use crate::prices::models::PricesResponse;
 
     PriceApiResponse {
         payload: PricesResponse {
@@ -77,7 +571,258 @@ fn create_cached_response(price_cents: u32, travel_class: &str) -> PriceApiRespo
                 is_best_option: true,
                 display_name: "Cached Price".to_string(),
                 operator_name: None,
+                travel_type: None,
+                supplement_in_cents: None,
             }],
+            fare_units: None,
         },
     }
 }
+
+/// The answer of the price API for a route it has no prices for
+fn no_prices_response() -> PriceApiResponse {
+    use crate::prices::models::PricesResponse;
+
+    PriceApiResponse {
+        payload: PricesResponse {
+            prices: Vec::new(),
+            fare_units: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_routes() {
+        let text = "# busiest\nDen Haag C, Utrecht Centraal\n\nZwolle,Groningen\n";
+        let routes = parse_routes(text).unwrap();
+        assert_eq!(
+            routes,
+            [
+                ("Den Haag C".to_string(), "Utrecht Centraal".to_string()),
+                ("Zwolle".to_string(), "Groningen".to_string())
+            ]
+        );
+        assert!(parse_routes("Zwolle").is_err());
+        assert!(parse_routes("Zwolle,").is_err());
+        assert!(parse_routes("Zwolle,Groningen,Assen").is_err());
+    }
+
+    #[test]
+    fn test_busiest_routes_resolve_locally() {
+        for (from, to) in crate::constants::BUSIEST_ROUTES {
+            let from_station = pick_station_local(from).unwrap();
+            let to_station = pick_station_local(to).unwrap();
+            assert_eq!(from_station.names.long, *from);
+            assert_eq!(to_station.names.long, *to);
+        }
+    }
+
+    #[test]
+    fn test_sort_products() {
+        let price = |travel_type: &str, class: &str, cents: i32| Price {
+            total_price_in_cents: cents,
+            price_per_adult_in_cents: cents,
+            discount_in_cents: None,
+            operator_name: None,
+            discount_type: "NONE".to_string(),
+            travel_class: class.to_string(),
+            display_name: format!("{} {}", travel_type, class),
+            is_best_option: false,
+            travel_type: Some(travel_type.to_string()),
+            supplement_in_cents: None,
+        };
+        let mut prices = vec![
+            price("return", "SECOND_CLASS", 3000),
+            price("single", "FIRST_CLASS", 2500),
+            price("single", "SECOND_CLASS", 1600),
+            price("single", "SECOND_CLASS", 1500),
+            price("return", "FIRST_CLASS", 5000),
+        ];
+        sort_products(&mut prices);
+
+        let order: Vec<(&str, i32)> = prices
+            .iter()
+            .map(|p| (p.display_name.as_str(), p.total_price_in_cents))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("single SECOND_CLASS", 1500),
+                ("single SECOND_CLASS", 1600),
+                ("single FIRST_CLASS", 2500),
+                ("return SECOND_CLASS", 3000),
+                ("return FIRST_CLASS", 5000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_classes() {
+        let mut second = create_cached_response(1560, "SECOND_CLASS").payload.prices;
+        let mut first = create_cached_response(2650, "FIRST_CLASS").payload.prices;
+        first[0].display_name = "Enkele reis".to_string();
+        second[0].display_name = "Enkele reis".to_string();
+        first.extend(create_cached_response(900, "FIRST_CLASS").payload.prices);
+
+        let rows = compare_classes(&second, &first);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].difference_in_cents, Some(1090));
+        assert_eq!(rows[0].cells(), vec!["Enkele reis", "€15.60", "€26.50", "+€10.90"]);
+        assert_eq!(
+            (rows[1].second_class_in_cents, rows[1].first_class_in_cents, rows[1].difference_in_cents),
+            (None, Some(900), None)
+        );
+    }
+
+    #[test]
+    fn test_group_prices_cached_per_passenger_count() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::cache::MemoryBackend;
+
+        let cache = PriceCache::from_backend("memory", Box::new(MemoryBackend::new()));
+        let from = pick_station_local("Den Haag C")?;
+        let to = pick_station_local("Utrecht Centraal")?;
+        let fare = |adults, joint| FareQuery {
+            passengers: Passengers { adults, children: 0 },
+            joint,
+            ..Default::default()
+        };
+        let key = |fare: &FareQuery| PriceKey {
+            adults: fare.passengers.adults,
+            joint: fare.joint,
+            ..PriceKey::new(&from.names.long, &to.names.long, 2)
+        };
+        cache.set(&key(&fare(1, false)), 1300)?;
+        cache.set_fare(&key(&fare(2, false)), 2600, 1300)?;
+        cache.set_fare(&key(&fare(2, true)), 2340, 1170)?;
+
+        // Served from the cache without an API call, each under its own key
+        for (adults, joint, total, per_adult) in
+            [(1, false, 1300, 1300), (2, false, 2600, 1300), (2, true, 2340, 1170)]
+        {
+            let response = get_prices(&from, &to, None, None, &fare(adults, joint), Some(&cache))?;
+            let price = &response.payload.prices[0];
+            assert_eq!(
+                (price.total_price_in_cents, price.price_per_adult_in_cents),
+                (total, per_adult),
+                "{} adults, joint {}",
+                adults,
+                joint
+            );
+        }
+        assert_eq!(cache.stats().total_entries, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discount_is_applied_to_the_full_fare() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::cache::MemoryBackend;
+
+        let cache = PriceCache::from_backend("memory", Box::new(MemoryBackend::new()));
+        let from = pick_station_local("Den Haag C")?;
+        let to = pick_station_local("Utrecht Centraal")?;
+        cache.set(&PriceKey::new(&from.names.long, &to.names.long, 2), 1560)?;
+
+        // The full fare cached once serves every subscription
+        let quote = |discount| {
+            let fare = FareQuery { discount, ..Default::default() };
+            get_prices(&from, &to, None, None, &fare, Some(&cache))
+                .map(|response| response.payload.prices[0].total_price_in_cents)
+        };
+        assert_eq!(quote(Discount::None)?, 1560);
+        assert_eq!(quote(Discount::AltijdVrij)?, 0);
+        assert_eq!(cache.stats().total_entries, 1);
+
+        // June 3rd 2024 is a Monday
+        let off_peak = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let mut response = create_cached_response(1560, "SECOND_CLASS");
+        apply_discount(&mut response, Discount::DalVoordeel, off_peak);
+        let price = &response.payload.prices[0];
+        assert_eq!((price.total_price_in_cents, price.discount_in_cents), (936, Some(624)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_fare() {
+        let mut prices = create_cached_response(9000, "SECOND_CLASS").payload.prices;
+        prices.extend(create_cached_response(12000, "SECOND_CLASS").payload.prices);
+        assert_eq!(group_fare(&prices).unwrap().total_price_in_cents, 9000);
+
+        prices[1].display_name = "Groepsretour".to_string();
+        assert_eq!(group_fare(&prices).unwrap().display_name, "Groepsretour");
+        assert!(group_fare(&[]).is_none());
+    }
+
+    #[test]
+    fn test_fare_units() {
+        let body = r#"{"payload":{"tariefEenheden":42,"prices":[{"totalPriceInCents":1560,
+            "pricePerAdultInCents":1560,"discountType":"NONE","travelClass":"SECOND_CLASS",
+            "displayName":"Enkele reis"}]}}"#;
+        let response: PriceApiResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.payload.fare_units, Some(42));
+
+        let response: PriceApiResponse =
+            serde_json::from_str(r#"{"payload":{"prices":[]}}"#).unwrap();
+        assert_eq!(response.payload.fare_units, None);
+    }
+
+    #[test]
+    fn test_total_with_supplement() {
+        let mut price = create_cached_response(1820, "SECOND_CLASS").payload.prices.remove(0);
+        assert_eq!(price.total_with_supplement_cents(), None);
+        assert!(!price.accessible_description().contains("supplement"));
+
+        price.supplement_in_cents = Some(260);
+        assert_eq!(price.total_with_supplement_cents(), Some(2080));
+        assert!(price.accessible_description().contains("supplement 2.60 euro"));
+        assert_eq!(
+            serde_json::to_value(&price).unwrap()["supplement_in_cents"],
+            serde_json::json!(260)
+        );
+    }
+
+    #[test]
+    fn test_discount_by_time_of_travel() {
+        let at = |day: u32, h: u32, m: u32| {
+            // June 3rd 2024 is a Monday
+            NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(h, m, 0).unwrap()
+        };
+        assert!(is_peak(at(3, 8, 59)));
+        assert!(!is_peak(at(3, 9, 0)));
+        assert!(!is_peak(at(1, 8, 0)));
+
+        assert_eq!(Discount::DalVoordeel.rate_at(at(3, 8, 0)), NO_DISCOUNT);
+        assert_eq!(Discount::DalVoordeel.rate_at(at(3, 12, 0)), "DISCOUNT_40_PERCENT");
+        assert_eq!(Discount::AltijdVoordeel.rate_at(at(3, 17, 0)), "DISCOUNT_20_PERCENT");
+        assert_eq!(Discount::WeekendVrij.rate_at(at(1, 8, 0)), "FREE");
+        assert_eq!(Discount::WeekendVrij.rate_at(at(3, 12, 0)), "DISCOUNT_40_PERCENT");
+        assert_eq!(Discount::None.rate_at(at(1, 12, 0)), NO_DISCOUNT);
+    }
+
+    #[test]
+    fn test_bike_restrictions() {
+        let at = |month: u32, day: u32, h: u32, m: u32| {
+            NaiveDate::from_ymd_opt(2024, month, day).unwrap().and_hms_opt(h, m, 0).unwrap()
+        };
+        // June 3rd and July 1st 2024 are Mondays
+        assert!(bikes_restricted(at(6, 3, 8, 0)));
+        assert!(!bikes_restricted(at(7, 1, 8, 0)));
+        assert!(!bikes_restricted(at(6, 1, 8, 0)));
+
+        assert!(bikes_restricted_during(at(6, 3, 6, 0), at(6, 3, 6, 45)));
+        assert!(bikes_restricted_during(at(6, 3, 15, 0), at(6, 3, 19, 0)));
+        assert!(!bikes_restricted_during(at(6, 3, 9, 0), at(6, 3, 15, 59)));
+
+        // Peak hours for the fares, but outside the ban
+        assert!(!bikes_restricted(at(6, 3, 16, 15)));
+        assert!(!bikes_restricted(at(6, 3, 18, 15)));
+        assert!(bikes_restricted(at(6, 3, 16, 30)));
+        assert!(!bikes_restricted(at(6, 3, 18, 0)));
+        assert!(!bikes_restricted_during(at(6, 3, 16, 0), at(6, 3, 16, 29)));
+        assert!(bikes_restricted_during(at(6, 3, 16, 0), at(6, 3, 16, 45)));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::server::models::{BatchItemResult, JobResponse, JobStatus};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Pairs accepted in one `POST /prices/batch`
+pub const MAX_BATCH_ITEMS: usize = 5000;
+
+/// Largest page `GET /jobs/{id}` returns
+pub const MAX_PAGE: usize = 500;
+
+/// How long a finished job's results stay available
+const FINISHED_TTL: Duration = Duration::from_secs(3600);
+
+struct Job {
+    total: usize,
+    status: JobStatus,
+    results: Vec<BatchItemResult>,
+    finished: Option<Instant>,
+}
+
+/// In-memory batch jobs behind `POST /prices/batch` and `GET /jobs/{id}`.
+/// Results are appended as each pair finishes, so pages fill while the job
+/// runs and no request has to wait for the whole batch.
+#[derive(Default)]
+pub struct JobStore {
+    jobs: Mutex<HashMap<u64, Job>>,
+    last_id: AtomicU64,
+}
+
+impl JobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a queued job of `total` pairs, dropping expired finished jobs
+    pub fn create(&self, total: usize) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return id;
+        };
+        jobs.retain(|_, job| job.finished.is_none_or(|at| at.elapsed() < FINISHED_TTL));
+        jobs.insert(
+            id,
+            Job {
+                total,
+                status: JobStatus::Queued,
+                results: Vec::with_capacity(total),
+                finished: None,
+            },
+        );
+        id
+    }
+
+    /// Record the outcome of the next pair
+    pub fn push(&self, id: u64, result: BatchItemResult) {
+        if let Ok(mut jobs) = self.jobs.lock()
+            && let Some(job) = jobs.get_mut(&id)
+        {
+            job.status = JobStatus::Running;
+            job.results.push(result);
+        }
+    }
+
+    pub fn finish(&self, id: u64) {
+        if let Ok(mut jobs) = self.jobs.lock()
+            && let Some(job) = jobs.get_mut(&id)
+        {
+            job.status = JobStatus::Done;
+            job.finished = Some(Instant::now());
+        }
+    }
+
+    /// Up to `limit` results from `offset`, linking to the next page once this
+    /// one is full and the batch has more pairs
+    pub fn page(&self, id: u64, offset: usize, limit: usize) -> Option<JobResponse> {
+        let jobs = self.jobs.lock().ok()?;
+        let job = jobs.get(&id)?;
+        let limit = limit.clamp(1, MAX_PAGE);
+
+        let results: Vec<BatchItemResult> =
+            job.results.iter().skip(offset).take(limit).cloned().collect();
+        let next_offset = offset + results.len();
+        let next = (results.len() == limit && next_offset < job.total)
+            .then(|| format!("/jobs/{}?offset={}&limit={}", id, next_offset, limit));
+
+        Some(JobResponse {
+            id,
+            status: job.status,
+            total: job.total,
+            completed: job.results.len(),
+            offset,
+            results,
+            next,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(index: usize) -> BatchItemResult {
+        BatchItemResult {
+            index,
+            from: "Den Haag Centraal".to_string(),
+            to: "Utrecht Centraal".to_string(),
+            status: 400,
+            price: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_pages_follow_progress() {
+        let store = JobStore::new();
+        let id = store.create(3);
+        assert_eq!(store.page(id, 0, 2).unwrap().status, JobStatus::Queued);
+
+        store.push(id, result(0));
+        let page = store.page(id, 0, 2).unwrap();
+        assert_eq!((page.status, page.completed, page.results.len()), (JobStatus::Running, 1, 1));
+        assert_eq!(page.next, None);
+
+        store.push(id, result(1));
+        store.push(id, result(2));
+        store.finish(id);
+        let page = store.page(id, 0, 2).unwrap();
+        assert_eq!(page.next.as_deref(), Some("/jobs/1?offset=2&limit=2"));
+        let last = store.page(id, 2, 2).unwrap();
+        assert_eq!((last.status, last.results[0].index), (JobStatus::Done, 2));
+        assert_eq!(last.next, None);
+
+        assert_eq!(store.create(1), 2);
+        assert!(store.page(99, 0, 2).is_none());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod jobs;
+pub mod models;
+pub mod service;
+pub mod summary;
+
+pub use service::*;
@@ -0,0 +1,251 @@
+use crate::cache::CacheStats;
+use crate::client::{EndpointStats, PoolStats};
+use crate::trips::TripQuery;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceQuery {
+    /// Origin station name (e.g., "Amsterdam Centraal")
+    pub from: String,
+    /// Destination station name (e.g., "Utrecht Centraal")
+    pub to: String,
+    /// Travel class: 1 for first class, 2 for second class (default: 2)
+    #[serde(default = "default_class")]
+    #[param(default = 2, minimum = 1, maximum = 2)]
+    pub class: u8,
+    /// Price as one joint journey when part of it is run by other carriers (default: false)
+    #[serde(default)]
+    pub joint: bool,
+    /// Also look up the Intercity direct / ICE supplement of the next fast journey (default: false)
+    #[serde(default)]
+    pub supplement: bool,
+}
+
+fn default_class() -> u8 {
+    2
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TripsQuery {
+    /// Origin station name (e.g., "Amsterdam Centraal")
+    pub from: String,
+    /// Destination station name (e.g., "Utrecht Centraal")
+    pub to: String,
+    /// Maximum number of trips to return
+    #[param(minimum = 1)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StationsGeoJsonQuery {
+    /// Only stations inside `minLng,minLat,maxLng,maxLat` (e.g., "4.7,52.3,5.0,52.4")
+    pub bbox: Option<String>,
+}
+
+impl TripsQuery {
+    pub fn trip_query(&self) -> TripQuery {
+        TripQuery {
+            limit: self.limit,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Clone, utoipa::ToSchema)]
+pub struct PriceResponse {
+    /// Full name of the origin station
+    pub from: String,
+    /// Full name of the destination station
+    pub to: String,
+    /// Price in cents
+    #[schema(example = 940)]
+    pub price_cents: i32,
+    /// Travel class description
+    #[schema(example = "2nd class")]
+    pub travel_class: String,
+    /// Supplement of the next Intercity direct / ICE journey in cents, with `supplement=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 260)]
+    pub supplement_cents: Option<i32>,
+    /// Fare-unit distance of the route (tariefeenheden), when NS sends it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 42)]
+    pub fare_units: Option<u32>,
+}
+
+/// One pair in `POST /prices/batch`
+#[derive(Deserialize, Clone, utoipa::ToSchema)]
+pub struct BatchPriceItem {
+    /// Origin station name (e.g., "Amsterdam Centraal")
+    pub from: String,
+    /// Destination station name (e.g., "Utrecht Centraal")
+    pub to: String,
+    /// Travel class: 1 for first class, 2 for second class (default: 2)
+    #[serde(default = "default_class")]
+    pub class: u8,
+    /// Price as one joint journey when part of it is run by other carriers (default: false)
+    #[serde(default)]
+    pub joint: bool,
+}
+
+/// Body of `POST /prices/batch`
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct BatchPriceRequest {
+    pub items: Vec<BatchPriceItem>,
+}
+
+/// Returned with `202 Accepted` when a batch job is queued
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct BatchAccepted {
+    /// Job id for `GET /jobs/{id}`
+    pub id: u64,
+    /// Number of pairs in the batch
+    pub total: usize,
+    /// Where to collect the results, also sent as the `Location` header
+    #[schema(example = "/jobs/1")]
+    pub status_url: String,
+}
+
+/// Outcome of one batch pair: either `price` or `error` is set
+#[derive(Serialize, Clone, utoipa::ToSchema)]
+pub struct BatchItemResult {
+    /// Position of the pair in the request
+    pub index: usize,
+    pub from: String,
+    pub to: String,
+    /// HTTP status `GET /price` would have answered for this pair
+    #[schema(example = 200)]
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JobPageQuery {
+    /// Index of the first result to return
+    #[serde(default)]
+    pub offset: usize,
+    /// Results per page (default 100, at most 500)
+    #[serde(default = "default_page_limit")]
+    #[param(default = 100, minimum = 1, maximum = 500)]
+    pub limit: usize,
+}
+
+fn default_page_limit() -> usize {
+    100
+}
+
+/// One page of a batch job's results
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct JobResponse {
+    pub id: u64,
+    pub status: JobStatus,
+    /// Number of pairs in the batch
+    pub total: usize,
+    /// Number of pairs processed so far
+    pub completed: usize,
+    /// Index of the first result on this page
+    pub offset: usize,
+    pub results: Vec<BatchItemResult>,
+    /// Next page, also sent as a `Link: <...>; rel="next"` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+}
+
+#[derive(Serialize, Clone, utoipa::ToSchema)]
+pub struct StationMatch {
+    /// Station name
+    #[schema(example = "Amsterdam Centraal")]
+    pub name: String,
+    /// UIC station code
+    #[schema(example = 8400058)]
+    pub uic_code: i32,
+}
+
+#[derive(Serialize, Clone, utoipa::ToSchema)]
+pub struct ErrorResponse {
+    /// Error message
+    pub error: String,
+    /// List of matching stations (if query was ambiguous)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<StationMatch>>,
+}
+
+/// Body of `POST /admin/monitors`
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct NewMonitor {
+    /// Origin station name (e.g., "Den Haag C")
+    pub from: String,
+    /// Destination station name (e.g., "Amersfoort C")
+    pub to: String,
+    /// Seconds between refreshes (default 60, minimum 10)
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Sink specs: http(s) webhook URLs (optionally with #secret=<secret>) or
+    /// mqtt://host/topic
+    #[serde(default)]
+    pub sinks: Vec<String>,
+}
+
+fn default_interval() -> u64 {
+    60
+}
+
+/// One part of a station summary with its own freshness
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SummarySection {
+    /// Section payload, `null` when it could not be fetched yet
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+    /// When the payload was fetched from NS (RFC 3339)
+    pub fetched_at: Option<String>,
+    /// How long this section is cached before NS is asked again
+    pub max_age_secs: u64,
+    /// Whether `data` is older than `max_age_secs` because the refresh failed
+    pub stale: bool,
+    /// Why the last refresh failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Everything a station screen shows, in one document
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct StationSummaryResponse {
+    #[schema(example = "Utrecht Centraal")]
+    pub name: String,
+    #[schema(example = "8400621")]
+    pub uic_code: String,
+    /// List of `Facility` objects
+    pub facilities: SummarySection,
+    /// List of `BikeRental` objects (OV-fiets)
+    pub bike_rentals: SummarySection,
+    /// List of `Disruption` objects touching this station
+    pub disruptions: SummarySection,
+    /// List of the next `Departure` objects
+    pub departures: SummarySection,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MetricsResponse {
+    /// Latency metrics per upstream NS API endpoint
+    pub upstream: Vec<EndpointStats>,
+    /// Keep-alive pool the upstream calls share
+    pub pool: PoolStats,
+    /// Price cache entries and hit/miss counters, when the cache is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+}
@@ -0,0 +1,778 @@
+use crate::cache::{CacheCounters, CacheStats, PriceCache, REVALIDATE_INTERVAL, SAVE_INTERVAL};
+use crate::client::{client, ApiClient, EndpointStats, PoolStats};
+use crate::departures::Departure;
+use crate::disruptions::Disruption;
+#[cfg(feature = "web")]
+use crate::monitor::{MonitorRegistry, MonitoredRoute};
+use crate::constants::STATION_INFO;
+use crate::output::{stations_to_geojson, trips_to_geojson, BoundingBox};
+use crate::places::{BikeRental, Facility};
+use crate::prices::{self, FareQuery};
+use crate::server::models::{
+    BatchAccepted, BatchItemResult, BatchPriceItem, BatchPriceRequest, ErrorResponse,
+    JobPageQuery, JobResponse, JobStatus, MetricsResponse, PriceQuery, PriceResponse, StationMatch,
+    StationSummaryResponse, StationsGeoJsonQuery, SummarySection, TripsQuery,
+};
+#[cfg(feature = "web")]
+use crate::server::models::NewMonitor;
+use crate::server::jobs::{JobStore, MAX_BATCH_ITEMS};
+use crate::server::summary::SummaryCache;
+use crate::stations::{self, models::Station, StationLookupResult};
+use crate::trips;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+#[cfg(feature = "web")]
+use axum::{http::HeaderMap, routing::delete};
+use std::sync::Arc;
+#[cfg(feature = "web")]
+use subtle::ConstantTimeEq;
+use tower_http::cors::{Any, CorsLayer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+/// Flags shared by `cli serve` and the standalone `server` binary
+#[derive(clap::Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// Enable Swagger UI documentation at /docs
+    #[arg(long)]
+    pub docs: bool,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 3000)]
+    pub port: u16,
+
+    /// Persist server-side monitors in this file and enable /admin (requires NSTIMES_ADMIN_TOKEN)
+    #[cfg(feature = "web")]
+    #[arg(long, value_name = "FILE")]
+    pub monitors: Option<String>,
+}
+
+/// Monitor registry plus the bearer token guarding the admin endpoints
+#[cfg(feature = "web")]
+pub struct Admin {
+    pub registry: MonitorRegistry,
+    pub token: String,
+}
+
+// Application state shared across handlers
+#[derive(Clone)]
+struct AppState {
+    cache: Option<Arc<PriceCache>>,
+    #[cfg(feature = "web")]
+    admin: Option<Arc<Admin>>,
+    summaries: Arc<SummaryCache>,
+    jobs: Arc<JobStore>,
+    /// Pooled keep-alive client shared by every handler's upstream calls
+    upstream: &'static ApiClient,
+}
+
+/// Error status plus JSON body, returned by handlers via `into_response()`
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// Resolve a station query, or build the 400 response explaining why it failed
+fn resolve_station(query: &str, field: &str) -> Result<Station, ApiError> {
+    match stations::lookup_station_local(query) {
+        StationLookupResult::Single(s) => Ok(s),
+        StationLookupResult::None => Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("No stations found for '{}' query: {}", field, query),
+        )),
+        StationLookupResult::Multiple(matches) => {
+            let match_list = matches
+                .into_iter()
+                .map(|m| StationMatch {
+                    name: m.name,
+                    uic_code: m.uic_code,
+                })
+                .collect();
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Multiple stations matched for '{}' query: {}. Please refine your query.",
+                        field, query
+                    ),
+                    matches: Some(match_list),
+                }),
+            ))
+        }
+    }
+}
+
+fn error_response(status: StatusCode, error: String) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error,
+            matches: None,
+        }),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/price",
+    params(PriceQuery),
+    responses(
+        (status = 200, description = "Price information retrieved successfully", body = PriceResponse),
+        (status = 400, description = "Invalid input or ambiguous station name", body = ErrorResponse),
+        (status = 404, description = "No prices found for this route", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "prices"
+)]
+async fn get_price(
+    State(state): State<AppState>,
+    Query(params): Query<PriceQuery>,
+) -> impl IntoResponse {
+    let cache_ref = state.cache.as_ref().map(|arc| arc.as_ref());
+    let mut price = match price_for(&params.from, &params.to, params.class, params.joint, cache_ref) {
+        Ok(price) => price,
+        Err(e) => return e.into_response(),
+    };
+    if params.supplement {
+        match supplement_for(&params.from, &params.to) {
+            Ok(cents) => price.supplement_cents = cents,
+            Err(e) => return e.into_response(),
+        }
+    }
+    (StatusCode::OK, Json(price)).into_response()
+}
+
+/// Supplement of the next journey that needs one; the price API only quotes
+/// regular fares
+fn supplement_for(from: &str, to: &str) -> Result<Option<i32>, ApiError> {
+    let station_from = resolve_station(from, "from")?;
+    let station_to = resolve_station(to, "to")?;
+    let trip = trips::next_supplement_trip(&station_from, &station_to).map_err(|e| {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to fetch trips: {}", e),
+        )
+    })?;
+    Ok(trip.and_then(|trip| trip.supplement_cents()))
+}
+
+/// Price of one pair as answered by `/price`, shared with the batch jobs
+fn price_for(
+    from: &str,
+    to: &str,
+    class: u8,
+    joint: bool,
+    cache: Option<&PriceCache>,
+) -> Result<PriceResponse, ApiError> {
+    // Validate class parameter
+    if class != 1 && class != 2 {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "class must be 1 or 2".to_string(),
+        ));
+    }
+
+    // Lookup stations
+    let station_from = resolve_station(from, "from")?;
+    let station_to = resolve_station(to, "to")?;
+
+    // Get travel class
+    let travel_class = if class == 1 {
+        Some("FIRST_CLASS")
+    } else {
+        Some("SECOND_CLASS")
+    };
+
+    // Fetch price (with cache if available)
+    let response =
+        prices::get_prices(
+            &station_from,
+            &station_to,
+            travel_class,
+            Some("single"),
+            &FareQuery {
+                joint,
+                ..Default::default()
+            },
+            cache,
+        )
+            .map_err(|e| {
+                error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to fetch prices: {}", e),
+                )
+            })?;
+
+    // Extract first price
+    let price = response.payload.prices.first().ok_or_else(|| {
+        error_response(
+            StatusCode::NOT_FOUND,
+            "No prices found for this route".to_string(),
+        )
+    })?;
+    Ok(PriceResponse {
+        from: station_from.names.long,
+        to: station_to.names.long,
+        price_cents: price.total_price_in_cents,
+        travel_class: if class == 1 {
+            "1st class".to_string()
+        } else {
+            "2nd class".to_string()
+        },
+        supplement_cents: None,
+        fare_units: response.payload.fare_units,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/prices/batch",
+    request_body = BatchPriceRequest,
+    responses(
+        (status = 202, description = "Batch queued, collect the results from the Location URL", body = BatchAccepted),
+        (status = 400, description = "Empty batch or more than 5000 pairs", body = ErrorResponse)
+    ),
+    tag = "prices"
+)]
+async fn batch_prices(
+    State(state): State<AppState>,
+    Json(body): Json<BatchPriceRequest>,
+) -> Response {
+    if body.items.is_empty() || body.items.len() > MAX_BATCH_ITEMS {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("items must hold 1 to {} pairs", MAX_BATCH_ITEMS),
+        )
+        .into_response();
+    }
+
+    let total = body.items.len();
+    let id = state.jobs.create(total);
+    let jobs = state.jobs.clone();
+    let cache = state.cache.clone();
+    // One pair at a time so a large batch never floods the NS API; every
+    // result is visible on /jobs/{id} as soon as it is known
+    tokio::task::spawn_blocking(move || {
+        for (index, item) in body.items.into_iter().enumerate() {
+            let (status, price, error) =
+                match price_for(&item.from, &item.to, item.class, item.joint, cache.as_deref()) {
+                    Ok(price) => (StatusCode::OK, Some(price), None),
+                    Err((status, Json(error))) => (status, None, Some(error)),
+                };
+            jobs.push(
+                id,
+                BatchItemResult {
+                    index,
+                    from: item.from,
+                    to: item.to,
+                    status: status.as_u16(),
+                    price,
+                    error,
+                },
+            );
+        }
+        jobs.finish(id);
+    });
+
+    let status_url = format!("/jobs/{}", id);
+    (
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, status_url.clone())],
+        Json(BatchAccepted {
+            id,
+            total,
+            status_url,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    params(("id" = u64, Path, description = "Batch job id"), JobPageQuery),
+    responses(
+        (status = 200, description = "Job progress and one page of per-pair results; a `Link` header points to the next page", body = JobResponse),
+        (status = 404, description = "Unknown or expired job", body = ErrorResponse)
+    ),
+    tag = "prices"
+)]
+async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(page): Query<JobPageQuery>,
+) -> Response {
+    let Some(job) = state.jobs.page(id, page.offset, page.limit) else {
+        return error_response(StatusCode::NOT_FOUND, format!("No job with id {}", id))
+            .into_response();
+    };
+
+    match &job.next {
+        Some(next) => (
+            [(header::LINK, format!("<{}>; rel=\"next\"", next))],
+            Json(job),
+        )
+            .into_response(),
+        None => Json(job).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/trips.geojson",
+    params(TripsQuery),
+    responses(
+        (status = 200, description = "GeoJSON FeatureCollection with a LineString per leg and a Point per station", body = inline(Object)),
+        (status = 400, description = "Invalid input or ambiguous station name", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "trips"
+)]
+async fn get_trips_geojson(Query(params): Query<TripsQuery>) -> Response {
+    let station_from = match resolve_station(&params.from, "from") {
+        Ok(s) => s,
+        Err(e) => return e.into_response(),
+    };
+    let station_to = match resolve_station(&params.to, "to") {
+        Ok(s) => s,
+        Err(e) => return e.into_response(),
+    };
+
+    match trips::trips(&station_from, &station_to, &params.trip_query()) {
+        Ok(trips) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/geo+json")],
+            Json(trips_to_geojson(&trips)),
+        )
+            .into_response(),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to fetch trips: {}", e),
+        )
+        .into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stations.geojson",
+    params(StationsGeoJsonQuery),
+    responses(
+        (status = 200, description = "GeoJSON FeatureCollection with a Point per station that has coordinates; properties name, code, uic_code, country, type and facilities (counts by kind, once a summary fetched them)", body = inline(Object)),
+        (status = 400, description = "Invalid bbox", body = ErrorResponse)
+    ),
+    tag = "stations"
+)]
+async fn get_stations_geojson(
+    State(state): State<AppState>,
+    Query(params): Query<StationsGeoJsonQuery>,
+) -> Response {
+    let bbox = match params.bbox.as_deref().map(str::parse::<BoundingBox>).transpose() {
+        Ok(bbox) => bbox,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let stations = stations_to_geojson(STATION_INFO, bbox, |uic| {
+        state.summaries.facility_counts(&uic.to_string())
+    });
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(stations),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/stations/{uic}/summary",
+    params(("uic" = String, Path, description = "UIC station code, e.g. 8400621")),
+    responses(
+        (status = 200, description = "Facilities, OV-fiets stock, disruptions and next departures; every section carries its own freshness and error", body = StationSummaryResponse),
+        (status = 404, description = "Unknown UIC code", body = ErrorResponse)
+    ),
+    tag = "stations"
+)]
+async fn station_summary(State(state): State<AppState>, Path(uic): Path<String>) -> Response {
+    let Some(station) = stations::station_by_uic(&uic) else {
+        return error_response(StatusCode::NOT_FOUND, format!("Unknown station UIC code: {}", uic))
+            .into_response();
+    };
+
+    let summaries = state.summaries.clone();
+    match tokio::task::spawn_blocking(move || summaries.summary(&station)).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Per-endpoint latency metrics for upstream NS API calls and price cache hit/miss counters", body = MetricsResponse)
+    ),
+    tag = "health"
+)]
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    Json(MetricsResponse {
+        upstream: state.upstream.stats(),
+        pool: state.upstream.pool().clone(),
+        cache: state.cache.as_ref().map(|cache| cache.stats()),
+    })
+}
+
+/// Admin state if the request carries the right bearer token
+#[cfg(feature = "web")]
+fn authorize<'a>(state: &'a AppState, headers: &HeaderMap) -> Result<&'a Admin, ApiError> {
+    let admin = state
+        .admin
+        .as_deref()
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Admin API is disabled".to_string()))?;
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Constant-time so the response time does not leak how much of the token matched
+    let valid =
+        provided.is_some_and(|token| bool::from(token.as_bytes().ct_eq(admin.token.as_bytes())));
+    if !valid {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token".to_string(),
+        ));
+    }
+    Ok(admin)
+}
+
+#[cfg(feature = "web")]
+#[utoipa::path(
+    get,
+    path = "/admin/monitors",
+    responses(
+        (status = 200, description = "Monitored routes", body = [MonitoredRoute]),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse)
+    ),
+    tag = "admin"
+)]
+async fn list_monitors(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    match authorize(&state, &headers) {
+        Ok(admin) => {
+            let routes: Vec<MonitoredRoute> =
+                admin.registry.list().iter().map(MonitoredRoute::redacted).collect();
+            Json(routes).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(feature = "web")]
+#[utoipa::path(
+    post,
+    path = "/admin/monitors",
+    request_body = NewMonitor,
+    responses(
+        (status = 201, description = "Monitor created and started", body = MonitoredRoute),
+        (status = 400, description = "Unknown station, bad interval or sink", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse)
+    ),
+    tag = "admin"
+)]
+async fn add_monitor(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<NewMonitor>,
+) -> Response {
+    let admin = match authorize(&state, &headers) {
+        Ok(admin) => admin,
+        Err(e) => return e.into_response(),
+    };
+
+    match admin
+        .registry
+        .add(&body.from, &body.to, body.interval_secs, body.sinks)
+    {
+        Ok(route) => (StatusCode::CREATED, Json(route.redacted())).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+#[cfg(feature = "web")]
+#[utoipa::path(
+    delete,
+    path = "/admin/monitors/{id}",
+    params(("id" = u64, Path, description = "Monitor id")),
+    responses(
+        (status = 204, description = "Monitor stopped and removed"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 404, description = "No monitor with this id", body = ErrorResponse)
+    ),
+    tag = "admin"
+)]
+async fn remove_monitor(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> Response {
+    let admin = match authorize(&state, &headers) {
+        Ok(admin) => admin,
+        Err(e) => return e.into_response(),
+    };
+
+    match admin.registry.remove(id) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => {
+            error_response(StatusCode::NOT_FOUND, format!("No monitor with id {}", id))
+                .into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Static management page; it talks to the JSON endpoints with the token the user enters
+#[cfg(feature = "web")]
+async fn admin_page() -> axum::response::Html<&'static str> {
+    axum::response::Html(include_str!("admin.html"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service is healthy", body = inline(Object))
+    ),
+    tag = "health"
+)]
+async fn health_check() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_price,
+        batch_prices,
+        get_job,
+        get_trips_geojson,
+        get_stations_geojson,
+        station_summary,
+        metrics,
+        health_check
+    ),
+    components(schemas(
+        PriceResponse,
+        BatchPriceRequest,
+        BatchPriceItem,
+        BatchAccepted,
+        BatchItemResult,
+        JobResponse,
+        JobStatus,
+        ErrorResponse,
+        StationMatch,
+        MetricsResponse,
+        EndpointStats,
+        PoolStats,
+        CacheStats,
+        CacheCounters,
+        StationSummaryResponse,
+        SummarySection,
+        Facility,
+        BikeRental,
+        Disruption,
+        Departure
+    )),
+    tags(
+        (name = "prices", description = "Train ticket price endpoints"),
+        (name = "trips", description = "Journey planning endpoints"),
+        (name = "stations", description = "Station information for screens and kiosks"),
+        (name = "health", description = "Health check endpoint")
+    ),
+    info(
+        title = "NSTimes API",
+        version = "0.1.0",
+        description = "Dutch railway (NS) travel information API - get train ticket prices",
+    )
+)]
+pub struct ApiDoc;
+
+/// The admin endpoints of the `web` feature, merged into `ApiDoc` by `openapi()`
+#[cfg(feature = "web")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_monitors, add_monitor, remove_monitor),
+    components(schemas(MonitoredRoute, NewMonitor)),
+    tags(
+        (name = "admin", description = "Monitor and webhook management (bearer token, enabled with --monitors)")
+    )
+)]
+struct AdminApiDoc;
+
+/// The OpenAPI spec of every endpoint this build serves
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let doc = ApiDoc::openapi();
+    #[cfg(feature = "web")]
+    let doc = doc.merge_from(AdminApiDoc::openapi());
+    doc
+}
+
+/// Build the application router, optionally including the Swagger UI and
+/// (with the `web` feature) the admin endpoints
+pub fn router(
+    cache: Option<Arc<PriceCache>>,
+    docs: bool,
+    #[cfg(feature = "web")] admin: Option<Arc<Admin>>,
+) -> Router {
+    #[cfg(feature = "web")]
+    let admin_enabled = admin.is_some();
+    let state = AppState {
+        cache,
+        #[cfg(feature = "web")]
+        admin,
+        summaries: Arc::new(SummaryCache::new()),
+        jobs: Arc::new(JobStore::new()),
+        upstream: client(),
+    };
+
+    // Configure CORS to allow requests from anywhere
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+        .allow_headers(Any);
+
+    let app = Router::new()
+        .route("/price", get(get_price))
+        .route("/prices/batch", post(batch_prices))
+        .route("/jobs/:id", get(get_job))
+        .route("/trips.geojson", get(get_trips_geojson))
+        .route("/stations.geojson", get(get_stations_geojson))
+        .route("/stations/:uic/summary", get(station_summary))
+        .route("/metrics", get(metrics))
+        .route("/health", get(health_check));
+    #[cfg(feature = "web")]
+    let app = if admin_enabled {
+        app.route("/admin", get(admin_page))
+            .route("/admin/monitors", get(list_monitors).post(add_monitor))
+            .route("/admin/monitors/:id", delete(remove_monitor))
+    } else {
+        app
+    };
+    let mut app = app.with_state(state).layer(cors);
+
+    if docs {
+        let swagger_ui = SwaggerUi::new("/docs")
+            .url("/docs/openapi.json", openapi());
+        app = app.merge(swagger_ui);
+    }
+
+    app
+}
+
+/// Start the API server and block until it shuts down
+pub async fn serve(
+    args: &ServeArgs,
+    cache: Option<PriceCache>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(cache) = &cache {
+        println!("💾 Cache enabled: {}", cache.path());
+    }
+
+    #[cfg(feature = "web")]
+    let admin = match &args.monitors {
+        Some(path) => {
+            // An empty token would let any `Authorization: Bearer ` header through
+            let token = std::env::var("NSTIMES_ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty())
+                .ok_or("--monitors requires a non-empty NSTIMES_ADMIN_TOKEN to protect /admin")?;
+            let registry = MonitorRegistry::open(path)?;
+            println!(
+                "👀 Monitors: {} route(s) from {}",
+                registry.list().len(),
+                registry.path().display()
+            );
+            Some(Arc::new(Admin { registry, token }))
+        }
+        None => None,
+    };
+    #[cfg(feature = "web")]
+    let admin_enabled = admin.is_some();
+
+    let cache = cache.map(Arc::new);
+    let app = router(
+        cache.clone(),
+        args.docs,
+        #[cfg(feature = "web")]
+        admin,
+    );
+    if let Some(cache) = &cache {
+        let cache = Arc::clone(cache);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(SAVE_INTERVAL);
+            loop {
+                ticks.tick().await;
+                if let Err(e) = cache.flush() {
+                    eprintln!("⚠️  Failed to save the price cache: {}", e);
+                }
+            }
+        });
+    }
+    if let Some(cache) = cache.as_ref().filter(|cache| cache.stale_while_revalidate()) {
+        let cache = Arc::clone(cache);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(REVALIDATE_INTERVAL);
+            loop {
+                ticks.tick().await;
+                let cache = Arc::clone(&cache);
+                let _ = tokio::task::spawn_blocking(move || cache.run_revalidations()).await;
+            }
+        });
+    }
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", args.port)).await?;
+
+    println!("🚀 Server running on http://localhost:{}", args.port);
+    if args.docs {
+        println!("   📚 Docs: http://localhost:{}/docs", args.port);
+        println!("   📄 OpenAPI spec: http://localhost:{}/docs/openapi.json", args.port);
+    }
+    #[cfg(feature = "web")]
+    if admin_enabled {
+        println!("   🔧 Admin: http://localhost:{}/admin", args.port);
+    }
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    if let Some(cache) = &cache {
+        cache.flush()?;
+    }
+    Ok(())
+}
+
+/// Resolves on Ctrl+C or, on Unix, SIGTERM (as sent by `docker stop`)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    println!("👋 Shutting down, saving the price cache");
+}
@@ -0,0 +1,206 @@
+use crate::departures::departures;
+use crate::disruptions::disruptions_at;
+use crate::places::{bike_rentals, facilities};
+use crate::server::models::{StationSummaryResponse, SummarySection};
+use crate::stations::{self, models::Station};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Departures included in a summary
+const SUMMARY_DEPARTURES: usize = 10;
+
+/// The independently cached parts of a station summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Facilities,
+    BikeRentals,
+    Disruptions,
+    Departures,
+    /// Station abbreviation needed by the places API
+    StationCode,
+}
+
+impl Section {
+    /// How long a fetched section is served before NS is asked again
+    pub fn ttl(&self) -> Duration {
+        match self {
+            Section::Facilities => Duration::from_secs(6 * 3600),
+            Section::BikeRentals => Duration::from_secs(60),
+            Section::Disruptions => Duration::from_secs(120),
+            Section::Departures => Duration::from_secs(30),
+            Section::StationCode => Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+struct Cached {
+    fetched_at: DateTime<Utc>,
+    fetched: Instant,
+    value: serde_json::Value,
+}
+
+/// In-memory cache for `/stations/{uic}/summary`, one entry per station and section
+#[derive(Default)]
+pub struct SummaryCache {
+    entries: Mutex<HashMap<(Section, String), Cached>>,
+}
+
+impl SummaryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the summary, refreshing only the sections whose TTL expired
+    pub fn summary(&self, station: &Station) -> StationSummaryResponse {
+        let uic = station.id.uic_code.as_str();
+        let code = self.section(Section::StationCode, uic, || {
+            stations::station_code(station)
+        });
+        let code = code.data.as_str().map(str::to_string);
+        let with_code = |code: &Option<String>| {
+            code.clone()
+                .ok_or_else(|| format!("No station code known for {}", station.names.long))
+        };
+
+        StationSummaryResponse {
+            name: station.names.long.clone(),
+            uic_code: uic.to_string(),
+            facilities: self.section(Section::Facilities, uic, || facilities(&with_code(&code)?)),
+            bike_rentals: self.section(Section::BikeRentals, uic, || {
+                bike_rentals(&with_code(&code)?)
+            }),
+            disruptions: self.section(Section::Disruptions, uic, || disruptions_at(uic)),
+            departures: self.section(Section::Departures, uic, || {
+                departures(uic, SUMMARY_DEPARTURES)
+            }),
+        }
+    }
+
+    /// Cached section if still fresh, otherwise fetch it. A failed refresh
+    /// keeps serving the previous data marked as stale.
+    fn section<T: Serialize>(
+        &self,
+        section: Section,
+        uic: &str,
+        fetch: impl FnOnce() -> Result<T, Box<dyn Error>>,
+    ) -> SummarySection {
+        let key = (section, uic.to_string());
+        let ttl = section.ttl();
+
+        if let Some(cached) = self.lookup(&key, ttl, true) {
+            return cached;
+        }
+
+        let result = fetch().and_then(|value| Ok(serde_json::to_value(value)?));
+        match result {
+            Ok(value) => {
+                let fetched_at = Utc::now();
+                let response = SummarySection {
+                    data: value.clone(),
+                    fetched_at: Some(fetched_at.to_rfc3339()),
+                    max_age_secs: ttl.as_secs(),
+                    stale: false,
+                    error: None,
+                };
+                if let Ok(mut entries) = self.entries.lock() {
+                    entries.insert(
+                        key,
+                        Cached {
+                            fetched_at,
+                            fetched: Instant::now(),
+                            value,
+                        },
+                    );
+                }
+                response
+            }
+            Err(e) => {
+                let mut response = self.lookup(&key, ttl, false).unwrap_or(SummarySection {
+                    data: serde_json::Value::Null,
+                    fetched_at: None,
+                    max_age_secs: ttl.as_secs(),
+                    stale: false,
+                    error: None,
+                });
+                response.error = Some(e.to_string());
+                response
+            }
+        }
+    }
+
+    /// How many facilities of each kind the last fetch for `uic` listed,
+    /// fresh or stale; None until a summary of the station fetched them
+    pub fn facility_counts(&self, uic: &str) -> Option<BTreeMap<String, usize>> {
+        let entries = self.entries.lock().ok()?;
+        let cached = entries.get(&(Section::Facilities, uic.to_string()))?;
+        let mut counts = BTreeMap::new();
+        for facility in cached.value.as_array()? {
+            if let Some(kind) = facility["kind"].as_str() {
+                *counts.entry(kind.to_string()).or_insert(0) += 1;
+            }
+        }
+        Some(counts)
+    }
+
+    fn lookup(
+        &self,
+        key: &(Section, String),
+        ttl: Duration,
+        fresh_only: bool,
+    ) -> Option<SummarySection> {
+        let entries = self.entries.lock().ok()?;
+        let cached = entries.get(key)?;
+        let stale = cached.fetched.elapsed() > ttl;
+        if stale && fresh_only {
+            return None;
+        }
+
+        Some(SummarySection {
+            data: cached.value.clone(),
+            fetched_at: Some(cached.fetched_at.to_rfc3339()),
+            max_age_secs: ttl.as_secs(),
+            stale,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::places::Facility;
+
+    #[test]
+    fn test_fresh_section_is_not_refetched() {
+        let cache = SummaryCache::new();
+        let first = cache.section(Section::Departures, "8400621", || Ok(vec![1, 2]));
+        assert_eq!(first.data, serde_json::json!([1, 2]));
+
+        let second = cache.section(Section::Departures, "8400621", || -> Result<Vec<u32>, _> {
+            panic!("fresh section must come from the cache")
+        });
+        assert_eq!(second.data, first.data);
+        assert_eq!(second.max_age_secs, 30);
+    }
+
+    #[test]
+    fn test_failed_fetch_reports_error() {
+        let cache = SummaryCache::new();
+        let section = cache.section(Section::Facilities, "8400621", || -> Result<Vec<u32>, _> {
+            Err("places API down".into())
+        });
+        assert!(section.data.is_null());
+        assert_eq!(section.error.as_deref(), Some("places API down"));
+        assert_eq!(cache.facility_counts("8400621"), None);
+
+        let kinds = ["Toilet", "Lockers", "Toilet"];
+        let fetched = kinds.map(|kind| Facility { kind: kind.to_string(), name: String::new() });
+        cache.section(Section::Facilities, "8400058", || Ok(fetched));
+        let counts = cache.facility_counts("8400058").unwrap();
+        assert_eq!((counts["Toilet"], counts["Lockers"]), (2, 1));
+    }
+}
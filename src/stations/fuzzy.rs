@@ -0,0 +1,166 @@
+/// A row of a station table such as `STATIONS`: name and UIC code
+pub type StationRow<'a> = &'a (&'a str, i32);
+
+/// Lowest score a station needs to be accepted on its own
+pub const ACCEPT_SCORE: f64 = 0.9;
+
+/// Lowest score a station needs to be suggested
+pub const SUGGEST_SCORE: f64 = 0.8;
+
+/// How far the best station must score above the runner-up to be accepted
+const ACCEPT_MARGIN: f64 = 0.02;
+
+/// Taken off the score of a name that had to be cut, so "Groningen" beats
+/// "Groningen Europapark" for "groningn"
+const CUT_PENALTY: f64 = 0.03;
+
+/// Suggestions listed when no station clearly wins
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Lowercase `text` and fold away what people type differently: accents
+/// ("Liège" → "liege", decomposed marks included), apostrophes
+/// ("'s-Hertogenbosch" → "s hertogenbosch") and hyphens, which become spaces
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.to_lowercase().chars() {
+        match c {
+            'à'..='å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+            'æ' => folded.push_str("ae"),
+            'ç' | 'ć' | 'č' => folded.push('c'),
+            'ď' => folded.push('d'),
+            'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+            'ì'..='ï' | 'ī' | 'į' => folded.push('i'),
+            'ł' => folded.push('l'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ò'..='ö' | 'ø' | 'ō' | 'ő' => folded.push('o'),
+            'œ' => folded.push_str("oe"),
+            'ř' => folded.push('r'),
+            'ß' => folded.push_str("ss"),
+            'ś' | 'š' | 'ş' => folded.push('s'),
+            'ť' => folded.push('t'),
+            'ù'..='ü' | 'ū' | 'ů' | 'ű' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            '\u{300}'..='\u{36f}' | '\'' | '’' | '‘' | '`' => {}
+            '-' => folded.push(' '),
+            c => folded.push(c),
+        }
+    }
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Jaro-Winkler similarity of two strings, from 0.0 (nothing in common) to 1.0 (equal)
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == *ca) {
+            b_matched[j] = true;
+            a_matches.push(*ca);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+    let transpositions = a_matches.iter().zip(b_matches).filter(|(x, y)| x != y).count();
+
+    let m = a_matches.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64 / 2.0) / m)
+        / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// How well `query` matches a station name. The name is also cut to as many
+/// words as the query has, so "utrcht" scores against "utrecht" rather than
+/// the whole of "utrecht centraal", at a small `CUT_PENALTY`.
+pub fn score(query: &str, name: &str) -> f64 {
+    let query = fold(query);
+    let name = fold(name);
+    let full = jaro_winkler(&query, &name);
+
+    let words = query.split_whitespace().count().max(1);
+    if name.split_whitespace().count() <= words {
+        return full;
+    }
+    let head: Vec<&str> = name.split_whitespace().take(words).collect();
+    full.max(jaro_winkler(&query, &head.join(" ")) - CUT_PENALTY)
+}
+
+/// Stations ranked by their score for `query`, best first, above `SUGGEST_SCORE`
+pub fn ranked<'a>(query: &str, stations: &'a [(&'a str, i32)]) -> Vec<(f64, StationRow<'a>)> {
+    let mut scored: Vec<(f64, StationRow)> = stations
+        .iter()
+        .map(|station| (score(query, station.0), station))
+        .filter(|(score, _)| *score >= SUGGEST_SCORE)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.0.cmp(b.1.0)));
+    scored
+}
+
+/// The single station `query` most likely means, or else the best few
+/// suggestions with their score (empty when nothing comes close)
+pub fn best_match<'a>(
+    query: &str,
+    stations: &'a [(&'a str, i32)],
+) -> Result<StationRow<'a>, Vec<(f64, StationRow<'a>)>> {
+    let ranked = ranked(query, stations);
+    match ranked.as_slice() {
+        [(best, station), rest @ ..]
+            if *best >= ACCEPT_SCORE
+                && rest.first().is_none_or(|(next, _)| best - next >= ACCEPT_MARGIN) =>
+        {
+            Ok(station)
+        }
+        _ => Err(ranked.into_iter().take(MAX_SUGGESTIONS).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::STATIONS;
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("Liège-Guillemins"), "liege guillemins");
+        assert_eq!(fold("Liege\u{300}"), "liege");
+        assert_eq!(fold(" 's-Hertogenbosch "), fold("s-Hertogenbosch"));
+        assert_eq!(fold("Etten-Leur"), fold("etten leur"));
+        assert_eq!(fold("Feanwâlden"), "feanwalden");
+    }
+
+    #[test]
+    fn test_common_misspellings() {
+        assert_eq!(jaro_winkler("martha", "martha"), 1.0);
+        assert!((jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+
+        for (typo, station) in [
+            ("Utrcht Centraal", "Utrecht Centraal"),
+            ("Amsterdm Centraal", "Amsterdam Centraal"),
+            ("Rotterdam Centrall", "Rotterdam Centraal"),
+            ("Den Haag Cenraal", "Den Haag Centraal"),
+            ("Schiphol Airprot", "Schiphol Airport"),
+            ("Groningn", "Groningen"),
+        ] {
+            assert_eq!(best_match(typo, STATIONS).map(|s| s.0), Ok(station), "{}", typo);
+        }
+
+        let suggestions = best_match("Utrcht", STATIONS).unwrap_err();
+        assert!(suggestions.len() > 1);
+        assert!(suggestions.iter().all(|(_, (name, _))| name.starts_with("Utrecht")));
+        assert!(best_match("qqqq", STATIONS).unwrap_err().is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod fuzzy;
 pub mod models;
 pub mod service;
 
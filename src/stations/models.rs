@@ -1,23 +1,116 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
 pub struct ApiResponse {
     pub payload: Vec<Station>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Station {
     pub id: StationId,
     pub names: StationNames,
+    /// Only known for API results
+    #[serde(default)]
+    pub location: Option<StationLocation>,
+    /// NS station type such as "MEGA_STATION", only known for API results
+    #[serde(rename = "stationType", default)]
+    pub station_type: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct StationLocation {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct StationId {
     #[serde(rename = "uicCode")]
     pub uic_code: String,
+    /// Station abbreviation such as "UT", only known for API results
+    #[serde(default)]
+    pub code: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct StationNames {
     pub long: String,
 }
+
+/// A station that matched an ambiguous query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationCandidate {
+    pub name: String,
+    pub uic_code: i32,
+    /// How well the name matches the query, from 0.0 to 1.0
+    pub score: f64,
+}
+
+/// A station of the compiled-in table generated from data/stations.csv
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StationInfo {
+    pub name: &'static str,
+    pub uic_code: i32,
+    /// NS abbreviation such as "UT"
+    pub code: Option<&'static str>,
+    /// ISO 3166 country code such as "NL"
+    pub country: &'static str,
+    /// Latitude and longitude, where data/stations.csv has them
+    pub coordinates: Option<(f64, f64)>,
+    /// NS station type such as "MEGA_STATION", where data/stations.csv has it
+    pub station_type: Option<&'static str>,
+}
+
+/// A station as stored in the local table: name and UIC code
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationEntry {
+    pub name: String,
+    pub uic_code: i32,
+}
+
+/// A station whose name or UIC code differs between the local table and the API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationChange {
+    pub local: StationEntry,
+    pub live: StationEntry,
+}
+
+/// Differences between the local station table and the live stations API
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Local stations the API no longer knows, by name or code
+    pub removed: Vec<StationEntry>,
+    /// Same name, different UIC code
+    pub changed_uic: Vec<StationChange>,
+    /// Same UIC code, different name
+    pub renamed: Vec<StationChange>,
+    /// API stations missing from the local table
+    pub added: Vec<StationEntry>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.removed.is_empty()
+            && self.changed_uic.is_empty()
+            && self.renamed.is_empty()
+            && self.added.is_empty()
+    }
+}
+
+/// A station in the data file written by `stations sync`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StationRecord {
+    pub name: String,
+    pub uic_code: i32,
+    /// NS abbreviation such as "UT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// The station data file that lookups prefer over the compiled-in `STATIONS`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StationData {
+    pub synced_at: DateTime<Utc>,
+    pub stations: Vec<StationRecord>,
+}
@@ -1,13 +1,84 @@
-use crate::constants::STATIONS;
-use crate::stations::models::{ApiResponse, Station, StationId, StationNames};
-use std::env;
+use crate::cache::{read_owned_file, user_cache_dir};
+use crate::client::{client, Endpoint};
+use crate::constants::{CITY_GROUPS, STATIONS, STATION_CODES, STATION_SYNONYMS};
+use crate::i18n::{tr, trf, Text};
+use crate::international::Country;
+use crate::output::{CsvRow, TableRow};
+use crate::stations::fuzzy;
+use crate::stations::models::{
+    ApiResponse, Station, StationCandidate, StationChange, StationData, StationEntry, StationId,
+    StationInfo, StationNames, StationRecord, ValidationReport,
+};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Most candidates an ambiguous lookup returns
+pub const MAX_MATCHES: usize = 10;
 
 pub enum StationLookupResult {
     Single(Station),
-    Multiple(Vec<(String, i32)>), // (name, uic_code)
+    /// Ranked best match first, at most `MAX_MATCHES`
+    Multiple(Vec<StationCandidate>),
     None,
 }
 
+/// Why a station query did not settle on one station. The candidates of an
+/// ambiguous query are left for the front-end to present.
+#[derive(Debug)]
+pub enum StationLookupError {
+    NotFound { query: String },
+    Ambiguous { query: String, matches: Vec<StationCandidate> },
+    NotAUicCode { query: String },
+    /// Only stations abroad match, which `--include-foreign` would search
+    OnlyAbroad { query: String },
+}
+
+impl fmt::Display for StationLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StationLookupError::NotFound { .. } => write!(f, "❌ {}", tr(Text::NoStationsFound)),
+            StationLookupError::Ambiguous { .. } => write!(f, "⚠️ {}", tr(Text::RefineQuery)),
+            StationLookupError::NotAUicCode { query } => {
+                write!(f, "❌ {}", trf(Text::NotAUicCode, &[query]))
+            }
+            StationLookupError::OnlyAbroad { query } => {
+                write!(f, "❌ {}", trf(Text::OnlyForeignMatches, &[query]))
+            }
+        }
+    }
+}
+
+impl std::error::Error for StationLookupError {}
+
+/// Settle a lookup on its single station, or say why it has none
+fn settle(query: &str, result: StationLookupResult) -> Result<Station, StationLookupError> {
+    let query = query.to_string();
+    match result {
+        StationLookupResult::Single(station) => Ok(station),
+        StationLookupResult::None => Err(StationLookupError::NotFound { query }),
+        StationLookupResult::Multiple(matches) => {
+            Err(StationLookupError::Ambiguous { query, matches })
+        }
+    }
+}
+
+/// Stations ranked by how well their name matches `query`, at most `MAX_MATCHES`
+fn rank(query: &str, stations: impl IntoIterator<Item = (String, i32)>) -> Vec<StationCandidate> {
+    let mut ranked: Vec<StationCandidate> = stations
+        .into_iter()
+        .map(|(name, uic_code)| StationCandidate {
+            score: fuzzy::score(query, &name),
+            name,
+            uic_code,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    ranked.truncate(MAX_MATCHES);
+    ranked
+}
+
 #[allow(dead_code)]
 pub fn pick_station(query: &str) -> Result<Station, Box<dyn std::error::Error>> {
     let encoded_query = urlencoding::encode(query);
@@ -16,109 +87,907 @@ pub fn pick_station(query: &str) -> Result<Station, Box<dyn std::error::Error>>
         encoded_query
     );
 
-    let ns_api_token = env::var("NS_API_TOKEN").map_err(|_| "NS_API_TOKEN not found")?;
+    let body = client().send(Endpoint::Stations, client().get(&url)?)?;
+
+    let response: ApiResponse = serde_json::from_str(&body)?;
+
+    let result = match response.payload.len() {
+        0 => StationLookupResult::None,
+        1 => StationLookupResult::Single(response.payload.into_iter().next().unwrap()),
+        _ => StationLookupResult::Multiple(rank(
+            query,
+            response
+                .payload
+                .into_iter()
+                .filter_map(|s| Some((s.names.long, s.id.uic_code.parse().ok()?))),
+        )),
+    };
+    Ok(settle(query, result)?)
+}
 
-    let body: String = ureq::get(url)
-        .header("Cache-Control", "no-cache")
-        .header("Ocp-Apim-Subscription-Key", &ns_api_token)
-        .call()?
-        .body_mut()
-        .read_to_string()?;
+/// Every station the live stations API knows
+pub fn fetch_all_stations() -> Result<Vec<Station>, Box<dyn std::error::Error>> {
+    let url = "https://gateway.apiportal.ns.nl/nsapp-stations/v3";
 
-    let response: ApiResponse = serde_json::from_str(&body).unwrap();
+    let body = client().send(Endpoint::Stations, client().get(url)?)?;
 
-    match response.payload.len() {
-        0 => Err("❌ No stations found for your query".into()),
-        1 => Ok(response.payload.into_iter().next().unwrap()),
-        _ => {
-            println!(
-                "Your query `{}` was ambiguous, multiple stations matched:",
-                query
-            );
-            for s in &response.payload {
-                println!("{} - {}", s.id.uic_code, s.names.long);
+    let response: ApiResponse = serde_json::from_str(&body)?;
+    Ok(response.payload)
+}
+
+/// Compare the local station table against the live API list.
+/// Stations are matched by name and by UIC code, so a station whose code
+/// changed shows up once under `changed_uic` rather than as removed and added.
+pub fn validate_stations(local: &[(&str, i32)], live: &[Station]) -> ValidationReport {
+    let live: Vec<StationEntry> = live
+        .iter()
+        .filter_map(|station| {
+            Some(StationEntry {
+                name: station.names.long.clone(),
+                uic_code: station.id.uic_code.parse().ok()?,
+            })
+        })
+        .collect();
+    let local: Vec<StationEntry> = local
+        .iter()
+        .map(|(name, code)| StationEntry {
+            name: name.to_string(),
+            uic_code: *code,
+        })
+        .collect();
+
+    let mut report = ValidationReport::default();
+    for entry in &local {
+        let by_name = live.iter().find(|l| l.name == entry.name);
+        let by_code = live.iter().find(|l| l.uic_code == entry.uic_code);
+        match (by_name, by_code) {
+            (Some(l), _) if l.uic_code == entry.uic_code => {}
+            (Some(l), _) => report.changed_uic.push(StationChange {
+                local: entry.clone(),
+                live: l.clone(),
+            }),
+            (None, Some(l)) => report.renamed.push(StationChange {
+                local: entry.clone(),
+                live: l.clone(),
+            }),
+            (None, None) => report.removed.push(entry.clone()),
+        }
+    }
+    report.added = live
+        .iter()
+        .filter(|l| !local.iter().any(|e| e.name == l.name || e.uic_code == l.uic_code))
+        .cloned()
+        .collect();
+
+    report
+}
+
+/// The live station list as rows for data/stations.csv, from which build.rs
+/// generates `STATIONS`; names with a comma or quote are quoted
+pub fn stations_to_csv(stations: &[Station]) -> String {
+    let rows: Vec<Station> = stations
+        .iter()
+        .filter(|station| station.id.uic_code.parse::<i32>().is_ok())
+        .cloned()
+        .collect();
+    crate::output::to_csv(&rows)
+}
+
+/// A row of data/stations.csv
+impl CsvRow for Station {
+    fn headers() -> &'static [&'static str] {
+        <StationInfo as CsvRow>::headers()
+    }
+
+    fn row(&self) -> Vec<String> {
+        let (lat, lng) = self
+            .location
+            .map(|l| (l.lat.to_string(), l.lng.to_string()))
+            .unwrap_or_default();
+        vec![
+            self.names.long.clone(),
+            self.id.uic_code.clone(),
+            self.id.code.clone().unwrap_or_default(),
+            Country::of(self).map(|c| c.iso_code()).unwrap_or_default().to_string(),
+            lat,
+            lng,
+            self.station_type.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl CsvRow for StationInfo {
+    fn headers() -> &'static [&'static str] {
+        &["name", "uic_code", "code", "country", "lat", "lng", "type"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let (lat, lng) = self
+            .coordinates
+            .map(|(lat, lng)| (lat.to_string(), lng.to_string()))
+            .unwrap_or_default();
+        vec![
+            self.name.to_string(),
+            self.uic_code.to_string(),
+            self.code.unwrap_or_default().to_string(),
+            self.country.to_string(),
+            lat,
+            lng,
+            self.station_type.unwrap_or_default().to_string(),
+        ]
+    }
+}
+
+impl TableRow for StationInfo {
+    fn headers() -> &'static [&'static str] {
+        &["Station", "UIC", "Code", "Country", "Coordinates"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.to_string(),
+            self.uic_code.to_string(),
+            self.code.unwrap_or("-").to_string(),
+            self.country.to_string(),
+            match self.coordinates {
+                Some((lat, lng)) => format!("{:.4}, {:.4}", lat, lng),
+                None => "-".to_string(),
+            },
+        ]
+    }
+}
+
+/// Station abbreviation (e.g. "UT") for a UIC code, which the places API needs
+pub fn station_code(station: &Station) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(code) = &station.id.code {
+        return Ok(code.clone());
+    }
+
+    let url = "https://gateway.apiportal.ns.nl/nsapp-stations/v3";
+    let request = client()
+        .get(url)?
+        .query("q", &station.names.long)
+        .query("limit", "10");
+    let body = client().send(Endpoint::Stations, request)?;
+
+    let response: ApiResponse = serde_json::from_str(&body)?;
+    response
+        .payload
+        .into_iter()
+        .find(|s| s.id.uic_code == station.id.uic_code)
+        .and_then(|s| s.id.code)
+        .ok_or_else(|| format!("No station code known for {}", station.names.long).into())
+}
+
+static UIC_ONLY: OnceLock<bool> = OnceLock::new();
+
+/// Treat every station query of the rest of the process as a UIC code,
+/// skipping name resolution (`--uic`). Only the first call has effect.
+pub fn set_uic_only(uic_only: bool) {
+    let _ = UIC_ONLY.set(uic_only);
+}
+
+fn uic_only() -> bool {
+    UIC_ONLY.get().copied().unwrap_or_default()
+}
+
+static INCLUDE_FOREIGN: OnceLock<bool> = OnceLock::new();
+
+/// Let partial and fuzzy station matches of the rest of the process include
+/// stations abroad (`--include-foreign`). Only the first call has effect.
+pub fn set_include_foreign(include_foreign: bool) {
+    let _ = INCLUDE_FOREIGN.set(include_foreign);
+}
+
+fn include_foreign() -> bool {
+    INCLUDE_FOREIGN.get().copied().unwrap_or_default()
+}
+
+/// Dutch stations are numbered 84xxxxx
+fn is_domestic(uic_code: i32) -> bool {
+    uic_code.to_string().starts_with("84")
+}
+
+fn is_uic_code(query: &str) -> bool {
+    !query.is_empty() && query.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Station data file written by `stations sync`: `NS_STATIONS_FILE`, or
+/// stations.json in the user's cache directory; None without either
+pub fn station_data_path() -> Option<PathBuf> {
+    match std::env::var_os("NS_STATIONS_FILE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(user_cache_dir()?.join("stations.json")),
+    }
+}
+
+/// The live station list as a `StationData` file
+pub fn station_data(stations: &[Station], synced_at: DateTime<Utc>) -> StationData {
+    StationData {
+        synced_at,
+        stations: stations
+            .iter()
+            .filter_map(|station| {
+                Some(StationRecord {
+                    name: station.names.long.clone(),
+                    uic_code: station.id.uic_code.parse().ok()?,
+                    code: station.id.code.clone(),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// The stations and abbreviations every local lookup reads
+struct LocalTable {
+    stations: Vec<(&'static str, i32)>,
+    /// The Dutch part of `stations`, which partial matches search by default
+    domestic: Vec<(&'static str, i32)>,
+    codes: Vec<(&'static str, &'static str)>,
+}
+
+impl LocalTable {
+    fn compiled() -> Self {
+        Self {
+            stations: STATIONS.to_vec(),
+            domestic: Vec::new(),
+            codes: STATION_CODES.to_vec(),
+        }
+        .with_domestic()
+    }
+
+    fn with_domestic(mut self) -> Self {
+        self.domestic = self
+            .stations
+            .iter()
+            .filter(|(_, code)| is_domestic(*code))
+            .copied()
+            .collect();
+        self
+    }
+
+    /// Stations partial and fuzzy matches search
+    fn searchable(&self, include_foreign: bool) -> &[(&'static str, i32)] {
+        if include_foreign {
+            &self.stations
+        } else {
+            &self.domestic
+        }
+    }
+
+    /// Table of a synced data file. The names are leaked once so the table
+    /// has the same `'static` shape as `STATIONS`; `STATION_CODES` still
+    /// fills in abbreviations the file lacks.
+    fn synced(data: StationData) -> Self {
+        let leak = |text: String| -> &'static str { Box::leak(text.into_boxed_str()) };
+        let mut table = Self {
+            stations: Vec::with_capacity(data.stations.len()),
+            domestic: Vec::new(),
+            codes: Vec::new(),
+        };
+        for record in data.stations {
+            let name = leak(record.name);
+            table.stations.push((name, record.uic_code));
+            if let Some(code) = record.code {
+                table.codes.push((leak(code.to_uppercase()), name));
             }
-            Err("⚠️ Multiple stations matched. Please refine your query.".into())
         }
+        table.codes.extend_from_slice(STATION_CODES);
+        table.with_domestic()
     }
 }
 
-#[allow(dead_code)]
-pub fn get_all_stations() -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://gateway.apiportal.ns.nl/nsapp-stations/v3",);
+fn local_table() -> &'static LocalTable {
+    static TABLE: OnceLock<LocalTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let Some(path) = station_data_path() else {
+            return LocalTable::compiled();
+        };
+        let Some(json) = read_owned_file(&path) else {
+            return LocalTable::compiled();
+        };
+        match serde_json::from_str::<StationData>(&json) {
+            Ok(data) if !data.stations.is_empty() => LocalTable::synced(data),
+            Ok(_) => LocalTable::compiled(),
+            Err(e) => {
+                eprintln!("⚠️ Ignoring station data file {}: {}", path.display(), e);
+                LocalTable::compiled()
+            }
+        }
+    })
+}
+
+/// Stations the local lookup searches: the `stations sync` data file when
+/// there is one, the compiled-in `STATIONS` otherwise
+pub fn station_table() -> &'static [(&'static str, i32)] {
+    &local_table().stations
+}
+
+/// Station from the local table, with its abbreviation when one is known
+fn local_station(name: &str, uic_code: i32) -> Station {
+    Station {
+        id: StationId {
+            uic_code: uic_code.to_string(),
+            code: local_table()
+                .codes
+                .iter()
+                .find(|(_, station)| *station == name)
+                .map(|(code, _)| code.to_string()),
+        },
+        names: StationNames {
+            long: name.to_string(),
+        },
+        location: None,
+        station_type: None,
+    }
+}
+
+/// Station from the local table by its UIC code
+pub fn station_by_uic(uic_code: &str) -> Option<Station> {
+    station_table()
+        .iter()
+        .find(|(_, code)| code.to_string() == uic_code)
+        .map(|(name, code)| local_station(name, *code))
+}
+
+/// Station from the local table by its NS abbreviation such as "asd" (`STATION_CODES`)
+pub fn station_by_code(query: &str) -> Option<Station> {
+    let (_, name) = local_table()
+        .codes
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(query.trim()))?;
+    station_table()
+        .iter()
+        .find(|(station, _)| station == name)
+        .map(|(name, code)| local_station(name, *code))
+}
 
-    let ns_api_token = env::var("NS_API_TOKEN").map_err(|_| "NS_API_TOKEN not found")?;
+/// Synonyms file: `NSTIMES_SYNONYMS_FILE`, or synonyms.txt in the user's
+/// cache directory; None without either
+pub fn synonyms_path() -> Option<PathBuf> {
+    match std::env::var_os("NSTIMES_SYNONYMS_FILE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(user_cache_dir()?.join("synonyms.txt")),
+    }
+}
+
+/// Extra station synonyms from `NSTIMES_SYNONYMS`,
+/// e.g. `Dam = Amsterdam Centraal; Zuidas = Amsterdam Zuid`, followed by
+/// those in the `synonyms_path()` file, one per line
+fn user_synonyms() -> &'static [(String, String)] {
+    static SYNONYMS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    SYNONYMS.get_or_init(|| {
+        let from_env = std::env::var("NSTIMES_SYNONYMS")
+            .ok()
+            .map(|spec| ("NSTIMES_SYNONYMS".to_string(), spec));
+        let from_file = synonyms_path().and_then(|path| {
+            read_owned_file(&path).map(|content| (path.display().to_string(), content))
+        });
+        from_env
+            .into_iter()
+            .chain(from_file)
+            .flat_map(|(source, spec)| {
+                parse_synonyms(&spec).unwrap_or_else(|e| {
+                    eprintln!("⚠️ Ignoring {}: {}", source, e);
+                    Vec::new()
+                })
+            })
+            .collect()
+    })
+}
+
+/// Parse `synonym = station` pairs separated by `;` or newlines, skipping
+/// `#` comments
+pub fn parse_synonyms(spec: &str) -> Result<Vec<(String, String)>, String> {
+    spec.split([';', '\n'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        .map(|entry| {
+            let (synonym, station) = entry
+                .split_once('=')
+                .map(|(synonym, station)| (synonym.trim(), station.trim()))
+                .filter(|(synonym, station)| !synonym.is_empty() && !station.is_empty())
+                .ok_or_else(|| format!("entry '{}' is not 'synonym = station'", entry))?;
+            Ok((synonym.to_string(), station.to_string()))
+        })
+        .collect()
+}
+
+/// The station a colloquial name stands for: `NSTIMES_SYNONYMS`, then
+/// `STATION_SYNONYMS`, then a trailing "CS" read as Centraal ("Utrecht CS").
+/// `query` is folded; the target is any exact station name or abbreviation.
+fn synonym_station(query: &str) -> Option<Station> {
+    let target = user_synonyms()
+        .iter()
+        .map(|(synonym, station)| (synonym.as_str(), station.as_str()))
+        .chain(STATION_SYNONYMS.iter().copied())
+        .find(|(synonym, _)| fuzzy::fold(synonym) == query)
+        .map(|(_, station)| fuzzy::fold(station))
+        .or_else(|| query.strip_suffix(" cs").map(|city| format!("{} centraal", city)))?;
+
+    station_table()
+        .iter()
+        .find(|(name, _)| fuzzy::fold(name) == target)
+        .map(|(name, code)| local_station(name, *code))
+        .or_else(|| station_by_code(&target))
+}
+
+/// Member stations of a city meta-station such as "amsterdam" (`CITY_GROUPS`),
+/// `None` when the query is not a city name
+pub fn city_group(query: &str) -> Option<Vec<Station>> {
+    let (_, members) = CITY_GROUPS
+        .iter()
+        .find(|(city, _)| city.eq_ignore_ascii_case(query.trim()))?;
+
+    Some(
+        members
+            .iter()
+            .filter_map(|member| station_table().iter().find(|(name, _)| name == member))
+            .map(|(name, code)| local_station(name, *code))
+            .collect(),
+    )
+}
+
+/// Prefix that makes a query a station group: "group:zwolle"
+pub const GROUP_PREFIX: &str = "group:";
 
-    let body: String = ureq::get(url)
-        .header("Cache-Control", "no-cache")
-        .header("Ocp-Apim-Subscription-Key", &ns_api_token)
-        .call()?
-        .body_mut()
-        .read_to_string()?;
+/// The stations a group query stands for: a `CITY_GROUPS` city by its plain
+/// name ("amsterdam"), or after `GROUP_PREFIX` every station named after the
+/// place ("group:zwolle" is Zwolle and Zwolle Stadshagen). `None` when the
+/// query is not a group.
+pub fn station_group(query: &str) -> Option<Vec<Station>> {
+    let query = query.trim();
+    let place = match query.get(..GROUP_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(GROUP_PREFIX) => &query[GROUP_PREFIX.len()..],
+        _ => return city_group(query),
+    };
+
+    let place = fuzzy::fold(place);
+    let member = format!("{} ", place);
+    Some(
+        local_table()
+            .searchable(include_foreign())
+            .iter()
+            .filter(|(name, _)| {
+                let name = fuzzy::fold(name);
+                name == place || name.starts_with(&member)
+            })
+            .map(|(name, code)| local_station(name, *code))
+            .collect(),
+    )
+}
 
-    let response: ApiResponse = serde_json::from_str(&body).unwrap();
-    for s in &response.payload {
-        println!("(\"{}\", {}),", s.names.long, s.id.uic_code);
+/// Every station a query stands for: the members of a station group, or the
+/// one station it names
+pub fn pick_stations(query: &str) -> Result<Vec<Station>, StationLookupError> {
+    match station_group(query) {
+        Some(members) if members.is_empty() => Err(StationLookupError::NotFound {
+            query: query.to_string(),
+        }),
+        Some(members) => Ok(members),
+        None => Ok(vec![pick_station_local(query)?]),
     }
-    return Ok(());
 }
+
+/// Partial-match tiers of the local lookup over folded names, tightest
+/// first: prefix, start of a word, anywhere
+const MATCH_TIERS: [fn(&str, &str) -> bool; 3] = [
+    |name, query| name.starts_with(query),
+    |name, query| name.contains(&format!(" {}", query)),
+    |name, query| name.contains(query),
+];
+
+/// Stations of the first `MATCH_TIERS` tier with any match for the folded
+/// query, so "bergen" means Bergen op Zoom rather than every name containing it
+fn partial_matches<'a>(q: &str, stations: &'a [(&'a str, i32)]) -> Vec<&'a (&'a str, i32)> {
+    let folded: Vec<(String, &(&str, i32))> =
+        stations.iter().map(|station| (fuzzy::fold(station.0), station)).collect();
+    MATCH_TIERS
+        .iter()
+        .map(|matches| {
+            folded
+                .iter()
+                .filter(|(name, _)| matches(name, q))
+                .map(|(_, station)| *station)
+                .collect::<Vec<_>>()
+        })
+        .find(|tier| !tier.is_empty())
+        .unwrap_or_default()
+}
+
 pub fn lookup_station_local(query: &str) -> StationLookupResult {
-    let q = query.to_lowercase();
+    lookup_in(query, include_foreign())
+}
 
-    // 1️⃣ Exact (case-insensitive) match first
-    if let Some((name, code)) = STATIONS.iter().find(|(key, _)| key.to_lowercase() == q) {
-        return StationLookupResult::Single(Station {
+/// `lookup_station_local()`, with partial and fuzzy matches limited to Dutch
+/// stations unless `include_foreign`. Full names and codes match everywhere.
+fn lookup_in(query: &str, include_foreign: bool) -> StationLookupResult {
+    let q = fuzzy::fold(query);
+
+    // 0️⃣ UIC codes and NS abbreviations are never ambiguous. All digits is a
+    // UIC code, taken as is with --uic so even a station missing from the
+    // table resolves
+    if is_uic_code(&q) {
+        return match station_by_uic(&q) {
+            Some(station) => StationLookupResult::Single(station),
+            None if uic_only() => {
+                StationLookupResult::Single(local_station(&q, q.parse().unwrap_or_default()))
+            }
+            None => StationLookupResult::None,
+        };
+    }
+
+    if let Some(station) = station_by_code(query) {
+        return StationLookupResult::Single(station);
+    }
+
+    // 1️⃣ Exact match first, ignoring case, accents, apostrophes and hyphens
+    if let Some((name, code)) = station_table().iter().find(|(key, _)| fuzzy::fold(key) == q) {
+        return StationLookupResult::Single(local_station(name, *code));
+    }
+
+    // Colloquial names such as "Den Bosch" before any partial or fuzzy match
+    if let Some(station) = synonym_station(&q) {
+        return StationLookupResult::Single(station);
+    }
+    let stations = local_table().searchable(include_foreign);
+
+    // 2️⃣ Then partial matches by tier
+    let matches = partial_matches(&q, stations);
+    match matches.len() {
+        // 3️⃣ No name contains the query, so it may be misspelled: take the
+        // best fuzzy match, or offer the closest stations
+        0 => match fuzzy::best_match(query, stations) {
+            Ok((name, code)) => StationLookupResult::Single(local_station(name, *code)),
+            Err(suggestions) if suggestions.is_empty() => StationLookupResult::None,
+            Err(suggestions) => StationLookupResult::Multiple(
+                suggestions
+                    .into_iter()
+                    .map(|(score, (name, code))| StationCandidate {
+                        name: name.to_string(),
+                        uic_code: *code,
+                        score,
+                    })
+                    .collect(),
+            ),
+        },
+        1 => {
+            let (name, code) = *matches[0];
+            StationLookupResult::Single(local_station(name, code))
+        }
+        _ => StationLookupResult::Multiple(rank(
+            query,
+            matches.iter().map(|(name, code)| (name.to_string(), *code)),
+        )),
+    }
+}
+
+/// Resolve a query against the local table. An ambiguous query fails with
+/// its ranked candidates in `StationLookupError::Ambiguous`.
+pub fn pick_station_local(query: &str) -> Result<Station, StationLookupError> {
+    if uic_only() && !is_uic_code(query.trim()) {
+        return Err(StationLookupError::NotAUicCode {
+            query: query.to_string(),
+        });
+    }
+    pick_in(query, include_foreign())
+}
+
+fn pick_in(query: &str, include_foreign: bool) -> Result<Station, StationLookupError> {
+    let result = lookup_in(query, include_foreign);
+    // A name only stations abroad contain is not worth Dutch fuzzy suggestions
+    let q = fuzzy::fold(query);
+    if !include_foreign
+        && !matches!(result, StationLookupResult::Single(_))
+        && partial_matches(&q, local_table().searchable(false)).is_empty()
+        && !partial_matches(&q, station_table()).is_empty()
+    {
+        return Err(StationLookupError::OnlyAbroad {
+            query: query.to_string(),
+        });
+    }
+    settle(query, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::STATION_INFO;
+    use crate::stations::models::StationLocation;
+
+    #[test]
+    fn test_validate_stations() {
+        let live = |name: &str, code: &str| Station {
             id: StationId {
                 uic_code: code.to_string(),
+                code: None,
             },
             names: StationNames {
                 long: name.to_string(),
             },
-        });
+            location: None,
+            station_type: None,
+        };
+        let local = [
+            ("Gouda", 8400258),
+            ("Den Haag Laan v NOI", 8400380),
+            ("Utrecht Centraal", 1),
+            ("Gone", 8400999),
+        ];
+        let api = [
+            live("Gouda", "8400258"),
+            live("Den Haag Laan van NOI", "8400380"),
+            live("Utrecht Centraal", "8400621"),
+            live("Nieuw", "8400998"),
+        ];
+
+        let report = validate_stations(&local, &api);
+        assert_eq!(report.removed[0].name, "Gone");
+        assert_eq!(report.changed_uic[0].live.uic_code, 8400621);
+        assert_eq!(report.renamed[0].live.name, "Den Haag Laan van NOI");
+        assert_eq!(report.added[0].name, "Nieuw");
+        assert!(validate_stations(&local[..1], &api[..1]).is_clean());
+
+        let mut gouda = live("Gouda", "8400258");
+        gouda.id.code = Some("GD".to_string());
+        gouda.location = Some(StationLocation { lat: 52.0175, lng: 4.7046 });
+        gouda.station_type = Some("INTERCITY_STATION".to_string());
+        let mut quoted = live("Station, \"Noord\"", "8400999");
+        quoted.location = Some(StationLocation { lat: 52.1, lng: 5.2 });
+        assert_eq!(
+            stations_to_csv(&[gouda, live("Gone", "not-a-code"), quoted]),
+            "name,uic_code,code,country,lat,lng,type\n\
+             Gouda,8400258,GD,NL,52.0175,4.7046,INTERCITY_STATION\n\
+             \"Station, \"\"Noord\"\"\",8400999,,NL,52.1,5.2,\n"
+        );
     }
 
-    // 2️⃣ Fall back to case-insensitive substring matches
-    let matches: Vec<&(&str, i32)> = STATIONS
-        .iter()
-        .filter(|(key, _)| key.to_lowercase().contains(&q))
-        .collect();
+    #[test]
+    fn test_city_groups_only_list_known_stations() {
+        for (city, members) in CITY_GROUPS {
+            let stations = city_group(&city.to_uppercase()).unwrap();
+            assert_eq!(stations.len(), members.len(), "unknown member station in {}", city);
+        }
+        assert!(city_group("Amsterdam Zuid").is_none());
+    }
 
-    match matches.len() {
-        0 => StationLookupResult::None,
-        1 => {
-            let (name, code) = *matches[0];
-            StationLookupResult::Single(Station {
-                id: StationId {
-                    uic_code: code.to_string(),
-                },
-                names: StationNames {
-                    long: name.to_string(),
-                },
-            })
+    #[test]
+    fn test_synced_station_data() {
+        let station = |name: &str, uic: &str, code: Option<&str>| Station {
+            id: StationId {
+                uic_code: uic.to_string(),
+                code: code.map(str::to_string),
+            },
+            names: StationNames {
+                long: name.to_string(),
+            },
+            location: None,
+            station_type: None,
+        };
+        let live = [
+            station("Utrecht Centraal", "8400621", Some("UT")),
+            station("Nieuw Station", "8400998", Some("nws")),
+            station("Bad code", "not-a-number", None),
+        ];
+        let synced_at = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z").unwrap().to_utc();
+
+        let data = station_data(&live, synced_at);
+        assert_eq!(data.stations.len(), 2);
+        let json = serde_json::to_string(&data).unwrap();
+        let data: StationData = serde_json::from_str(&json).unwrap();
+
+        let table = LocalTable::synced(data);
+        assert_eq!(table.stations, [("Utrecht Centraal", 8400621), ("Nieuw Station", 8400998)]);
+        assert!(table.codes.contains(&("NWS", "Nieuw Station")));
+        assert!(table.codes.contains(&("ASD", "Amsterdam Centraal")));
+    }
+
+    #[test]
+    fn test_station_synonyms() {
+        for (synonym, station) in STATION_SYNONYMS {
+            assert_eq!(pick_station_local(synonym).unwrap().names.long, *station, "{}", synonym);
         }
-        _ => {
-            let match_list: Vec<(String, i32)> = matches
-                .iter()
-                .map(|(name, code)| (name.to_string(), *code))
-                .collect();
-            StationLookupResult::Multiple(match_list)
+        for (query, station) in [
+            ("den bosch", "'s-Hertogenbosch"),
+            ("hs", "Den Haag HS"),
+            ("Amsterdam CS", "Amsterdam Centraal"),
+            ("utrecht cs", "Utrecht Centraal"),
+        ] {
+            assert_eq!(pick_station_local(query).unwrap().names.long, station, "{}", query);
         }
+
+        assert_eq!(
+            parse_synonyms(" Dam = Amsterdam Centraal; Zuidas=ASDZ; ").unwrap(),
+            [
+                ("Dam".to_string(), "Amsterdam Centraal".to_string()),
+                ("Zuidas".to_string(), "ASDZ".to_string()),
+            ]
+        );
+        let file = "# synonyms.txt\nDam = Amsterdam Centraal\r\n\nHS = Den Haag HS\n";
+        assert_eq!(
+            parse_synonyms(file).unwrap(),
+            [
+                ("Dam".to_string(), "Amsterdam Centraal".to_string()),
+                ("HS".to_string(), "Den Haag HS".to_string()),
+            ]
+        );
+        assert!(parse_synonyms("Dam").is_err());
+        assert!(parse_synonyms("Dam = ").is_err());
     }
-}
 
-pub fn pick_station_local(query: &str) -> Result<Station, Box<dyn std::error::Error>> {
-    match lookup_station_local(query) {
-        StationLookupResult::Single(station) => Ok(station),
-        StationLookupResult::None => Err("❌ No stations found for your query".into()),
-        StationLookupResult::Multiple(matches) => {
-            println!(
-                "Your query `{}` was ambiguous, multiple stations matched:",
-                query
+    #[test]
+    fn test_station_groups() {
+        let names = |stations: Vec<Station>| -> Vec<String> {
+            stations.into_iter().map(|s| s.names.long).collect()
+        };
+        let mut zwolle = names(station_group("GROUP:zwolle").unwrap());
+        zwolle.sort();
+        assert_eq!(zwolle, ["Zwolle", "Zwolle Stadshagen"]);
+
+        let amsterdam = names(station_group("group:Amsterdam").unwrap());
+        assert!(amsterdam.len() > 4 && amsterdam.contains(&"Amsterdam RAI".to_string()));
+        assert_eq!(station_group("amsterdam").unwrap().len(), 4);
+        assert!(station_group("Utrecht Centraal").is_none());
+
+        assert_eq!(pick_stations("Utrecht Centraal").unwrap().len(), 1);
+        assert!(matches!(
+            pick_stations("group:nowhere"),
+            Err(StationLookupError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_station_codes() {
+        for (code, _) in STATION_CODES {
+            assert!(station_by_code(code).is_some(), "unknown station for {}", code);
+        }
+        for (query, name) in [
+            ("asd", "Amsterdam Centraal"),
+            ("Ut", "Utrecht Centraal"),
+            ("GVC", "Den Haag Centraal"),
+        ] {
+            match lookup_station_local(query) {
+                StationLookupResult::Single(station) => {
+                    assert_eq!(station.names.long, name);
+                    assert_eq!(station.id.code.as_deref(), Some(query.to_uppercase().as_str()));
+                }
+                _ => panic!("{} did not resolve", query),
+            }
+        }
+        assert_eq!(station_by_uic("8400282").unwrap().id.code.as_deref(), Some("GVC"));
+        assert!(station_by_code("Utrecht").is_none());
+
+        assert_eq!(STATION_INFO.len(), STATIONS.len());
+        assert_eq!(
+            crate::output::to_csv(STATION_INFO),
+            include_str!("../../data/stations.csv"),
+            "the export no longer round-trips data/stations.csv"
+        );
+        let utrecht = STATION_INFO.iter().find(|s| s.uic_code == 8400621).unwrap();
+        assert_eq!((utrecht.code, utrecht.country), (Some("UT"), "NL"));
+    }
+
+    #[test]
+    fn test_dutch_stations_are_complete() {
+        const STATION_TYPES: [&str; 8] = [
+            "MEGA_STATION",
+            "KNOOPPUNT_INTERCITY_STATION",
+            "INTERCITY_STATION",
+            "KNOOPPUNT_SNELTREIN_STATION",
+            "SNELTREIN_STATION",
+            "KNOOPPUNT_STOPTREIN_STATION",
+            "STOPTREIN_STATION",
+            "FACULTATIEF_STATION",
+        ];
+        let mut codes = std::collections::HashSet::new();
+        for station in STATION_INFO.iter().filter(|s| s.country == "NL") {
+            let code = station.code.unwrap_or_else(|| panic!("{} has no code", station.name));
+            assert!(codes.insert(code), "{} reuses code {}", station.name, code);
+            let (lat, lng) = station
+                .coordinates
+                .unwrap_or_else(|| panic!("{} has no coordinates", station.name));
+            assert!(
+                (50.7..53.6).contains(&lat) && (3.3..7.3).contains(&lng),
+                "{} lies outside the Netherlands",
+                station.name
+            );
+            assert!(
+                station.station_type.is_some_and(|t| STATION_TYPES.contains(&t)),
+                "{} has no known station type",
+                station.name
             );
-            for (name, code) in matches {
-                println!("{} - {}", code, name);
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_lookup_ranks_candidates() {
+        let Err(StationLookupError::Ambiguous { query, matches }) = pick_station_local("Amsterdam")
+        else {
+            panic!("Amsterdam should be ambiguous");
+        };
+        assert_eq!(query, "Amsterdam");
+        assert_eq!(matches.len(), MAX_MATCHES);
+        assert!(matches.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(matches.iter().all(|m| m.name.starts_with("Amsterdam")));
+
+        assert!(matches!(
+            pick_station_local("qqqq"),
+            Err(StationLookupError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tiered_matching() {
+        for (query, name) in [
+            ("Duivendrecht", "Duivendrecht"),
+            ("Delft", "Delft"),
+            ("Bergen", "Bergen op Zoom"),
+            ("laan", "Den Haag Laan v NOI"),
+            ("Etten", "Etten-Leur"),
+        ] {
+            match lookup_station_local(query) {
+                StationLookupResult::Single(station) => assert_eq!(station.names.long, name),
+                _ => panic!("{} did not resolve", query),
+            }
+        }
+        match lookup_station_local("Leiden") {
+            StationLookupResult::Multiple(matches) => {
+                assert!(matches.iter().all(|m| m.name.starts_with("Leiden")))
+            }
+            _ => panic!("Leiden should be ambiguous"),
+        }
+    }
+
+    #[test]
+    fn test_foreign_stations() {
+        let single = |result| match result {
+            StationLookupResult::Single(station) => station.names.long,
+            _ => panic!("expected a single station"),
+        };
+        assert_eq!(single(lookup_in("Brussel Zuid", false)), "Brussel-Zuid");
+        assert_eq!(single(lookup_in("8008094", false)), "Düsseldorf Hbf");
+        assert!(!matches!(lookup_in("Antwerpen", false), StationLookupResult::Single(_)));
+        match lookup_in("Antwerpen", true) {
+            StationLookupResult::Multiple(matches) => {
+                assert!(matches.iter().any(|m| m.name == "Antwerpen-Centraal"))
+            }
+            _ => panic!("Antwerpen should match several stations abroad"),
+        }
+        assert_eq!(single(lookup_in("Dusseldorf Hbf", true)), "Düsseldorf Hbf");
+
+        assert!(matches!(
+            pick_in("Antwerpen", false),
+            Err(StationLookupError::OnlyAbroad { .. })
+        ));
+        assert!(matches!(pick_in("qqqq", false), Err(StationLookupError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_lookup_by_uic_code() {
+        match lookup_station_local(" 8400058 ") {
+            StationLookupResult::Single(station) => {
+                assert_eq!(station.names.long, "Amsterdam Centraal");
+                assert_eq!(station.id.code.as_deref(), Some("ASD"));
+            }
+            _ => panic!("8400058 did not resolve"),
+        }
+        assert!(matches!(lookup_station_local("8400000"), StationLookupResult::None));
+        assert!(is_uic_code("551009") && !is_uic_code("84a") && !is_uic_code(""));
+    }
+
+    #[test]
+    fn test_lookup_ignores_accents_and_apostrophes() {
+        for (query, name) in [
+            ("s-Hertogenbosch", "'s-Hertogenbosch"),
+            ("’s Hertogenbosch", "'s-Hertogenbosch"),
+            ("Etten Leur", "Etten-Leur"),
+            ("Liege-Guillemins", "Liège-Guillemins"),
+            ("koln hbf", "Köln Hbf"),
+        ] {
+            match lookup_station_local(query) {
+                StationLookupResult::Single(station) => assert_eq!(station.names.long, name),
+                _ => panic!("{} did not resolve", query),
             }
-            Err("⚠️ Multiple stations matched. Please refine your query.".into())
         }
     }
 }
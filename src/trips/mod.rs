@@ -1,4 +1,5 @@
 pub mod models;
 pub mod service;
 
+pub use models::{train_type_allowed, CancelledMode, TripQuery, TripSort};
 pub use service::*;
@@ -1,14 +1,114 @@
+use chrono::{DateTime, FixedOffset};
 use serde::Deserialize;
 
+/// Search parameters for a trips query beyond origin and destination
+#[derive(Debug, Clone, Default)]
+pub struct TripQuery {
+    /// Maximum number of trips to return
+    pub limit: Option<usize>,
+    /// Depart at this moment instead of now
+    pub date_time: Option<DateTime<FixedOffset>>,
+    /// Treat `date_time` as the latest arrival instead of the departure
+    pub arrive_by: bool,
+    /// UIC code of a station the journey must pass through
+    pub via_uic: Option<String>,
+    /// Drop journeys with more transfers than this (0 = direct trains only)
+    pub max_transfers: Option<u32>,
+    /// Train categories to skip, e.g. ["SPR"]
+    pub exclude_types: Vec<String>,
+    /// When not empty, only keep these train categories, e.g. ["IC"]
+    pub only_types: Vec<String>,
+    /// Only plan journeys that are accessible for wheelchair users
+    pub accessible: bool,
+    /// Scroll this many result pages later (positive) or earlier (negative)
+    pub pages: i32,
+    /// Drop journeys with a train that needs a supplement (Intercity direct, ICE)
+    pub no_supplement: bool,
+}
+
+/// Order of the listed trips (`trip --sort`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TripSort {
+    /// Earliest planned departure first, as returned by NS
+    #[default]
+    Departure,
+    /// Earliest arrival first
+    Arrival,
+    /// Shortest travel time first
+    Duration,
+    /// Fewest transfers first
+    Transfers,
+}
+
+/// What to do with cancelled trips (`trip --cancelled`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CancelledMode {
+    /// List them struck through, like any other trip
+    #[default]
+    Show,
+    /// Leave them out
+    Hide,
+    /// List them with the next running trip on the same route
+    Alternative,
+}
+
+impl TripQuery {
+    /// Whether a train category passes the --exclude-type/--only-type filters
+    pub fn allows_train_type(&self, train_type: &str) -> bool {
+        train_type_allowed(train_type, &self.only_types, &self.exclude_types)
+    }
+}
+
+/// The --only-type/--exclude-type check shared by `trip` and `departures`:
+/// `train_type` is in `only_types` (when not empty) and not in `exclude_types`
+pub fn train_type_allowed(train_type: &str, only_types: &[String], exclude_types: &[String]) -> bool {
+    let listed = |types: &[String]| types.iter().any(|t| t.eq_ignore_ascii_case(train_type));
+    (only_types.is_empty() || listed(only_types)) && !listed(exclude_types)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TripsResponse {
     pub trips: Vec<TripRaw>,
+    /// Token for the page of journeys before this one
+    #[serde(rename = "scrollRequestBackwardContext", default)]
+    pub backward_context: Option<String>,
+    /// Token for the page of journeys after this one
+    #[serde(rename = "scrollRequestForwardContext", default)]
+    pub forward_context: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TripRaw {
     pub legs: Vec<LegRaw>,
     pub status: String,
+    #[serde(default)]
+    pub transfers: u32,
+    /// Travel advice for the whole journey, e.g. "Bus replaces train"
+    #[serde(default)]
+    pub messages: Vec<MessageRaw>,
+    #[serde(rename = "primaryMessage", default)]
+    pub primary_message: Option<PrimaryMessageRaw>,
+    #[serde(rename = "fareOptions", default)]
+    pub fare_options: Option<FareOptionsRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FareOptionsRaw {
+    /// Supplements on top of the fare, e.g. for the Intercity direct
+    #[serde(rename = "supplementsBasedOnSelectedFare", default)]
+    pub supplements: Vec<SupplementRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplementRaw {
+    #[serde(default)]
+    pub supplement_price_in_cents: Option<i32>,
+    /// Index of the leg the supplement is for, as a string
+    #[serde(default)]
+    pub leg_idx: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,12 +116,93 @@ pub struct LegRaw {
     pub origin: StopRaw,
     pub destination: StopRaw,
     pub product: ProductRaw,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub notes: Vec<NoteRaw>,
+    /// Travel advice for this leg, e.g. a disruption or maintenance message
+    #[serde(default)]
+    pub messages: Vec<MessageRaw>,
+    /// "PUBLIC_TRANSIT" for rides, "WALK" for walks between stations
+    #[serde(rename = "travelType", default)]
+    pub travel_type: Option<String>,
+    /// Set when the transfer into this leg is to the other side of the same platform
+    #[serde(rename = "crossPlatformTransfer", default)]
+    pub cross_platform_transfer: bool,
+    #[serde(rename = "transferMessages", default)]
+    pub transfer_messages: Vec<TransferMessageRaw>,
+    /// Every station on the leg, including its origin and destination
+    #[serde(default)]
+    pub stops: Vec<StopPointRaw>,
+}
+
+/// A station the train calls at (or passes) during a leg
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopPointRaw {
+    pub name: String,
+    #[serde(default)]
+    pub uic_code: Option<String>,
+    pub planned_arrival_date_time: Option<String>,
+    pub actual_arrival_date_time: Option<String>,
+    pub planned_departure_date_time: Option<String>,
+    pub actual_departure_date_time: Option<String>,
+    pub planned_departure_track: Option<String>,
+    pub actual_departure_track: Option<String>,
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lng: Option<f64>,
+    /// Passed without stopping
+    #[serde(default)]
+    pub passing: bool,
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Instructions for changing into a leg, e.g. "Overstap op zelfde perron"
+#[derive(Debug, Deserialize)]
+pub struct TransferMessageRaw {
+    pub message: String,
+    #[serde(rename = "accessibilityMessage", default)]
+    pub accessibility_message: Option<String>,
+}
+
+/// Remark attached to a leg, e.g. on accessibility, travel assistance or
+/// "Reservering verplicht"
+#[derive(Debug, Deserialize)]
+pub struct NoteRaw {
+    pub value: String,
+    /// "ATTRIBUTE" for train facilities, "INFOTEXT", "REALTIME", "TICKET", ...
+    #[serde(rename = "noteType", default)]
+    pub note_type: Option<String>,
+    /// NS asks for the note to always be shown with the journey
+    #[serde(rename = "isPresentationRequired", default)]
+    pub is_presentation_required: bool,
+}
+
+/// Travel advice message on a journey or leg
+#[derive(Debug, Deserialize)]
+pub struct MessageRaw {
+    #[serde(default)]
+    pub head: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// The headline NS shows above a journey, e.g. "Extra reistijd"
+#[derive(Debug, Deserialize)]
+pub struct PrimaryMessageRaw {
+    pub title: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct StopRaw {
     pub name: String,
 
+    #[serde(rename = "uicCode", default)]
+    pub uic_code: Option<String>,
+
     #[serde(rename = "actualTrack")]
     pub actual_track: Option<String>,
 
@@ -33,10 +214,74 @@ pub struct StopRaw {
 
     #[serde(rename = "actualDateTime")]
     pub actual_date_time: Option<String>,
+
+    #[serde(default)]
+    pub lat: Option<f64>,
+
+    #[serde(default)]
+    pub lng: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ProductRaw {
-    #[serde(rename = "categoryCode")]
+    /// Empty for walk legs
+    #[serde(rename = "categoryCode", default)]
     pub category_code: String,
+    /// e.g. "NS Intercity direct"
+    #[serde(rename = "displayName", default)]
+    pub display_name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_contexts() {
+        let body = r#"{"trips": [], "scrollRequestForwardContext": "a|1|dep|later"}"#;
+
+        let response: TripsResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.forward_context.as_deref(), Some("a|1|dep|later"));
+        assert_eq!(response.backward_context, None);
+    }
+
+    #[test]
+    fn test_messages_and_notes() {
+        let body = r#"{
+            "status": "NORMAL",
+            "primaryMessage": {"title": "Extra reistijd"},
+            "messages": [{"head": "Bus replaces train", "text": "Between Gouda and Utrecht"}],
+            "legs": [{
+                "origin": {"name": "Gouda", "plannedDateTime": "2024-06-01T08:00:00+0200"},
+                "destination": {"name": "Utrecht Centraal", "plannedDateTime": "2024-06-01T08:20:00+0200"},
+                "product": {"categoryCode": "BUS"},
+                "notes": [{"value": "Reservering verplicht", "noteType": "INFOTEXT", "isPresentationRequired": true}],
+                "messages": [{"text": "Werkzaamheden"}]
+            }]
+        }"#;
+        let trip: TripRaw = serde_json::from_str(body).unwrap();
+        assert_eq!(trip.primary_message.unwrap().title, "Extra reistijd");
+        assert_eq!(trip.messages[0].head.as_deref(), Some("Bus replaces train"));
+        let leg = &trip.legs[0];
+        assert!(leg.notes[0].is_presentation_required);
+        assert_eq!(leg.notes[0].note_type.as_deref(), Some("INFOTEXT"));
+        assert_eq!(leg.messages[0].text.as_deref(), Some("Werkzaamheden"));
+    }
+
+    #[test]
+    fn test_allows_train_type() {
+        let query = TripQuery {
+            exclude_types: vec!["SPR".to_string()],
+            ..Default::default()
+        };
+        assert!(query.allows_train_type("IC"));
+        assert!(!query.allows_train_type("spr"));
+
+        let query = TripQuery {
+            only_types: vec!["IC".to_string(), "ICD".to_string()],
+            ..Default::default()
+        };
+        assert!(query.allows_train_type("ICD"));
+        assert!(!query.allows_train_type("SPR"));
+    }
 }
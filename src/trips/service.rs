@@ -1,98 +1,742 @@
+use crate::client::{client, Endpoint};
+use crate::i18n::{status_label, tr, trf, Text};
+use crate::output::{countdown, CsvRow, TableRow, TemplateFields, TimeDisplay, Value};
 use crate::stations::models::Station;
-use crate::trips::models::{TripRaw, TripsResponse};
-use chrono::{DateTime, FixedOffset};
+use crate::trips::models::{
+    LegRaw, MessageRaw, NoteRaw, StopPointRaw, TripQuery, TripRaw, TripSort, TripsResponse,
+};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
 use colored::*;
-use std::{env, fmt};
+use serde::Serialize;
+use std::fmt;
 
-#[derive(Debug)]
-pub struct Trip {
+/// WGS84 position of a stop as reported by the trips API
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Coordinates {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl Coordinates {
+    fn from_raw(lat: Option<f64>, lng: Option<f64>) -> Option<Self> {
+        Some(Self { lat: lat?, lng: lng? })
+    }
+
+    /// Great-circle distance in meters
+    fn distance_to(&self, other: &Coordinates) -> f64 {
+        const EARTH_RADIUS_M: f64 = 6_371_000.0;
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lng = (other.lng - self.lng).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+}
+
+/// Grams of CO₂ per passenger-kilometre by train (NS runs on wind power)
+const TRAIN_G_PER_KM: f64 = 3.0;
+/// Grams of CO₂ per passenger-kilometre by bus
+const BUS_G_PER_KM: f64 = 80.0;
+/// Grams of CO₂ per kilometre for an average petrol car with one occupant
+const CAR_G_PER_KM: f64 = 150.0;
+
+/// Estimated emissions of a journey compared to driving the same distance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Co2 {
+    /// Distance along the stations of every leg
+    pub distance_km: f64,
+    pub trip_grams: u32,
+    pub car_grams: u32,
+}
+
+impl Co2 {
+    pub fn saved_grams(&self) -> u32 {
+        self.car_grams.saturating_sub(self.trip_grams)
+    }
+}
+
+/// Train categories that need a supplement on top of a regular ticket
+/// (Intercity direct between Schiphol and Rotterdam, ICE within the Netherlands)
+pub const SUPPLEMENT_TYPES: &[&str] = &["ICD", "ICE"];
+
+/// One train ride within a journey
+#[derive(Debug, Clone, Serialize)]
+pub struct Leg {
     pub origin_name: String,
     pub destination_name: String,
+    pub origin_uic: Option<String>,
+    pub destination_uic: Option<String>,
+    /// Departure track, the actual one when it changed
     pub track: String,
-    pub status: String,
+    pub planned_track: Option<String>,
+    pub arrival_track: Option<String>,
     pub departure_time: DateTime<FixedOffset>,
     pub actual_departure_time: Option<DateTime<FixedOffset>>,
     pub arrival_time: DateTime<FixedOffset>,
     pub actual_arrival_time: Option<DateTime<FixedOffset>>,
+    /// Train category, "WALK" for walks between stations
     pub train_type: String,
+    pub cancelled: bool,
+    pub origin_location: Option<Coordinates>,
+    pub destination_location: Option<Coordinates>,
+    /// Leg remarks from NS (accessibility, travel assistance, reservations, ...)
+    pub notes: Vec<Note>,
+    /// Travel advice for this leg, e.g. "Werkzaamheden: bus replaces train"
+    pub messages: Vec<String>,
+    /// A walk (e.g. between two nearby stations) rather than a ride
+    pub walking: bool,
+    /// The change into this leg is to the other side of the same platform
+    pub cross_platform: bool,
+    /// How to get to this leg, e.g. "Overstap via de tunnel"
+    pub transfer_hints: Vec<String>,
+    /// Stations the train calls at between origin and destination
+    pub stops: Vec<Stop>,
+    /// Length of the leg along every station it passes, when coordinates are known
+    pub distance_meters: Option<u32>,
+    /// The train needs a supplement on top of a regular ticket
+    pub supplement: bool,
+    /// Supplement price when NS quotes one for this leg
+    pub supplement_cents: Option<i32>,
+}
+
+/// A leg remark as NS sends it
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    pub text: String,
+    /// NS note type, e.g. "ATTRIBUTE" (train facilities) or "INFOTEXT"
+    pub kind: Option<String>,
+    /// NS asks for the note to always be shown
+    pub required: bool,
+}
+
+impl From<NoteRaw> for Note {
+    fn from(raw: NoteRaw) -> Self {
+        Note {
+            text: raw.value,
+            kind: raw.note_type,
+            required: raw.is_presentation_required,
+        }
+    }
+}
+
+impl Note {
+    /// Whether the note warns about the journey itself (reservations, changed
+    /// service, tickets) rather than describing train facilities
+    pub fn is_warning(&self) -> bool {
+        self.required
+            || matches!(
+                self.kind.as_deref(),
+                Some("INFOTEXT" | "REALTIME" | "TICKET")
+            )
+    }
+}
+
+/// "head: text" of a travel advice message, whichever parts are present
+fn message_text(raw: MessageRaw) -> Option<String> {
+    let parts: Vec<String> = [raw.head, raw.text]
+        .into_iter()
+        .flatten()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(": "))
+}
+
+/// Why a trip from the NS API could not be converted
+#[derive(Debug, PartialEq)]
+pub enum TripError {
+    NoLegs,
+    InvalidTime(String),
+}
+
+impl fmt::Display for TripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TripError::NoLegs => write!(f, "trip has no legs"),
+            TripError::InvalidTime(txt) => write!(f, "invalid date/time `{}`", txt),
+        }
+    }
 }
 
-impl From<TripRaw> for Trip {
-    fn from(raw: TripRaw) -> Self {
-        // we only care about the first leg
-        let leg = raw.legs.into_iter().next().expect("No legs in trip");
+impl std::error::Error for TripError {}
+
+/// Parse an NS timestamp such as "2024-06-01T08:12:00+0200"
+fn parse_ns_time(txt: &str) -> Result<DateTime<FixedOffset>, TripError> {
+    DateTime::parse_from_str(txt, "%Y-%m-%dT%H:%M:%S%z")
+        .map_err(|_| TripError::InvalidTime(txt.to_string()))
+}
 
+/// An intermediate call of a train
+#[derive(Debug, Clone, Serialize)]
+pub struct Stop {
+    pub name: String,
+    pub uic: Option<String>,
+    pub arrival_time: Option<DateTime<FixedOffset>>,
+    pub actual_arrival_time: Option<DateTime<FixedOffset>>,
+    pub departure_time: Option<DateTime<FixedOffset>>,
+    pub actual_departure_time: Option<DateTime<FixedOffset>>,
+    pub track: Option<String>,
+    pub cancelled: bool,
+}
+
+impl TryFrom<StopPointRaw> for Stop {
+    type Error = TripError;
+
+    fn try_from(raw: StopPointRaw) -> Result<Self, TripError> {
+        let parse_time = |txt: Option<String>| txt.as_deref().map(parse_ns_time).transpose();
+
+        Ok(Stop {
+            name: raw.name,
+            uic: raw.uic_code,
+            arrival_time: parse_time(raw.planned_arrival_date_time)?,
+            actual_arrival_time: parse_time(raw.actual_arrival_date_time)?,
+            departure_time: parse_time(raw.planned_departure_date_time)?,
+            actual_departure_time: parse_time(raw.actual_departure_date_time)?,
+            track: raw.actual_departure_track.or(raw.planned_departure_track),
+            cancelled: raw.cancelled,
+        })
+    }
+}
+
+impl Stop {
+    /// "08:21 Leiden Centraal tr.8", with the delay when running late
+    pub fn display_line(&self) -> String {
+        let planned = self.departure_time.or(self.arrival_time);
+        let actual = self.actual_departure_time.or(self.actual_arrival_time);
+        let delay = match (planned, actual) {
+            (Some(planned), Some(actual)) => Some(actual.signed_duration_since(planned).num_minutes())
+                .filter(|minutes| *minutes > 0)
+                .map(|minutes| format!("+{}", minutes))
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        let mut line = format!(
+            "{}{} {}",
+            planned.map(|time| time.format("%H:%M").to_string()).unwrap_or_else(|| "--:--".to_string()),
+            delay.red(),
+            self.name
+        );
+        if let Some(track) = &self.track {
+            line.push_str(&format!(" tr.{}", track));
+        }
+        if self.cancelled {
+            line = line.strikethrough().to_string();
+        }
+        line
+    }
+}
+
+impl TryFrom<LegRaw> for Leg {
+    type Error = TripError;
+
+    fn try_from(leg: LegRaw) -> Result<Self, TripError> {
+        let parse_time = |txt: Option<String>| txt.as_deref().map(parse_ns_time).transpose();
+
+        let walking = leg.travel_type.as_deref() == Some("WALK");
+        let supplement = SUPPLEMENT_TYPES
+            .iter()
+            .any(|code| code.eq_ignore_ascii_case(&leg.product.category_code));
+        let origin_location = Coordinates::from_raw(leg.origin.lat, leg.origin.lng);
+        let destination_location = Coordinates::from_raw(leg.destination.lat, leg.destination.lng);
+        let distance_meters = leg_distance(&leg, origin_location, destination_location);
+        let calls = leg.stops.len().saturating_sub(1);
+        let stops = leg
+            .stops
+            .into_iter()
+            .take(calls)
+            .skip(1)
+            .filter(|stop| !stop.passing)
+            .map(Stop::try_from)
+            .collect::<Result<_, _>>()?;
+        let planned_track = leg.origin.planned_track;
         let track = leg
             .origin
             .actual_track
-            .or(leg.origin.planned_track)
+            .or_else(|| planned_track.clone())
             .unwrap_or_else(|| "?".to_string());
 
-        let parse_time = |txt: String| {
-            DateTime::parse_from_str(&txt, "%Y-%m-%dT%H:%M:%S%z").expect("Invalid datetime format")
-        };
-
-        Trip {
+        Ok(Leg {
             origin_name: leg.origin.name,
             destination_name: leg.destination.name,
+            origin_uic: leg.origin.uic_code,
+            destination_uic: leg.destination.uic_code,
             track,
-            status: raw.status,
-            departure_time: parse_time(leg.origin.planned_date_time),
-            actual_departure_time: leg.origin.actual_date_time.map(parse_time),
-            arrival_time: parse_time(leg.destination.planned_date_time),
-            actual_arrival_time: leg.destination.actual_date_time.map(parse_time),
-            train_type: leg.product.category_code,
+            planned_track,
+            arrival_track: leg.destination.actual_track.or(leg.destination.planned_track),
+            departure_time: parse_ns_time(&leg.origin.planned_date_time)?,
+            actual_departure_time: parse_time(leg.origin.actual_date_time)?,
+            arrival_time: parse_ns_time(&leg.destination.planned_date_time)?,
+            actual_arrival_time: parse_time(leg.destination.actual_date_time)?,
+            train_type: if walking && leg.product.category_code.is_empty() {
+                "WALK".to_string()
+            } else {
+                leg.product.category_code
+            },
+            cancelled: leg.cancelled,
+            origin_location,
+            destination_location,
+            notes: leg.notes.into_iter().map(Note::from).collect(),
+            messages: leg.messages.into_iter().filter_map(message_text).collect(),
+            walking,
+            cross_platform: leg.cross_platform_transfer,
+            transfer_hints: leg
+                .transfer_messages
+                .into_iter()
+                .map(|message| message.message)
+                .collect(),
+            stops,
+            distance_meters,
+            supplement,
+            supplement_cents: None,
+        })
+    }
+}
+
+/// Sum of the hops between the leg's stations (passing ones included), or the
+/// straight line between origin and destination without stop coordinates
+fn leg_distance(
+    leg: &LegRaw,
+    origin: Option<Coordinates>,
+    destination: Option<Coordinates>,
+) -> Option<u32> {
+    let mut points: Vec<Coordinates> = leg
+        .stops
+        .iter()
+        .filter_map(|stop| Coordinates::from_raw(stop.lat, stop.lng))
+        .collect();
+    if points.len() < 2 {
+        points = [origin, destination].into_iter().flatten().collect();
+    }
+    if points.len() < 2 {
+        return None;
+    }
+
+    let meters: f64 = points.windows(2).map(|hop| hop[0].distance_to(&hop[1])).sum();
+    Some(meters.round() as u32)
+}
+
+impl Leg {
+    pub fn effective_departure(&self) -> DateTime<FixedOffset> {
+        self.actual_departure_time.unwrap_or(self.departure_time)
+    }
+
+    pub fn effective_arrival(&self) -> DateTime<FixedOffset> {
+        self.actual_arrival_time.unwrap_or(self.arrival_time)
+    }
+
+    fn minutes(&self) -> i64 {
+        self.effective_arrival()
+            .signed_duration_since(self.effective_departure())
+            .num_minutes()
+    }
+}
+
+/// Change of trains between two consecutive rides, including any walk in between
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    /// Station where the previous train arrives
+    pub station: String,
+    /// Minutes between (actual) arrival and (actual) departure
+    pub minutes: i64,
+    pub arrival_track: Option<String>,
+    pub departure_track: String,
+    /// Same platform, other side: no stairs or tunnel needed
+    pub cross_platform: bool,
+    /// Walking time when the next train leaves from another station
+    pub walk_minutes: Option<i64>,
+    /// Station the next train leaves from, when it differs from `station`
+    pub walk_to: Option<String>,
+    /// Transfer instructions from NS, e.g. "Overstap via de tunnel"
+    pub hints: Vec<String>,
+    /// Minutes the incoming train is running late
+    pub arrival_delay: i64,
+}
+
+/// Default minimum minutes to change trains before a transfer is flagged
+pub const MIN_TRANSFER_MINUTES: i64 = 4;
+
+/// Why a transfer may not work out, see `Transfer::risk()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferRisk {
+    /// Planned with less than the minimum time to change
+    Tight,
+    /// The incoming delay eats into the time to change
+    Endangered,
+    /// By the current times the next train leaves before you can get there
+    Missed,
+}
+
+impl Transfer {
+    /// Minutes left to change trains once any walk between stations is done
+    pub fn spare_minutes(&self) -> i64 {
+        self.minutes - self.walk_minutes.unwrap_or(0)
+    }
+
+    /// Risk of missing this connection with `min_minutes` needed to change;
+    /// `None` when it looks safe
+    pub fn risk(&self, min_minutes: i64) -> Option<TransferRisk> {
+        let spare = self.spare_minutes();
+        if spare < 0 {
+            Some(TransferRisk::Missed)
+        } else if spare >= min_minutes {
+            None
+        } else if self.arrival_delay > 0 {
+            Some(TransferRisk::Endangered)
+        } else {
+            Some(TransferRisk::Tight)
+        }
+    }
+
+    /// Short description such as "8 min, tr.7 → tr.12, cross-platform"
+    pub fn describe(&self) -> String {
+        let mut text = match &self.arrival_track {
+            Some(arrival) => format!("{} min, tr.{} → tr.{}", self.minutes, arrival, self.departure_track),
+            None => format!("{} min, tr.{}", self.minutes, self.departure_track),
+        };
+        if self.cross_platform {
+            text.push_str(", cross-platform");
+        }
+        if let Some(walk) = self.walk_minutes {
+            match &self.walk_to {
+                Some(to) => text.push_str(&format!(", walk {} min to {}", walk, to)),
+                None => text.push_str(&format!(", walk {} min", walk)),
+            }
         }
+        text
+    }
+}
+
+/// A complete journey. The top-level fields describe the journey as a whole:
+/// departure data from the first leg, arrival data from the last one.
+#[derive(Debug, Clone, Serialize)]
+pub struct Trip {
+    pub origin_name: String,
+    pub destination_name: String,
+    pub origin_uic: Option<String>,
+    pub destination_uic: Option<String>,
+    pub track: String,
+    pub planned_track: Option<String>,
+    pub status: String,
+    pub departure_time: DateTime<FixedOffset>,
+    pub actual_departure_time: Option<DateTime<FixedOffset>>,
+    pub arrival_time: DateTime<FixedOffset>,
+    pub actual_arrival_time: Option<DateTime<FixedOffset>>,
+    /// Train type of the first ride
+    pub train_type: String,
+    pub transfers: u32,
+    /// Door-to-door travel time in minutes, using actual times when known
+    pub duration_minutes: i64,
+    /// Travel advice for the whole journey, the headline first
+    pub messages: Vec<String>,
+    pub legs: Vec<Leg>,
+}
+
+impl TryFrom<TripRaw> for Trip {
+    type Error = TripError;
+
+    fn try_from(raw: TripRaw) -> Result<Self, TripError> {
+        let mut legs: Vec<Leg> = raw
+            .legs
+            .into_iter()
+            .map(Leg::try_from)
+            .collect::<Result<_, _>>()?;
+        let supplements = raw.fare_options.map(|fare| fare.supplements).unwrap_or_default();
+        for supplement in supplements {
+            if let Some(leg) = supplement
+                .leg_idx
+                .and_then(|idx| idx.parse::<usize>().ok())
+                .and_then(|idx| legs.get_mut(idx))
+            {
+                leg.supplement = true;
+                leg.supplement_cents = supplement.supplement_price_in_cents;
+            }
+        }
+        let mut trip = Trip::from_legs(legs, raw.status, raw.transfers)?;
+        trip.messages = raw
+            .primary_message
+            .map(|message| message.title)
+            .into_iter()
+            .chain(raw.messages.into_iter().filter_map(message_text))
+            .collect();
+        Ok(trip)
+    }
+}
+
+/// Convert the trips NS sent, skipping (and reporting) malformed ones so a
+/// single bad item doesn't fail the whole search
+fn convert_trips(raws: Vec<TripRaw>) -> Vec<Trip> {
+    raws.into_iter()
+        .filter_map(|raw| match Trip::try_from(raw) {
+            Ok(trip) => Some(trip),
+            Err(e) => {
+                eprintln!("⚠️  Skipping a trip from the NS API: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+impl Trip {
+    /// Build the journey-level view over its legs
+    fn from_legs(legs: Vec<Leg>, status: String, transfers: u32) -> Result<Self, TripError> {
+        let (Some(first), Some(last)) = (legs.first(), legs.last()) else {
+            return Err(TripError::NoLegs);
+        };
+        let first_ride = legs.iter().find(|leg| !leg.walking).unwrap_or(first);
+
+        Ok(Trip {
+            origin_name: first.origin_name.clone(),
+            destination_name: last.destination_name.clone(),
+            origin_uic: first.origin_uic.clone(),
+            destination_uic: last.destination_uic.clone(),
+            track: first_ride.track.clone(),
+            planned_track: first_ride.planned_track.clone(),
+            status,
+            departure_time: first.departure_time,
+            actual_departure_time: first.actual_departure_time,
+            arrival_time: last.arrival_time,
+            actual_arrival_time: last.actual_arrival_time,
+            train_type: first_ride.train_type.clone(),
+            transfers,
+            duration_minutes: last
+                .effective_arrival()
+                .signed_duration_since(first.effective_departure())
+                .num_minutes(),
+            messages: Vec::new(),
+            legs,
+        })
+    }
+
+    /// Every change of trains with the time available for it. Walks between
+    /// two rides are part of the transfer.
+    pub fn transfer_points(&self) -> Vec<Transfer> {
+        let rides: Vec<usize> = (0..self.legs.len())
+            .filter(|&index| !self.legs[index].walking)
+            .collect();
+
+        rides
+            .windows(2)
+            .map(|pair| {
+                let (arriving, departing) = (&self.legs[pair[0]], &self.legs[pair[1]]);
+                let walks = &self.legs[pair[0] + 1..pair[1]];
+
+                let mut hints: Vec<String> = walks
+                    .iter()
+                    .flat_map(|walk| walk.transfer_hints.iter().cloned())
+                    .collect();
+                hints.extend(departing.transfer_hints.iter().cloned());
+
+                Transfer {
+                    station: arriving.destination_name.clone(),
+                    minutes: departing
+                        .effective_departure()
+                        .signed_duration_since(arriving.effective_arrival())
+                        .num_minutes(),
+                    arrival_track: arriving.arrival_track.clone(),
+                    departure_track: departing.track.clone(),
+                    cross_platform: departing.cross_platform,
+                    walk_minutes: (!walks.is_empty())
+                        .then(|| walks.iter().map(Leg::minutes).sum()),
+                    walk_to: (departing.origin_name != arriving.destination_name)
+                        .then(|| departing.origin_name.clone()),
+                    hints,
+                    arrival_delay: arriving
+                        .effective_arrival()
+                        .signed_duration_since(arriving.arrival_time)
+                        .num_minutes(),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether any train of the journey needs a supplement
+    pub fn requires_supplement(&self) -> bool {
+        self.legs.iter().any(|leg| leg.supplement)
+    }
+
+    /// Total of the supplements NS quotes for the journey
+    pub fn supplement_cents(&self) -> Option<i32> {
+        let quoted: Vec<i32> = self.legs.iter().filter_map(|leg| leg.supplement_cents).collect();
+        (!quoted.is_empty()).then(|| quoted.iter().sum())
+    }
+
+    /// Train categories of the legs that need a supplement, e.g. "ICD"
+    pub fn supplement_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = Vec::new();
+        for leg in self.legs.iter().filter(|leg| leg.supplement) {
+            if !types.contains(&leg.train_type.as_str()) {
+                types.push(&leg.train_type);
+            }
+        }
+        types
+    }
+
+    /// Transfers that may not work out with `min_minutes` needed to change
+    pub fn transfer_risks(&self, min_minutes: i64) -> Vec<(Transfer, TransferRisk)> {
+        self.transfer_points()
+            .into_iter()
+            .filter_map(|transfer| {
+                let risk = transfer.risk(min_minutes)?;
+                Some((transfer, risk))
+            })
+            .collect()
+    }
+
+    /// Indented per-leg lines with the transfers in between, empty for direct
+    /// trains unless the intermediate `stops` are requested
+    pub fn leg_lines(&self, stops: bool) -> Vec<String> {
+        if self.legs.len() < 2 && !stops {
+            return Vec::new();
+        }
+
+        let mut transfers = self.transfer_points().into_iter();
+        let last_ride = self.legs.iter().rposition(|leg| !leg.walking);
+        let mut lines = Vec::new();
+        let mut between_rides = false;
+
+        for (index, leg) in self.legs.iter().enumerate() {
+            if leg.walking {
+                // Walks between rides are described by the transfer line
+                if !between_rides {
+                    lines.push(format!(
+                        "    🚶 {} walk {} min to {}",
+                        leg.departure_time.format("%H:%M"),
+                        leg.minutes(),
+                        leg.destination_name
+                    ));
+                }
+                continue;
+            }
+
+            let mut line = format!(
+                "    {} {} tr.{} → {} {} [{}]",
+                leg.departure_time.format("%H:%M"),
+                leg.origin_name,
+                leg.track,
+                leg.arrival_time.format("%H:%M"),
+                leg.destination_name,
+                leg.train_type,
+            );
+            if leg.cancelled {
+                line = line.strikethrough().to_string();
+            }
+            lines.push(line);
+            if stops {
+                for stop in &leg.stops {
+                    lines.push(format!("      · {}", stop.display_line()));
+                }
+            }
+
+            between_rides = Some(index) != last_ride;
+            if between_rides && let Some(transfer) = transfers.next() {
+                lines.push(format!("    ↳ transfer at {}: {}", transfer.station, transfer.describe()));
+                for hint in &transfer.hints {
+                    lines.push(format!("      {}", hint));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Notes of all legs, without duplicates
+    pub fn notes(&self) -> Vec<&str> {
+        let mut notes: Vec<&str> = Vec::new();
+        for note in self.legs.iter().flat_map(|leg| &leg.notes) {
+            if !notes.contains(&note.text.as_str()) {
+                notes.push(&note.text);
+            }
+        }
+        notes
+    }
+
+    /// Estimated CO₂ of the journey against driving the same distance by car,
+    /// `None` when a ride has no coordinates to measure it by
+    pub fn co2(&self) -> Option<Co2> {
+        let mut distance_m = 0.0;
+        let mut trip_grams = 0.0;
+        for leg in &self.legs {
+            let meters = f64::from(leg.distance_meters?);
+            let per_km = match leg.train_type.as_str() {
+                _ if leg.walking => 0.0,
+                "BUS" => BUS_G_PER_KM,
+                _ => TRAIN_G_PER_KM,
+            };
+            distance_m += meters;
+            trip_grams += meters / 1000.0 * per_km;
+        }
+
+        let distance_km = distance_m / 1000.0;
+        Some(Co2 {
+            distance_km,
+            trip_grams: trip_grams.round() as u32,
+            car_grams: (distance_km * CAR_G_PER_KM).round() as u32,
+        })
+    }
+
+    /// Travel advice and warning notes that should always be shown with the
+    /// journey, without duplicates
+    pub fn warnings(&self) -> Vec<&str> {
+        let leg_texts = self.legs.iter().flat_map(|leg| {
+            leg.messages.iter().map(String::as_str).chain(
+                leg.notes
+                    .iter()
+                    .filter(|note| note.is_warning())
+                    .map(|note| note.text.as_str()),
+            )
+        });
+
+        let mut warnings: Vec<&str> = Vec::new();
+        for text in self.messages.iter().map(String::as_str).chain(leg_texts) {
+            if !warnings.contains(&text) {
+                warnings.push(text);
+            }
+        }
+        warnings
     }
 }
 
 impl fmt::Display for Trip {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // 1️⃣ Always print the planned (scheduled) times
-        let dep = self.departure_time.format("%H:%M").to_string();
+        write!(f, "{}", self.display_line(TimeDisplay::Absolute, Local::now().fixed_offset()))
+    }
+}
+
+impl Trip {
+    /// Colored one-line summary, with the departure shown as a clock time,
+    /// a countdown relative to `now`, or both
+    pub fn display_line(&self, times: TimeDisplay, now: DateTime<FixedOffset>) -> String {
+        // 1️⃣ Always print the planned (scheduled) times, countdowns use the actual departure
+        let clock = self.departure_time.format("%H:%M").to_string();
+        let relative = countdown(self.actual_departure_time.unwrap_or(self.departure_time), now);
+        let dep = match times {
+            TimeDisplay::Absolute => clock,
+            TimeDisplay::Relative => relative,
+            TimeDisplay::Both => format!("{} ({})", clock, relative),
+        };
         let arr = self.arrival_time.format("%H:%M").to_string();
 
-        // 2️⃣ Departure delay (only if actual departure exists)
+        // 2️⃣ Delays (only shown if the train is running late)
         let dep_delay = self
-            .actual_departure_time
-            .as_ref() // borrow Option<&DateTime>
-            .map(|actual| {
-                // runs only if Some(actual)
-                let diff = actual.signed_duration_since(self.departure_time);
-                let minutes = diff.num_minutes();
-                if minutes > 0 {
-                    // only show if late
-                    format!("+{}", minutes)
-                } else {
-                    String::new()
-                }
-            })
-            .unwrap_or_default(); // if None → ""
-
-        // 3️⃣ Arrival delay (same logic)
+            .departure_delay()
+            .map(|m| format!("+{}", m))
+            .unwrap_or_default();
         let arr_delay = self
-            .actual_arrival_time
-            .as_ref()
-            .map(|actual| {
-                let diff = actual.signed_duration_since(self.arrival_time);
-                let minutes = diff.num_minutes();
-                if minutes > 0 {
-                    format!("+{}", minutes)
-                } else {
-                    String::new()
-                }
-            })
+            .arrival_delay()
+            .map(|m| format!("+{}", m))
             .unwrap_or_default();
 
+        let summary = format!("({}, {})", format_duration(self.duration()), self.transfers_label());
+
         let status_msg = if self.status == "NORMAL" {
             ""
         } else {
-            &format!("({})", self.status)
+            &format!("({})", status_label(&self.status))
         };
 
-        // 4️⃣ Final formatted string
+        // 3️⃣ Final formatted string
         let mut line = format!(
-            "{} -> {} [{}] tr.{} {}{} -> {}{} {}",
+            "{} -> {} [{}] tr.{} {}{} -> {}{} {} {}",
             self.origin_name,
             self.destination_name,
             self.train_type,
@@ -101,35 +745,983 @@ impl fmt::Display for Trip {
             dep_delay.red(),
             arr,
             arr_delay.red(),
+            summary.dimmed(),
             status_msg,
         );
 
-        if self.status == "CANCELLED" {
+        if self.is_cancelled() {
             line = line.strikethrough().color(Color::BrightBlack).to_string();
         }
-        write!(f, "{}", line)
+        line
     }
 }
 
-pub fn trips(from: Station, to: Station) -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://gateway.apiportal.ns.nl/reisinformatie-api/api/v3/trips");
+impl Trip {
+    /// Minutes of departure delay, if the train is running late
+    pub fn departure_delay(&self) -> Option<i64> {
+        self.actual_departure_time
+            .map(|actual| actual.signed_duration_since(self.departure_time).num_minutes())
+            .filter(|minutes| *minutes > 0)
+    }
 
-    let ns_api_token = env::var("NS_API_TOKEN").map_err(|_| "NS_API_TOKEN not found")?;
+    /// Minutes of arrival delay, if the train is running late
+    pub fn arrival_delay(&self) -> Option<i64> {
+        self.actual_arrival_time
+            .map(|actual| actual.signed_duration_since(self.arrival_time).num_minutes())
+            .filter(|minutes| *minutes > 0)
+    }
+}
+
+impl Trip {
+    /// Travel time from (actual) departure to (actual) arrival
+    pub fn duration(&self) -> chrono::Duration {
+        let departure = self.actual_departure_time.unwrap_or(self.departure_time);
+        self.effective_arrival().signed_duration_since(departure)
+    }
+
+    /// "direct", "1 transfer" or "N transfers" in the current language
+    pub fn transfers_label(&self) -> String {
+        match self.transfers {
+            0 => tr(Text::Direct).to_string(),
+            1 => tr(Text::OneTransfer).to_string(),
+            n => trf(Text::Transfers, &[&n.to_string()]),
+        }
+    }
 
-    let body: String = ureq::get(url)
-        .header("Cache-Control", "no-cache")
-        .header("Ocp-Apim-Subscription-Key", &ns_api_token)
-        .query("originUicCode", from.id.uic_code)
-        .query("destinationUicCode", to.id.uic_code)
-        .call()?
-        .body_mut()
-        .read_to_string()?;
+    /// Whether the trip is running late or cancelled
+    pub fn is_disrupted(&self) -> bool {
+        self.is_cancelled() || self.departure_delay().is_some() || self.arrival_delay().is_some()
+    }
+
+    /// Whether the whole trip or one of its rides is cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.status == "CANCELLED" || self.legs.iter().any(|leg| leg.cancelled && !leg.walking)
+    }
+
+    /// Actual arrival time when known, otherwise the planned one
+    pub fn effective_arrival(&self) -> DateTime<FixedOffset> {
+        self.actual_arrival_time.unwrap_or(self.arrival_time)
+    }
+
+    /// Whether the departure track differs from the planned one
+    pub fn track_changed(&self) -> bool {
+        self.planned_track
+            .as_ref()
+            .is_some_and(|planned| *planned != self.track)
+    }
+
+    /// Plain-language description for screen readers: no symbols or colors,
+    /// and always the same order (route, type, departure, arrival, status)
+    pub fn accessible_description(&self) -> String {
+        let delayed = |minutes: Option<i64>| match minutes {
+            Some(1) => ", delayed 1 minute".to_string(),
+            Some(m) => format!(", delayed {} minutes", m),
+            None => String::new(),
+        };
+
+        let platform = if self.track_changed() {
+            format!("platform changed to {}", self.track)
+        } else {
+            format!("platform {}", self.track)
+        };
+
+        let status = match self.status.as_str() {
+            "NORMAL" => String::new(),
+            other => format!(", {}", other),
+        };
+
+        let transfers: String = self
+            .transfer_points()
+            .iter()
+            .map(|t| {
+                let mut change = format!(
+                    ", change at {} with {} minutes to platform {}",
+                    t.station, t.minutes, t.departure_track
+                );
+                if t.cross_platform {
+                    change.push_str(" on the other side of the same platform");
+                }
+                if let (Some(walk), Some(to)) = (t.walk_minutes, &t.walk_to) {
+                    change.push_str(&format!(" after a {} minute walk to {}", walk, to));
+                }
+                change
+            })
+            .collect();
+
+        format!(
+            "From {} to {}, train type {}, departs {} from {}{}{}, arrives {}{}, travel time {} minutes, {}{}",
+            self.origin_name,
+            self.destination_name,
+            self.train_type,
+            self.departure_time.format("%H:%M"),
+            platform,
+            delayed(self.departure_delay()),
+            transfers,
+            self.arrival_time.format("%H:%M"),
+            delayed(self.arrival_delay()),
+            self.duration().num_minutes(),
+            match self.transfers {
+                0 => "no transfers".to_string(),
+                1 => "1 transfer".to_string(),
+                n => format!("{} transfers", n),
+            },
+            status,
+        )
+    }
+}
+
+impl TemplateFields for Trip {
+    fn field(&self, name: &str) -> Option<Value> {
+        let delay = |minutes: Option<i64>| {
+            Value::Text(minutes.map(|m| format!("+{}", m)).unwrap_or_default())
+        };
+
+        Some(match name {
+            "origin" => Value::Text(self.origin_name.clone()),
+            "destination" => Value::Text(self.destination_name.clone()),
+            "track" => Value::Text(self.track.clone()),
+            "status" => Value::Text(self.status.clone()),
+            "type" => Value::Text(self.train_type.clone()),
+            "departure" => Value::Time(self.departure_time),
+            "arrival" => Value::Time(self.arrival_time),
+            "actual_departure" => {
+                Value::Time(self.actual_departure_time.unwrap_or(self.departure_time))
+            }
+            "actual_arrival" => Value::Time(self.actual_arrival_time.unwrap_or(self.arrival_time)),
+            "departure_delay" => delay(self.departure_delay()),
+            "departure_in" => Value::Text(countdown(
+                self.actual_departure_time.unwrap_or(self.departure_time),
+                Local::now().fixed_offset(),
+            )),
+            "arrival_delay" => delay(self.arrival_delay()),
+            "duration" => Value::Text(format_duration(self.duration())),
+            "transfers" => Value::Text(self.transfers.to_string()),
+            _ => return None,
+        })
+    }
+}
+
+impl TableRow for Trip {
+    fn headers() -> &'static [&'static str] {
+        &["Departure", "Arrival", "Duration", "Transfers", "Track", "Type", "Status"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let with_delay = |time: DateTime<FixedOffset>, delay: Option<i64>| match delay {
+            Some(minutes) => format!("{} +{}", time.format("%H:%M"), minutes),
+            None => time.format("%H:%M").to_string(),
+        };
+        vec![
+            with_delay(self.departure_time, self.departure_delay()),
+            with_delay(self.arrival_time, self.arrival_delay()),
+            format_duration(self.duration()),
+            self.transfers.to_string(),
+            if self.track_changed() {
+                format!("{} (was {})", self.track, self.planned_track.as_deref().unwrap_or("?"))
+            } else {
+                self.track.clone()
+            },
+            self.train_type.clone(),
+            self.status.clone(),
+        ]
+    }
+}
+
+impl CsvRow for Trip {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "origin",
+            "destination",
+            "departure",
+            "actual_departure",
+            "departure_delay",
+            "arrival",
+            "actual_arrival",
+            "arrival_delay",
+            "track",
+            "type",
+            "status",
+            "duration_minutes",
+            "transfers",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let time = |t: Option<DateTime<FixedOffset>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+        let minutes = |m: Option<i64>| m.unwrap_or(0).to_string();
+
+        vec![
+            self.origin_name.clone(),
+            self.destination_name.clone(),
+            self.departure_time.to_rfc3339(),
+            time(self.actual_departure_time),
+            minutes(self.departure_delay()),
+            self.arrival_time.to_rfc3339(),
+            time(self.actual_arrival_time),
+            minutes(self.arrival_delay()),
+            self.track.clone(),
+            self.train_type.clone(),
+            self.status.clone(),
+            self.duration().num_minutes().to_string(),
+            self.transfers.to_string(),
+        ]
+    }
+}
+
+/// Travel time as "H:MM", e.g. "1:05"
+pub fn format_duration(duration: chrono::Duration) -> String {
+    format!("{}:{:02}", duration.num_hours(), duration.num_minutes() % 60)
+}
+
+/// Link to the same search in the ns.nl journey planner
+pub fn planner_url(from: &Station, to: &Station, via: Option<&Station>, query: &TripQuery) -> String {
+    let time = query.date_time.unwrap_or_else(|| Local::now().fixed_offset());
+    let station = |station: &Station| urlencoding::encode(&station.names.long).into_owned();
+
+    let mut url = format!(
+        "https://www.ns.nl/reisplanner/#/?vertrek={}&vertrektype=treinstation&aankomst={}&aankomsttype=treinstation",
+        station(from),
+        station(to)
+    );
+    if let Some(via) = via {
+        url.push_str(&format!("&via={}&viatype=treinstation", station(via)));
+    }
+    url.push_str(&format!(
+        "&type={}&tijd={}",
+        if query.arrive_by { "aankomst" } else { "vertrek" },
+        urlencoding::encode(&time.format("%Y-%m-%dT%H:%M").to_string())
+    ));
+    url
+}
+
+/// Pair each of `theirs` with the trip in `mine` whose arrival is closest,
+/// returning `(their index, my index, minutes between arrivals)` sorted by
+/// how well the arrivals line up
+pub fn align_arrivals(theirs: &[Trip], mine: &[Trip]) -> Vec<(usize, usize, i64)> {
+    let mut pairs: Vec<(usize, usize, i64)> = theirs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, their)| {
+            mine.iter()
+                .enumerate()
+                .map(|(j, my)| {
+                    let gap = my
+                        .effective_arrival()
+                        .signed_duration_since(their.effective_arrival())
+                        .num_minutes();
+                    (i, j, gap)
+                })
+                .min_by_key(|(_, _, gap)| gap.abs())
+        })
+        .collect();
+
+    pairs.sort_by_key(|(i, _, gap)| (gap.abs(), *i));
+    pairs
+}
+
+/// One page of journeys with the tokens to scroll from it
+#[derive(Debug)]
+pub struct TripsPage {
+    pub trips: Vec<Trip>,
+    /// `context` for the journeys before this page
+    pub earlier: Option<String>,
+    /// `context` for the journeys after this page
+    pub later: Option<String>,
+}
+
+/// Fetch journeys between two stations, scrolled `query.pages` pages from the
+/// first result
+pub fn trips(
+    from: &Station,
+    to: &Station,
+    query: &TripQuery,
+) -> Result<Vec<Trip>, Box<dyn std::error::Error>> {
+    let mut page = trips_page(from, to, query, None)?;
+
+    for _ in 0..query.pages.unsigned_abs() {
+        let context = if query.pages > 0 { page.later } else { page.earlier };
+        let context = context.ok_or("❌ The NS API offers no further journeys in that direction")?;
+        page = trips_page(from, to, query, Some(&context))?;
+    }
+    Ok(page.trips)
+}
+
+/// The first of the next few journeys that needs a supplement (Intercity
+/// direct, ICE); the price API only quotes regular fares
+pub fn next_supplement_trip(
+    from: &Station,
+    to: &Station,
+) -> Result<Option<Trip>, Box<dyn std::error::Error>> {
+    let query = TripQuery {
+        limit: Some(5),
+        ..Default::default()
+    };
+    Ok(trips(from, to, &query)?
+        .into_iter()
+        .find(|trip| trip.requires_supplement()))
+}
+
+/// Fetch one page of journeys; `context` is a scroll token from an earlier page
+/// and replaces the requested date and time
+pub fn trips_page(
+    from: &Station,
+    to: &Station,
+    query: &TripQuery,
+    context: Option<&str>,
+) -> Result<TripsPage, Box<dyn std::error::Error>> {
+    let url = "https://gateway.apiportal.ns.nl/reisinformatie-api/api/v3/trips";
+
+    let mut params = vec![
+        ("originUicCode", from.id.uic_code.clone()),
+        ("destinationUicCode", to.id.uic_code.clone()),
+    ];
+
+    // Ask for fewer advices so the payload shrinks, the API may still return a few extra
+    if let Some(limit) = query.limit {
+        params.push(("nextAdvices", limit.to_string()));
+    }
+    match context {
+        Some(context) => params.push(("context", context.to_string())),
+        None => {
+            if let Some(date_time) = query.date_time {
+                params.push(("dateTime", date_time.to_rfc3339()));
+            }
+        }
+    }
+    if query.arrive_by {
+        params.push(("searchForArrival", "true".to_string()));
+    }
+    if let Some(via) = &query.via_uic {
+        params.push(("viaUicCode", via.clone()));
+    }
+    if query.accessible {
+        params.push(("searchForAccessibleTrip", "true".to_string()));
+    }
+
+    let request = params
+        .iter()
+        .fold(client().get(url)?, |request, (name, value)| request.query(name, value));
+    let key: Vec<String> = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    let body = client().send_cached(Endpoint::Trips, &key.join("&"), request)?;
 
     let resp: TripsResponse = serde_json::from_str(&body)?;
-    let trips: Vec<Trip> = resp.trips.into_iter().map(Trip::from).collect();
+    let mut trips = convert_trips(resp.trips);
+    if let Some(max) = query.max_transfers {
+        trips.retain(|trip| trip.transfers <= max);
+    }
+    trips.retain(|trip| {
+        trip.legs
+            .iter()
+            .filter(|leg| !leg.walking)
+            .all(|leg| query.allows_train_type(&leg.train_type))
+    });
+    if query.no_supplement {
+        trips.retain(|trip| !trip.requires_supplement());
+    }
+    // Only the first page is bounded by the requested arrival time
+    if query.arrive_by
+        && context.is_none()
+        && let Some(deadline) = query.date_time
+    {
+        arriving_by(&mut trips, deadline);
+        // Keep the trips closest to the deadline
+        if let Some(limit) = query.limit {
+            trips.drain(..trips.len().saturating_sub(limit));
+        }
+    } else if let Some(limit) = query.limit {
+        trips.truncate(limit);
+    }
+
+    Ok(TripsPage {
+        trips,
+        earlier: resp.backward_context,
+        later: resp.forward_context,
+    })
+}
+
+/// Hour at which a timetable day starts; departures later that night still
+/// belong to the previous day
+pub const SERVICE_DAY_START_HOUR: u32 = 4;
+
+/// Result pages to scroll through looking for the first or last trip
+const BOUNDARY_PAGES: usize = 4;
+
+/// Which end of the service day `day_boundary_trip()` looks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayBoundary {
+    First,
+    Last,
+}
+
+/// The first or last running trip of the service day on `date`
+/// (from `SERVICE_DAY_START_HOUR` until the same hour the next day).
+///
+/// The first trip is searched forward from the start of the day, the last one
+/// backward from its end, scrolling a few pages when a page holds only
+/// cancelled trips or none inside the day.
+pub fn day_boundary_trip(
+    from: &Station,
+    to: &Station,
+    date: NaiveDate,
+    boundary: DayBoundary,
+    query: &TripQuery,
+) -> Result<Option<Trip>, Box<dyn std::error::Error>> {
+    let local = |date: NaiveDate| {
+        let start = date.and_time(
+            NaiveTime::from_hms_opt(SERVICE_DAY_START_HOUR, 0, 0).expect("valid hour"),
+        );
+        Local
+            .from_local_datetime(&start)
+            .earliest()
+            .map(|time| time.fixed_offset())
+            .ok_or_else(|| format!("❌ {} does not exist in the local timezone", start))
+    };
+    let start = local(date)?;
+    let end = local(date.succ_opt().ok_or("❌ Date out of range")?)?;
+
+    let query = TripQuery {
+        date_time: Some(match boundary {
+            DayBoundary::First => start,
+            DayBoundary::Last => end,
+        }),
+        arrive_by: false,
+        pages: 0,
+        ..query.clone()
+    };
+
+    let mut page = trips_page(from, to, &query, None)?;
+    for _ in 0..BOUNDARY_PAGES {
+        if let Some(trip) = pick_boundary(&page.trips, boundary, start, end) {
+            return Ok(Some(trip.clone()));
+        }
+        let context = match boundary {
+            DayBoundary::First => page.later,
+            DayBoundary::Last => page.earlier,
+        };
+        let Some(context) = context else {
+            break;
+        };
+        page = trips_page(from, to, &query, Some(&context))?;
+    }
+    Ok(None)
+}
+
+/// Earliest or latest running trip departing in `[start, end)`
+fn pick_boundary(
+    trips: &[Trip],
+    boundary: DayBoundary,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Option<&Trip> {
+    let running = trips.iter().filter(|trip| {
+        !trip.is_cancelled() && trip.departure_time >= start && trip.departure_time < end
+    });
+    match boundary {
+        DayBoundary::First => running.min_by_key(|trip| trip.departure_time),
+        DayBoundary::Last => running.max_by_key(|trip| trip.departure_time),
+    }
+}
+
+/// The first running trip departing after `trips[index]`, for cancelled trips
+pub fn next_alternative(trips: &[Trip], index: usize) -> Option<&Trip> {
+    let cancelled = &trips[index];
+    trips
+        .iter()
+        .filter(|trip| !trip.is_cancelled() && trip.departure_time > cancelled.departure_time)
+        .min_by_key(|trip| trip.departure_time)
+}
+
+/// Reorder trips; ties keep the earlier departure first
+pub fn sort_trips(trips: &mut [Trip], sort: TripSort) {
+    match sort {
+        TripSort::Departure => trips.sort_by_key(|trip| trip.departure_time),
+        TripSort::Arrival => trips.sort_by_key(|trip| (trip.effective_arrival(), trip.departure_time)),
+        TripSort::Duration => trips.sort_by_key(|trip| (trip.duration(), trip.departure_time)),
+        TripSort::Transfers => trips.sort_by_key(|trip| (trip.transfers, trip.departure_time)),
+    }
+}
+
+/// Keep trips planned to arrive no later than `deadline`, ordered by arrival
+fn arriving_by(trips: &mut Vec<Trip>, deadline: DateTime<FixedOffset>) {
+    trips.retain(|trip| trip.arrival_time <= deadline);
+    trips.sort_by_key(|trip| trip.arrival_time);
+}
+
+/// Combine optional `YYYY-MM-DD` and `HH:MM` inputs into a local departure moment.
+/// A missing date means today, a missing time means the current time of day.
+pub fn planned_time(
+    date: Option<&str>,
+    time: Option<&str>,
+    now: DateTime<Local>,
+) -> Result<DateTime<FixedOffset>, Box<dyn std::error::Error>> {
+    let date = match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| format!("❌ Invalid date '{}', expected YYYY-MM-DD", d))?,
+        None => now.date_naive(),
+    };
+    let time = match time {
+        Some(t) => NaiveTime::parse_from_str(t, "%H:%M")
+            .map_err(|_| format!("❌ Invalid time '{}', expected HH:MM", t))?,
+        None => now.time(),
+    };
+
+    let local = Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .ok_or_else(|| format!("❌ {} {} does not exist in the local timezone", date, time))?;
+    Ok(local.fixed_offset())
+}
+
+/// Minimal on-time trip used as a fixture by tests across the crate
+#[cfg(test)]
+pub(crate) fn test_trip(departure: &str, arrival: &str) -> Trip {
+    let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+    let leg = Leg {
+        origin_name: "Den Haag C".to_string(),
+        destination_name: "Utrecht Centraal".to_string(),
+        origin_uic: Some("8400282".to_string()),
+        destination_uic: Some("8400621".to_string()),
+        track: "5".to_string(),
+        planned_track: Some("5".to_string()),
+        arrival_track: Some("7".to_string()),
+        departure_time: time(departure),
+        actual_departure_time: None,
+        arrival_time: time(arrival),
+        actual_arrival_time: None,
+        train_type: "IC".to_string(),
+        cancelled: false,
+        origin_location: None,
+        destination_location: None,
+        notes: Vec::new(),
+        messages: Vec::new(),
+        walking: false,
+        cross_platform: false,
+        transfer_hints: Vec::new(),
+        stops: Vec::new(),
+        distance_meters: None,
+        supplement: false,
+        supplement_cents: None,
+    };
+    Trip::from_legs(vec![leg], "NORMAL".to_string(), 0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trip(arrival: &str) -> Trip {
+        test_trip("2024-06-01T08:30:00+02:00", arrival)
+    }
+
+    #[test]
+    fn test_align_arrivals() {
+        let theirs = [trip("2024-06-01T09:00:00+02:00"), trip("2024-06-01T09:30:00+02:00")];
+        let mine = [trip("2024-06-01T09:12:00+02:00"), trip("2024-06-01T09:28:00+02:00")];
+
+        // Best match first: their 09:30 with my 09:28, then their 09:00 with my 09:12
+        assert_eq!(align_arrivals(&theirs, &mine), vec![(1, 1, -2), (0, 0, 12)]);
+        assert!(align_arrivals(&theirs, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_multi_leg_journey() {
+        let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let first = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:00:00+02:00").legs;
+        let mut second = first[0].clone();
+        second.origin_name = "Utrecht Centraal".to_string();
+        second.destination_name = "Amersfoort C".to_string();
+        second.track = "12".to_string();
+        second.train_type = "SPR".to_string();
+        second.departure_time = time("2024-06-01T09:06:00+02:00");
+        second.actual_departure_time = Some(time("2024-06-01T09:08:00+02:00"));
+        second.arrival_time = time("2024-06-01T09:25:00+02:00");
+
+        let trip =
+            Trip::from_legs(vec![first[0].clone(), second], "NORMAL".to_string(), 1).unwrap();
+        assert_eq!(trip.destination_name, "Amersfoort C");
+        assert_eq!(trip.arrival_time, time("2024-06-01T09:25:00+02:00"));
+        assert_eq!(
+            trip.transfer_points(),
+            vec![Transfer {
+                station: "Utrecht Centraal".to_string(),
+                minutes: 8,
+                arrival_track: Some("7".to_string()),
+                departure_track: "12".to_string(),
+                cross_platform: false,
+                walk_minutes: None,
+                walk_to: None,
+                hints: Vec::new(),
+                arrival_delay: 0,
+            }]
+        );
+        assert_eq!(trip.leg_lines(false)[1], "    ↳ transfer at Utrecht Centraal: 8 min, tr.7 → tr.12");
+        assert_eq!(trip.duration_minutes, 55);
+        assert_eq!(format_duration(trip.duration()), "0:55");
+
+        let json = serde_json::to_value(&trip).unwrap();
+        assert_eq!(json["duration_minutes"], 55);
+        assert_eq!(json["transfers"], 1);
+        assert_eq!(json["legs"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_transfer_risks() {
+        let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let mut first = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:00:00+02:00").legs;
+        let mut second = first[0].clone();
+        second.origin_name = "Utrecht Centraal".to_string();
+        second.departure_time = time("2024-06-01T09:05:00+02:00");
+        second.arrival_time = time("2024-06-01T09:25:00+02:00");
+        let trip = |first: &[Leg], second: &Leg| {
+            Trip::from_legs(vec![first[0].clone(), second.clone()], "NORMAL".to_string(), 1)
+                .unwrap()
+        };
+
+        // 5 minutes planned is fine with 4 needed, tight with 6
+        assert!(trip(&first, &second).transfer_risks(4).is_empty());
+        assert_eq!(trip(&first, &second).transfer_risks(6)[0].1, TransferRisk::Tight);
+
+        first[0].actual_arrival_time = Some(time("2024-06-01T09:03:00+02:00"));
+        let risks = trip(&first, &second).transfer_risks(4);
+        assert_eq!(risks[0].1, TransferRisk::Endangered);
+        assert_eq!((risks[0].0.spare_minutes(), risks[0].0.arrival_delay), (2, 3));
+
+        first[0].actual_arrival_time = Some(time("2024-06-01T09:07:00+02:00"));
+        assert_eq!(trip(&first, &second).transfer_risks(4)[0].1, TransferRisk::Missed);
+    }
+
+    #[test]
+    fn test_walk_and_cross_platform_transfers() {
+        let stop = |name: &str, track: Option<&str>, time: &str| {
+            serde_json::json!({
+                "name": name,
+                "plannedTrack": track,
+                "plannedDateTime": time,
+            })
+        };
+        let raw = serde_json::json!({
+            "status": "NORMAL",
+            "transfers": 2,
+            "legs": [
+                {
+                    "origin": stop("Den Haag C", Some("5"), "2024-06-01T08:00:00+0200"),
+                    "destination": stop("Leiden Centraal", Some("8a"), "2024-06-01T08:12:00+0200"),
+                    "product": {"categoryCode": "SPR"},
+                },
+                {
+                    "origin": stop("Leiden Centraal", Some("8b"), "2024-06-01T08:16:00+0200"),
+                    "destination": stop("Schiphol Airport", Some("3"), "2024-06-01T08:35:00+0200"),
+                    "product": {"categoryCode": "IC"},
+                    "crossPlatformTransfer": true,
+                    "transferMessages": [{"message": "Overstap op zelfde perron"}],
+                },
+                {
+                    "origin": stop("Schiphol Airport", None, "2024-06-01T08:35:00+0200"),
+                    "destination": stop("Hoofddorp", None, "2024-06-01T08:41:00+0200"),
+                    "product": {},
+                    "travelType": "WALK",
+                },
+                {
+                    "origin": stop("Hoofddorp", Some("1"), "2024-06-01T08:50:00+0200"),
+                    "destination": stop("Haarlem", Some("2"), "2024-06-01T09:05:00+0200"),
+                    "product": {"categoryCode": "SPR"},
+                },
+            ],
+        });
+
+        let trip = Trip::try_from(serde_json::from_value::<TripRaw>(raw).unwrap()).unwrap();
+        let transfers = trip.transfer_points();
+        assert_eq!(transfers.len(), 2);
+        assert!(transfers[0].cross_platform);
+        assert_eq!(transfers[0].hints, ["Overstap op zelfde perron"]);
+        assert_eq!(transfers[1].walk_minutes, Some(6));
+        assert_eq!(transfers[1].walk_to.as_deref(), Some("Hoofddorp"));
+        assert_eq!(
+            transfers[1].describe(),
+            "15 min, tr.3 → tr.1, walk 6 min to Hoofddorp"
+        );
+        // The walk is folded into the transfer line, not listed as a ride
+        assert_eq!(trip.leg_lines(false).len(), 6);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let raw = serde_json::json!({
+            "status": "NORMAL",
+            "primaryMessage": {"title": "Extra reistijd"},
+            "messages": [{"head": "Werkzaamheden", "text": "Bus replaces train"}],
+            "legs": [{
+                "origin": {"name": "Gouda", "plannedDateTime": "2024-06-01T08:00:00+0200"},
+                "destination": {"name": "Utrecht Centraal", "plannedDateTime": "2024-06-01T08:20:00+0200"},
+                "product": {"categoryCode": "BUS"},
+                "notes": [
+                    {"value": "Fietsen niet toegestaan", "noteType": "ATTRIBUTE"},
+                    {"value": "Reservering verplicht", "noteType": "ATTRIBUTE", "isPresentationRequired": true},
+                    {"value": "Toeslag", "noteType": "TICKET"},
+                ],
+                "messages": [{"head": "Werkzaamheden", "text": "Bus replaces train"}],
+            }],
+        });
+        let trip = Trip::try_from(serde_json::from_value::<TripRaw>(raw).unwrap()).unwrap();
+
+        assert_eq!(
+            trip.warnings(),
+            [
+                "Extra reistijd",
+                "Werkzaamheden: Bus replaces train",
+                "Reservering verplicht",
+                "Toeslag"
+            ]
+        );
+        assert_eq!(trip.notes().len(), 3);
+    }
+
+    #[test]
+    fn test_co2_from_stop_coordinates() {
+        let stop = |name: &str, lat: f64, lng: f64| {
+            serde_json::json!({"name": name, "lat": lat, "lng": lng})
+        };
+        let raw = serde_json::json!({
+            "status": "NORMAL",
+            "legs": [{
+                "origin": {"name": "Den Haag C", "lat": 52.0809, "lng": 4.3242, "plannedDateTime": "2024-06-01T08:00:00+0200"},
+                "destination": {"name": "Utrecht Centraal", "lat": 52.0894, "lng": 5.1100, "plannedDateTime": "2024-06-01T08:40:00+0200"},
+                "product": {"categoryCode": "IC"},
+                "stops": [
+                    stop("Den Haag C", 52.0809, 4.3242),
+                    stop("Gouda", 52.0175, 4.7046),
+                    stop("Utrecht Centraal", 52.0894, 5.1100),
+                ],
+            }],
+        });
+        let trip = Trip::try_from(serde_json::from_value::<TripRaw>(raw).unwrap()).unwrap();
+
+        let co2 = trip.co2().unwrap();
+        // Via Gouda is longer than the ~54 km straight line
+        assert!((55.0..57.0).contains(&co2.distance_km), "{}", co2.distance_km);
+        assert_eq!(co2.trip_grams, (co2.distance_km * TRAIN_G_PER_KM).round() as u32);
+        assert_eq!(co2.saved_grams(), co2.car_grams - co2.trip_grams);
+
+        let fixture = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:00:00+02:00");
+        assert_eq!(fixture.co2(), None);
+    }
+
+    #[test]
+    fn test_malformed_trips_are_skipped() {
+        let leg = |departure: &str| {
+            serde_json::json!({
+                "origin": {"name": "Den Haag C", "plannedDateTime": departure},
+                "destination": {"name": "Utrecht Centraal", "plannedDateTime": "2024-06-01T08:40:00+0200"},
+                "product": {"categoryCode": "IC"},
+            })
+        };
+        let raw = |legs: serde_json::Value| {
+            serde_json::from_value::<TripRaw>(serde_json::json!({"status": "NORMAL", "legs": legs}))
+                .unwrap()
+        };
+
+        assert_eq!(Trip::try_from(raw(serde_json::json!([]))).unwrap_err(), TripError::NoLegs);
+        assert_eq!(
+            Trip::try_from(raw(serde_json::json!([leg("08:00")]))).unwrap_err(),
+            TripError::InvalidTime("08:00".to_string())
+        );
+
+        let mut bad_stop = leg("2024-06-01T08:00:00+0200");
+        bad_stop["stops"] = serde_json::json!([
+            {"name": "Den Haag C"},
+            {"name": "Gouda", "plannedArrivalDateTime": "tomorrow"},
+            {"name": "Utrecht Centraal"},
+        ]);
+        assert!(Trip::try_from(raw(serde_json::json!([bad_stop]))).is_err());
+
+        let trips = convert_trips(vec![
+            raw(serde_json::json!([])),
+            raw(serde_json::json!([leg("2024-06-01T08:00:00+0200")])),
+        ]);
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].duration_minutes, 40);
+    }
+
+    #[test]
+    fn test_supplements() {
+        let leg = |from: &str, to: &str, category: &str| {
+            serde_json::json!({
+                "origin": {"name": from, "plannedDateTime": "2024-06-01T08:00:00+0200"},
+                "destination": {"name": to, "plannedDateTime": "2024-06-01T08:30:00+0200"},
+                "product": {"categoryCode": category},
+            })
+        };
+        let raw = serde_json::json!({
+            "status": "NORMAL",
+            "legs": [
+                leg("Rotterdam Centraal", "Schiphol Airport", "ICD"),
+                leg("Schiphol Airport", "Amsterdam Centraal", "SPR"),
+            ],
+            "fareOptions": {"supplementsBasedOnSelectedFare": [{"supplementPriceInCents": 260, "legIdx": "0"}]},
+        });
+        let trip = Trip::try_from(serde_json::from_value::<TripRaw>(raw).unwrap()).unwrap();
+        assert!(trip.requires_supplement());
+        assert_eq!(trip.supplement_cents(), Some(260));
+        assert_eq!(trip.supplement_types(), vec!["ICD"]);
+        assert!(!trip.legs[1].supplement);
+
+        let fixture = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:00:00+02:00");
+        assert!(!fixture.requires_supplement());
+        assert_eq!(fixture.supplement_cents(), None);
+    }
+
+    #[test]
+    fn test_intermediate_stops() {
+        let raw = serde_json::json!({
+            "status": "NORMAL",
+            "legs": [{
+                "origin": {"name": "Den Haag C", "plannedTrack": "5", "plannedDateTime": "2024-06-01T08:00:00+0200"},
+                "destination": {"name": "Utrecht Centraal", "plannedDateTime": "2024-06-01T08:40:00+0200"},
+                "product": {"categoryCode": "IC"},
+                "stops": [
+                    {"name": "Den Haag C", "plannedDepartureDateTime": "2024-06-01T08:00:00+0200"},
+                    {"name": "Zoetermeer", "passing": true},
+                    {
+                        "name": "Gouda",
+                        "plannedDepartureDateTime": "2024-06-01T08:18:00+0200",
+                        "actualDepartureDateTime": "2024-06-01T08:20:00+0200",
+                        "plannedDepartureTrack": "8"
+                    },
+                    {"name": "Utrecht Centraal", "plannedArrivalDateTime": "2024-06-01T08:40:00+0200"}
+                ]
+            }]
+        });
+
+        let trip = Trip::try_from(serde_json::from_value::<TripRaw>(raw).unwrap()).unwrap();
+        assert_eq!(trip.legs[0].stops.len(), 1);
+        assert!(trip.leg_lines(false).is_empty());
+        assert_eq!(trip.leg_lines(true)[1], "      · 08:18+2 Gouda tr.8");
+    }
+
+    #[test]
+    fn test_pick_boundary() {
+        let time = |t: &str| DateTime::parse_from_rfc3339(t).unwrap();
+        let (start, end) = (time("2024-06-01T04:00:00+02:00"), time("2024-06-02T04:00:00+02:00"));
+        let night = test_trip("2024-06-01T03:30:00+02:00", "2024-06-01T04:10:00+02:00");
+        let mut first = test_trip("2024-06-01T05:40:00+02:00", "2024-06-01T06:20:00+02:00");
+        let second = test_trip("2024-06-01T06:10:00+02:00", "2024-06-01T06:50:00+02:00");
+        let late = test_trip("2024-06-02T00:40:00+02:00", "2024-06-02T01:20:00+02:00");
+        let trips = [night, first.clone(), second, late.clone()];
+
+        let pick = |trips: &[Trip], boundary| {
+            pick_boundary(trips, boundary, start, end).map(|trip| trip.departure_time)
+        };
+        assert_eq!(pick(&trips, DayBoundary::First), Some(first.departure_time));
+        assert_eq!(pick(&trips, DayBoundary::Last), Some(late.departure_time));
+
+        first.status = "CANCELLED".to_string();
+        let trips = [first, late];
+        assert_eq!(pick(&trips, DayBoundary::First), Some(trips[1].departure_time));
+        assert_eq!(pick(&trips[..1], DayBoundary::Last), None);
+    }
+
+    #[test]
+    fn test_next_alternative() {
+        let mut cancelled = test_trip("2024-06-01T08:00:00+02:00", "2024-06-01T08:30:00+02:00");
+        cancelled.status = "CANCELLED".to_string();
+        let mut also_cancelled = test_trip("2024-06-01T08:15:00+02:00", "2024-06-01T08:45:00+02:00");
+        also_cancelled.legs[0].cancelled = true;
+        let running = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:00:00+02:00");
+        let earlier = test_trip("2024-06-01T07:45:00+02:00", "2024-06-01T08:15:00+02:00");
+
+        let trips = vec![earlier, cancelled, also_cancelled, running];
+        assert!(trips[2].is_cancelled());
+        let alternative = next_alternative(&trips, 1).unwrap();
+        assert_eq!(alternative.departure_time, trips[3].departure_time);
+        assert!(next_alternative(&trips, 3).is_none());
+    }
+
+    #[test]
+    fn test_sort_trips() {
+        let mut slow = test_trip("2024-06-01T08:00:00+02:00", "2024-06-01T09:10:00+02:00");
+        slow.transfers = 0;
+        let mut fast = test_trip("2024-06-01T08:15:00+02:00", "2024-06-01T08:55:00+02:00");
+        fast.transfers = 2;
+        let later = test_trip("2024-06-01T08:30:00+02:00", "2024-06-01T09:10:00+02:00");
+        let order = |trips: &[Trip]| {
+            trips
+                .iter()
+                .map(|t| t.departure_time.format("%H:%M").to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut trips = vec![later, fast, slow];
+        sort_trips(&mut trips, TripSort::Departure);
+        assert_eq!(order(&trips), ["08:00", "08:15", "08:30"]);
+        sort_trips(&mut trips, TripSort::Duration);
+        assert_eq!(order(&trips), ["08:15", "08:30", "08:00"]);
+        sort_trips(&mut trips, TripSort::Transfers);
+        assert_eq!(order(&trips), ["08:00", "08:30", "08:15"]);
+        sort_trips(&mut trips, TripSort::Arrival);
+        assert_eq!(order(&trips), ["08:15", "08:00", "08:30"]);
+    }
+
+    #[test]
+    fn test_planner_url() {
+        let station = |name: &str| Station {
+            id: crate::stations::models::StationId {
+                uic_code: String::new(),
+                code: None,
+            },
+            names: crate::stations::models::StationNames {
+                long: name.to_string(),
+            },
+            location: None,
+            station_type: None,
+        };
+        let query = TripQuery {
+            date_time: Some(DateTime::parse_from_rfc3339("2024-06-01T08:30:00+02:00").unwrap()),
+            arrive_by: true,
+            ..Default::default()
+        };
+
+        let url = planner_url(&station("Den Haag C"), &station("Amersfoort C"), None, &query);
+        assert_eq!(
+            url,
+            "https://www.ns.nl/reisplanner/#/?vertrek=Den%20Haag%20C&vertrektype=treinstation\
+             &aankomst=Amersfoort%20C&aankomsttype=treinstation&type=aankomst&tijd=2024-06-01T08%3A30"
+        );
+    }
+
+    #[test]
+    fn test_arriving_by() {
+        let mut trips = vec![
+            trip("2024-06-01T09:05:00+02:00"),
+            trip("2024-06-01T08:55:00+02:00"),
+            trip("2024-06-01T08:40:00+02:00"),
+        ];
+        let deadline = DateTime::parse_from_rfc3339("2024-06-01T09:00:00+02:00").unwrap();
+        arriving_by(&mut trips, deadline);
+
+        let arrivals: Vec<String> = trips
+            .iter()
+            .map(|t| t.arrival_time.format("%H:%M").to_string())
+            .collect();
+        assert_eq!(arrivals, vec!["08:40", "08:55"]);
+    }
+
+    #[test]
+    fn test_planned_time() {
+        let now = Local.with_ymd_and_hms(2024, 6, 1, 7, 45, 0).unwrap();
+
+        let at = planned_time(Some("2024-06-02"), Some("08:30"), now).unwrap();
+        assert_eq!(at.format("%Y-%m-%d %H:%M").to_string(), "2024-06-02 08:30");
+
+        // Only a time: today; only a date: the current time of day
+        let today = planned_time(None, Some("18:05"), now).unwrap();
+        assert_eq!(today.format("%Y-%m-%d %H:%M").to_string(), "2024-06-01 18:05");
+        let tomorrow = planned_time(Some("2024-06-02"), None, now).unwrap();
+        assert_eq!(tomorrow.format("%Y-%m-%d %H:%M").to_string(), "2024-06-02 07:45");
 
-    for t in &trips {
-        println!("{}", t);
+        assert!(planned_time(Some("02-06-2024"), None, now).is_err());
+        assert!(planned_time(None, Some("8.30"), now).is_err());
     }
-    Ok(())
 }